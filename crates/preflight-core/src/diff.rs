@@ -2,16 +2,19 @@ use serde::{Deserialize, Serialize};
 
 #[non_exhaustive]
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum FileStatus {
     Added,
     Modified,
     Deleted,
     Renamed,
     Binary,
+    Symlink,
 }
 
 #[non_exhaustive]
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum LineKind {
     Context,
     Added,
@@ -19,6 +22,7 @@ pub enum LineKind {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct DiffLine {
     pub kind: LineKind,
     pub content: String,
@@ -26,9 +30,13 @@ pub struct DiffLine {
     pub new_line_no: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub highlighted: Option<String>,
+    /// Set on a removed/added line pair that differs only in whitespace.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub whitespace_only: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Hunk {
     pub old_start: u32,
     pub old_count: u32,
@@ -38,10 +46,40 @@ pub struct Hunk {
     pub lines: Vec<DiffLine>,
 }
 
+impl Hunk {
+    /// First and last new-file line numbers among the hunk's lines, or
+    /// `None` if it only removes lines.
+    pub fn new_line_span(&self) -> Option<(u32, u32)> {
+        line_span(self.lines.iter().filter_map(|l| l.new_line_no))
+    }
+
+    /// First and last old-file line numbers among the hunk's lines, or
+    /// `None` if it only adds lines.
+    pub fn old_line_span(&self) -> Option<(u32, u32)> {
+        line_span(self.lines.iter().filter_map(|l| l.old_line_no))
+    }
+}
+
+fn line_span(mut line_nos: impl DoubleEndedIterator<Item = u32>) -> Option<(u32, u32)> {
+    let first = line_nos.next()?;
+    Some((first, line_nos.next_back().unwrap_or(first)))
+}
+
+/// A file permission change from `old mode` / `new mode` header lines.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ModeChange {
+    pub old_mode: String,
+    pub new_mode: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct FileDiff {
     pub old_path: Option<String>,
     pub new_path: Option<String>,
     pub status: FileStatus,
     pub hunks: Vec<Hunk>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode_change: Option<ModeChange>,
 }
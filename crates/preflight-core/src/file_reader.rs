@@ -1,5 +1,8 @@
 use std::path::Path;
 
+/// Default upper bound on file content read for display (10 MiB).
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
 /// Errors that can occur when reading file content.
 #[derive(Debug)]
 pub enum FileReadError {
@@ -9,6 +12,8 @@ pub enum FileReadError {
     FileNotFound(String),
     /// Git command failed.
     GitError(String),
+    /// The file exceeds the configured size limit.
+    TooLarge { path: String, size: u64, limit: u64 },
 }
 
 impl std::fmt::Display for FileReadError {
@@ -17,6 +22,9 @@ impl std::fmt::Display for FileReadError {
             FileReadError::NotAGitRepo => write!(f, "not a git repository"),
             FileReadError::FileNotFound(path) => write!(f, "file not found: {path}"),
             FileReadError::GitError(msg) => write!(f, "git error: {msg}"),
+            FileReadError::TooLarge { path, size, limit } => {
+                write!(f, "file too large: {path} ({size} bytes, limit {limit})")
+            }
         }
     }
 }
@@ -40,41 +48,105 @@ fn validate_file_path(file_path: &str) -> Result<(), FileReadError> {
     Ok(())
 }
 
+/// Reject files larger than `max_size` before their content is read.
+fn check_size(file_path: &str, size: u64, max_size: u64) -> Result<(), FileReadError> {
+    if size > max_size {
+        return Err(FileReadError::TooLarge {
+            path: file_path.to_string(),
+            size,
+            limit: max_size,
+        });
+    }
+    Ok(())
+}
+
 /// Read the current (new) version of a file from the working directory.
-pub fn read_new_file(repo_path: &Path, file_path: &str) -> Result<String, FileReadError> {
+///
+/// Files larger than `max_size` bytes are rejected without being read.
+pub fn read_new_file(
+    repo_path: &Path,
+    file_path: &str,
+    max_size: u64,
+) -> Result<String, FileReadError> {
+    into_text(read_new_file_bytes(repo_path, file_path, max_size)?)
+}
+
+/// Like [`read_new_file`], returning the bytes as they are on disk.
+pub fn read_new_file_bytes(
+    repo_path: &Path,
+    file_path: &str,
+    max_size: u64,
+) -> Result<Vec<u8>, FileReadError> {
     validate_file_path(file_path)?;
     let full_path = repo_path.join(file_path);
-    std::fs::read_to_string(&full_path).map_err(|e| match e.kind() {
+    let map_io_err = |e: std::io::Error| match e.kind() {
         std::io::ErrorKind::NotFound => FileReadError::FileNotFound(file_path.to_string()),
         _ => FileReadError::GitError(e.to_string()),
-    })
+    };
+    let metadata = std::fs::metadata(&full_path).map_err(map_io_err)?;
+    check_size(file_path, metadata.len(), max_size)?;
+    std::fs::read(&full_path).map_err(map_io_err)
 }
 
 /// Read the old version of a file from git at the given ref.
+///
+/// The blob size is checked with `git cat-file -s` first, so blobs larger
+/// than `max_size` bytes are rejected without being read.
 pub fn read_old_file(
     repo_path: &Path,
     file_path: &str,
     base_ref: &str,
+    max_size: u64,
 ) -> Result<String, FileReadError> {
+    into_text(read_old_file_bytes(
+        repo_path, file_path, base_ref, max_size,
+    )?)
+}
+
+/// Like [`read_old_file`], returning the blob's bytes as they are.
+pub fn read_old_file_bytes(
+    repo_path: &Path,
+    file_path: &str,
+    base_ref: &str,
+    max_size: u64,
+) -> Result<Vec<u8>, FileReadError> {
     validate_file_path(file_path)?;
-    let output = std::process::Command::new("git")
+    let object = format!("{base_ref}:{file_path}");
+    let size_output = std::process::Command::new("git")
         .args([
             "-C",
             &repo_path.to_string_lossy(),
-            "show",
-            &format!("{base_ref}:{file_path}"),
+            "cat-file",
+            "-s",
+            &object,
         ])
         .output()
         .map_err(|e| FileReadError::GitError(e.to_string()))?;
+    if size_output.status.success()
+        && let Ok(size) = String::from_utf8_lossy(&size_output.stdout)
+            .trim()
+            .parse::<u64>()
+    {
+        check_size(file_path, size, max_size)?;
+    }
+
+    let output = std::process::Command::new("git")
+        .args(["-C", &repo_path.to_string_lossy(), "show", &object])
+        .output()
+        .map_err(|e| FileReadError::GitError(e.to_string()))?;
 
     if output.status.success() {
-        String::from_utf8(output.stdout).map_err(|e| FileReadError::GitError(e.to_string()))
+        Ok(output.stdout)
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
         Err(FileReadError::GitError(stderr.to_string()))
     }
 }
 
+fn into_text(bytes: Vec<u8>) -> Result<String, FileReadError> {
+    String::from_utf8(bytes).map_err(|e| FileReadError::GitError(e.to_string()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -129,21 +201,21 @@ mod tests {
     #[test]
     fn read_new_file_returns_working_copy() {
         let dir = setup_git_repo();
-        let content = read_new_file(dir.path(), "hello.rs").unwrap();
+        let content = read_new_file(dir.path(), "hello.rs", DEFAULT_MAX_FILE_SIZE).unwrap();
         assert!(content.contains("println"));
     }
 
     #[test]
     fn read_new_file_not_found() {
         let dir = setup_git_repo();
-        let result = read_new_file(dir.path(), "nonexistent.rs");
+        let result = read_new_file(dir.path(), "nonexistent.rs", DEFAULT_MAX_FILE_SIZE);
         assert!(matches!(result, Err(FileReadError::FileNotFound(_))));
     }
 
     #[test]
     fn read_old_file_returns_committed_version() {
         let dir = setup_git_repo();
-        let content = read_old_file(dir.path(), "hello.rs", "HEAD").unwrap();
+        let content = read_old_file(dir.path(), "hello.rs", "HEAD", DEFAULT_MAX_FILE_SIZE).unwrap();
         assert_eq!(content, "fn main() {}\n");
         assert!(!content.contains("println"));
     }
@@ -151,17 +223,48 @@ mod tests {
     #[test]
     fn read_old_file_bad_ref() {
         let dir = setup_git_repo();
-        let result = read_old_file(dir.path(), "hello.rs", "nonexistent-ref");
+        let result = read_old_file(
+            dir.path(),
+            "hello.rs",
+            "nonexistent-ref",
+            DEFAULT_MAX_FILE_SIZE,
+        );
         assert!(matches!(result, Err(FileReadError::GitError(_))));
     }
 
     #[test]
     fn read_old_file_not_in_commit() {
         let dir = setup_git_repo();
-        let result = read_old_file(dir.path(), "doesnotexist.rs", "HEAD");
+        let result = read_old_file(dir.path(), "doesnotexist.rs", "HEAD", DEFAULT_MAX_FILE_SIZE);
         assert!(matches!(result, Err(FileReadError::GitError(_))));
     }
 
+    #[test]
+    fn read_new_file_over_limit_is_rejected() {
+        let dir = setup_git_repo();
+        let result = read_new_file(dir.path(), "hello.rs", 8);
+        assert!(matches!(
+            result,
+            Err(FileReadError::TooLarge { size, limit: 8, .. }) if size > 8
+        ));
+    }
+
+    #[test]
+    fn read_old_file_over_limit_is_rejected() {
+        let dir = setup_git_repo();
+        // The committed version is 13 bytes ("fn main() {}\n")
+        let result = read_old_file(dir.path(), "hello.rs", "HEAD", 12);
+        assert!(matches!(
+            result,
+            Err(FileReadError::TooLarge {
+                size: 13,
+                limit: 12,
+                ..
+            })
+        ));
+        assert!(read_old_file(dir.path(), "hello.rs", "HEAD", 13).is_ok());
+    }
+
     #[test]
     fn validate_repo_path_valid() {
         let dir = setup_git_repo();
@@ -180,14 +283,14 @@ mod tests {
     #[test]
     fn read_new_file_rejects_path_traversal() {
         let dir = setup_git_repo();
-        let result = read_new_file(dir.path(), "../../../etc/passwd");
+        let result = read_new_file(dir.path(), "../../../etc/passwd", DEFAULT_MAX_FILE_SIZE);
         assert!(matches!(result, Err(FileReadError::FileNotFound(_))));
     }
 
     #[test]
     fn read_new_file_rejects_absolute_path() {
         let dir = setup_git_repo();
-        let result = read_new_file(dir.path(), "/etc/passwd");
+        let result = read_new_file(dir.path(), "/etc/passwd", DEFAULT_MAX_FILE_SIZE);
         assert!(matches!(result, Err(FileReadError::FileNotFound(_))));
     }
 }
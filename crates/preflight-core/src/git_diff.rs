@@ -1,11 +1,20 @@
 use std::path::Path;
 
+use serde::{Deserialize, Serialize};
+
 use crate::diff::FileDiff;
 use crate::parser;
+use crate::review::ExtraRepo;
 
 #[derive(Debug)]
 pub enum GitDiffError {
     NotAGitRepo,
+    /// A single-commit review names a commit that doesn't exist, or one
+    /// with no parent to diff against.
+    BadCommit(String),
+    /// The base ref doesn't name a commit: no such branch, tag, remote ref
+    /// or hash.
+    BadRef(String),
     GitFailed(String),
     ParseFailed(String),
 }
@@ -14,6 +23,11 @@ impl std::fmt::Display for GitDiffError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             GitDiffError::NotAGitRepo => write!(f, "not a git repository"),
+            GitDiffError::BadCommit(msg) => write!(f, "{msg}"),
+            GitDiffError::BadRef(base_ref) => write!(
+                f,
+                "base ref '{base_ref}' is not a branch, tag, remote ref or commit in this repository"
+            ),
             GitDiffError::GitFailed(msg) => write!(f, "git diff failed: {msg}"),
             GitDiffError::ParseFailed(msg) => write!(f, "failed to parse diff: {msg}"),
         }
@@ -81,13 +95,228 @@ pub fn detect_default_base(repo_path: &Path) -> String {
         .unwrap_or_else(|| "HEAD".to_string())
 }
 
-/// Run `git diff <base_ref>` in the given repo and return parsed file diffs.
-pub fn diff_against_base(repo_path: &Path, base_ref: &str) -> Result<Vec<FileDiff>, GitDiffError> {
+/// The branch checked out in the repo, or `None` for a detached HEAD or a
+/// path git can't read.
+pub fn current_branch(repo_path: &Path) -> Option<String> {
+    std::process::Command::new("git")
+        .args([
+            "-C",
+            &repo_path.to_string_lossy(),
+            "symbolic-ref",
+            "--short",
+            "-q",
+            "HEAD",
+        ])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Base ref value that asks for [`detect_default_base`] instead of a fixed ref.
+pub const AUTO_BASE_REF: &str = "auto";
+
+/// Resolve a requested base ref: a missing ref or [`AUTO_BASE_REF`] becomes the
+/// merge-base with the default branch; anything else is used as given.
+pub fn resolve_base_ref(repo_path: &Path, base_ref: Option<&str>) -> String {
+    match base_ref {
+        None | Some(AUTO_BASE_REF) => detect_default_base(repo_path),
+        Some(base_ref) => base_ref.to_string(),
+    }
+}
+
+/// Suffix on a base ref (`<commit>^!`, as in `git rev-list`) asking to review
+/// exactly the changes that commit introduced, instead of the working tree.
+pub const SINGLE_COMMIT_SUFFIX: &str = "^!";
+
+/// The commit a `<commit>^!` base ref names, if it is one.
+pub fn single_commit(base_ref: &str) -> Option<&str> {
+    base_ref
+        .strip_suffix(SINGLE_COMMIT_SUFFIX)
+        .filter(|c| !c.is_empty())
+}
+
+/// The full hash of the commit `rev` names, so a review of a branch or
+/// `HEAD` keeps pointing at the same commit after the ref moves.
+pub fn resolve_commit(repo_path: &Path, rev: &str) -> Result<String, GitDiffError> {
     if !repo_path.join(".git").exists() {
         return Err(GitDiffError::NotAGitRepo);
     }
-    let output = std::process::Command::new("git")
-        .args(["-C", &repo_path.to_string_lossy(), "diff", base_ref, "--"])
+    std::process::Command::new("git")
+        .args([
+            "-C",
+            &repo_path.to_string_lossy(),
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("{rev}^{{commit}}"),
+        ])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| GitDiffError::BadCommit(format!("commit not found: {rev}")))
+}
+
+/// The ref to read a file's old content from: the parent for a
+/// single-commit review, else the base ref itself.
+pub fn old_content_ref(base_ref: &str) -> String {
+    match single_commit(base_ref) {
+        Some(commit) => format!("{commit}^"),
+        None => base_ref.to_string(),
+    }
+}
+
+/// The ref to read a file's new content from: the commit for a
+/// single-commit review, or `None` for the working tree.
+pub fn new_content_ref(base_ref: &str) -> Option<&str> {
+    single_commit(base_ref)
+}
+
+fn verify_commit(repo_path: &Path, rev: &str) -> bool {
+    std::process::Command::new("git")
+        .args([
+            "-C",
+            &repo_path.to_string_lossy(),
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("{rev}^{{commit}}"),
+        ])
+        .output()
+        .is_ok_and(|o| o.status.success())
+}
+
+/// Similarity (in percent) a deleted/added file pair needs to be reported as
+/// a rename. Matches git's own default for `-M`.
+pub const DEFAULT_RENAME_THRESHOLD: u8 = 50;
+
+/// How `git diff` matches up old and new lines. Patience and histogram tend
+/// to give cleaner hunks than git's default, Myers, when code is reordered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum DiffAlgorithm {
+    #[default]
+    Myers,
+    Minimal,
+    Patience,
+    Histogram,
+}
+
+impl DiffAlgorithm {
+    /// The name `git diff --diff-algorithm` takes.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DiffAlgorithm::Myers => "myers",
+            DiffAlgorithm::Minimal => "minimal",
+            DiffAlgorithm::Patience => "patience",
+            DiffAlgorithm::Histogram => "histogram",
+        }
+    }
+}
+
+/// Settings for diffing a review's repositories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiffOptions {
+    /// See [`diff_against_base_with_threshold`].
+    pub rename_threshold: u8,
+    pub algorithm: DiffAlgorithm,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            rename_threshold: DEFAULT_RENAME_THRESHOLD,
+            algorithm: DiffAlgorithm::default(),
+        }
+    }
+}
+
+/// Run `git diff <base_ref>` in the given repo and return parsed file diffs,
+/// detecting renames at [`DEFAULT_RENAME_THRESHOLD`]. A `<commit>^!` base
+/// ref diffs the commit against its parent instead.
+/// When `path_prefix` is set, it is passed as a pathspec to limit the diff to that subtree.
+pub fn diff_against_base(
+    repo_path: &Path,
+    base_ref: &str,
+    path_prefix: Option<&str>,
+) -> Result<Vec<FileDiff>, GitDiffError> {
+    diff_against_base_with_threshold(repo_path, base_ref, path_prefix, DEFAULT_RENAME_THRESHOLD)
+}
+
+/// Like [`diff_against_base`], reporting a moved file as renamed when its old
+/// and new content are at least `rename_threshold` percent similar. Rename
+/// detection is requested explicitly (`-M`), so it works even where the
+/// user's git config turns `diff.renames` off.
+pub fn diff_against_base_with_threshold(
+    repo_path: &Path,
+    base_ref: &str,
+    path_prefix: Option<&str>,
+    rename_threshold: u8,
+) -> Result<Vec<FileDiff>, GitDiffError> {
+    let options = DiffOptions {
+        rename_threshold,
+        ..DiffOptions::default()
+    };
+    diff_against_base_with_options(repo_path, base_ref, path_prefix, &options)
+}
+
+/// Like [`diff_against_base_with_threshold`], also choosing the diff
+/// algorithm. It is always passed to git, so the user's `diff.algorithm`
+/// config doesn't change what a review shows.
+pub fn diff_against_base_with_options(
+    repo_path: &Path,
+    base_ref: &str,
+    path_prefix: Option<&str>,
+    options: &DiffOptions,
+) -> Result<Vec<FileDiff>, GitDiffError> {
+    if !repo_path.join(".git").exists() {
+        return Err(GitDiffError::NotAGitRepo);
+    }
+    let rename_arg = format!("-M{}%", options.rename_threshold.min(100));
+    let algorithm_arg = format!("--diff-algorithm={}", options.algorithm.as_str());
+    let mut cmd = std::process::Command::new("git");
+    cmd.args([
+        "-C",
+        &repo_path.to_string_lossy(),
+        "diff",
+        &rename_arg,
+        &algorithm_arg,
+    ]);
+    match single_commit(base_ref) {
+        // `git diff <root>^!` would silently diff the root against the
+        // working tree, so check the parent exists up front
+        Some(commit) => {
+            if !verify_commit(repo_path, commit) {
+                return Err(GitDiffError::BadCommit(format!(
+                    "commit not found: {commit}"
+                )));
+            }
+            let parent = format!("{commit}^");
+            if !verify_commit(repo_path, &parent) {
+                return Err(GitDiffError::BadCommit(format!(
+                    "commit {commit} has no parent to diff against"
+                )));
+            }
+            cmd.args([parent.as_str(), commit]);
+        }
+        // Resolve the ref first so a typo reads as such, not as a raw git
+        // error
+        None => {
+            if !verify_commit(repo_path, base_ref) {
+                return Err(GitDiffError::BadRef(base_ref.to_string()));
+            }
+            cmd.arg(base_ref);
+        }
+    }
+    cmd.arg("--");
+    if let Some(prefix) = path_prefix {
+        cmd.arg(prefix);
+    }
+    let output = cmd
         .output()
         .map_err(|e| GitDiffError::GitFailed(e.to_string()))?;
     if !output.status.success() {
@@ -98,9 +327,35 @@ pub fn diff_against_base(repo_path: &Path, base_ref: &str) -> Result<Vec<FileDif
     Ok(parser::parse_diff(&diff_text).unwrap_or_default())
 }
 
+/// Diff each of a review's extra repositories against its own base ref. The
+/// paths are qualified with the repository's prefix, so the files can be
+/// listed together with the main repository's.
+pub fn diff_extra_repos(
+    repos: &[ExtraRepo],
+    options: &DiffOptions,
+) -> Result<Vec<FileDiff>, GitDiffError> {
+    let mut files = Vec::new();
+    for repo in repos {
+        let diffs = diff_against_base_with_options(
+            Path::new(&repo.repo_path),
+            &repo.base_ref,
+            None,
+            options,
+        )?;
+        let qualify = |path: Option<String>| path.map(|p| format!("{}/{p}", repo.prefix));
+        files.extend(diffs.into_iter().map(|file| FileDiff {
+            old_path: qualify(file.old_path),
+            new_path: qualify(file.new_path),
+            ..file
+        }));
+    }
+    Ok(files)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::diff::LineKind;
     use std::process::Command;
     use tempfile::TempDir;
 
@@ -136,10 +391,28 @@ mod tests {
         dir
     }
 
+    #[test]
+    fn current_branch_names_checked_out_branch() {
+        let dir = setup_repo();
+        Command::new("git")
+            .args(["checkout", "-b", "feature"])
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        assert_eq!(current_branch(dir.path()).as_deref(), Some("feature"));
+
+        Command::new("git")
+            .args(["checkout", "--detach"])
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        assert_eq!(current_branch(dir.path()), None);
+    }
+
     #[test]
     fn diff_with_no_changes_returns_empty() {
         let dir = setup_repo();
-        let files = diff_against_base(dir.path(), "HEAD").unwrap();
+        let files = diff_against_base(dir.path(), "HEAD", None).unwrap();
         assert!(files.is_empty());
     }
 
@@ -151,7 +424,7 @@ mod tests {
             "fn main() { println!(\"hi\"); }\n",
         )
         .unwrap();
-        let files = diff_against_base(dir.path(), "HEAD").unwrap();
+        let files = diff_against_base(dir.path(), "HEAD", None).unwrap();
         assert_eq!(files.len(), 1);
         assert_eq!(files[0].new_path.as_deref(), Some("hello.rs"));
     }
@@ -165,14 +438,220 @@ mod tests {
             .current_dir(dir.path())
             .output()
             .unwrap();
-        let files = diff_against_base(dir.path(), "HEAD").unwrap();
+        let files = diff_against_base(dir.path(), "HEAD", None).unwrap();
         assert_eq!(files.len(), 1);
     }
 
+    #[test]
+    fn diff_limited_to_path_prefix() {
+        let dir = setup_repo();
+        let p = dir.path();
+        std::fs::create_dir_all(p.join("packages/foo")).unwrap();
+        std::fs::create_dir_all(p.join("packages/bar")).unwrap();
+        std::fs::write(p.join("packages/foo/a.rs"), "fn a() {}\n").unwrap();
+        std::fs::write(p.join("packages/bar/b.rs"), "fn b() {}\n").unwrap();
+        Command::new("git")
+            .args(["add", "."])
+            .current_dir(p)
+            .output()
+            .unwrap();
+        let files = diff_against_base(p, "HEAD", Some("packages/foo")).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].new_path.as_deref(), Some("packages/foo/a.rs"));
+    }
+
+    #[test]
+    fn diff_with_histogram_algorithm_parses_into_valid_hunks() {
+        let dir = setup_repo();
+        let p = dir.path();
+        let functions = [
+            "fn a() {\n    1\n}\n",
+            "fn b() {\n    2\n}\n",
+            "fn c() {\n    3\n}\n",
+        ];
+        std::fs::write(p.join("hello.rs"), functions.concat()).unwrap();
+        Command::new("git")
+            .args(["commit", "-am", "functions"])
+            .current_dir(p)
+            .output()
+            .unwrap();
+        // Move the first function to the end
+        let reordered = [functions[1], functions[2], functions[0]].concat();
+        std::fs::write(p.join("hello.rs"), reordered).unwrap();
+
+        let options = DiffOptions {
+            algorithm: DiffAlgorithm::Histogram,
+            ..DiffOptions::default()
+        };
+        let files = diff_against_base_with_options(p, "HEAD", None, &options).unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(!files[0].hunks.is_empty());
+        for hunk in &files[0].hunks {
+            let count = |kind: LineKind| hunk.lines.iter().filter(|l| l.kind == kind).count();
+            let context = count(LineKind::Context);
+            assert_eq!(context + count(LineKind::Removed), hunk.old_count as usize);
+            assert_eq!(context + count(LineKind::Added), hunk.new_count as usize);
+        }
+    }
+
+    /// Commit a file, then move it and tweak one line, staging the move.
+    /// Rename detection is switched off in the repo config to show `-M` is
+    /// passed explicitly.
+    fn setup_moved_file() -> TempDir {
+        let dir = setup_repo();
+        let p = dir.path();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(p)
+                .output()
+                .unwrap();
+        };
+        let content: String = (1..=20).map(|i| format!("line {i}\n")).collect();
+        std::fs::write(p.join("before.txt"), &content).unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "add before.txt"]);
+        git(&["config", "diff.renames", "false"]);
+
+        std::fs::remove_file(p.join("before.txt")).unwrap();
+        std::fs::write(
+            p.join("after.txt"),
+            content.replace("line 7\n", "line seven\n"),
+        )
+        .unwrap();
+        git(&["add", "-A"]);
+        dir
+    }
+
+    #[test]
+    fn diff_detects_modified_rename() {
+        let dir = setup_moved_file();
+        let files = diff_against_base(dir.path(), "HEAD", None).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].status, crate::diff::FileStatus::Renamed);
+        assert_eq!(files[0].old_path.as_deref(), Some("before.txt"));
+        assert_eq!(files[0].new_path.as_deref(), Some("after.txt"));
+    }
+
+    #[test]
+    fn diff_rename_threshold_is_respected() {
+        let dir = setup_moved_file();
+        // The move changed a line, so it is not a 100% match
+        let files = diff_against_base_with_threshold(dir.path(), "HEAD", None, 100).unwrap();
+        let statuses: Vec<_> = files.iter().map(|f| f.status.clone()).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                crate::diff::FileStatus::Added,
+                crate::diff::FileStatus::Deleted
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_single_commit_ignores_working_tree() {
+        let dir = setup_repo();
+        let p = dir.path();
+        std::fs::write(p.join("added.rs"), "fn added() {}\n").unwrap();
+        Command::new("git")
+            .args(["add", "."])
+            .current_dir(p)
+            .output()
+            .unwrap();
+        Command::new("git")
+            .args(["commit", "-m", "add file"])
+            .current_dir(p)
+            .output()
+            .unwrap();
+        let commit = String::from_utf8(
+            Command::new("git")
+                .args(["rev-parse", "HEAD"])
+                .current_dir(p)
+                .output()
+                .unwrap()
+                .stdout,
+        )
+        .unwrap()
+        .trim()
+        .to_string();
+        // Later, uncommitted work isn't part of the commit
+        std::fs::write(p.join("hello.rs"), "fn main() { todo!() }\n").unwrap();
+
+        let files = diff_against_base(p, &format!("{commit}^!"), None).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].new_path.as_deref(), Some("added.rs"));
+        assert_eq!(files[0].status, crate::diff::FileStatus::Added);
+    }
+
+    #[test]
+    fn diff_single_commit_rejects_unknown_or_root_commit() {
+        let dir = setup_repo();
+        let result = diff_against_base(dir.path(), "deadbeef^!", None);
+        assert!(matches!(result, Err(GitDiffError::BadCommit(_))));
+        // The only commit has no parent
+        let result = diff_against_base(dir.path(), "HEAD^!", None);
+        assert!(matches!(result, Err(GitDiffError::BadCommit(_))));
+    }
+
+    #[test]
+    fn resolve_commit_returns_full_hash() {
+        let dir = setup_repo();
+        let head = Command::new("git")
+            .args(["rev-parse", "HEAD"])
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        let head = String::from_utf8(head.stdout).unwrap();
+        assert_eq!(resolve_commit(dir.path(), "HEAD").unwrap(), head.trim());
+        assert!(matches!(
+            resolve_commit(dir.path(), "deadbeef"),
+            Err(GitDiffError::BadCommit(_))
+        ));
+    }
+
+    #[test]
+    fn diff_accepts_tags_remote_refs_and_short_hashes() {
+        let dir = setup_repo();
+        let p = dir.path();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(p)
+                .output()
+                .unwrap()
+        };
+        git(&["tag", "v1"]);
+        git(&["tag", "-a", "v1-annotated", "-m", "release"]);
+        git(&["update-ref", "refs/remotes/origin/main", "HEAD"]);
+        let head = String::from_utf8(git(&["rev-parse", "HEAD"]).stdout).unwrap();
+        std::fs::write(p.join("hello.rs"), "fn main() { println!(\"hi\"); }\n").unwrap();
+
+        for base_ref in ["v1", "v1-annotated", "origin/main", &head.trim()[..7]] {
+            let files = diff_against_base(p, base_ref, None).unwrap();
+            assert_eq!(files.len(), 1, "{base_ref}");
+        }
+    }
+
+    #[test]
+    fn diff_rejects_unknown_base_ref() {
+        let dir = setup_repo();
+        let result = diff_against_base(dir.path(), "v9.9-typo", None);
+        assert!(matches!(result, Err(GitDiffError::BadRef(ref r)) if r == "v9.9-typo"));
+    }
+
+    #[test]
+    fn content_refs_for_single_commit() {
+        assert_eq!(old_content_ref("abc123^!"), "abc123^");
+        assert_eq!(new_content_ref("abc123^!"), Some("abc123"));
+        assert_eq!(old_content_ref("main"), "main");
+        assert_eq!(new_content_ref("main"), None);
+        assert_eq!(single_commit("^!"), None);
+    }
+
     #[test]
     fn diff_not_a_repo() {
         let dir = TempDir::new().unwrap();
-        let result = diff_against_base(dir.path(), "HEAD");
+        let result = diff_against_base(dir.path(), "HEAD", None);
         assert!(matches!(result, Err(GitDiffError::NotAGitRepo)));
     }
 
@@ -255,10 +734,55 @@ mod tests {
         assert!(verify.status.success());
     }
 
+    #[test]
+    fn resolve_base_ref_auto_uses_merge_base_not_head() {
+        let dir = setup_repo();
+        let p = dir.path();
+        let git = |args: &[&str]| {
+            let output = Command::new("git")
+                .args(args)
+                .current_dir(p)
+                .output()
+                .unwrap();
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+
+        git(&["branch", "-M", "main"]);
+        let branch_point = git(&["rev-parse", "HEAD"]);
+        git(&["checkout", "-b", "feature"]);
+        std::fs::write(p.join("feature.rs"), "fn feature() {}\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "feature"]);
+        let feature_tip = git(&["rev-parse", "HEAD"]);
+
+        for requested in [None, Some(AUTO_BASE_REF)] {
+            let base = resolve_base_ref(p, requested);
+            assert_eq!(base, branch_point);
+            assert_ne!(base, feature_tip);
+        }
+        assert_eq!(resolve_base_ref(p, Some("HEAD")), "HEAD");
+    }
+
     #[test]
     fn detect_default_base_not_a_repo() {
         let dir = TempDir::new().unwrap();
         let base = detect_default_base(dir.path());
         assert_eq!(base, "HEAD");
     }
+
+    #[test]
+    fn diff_extra_repos_qualifies_paths_with_prefix() {
+        let dir = setup_repo();
+        std::fs::write(dir.path().join("hello.rs"), "fn main() { todo!() }\n").unwrap();
+        let repos = [ExtraRepo {
+            repo_path: dir.path().to_string_lossy().into_owned(),
+            base_ref: "HEAD".into(),
+            prefix: "vendor/hello".into(),
+        }];
+
+        let files = diff_extra_repos(&repos, &DiffOptions::default()).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].old_path.as_deref(), Some("vendor/hello/hello.rs"));
+        assert_eq!(files[0].new_path.as_deref(), Some("vendor/hello/hello.rs"));
+    }
 }
@@ -1,5 +1,8 @@
+use std::path::Path;
+
+use syntect::LoadingError;
 use syntect::html::ClassStyle;
-use syntect::parsing::{ScopeStack, SyntaxSet};
+use syntect::parsing::{ScopeStack, SyntaxReference, SyntaxSet};
 
 /// Holds loaded syntaxes for reuse across requests.
 pub struct Highlighter {
@@ -19,19 +22,84 @@ impl Highlighter {
         }
     }
 
-    /// Get the display name of a language by file extension.
-    pub fn language_name(&self, ext: &str) -> Option<&str> {
+    /// The built-in syntaxes plus every `.sublime-syntax` file under `dir`,
+    /// for languages the defaults miss. A missing `dir` just gives the
+    /// defaults; a syntax that fails to parse is an error.
+    pub fn with_syntax_dir(dir: &Path) -> Result<Self, LoadingError> {
+        if !dir.is_dir() {
+            return Ok(Self::new());
+        }
+        let mut builder = two_face::syntax::extra_newlines().into_builder();
+        builder.add_from_folder(dir, true)?;
+        Ok(Self {
+            syntax_set: builder.build(),
+        })
+    }
+
+    /// Look up a syntax by name or token (e.g. `"Dockerfile"`, `"python"`,
+    /// `"py"`), case-insensitively.
+    fn find_syntax_by_language(&self, language: &str) -> Option<&SyntaxReference> {
         self.syntax_set
-            .find_syntax_by_extension(ext)
+            .find_syntax_by_name(language)
+            .or_else(|| self.syntax_set.find_syntax_by_token(language))
+    }
+
+    /// Get the display name of a language by file extension, or by the
+    /// `language` override when one is given.
+    pub fn language_name(&self, ext: &str, language: Option<&str>) -> Option<&str> {
+        let syntax = match language {
+            Some(language) => self.find_syntax_by_language(language),
+            None => self.syntax_set.find_syntax_by_extension(ext),
+        };
+        syntax.map(|s| s.name.as_str())
+    }
+
+    /// Pick the syntax for a file: the `language` override if given, else by
+    /// extension, else by the first line (e.g. a `#!/usr/bin/env python`
+    /// shebang).
+    fn detect_syntax(
+        &self,
+        content: &str,
+        path: &str,
+        language: Option<&str>,
+    ) -> Option<&SyntaxReference> {
+        if let Some(language) = language {
+            return self.find_syntax_by_language(language);
+        }
+        std::path::Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| self.syntax_set.find_syntax_by_extension(ext))
+            .or_else(|| {
+                let first_line = content.lines().next()?;
+                self.syntax_set.find_syntax_by_first_line(first_line)
+            })
+    }
+
+    /// Get the display name of the language [`Self::highlight_file`] would
+    /// use for this file.
+    pub fn detect_language(
+        &self,
+        content: &str,
+        path: &str,
+        language: Option<&str>,
+    ) -> Option<&str> {
+        self.detect_syntax(content, path, language)
             .map(|s| s.name.as_str())
     }
 
     /// Highlight a file's content, returning one HTML string per line.
-    /// Returns `None` if the language is not recognized.
+    /// The syntax is picked by the file's extension, falling back to its
+    /// first line, unless `language` overrides it. Returns `None` if the
+    /// language is not recognized.
     /// Each line contains `<span class="sy-...">` elements with CSS classes.
-    pub fn highlight_file(&self, content: &str, path: &str) -> Option<Vec<String>> {
-        let ext = std::path::Path::new(path).extension()?.to_str()?;
-        let syntax = self.syntax_set.find_syntax_by_extension(ext)?;
+    pub fn highlight_file(
+        &self,
+        content: &str,
+        path: &str,
+        language: Option<&str>,
+    ) -> Option<Vec<String>> {
+        let syntax = self.detect_syntax(content, path, language)?;
 
         let mut parse_state = syntect::parsing::ParseState::new(syntax);
         let mut scope_stack = ScopeStack::new();
@@ -64,15 +132,17 @@ mod tests {
     #[test]
     fn language_name_works() {
         let hl = highlighter();
-        assert_eq!(hl.language_name("rs"), Some("Rust"));
-        assert_eq!(hl.language_name("js"), Some("JavaScript (Babel)"));
-        assert_eq!(hl.language_name("xyz123"), None);
+        assert_eq!(hl.language_name("rs", None), Some("Rust"));
+        assert_eq!(hl.language_name("js", None), Some("JavaScript (Babel)"));
+        assert_eq!(hl.language_name("xyz123", None), None);
     }
 
     #[test]
     fn highlights_rust_keyword() {
         let hl = highlighter();
-        let lines = hl.highlight_file("fn main() {}\n", "test.rs").unwrap();
+        let lines = hl
+            .highlight_file("fn main() {}\n", "test.rs", None)
+            .unwrap();
         assert_eq!(lines.len(), 1);
         // "fn" should be wrapped in a span with a sy- prefixed class
         assert!(
@@ -91,14 +161,14 @@ mod tests {
     fn highlights_multiple_lines() {
         let hl = highlighter();
         let content = "let x = 1;\nlet y = 2;\n";
-        let lines = hl.highlight_file(content, "test.rs").unwrap();
+        let lines = hl.highlight_file(content, "test.rs", None).unwrap();
         assert_eq!(lines.len(), 2);
     }
 
     #[test]
     fn returns_none_for_unknown_extension() {
         let hl = highlighter();
-        let result = hl.highlight_file("hello world\n", "test.xyz123");
+        let result = hl.highlight_file("hello world\n", "test.xyz123", None);
         assert!(result.is_none());
     }
 
@@ -106,7 +176,7 @@ mod tests {
     fn escapes_html_entities() {
         let hl = highlighter();
         let lines = hl
-            .highlight_file("let x = a < b && c > d;\n", "test.rs")
+            .highlight_file("let x = a < b && c > d;\n", "test.rs", None)
             .unwrap();
         assert_eq!(lines.len(), 1);
         // Should contain escaped entities, not raw < or >
@@ -120,14 +190,16 @@ mod tests {
     #[test]
     fn handles_empty_content() {
         let hl = highlighter();
-        let lines = hl.highlight_file("", "test.rs").unwrap();
+        let lines = hl.highlight_file("", "test.rs", None).unwrap();
         assert!(lines.is_empty());
     }
 
     #[test]
     fn handles_javascript() {
         let hl = highlighter();
-        let lines = hl.highlight_file("const x = 42;\n", "test.js").unwrap();
+        let lines = hl
+            .highlight_file("const x = 42;\n", "test.js", None)
+            .unwrap();
         assert_eq!(lines.len(), 1);
         assert!(lines[0].contains("sy-"));
     }
@@ -135,9 +207,9 @@ mod tests {
     #[test]
     fn handles_typescript() {
         let hl = highlighter();
-        assert_eq!(hl.language_name("ts"), Some("TypeScript"));
+        assert_eq!(hl.language_name("ts", None), Some("TypeScript"));
         let lines = hl
-            .highlight_file("const x: string = \"hello\";\n", "test.ts")
+            .highlight_file("const x: string = \"hello\";\n", "test.ts", None)
             .unwrap();
         assert_eq!(lines.len(), 1);
         assert!(lines[0].contains("sy-"));
@@ -146,13 +218,15 @@ mod tests {
     #[test]
     fn handles_tsx() {
         let hl = highlighter();
-        assert!(hl.language_name("tsx").is_some());
+        assert!(hl.language_name("tsx", None).is_some());
     }
 
     #[test]
     fn no_trailing_newline_in_output() {
         let hl = highlighter();
-        let lines = hl.highlight_file("fn main() {}\n", "test.rs").unwrap();
+        let lines = hl
+            .highlight_file("fn main() {}\n", "test.rs", None)
+            .unwrap();
         // Individual line strings should not end with \n
         for line in &lines {
             assert!(
@@ -161,4 +235,87 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn language_override_highlights_dockerfile() {
+        let hl = highlighter();
+        assert_eq!(hl.language_name("", Some("Dockerfile")), Some("Dockerfile"));
+        // No extension and no telling first line: nothing to go on
+        assert!(
+            hl.highlight_file("RUN cargo build\n", "Dockerfile", None)
+                .is_none()
+        );
+        let lines = hl
+            .highlight_file(
+                "RUN cargo build\nCOPY . .\n",
+                "Dockerfile",
+                Some("dockerfile"),
+            )
+            .unwrap();
+        assert_eq!(lines.len(), 2);
+        assert!(
+            lines[0].contains("sy-"),
+            "expected highlighting in: {}",
+            lines[0]
+        );
+    }
+
+    #[test]
+    fn language_override_beats_extension() {
+        let hl = highlighter();
+        assert_eq!(hl.language_name("h", Some("cpp")), Some("C++"));
+        assert!(
+            hl.highlight_file("x\n", "test.rs", Some("no-such-language"))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn detects_shebang_script_without_extension() {
+        let hl = highlighter();
+        let content = "#!/usr/bin/env python\nprint('hi')\n";
+        assert_eq!(hl.detect_language(content, "script", None), Some("Python"));
+        let lines = hl.highlight_file(content, "script", None).unwrap();
+        assert_eq!(lines.len(), 2);
+        assert!(
+            lines[1].contains("sy-"),
+            "expected highlighting in: {}",
+            lines[1]
+        );
+    }
+
+    #[test]
+    fn extension_wins_over_shebang() {
+        let hl = highlighter();
+        let content = "#!/usr/bin/env python\nfn main() {}\n";
+        assert_eq!(hl.detect_language(content, "main.rs", None), Some("Rust"));
+    }
+
+    #[test]
+    fn with_syntax_dir_adds_custom_languages() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("Toy.sublime-syntax"),
+            "%YAML 1.2\n---\nname: Toy\nfile_extensions: [toy]\nscope: source.toy\n\
+             contexts:\n  main:\n    - match: '\\bbeep\\b'\n      scope: keyword.control.toy\n",
+        )
+        .unwrap();
+
+        let hl = Highlighter::with_syntax_dir(dir.path()).unwrap();
+        assert_eq!(hl.language_name("toy", None), Some("Toy"));
+        assert_eq!(hl.language_name("rs", None), Some("Rust"));
+        let lines = hl.highlight_file("beep boop\n", "noise.toy", None).unwrap();
+        assert!(
+            lines[0].contains("sy-keyword sy-control sy-toy"),
+            "expected the custom scope in: {}",
+            lines[0]
+        );
+    }
+
+    #[test]
+    fn with_syntax_dir_missing_dir_uses_defaults() {
+        let hl = Highlighter::with_syntax_dir(Path::new("/nonexistent/syntaxes")).unwrap();
+        assert_eq!(hl.language_name("rs", None), Some("Rust"));
+        assert_eq!(hl.language_name("toy", None), None);
+    }
 }
@@ -93,6 +93,7 @@ pub fn compute_interdiff(base_content: &str, from_hunks: &[Hunk], to_hunks: &[Hu
                             old_line_no: Some(old_start + old_count - 1),
                             new_line_no: Some(new_start + new_count - 1),
                             highlighted: None,
+                            whitespace_only: false,
                         });
                     }
                     ChangeTag::Delete => {
@@ -103,6 +104,7 @@ pub fn compute_interdiff(base_content: &str, from_hunks: &[Hunk], to_hunks: &[Hu
                             old_line_no: Some(old_start + old_count - 1),
                             new_line_no: None,
                             highlighted: None,
+                            whitespace_only: false,
                         });
                     }
                     ChangeTag::Insert => {
@@ -113,6 +115,7 @@ pub fn compute_interdiff(base_content: &str, from_hunks: &[Hunk], to_hunks: &[Hu
                             old_line_no: None,
                             new_line_no: Some(new_start + new_count - 1),
                             highlighted: None,
+                            whitespace_only: false,
                         });
                     }
                 }
@@ -162,6 +165,7 @@ mod tests {
             old_line_no: Some(old),
             new_line_no: Some(new),
             highlighted: None,
+            whitespace_only: false,
         }
     }
 
@@ -172,6 +176,7 @@ mod tests {
             old_line_no: None,
             new_line_no: Some(new),
             highlighted: None,
+            whitespace_only: false,
         }
     }
 
@@ -182,6 +187,7 @@ mod tests {
             old_line_no: Some(old),
             new_line_no: None,
             highlighted: None,
+            whitespace_only: false,
         }
     }
 
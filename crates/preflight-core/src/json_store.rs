@@ -2,30 +2,23 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 
 use async_trait::async_trait;
-use chrono::Utc;
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
+use crate::memory_store::State;
 use crate::review::{
-    Comment, CommentThread, Review, ReviewStatus, Revision, ThreadOrigin, ThreadStatus,
+    Comment, CommentThread, Review, ReviewDecision, ReviewStatus, Revision, ThreadStatus,
 };
 use crate::store::{
-    AddCommentInput, CreateReviewInput, CreateRevisionInput, CreateThreadInput, ReviewStore,
-    ReviewSummary, StoreError,
+    AddCommentInput, AllThreadsFilter, CreateReviewInput, CreateRevisionInput, CreateThreadInput,
+    ReviewStore, ReviewSummary, StoreError, ThreadLimit, ThreadPage,
 };
 
-#[derive(Debug, Serialize, Deserialize, Default)]
-struct State {
-    reviews: HashMap<Uuid, Review>,
-    threads: HashMap<Uuid, CommentThread>,
-    #[serde(default)]
-    revisions: HashMap<Uuid, Revision>,
-}
-
 pub struct JsonFileStore {
     path: PathBuf,
     state: Mutex<State>,
+    pretty: bool,
 }
 
 impl JsonFileStore {
@@ -39,6 +32,7 @@ impl JsonFileStore {
         Ok(Self {
             path,
             state: Mutex::new(state),
+            pretty: false,
         })
     }
 
@@ -46,17 +40,48 @@ impl JsonFileStore {
         Self {
             path: path.into(),
             state: Mutex::new(State::default()),
+            pretty: false,
         }
     }
 
+    /// Write the state file indented for reading by hand instead of compact.
+    /// Either form loads.
+    pub fn pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+
+    /// A temp file next to the state file, so the final rename stays on one
+    /// filesystem. The random suffix keeps concurrent writers (e.g. two
+    /// processes sharing a state file) from clobbering each other's temp file.
+    fn tmp_path(&self) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".{}.tmp", Uuid::new_v4().simple()));
+        self.path.with_file_name(name)
+    }
+
     async fn persist(&self, state: &State) -> Result<(), StoreError> {
         if let Some(parent) = self.path.parent() {
-            tokio::fs::create_dir_all(parent).await?;
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                StoreError::PersistenceError(format!("creating {}: {e}", parent.display()))
+            })?;
+        }
+        let tmp = self.tmp_path();
+        let data = if self.pretty {
+            serde_json::to_string_pretty(state)?
+        } else {
+            serde_json::to_string(state)?
+        };
+        let result = match tokio::fs::write(&tmp, data).await {
+            Ok(()) => tokio::fs::rename(&tmp, &self.path)
+                .await
+                .map_err(|e| format!("replacing {}: {e}", self.path.display())),
+            Err(e) => Err(format!("writing {}: {e}", tmp.display())),
+        };
+        if let Err(msg) = result {
+            let _ = tokio::fs::remove_file(&tmp).await;
+            return Err(StoreError::PersistenceError(msg));
         }
-        let tmp = self.path.with_extension("tmp");
-        let data = serde_json::to_string_pretty(state)?;
-        tokio::fs::write(&tmp, data).await?;
-        tokio::fs::rename(&tmp, &self.path).await?;
         Ok(())
     }
 }
@@ -65,149 +90,135 @@ impl JsonFileStore {
 impl ReviewStore for JsonFileStore {
     async fn create_review(&self, input: CreateReviewInput) -> Result<Review, StoreError> {
         let mut state = self.state.lock().await;
-        let now = Utc::now();
-        let review = Review {
-            id: Uuid::new_v4(),
-            title: input.title,
-            status: ReviewStatus::Open,
-            created_at: now,
-            updated_at: now,
-            repo_path: input.repo_path,
-            base_ref: input.base_ref,
-        };
-        state.reviews.insert(review.id, review.clone());
+        let review = state.create_review(input);
         self.persist(&state).await?;
         Ok(review)
     }
 
     async fn get_review(&self, id: Uuid) -> Result<Review, StoreError> {
-        let state = self.state.lock().await;
-        state
-            .reviews
-            .get(&id)
-            .cloned()
-            .ok_or(StoreError::ReviewNotFound(id))
+        self.state.lock().await.get_review(id)
     }
 
-    // TODO: O(R*T) — pre-build a thread count map if this becomes a hot path
     async fn list_reviews(&self) -> Vec<ReviewSummary> {
-        let state = self.state.lock().await;
-        state
-            .reviews
-            .values()
-            .map(|review| {
-                let review_threads: Vec<_> = state
-                    .threads
-                    .values()
-                    .filter(|t| t.review_id == review.id)
-                    .collect();
-                let thread_count = review_threads.len();
-                let open_thread_count = review_threads
-                    .iter()
-                    .filter(|t| {
-                        t.status == ThreadStatus::Open && t.origin != ThreadOrigin::AgentExplanation
-                    })
-                    .count();
-                let file_count = state
-                    .revisions
-                    .values()
-                    .filter(|r| r.review_id == review.id)
-                    .max_by_key(|r| r.revision_number)
-                    .map(|r| r.files.len())
-                    .unwrap_or(0);
-                ReviewSummary {
-                    id: review.id,
-                    title: review.title.clone(),
-                    status: review.status.clone(),
-                    thread_count,
-                    open_thread_count,
-                    file_count,
-                }
-            })
-            .collect()
+        self.state.lock().await.list_reviews()
+    }
+
+    async fn list_reviews_by_status(&self, status: ReviewStatus) -> Vec<ReviewSummary> {
+        self.state.lock().await.list_reviews_by_status(status)
     }
 
     async fn update_review_status(&self, id: Uuid, status: ReviewStatus) -> Result<(), StoreError> {
         let mut state = self.state.lock().await;
-        let review = state
-            .reviews
-            .get_mut(&id)
-            .ok_or(StoreError::ReviewNotFound(id))?;
-        review.status = status;
-        review.updated_at = Utc::now();
-        self.persist(&state).await?;
-        Ok(())
+        state.update_review_status(id, status)?;
+        self.persist(&state).await
+    }
+
+    async fn set_labels(&self, id: Uuid, labels: Vec<String>) -> Result<(), StoreError> {
+        let mut state = self.state.lock().await;
+        state.set_labels(id, labels)?;
+        self.persist(&state).await
+    }
+
+    async fn set_decision(
+        &self,
+        id: Uuid,
+        decision: Option<ReviewDecision>,
+    ) -> Result<(), StoreError> {
+        let mut state = self.state.lock().await;
+        state.set_decision(id, decision)?;
+        self.persist(&state).await
+    }
+
+    async fn set_agent_seen(
+        &self,
+        id: Uuid,
+        seen_at: Option<DateTime<Utc>>,
+    ) -> Result<(), StoreError> {
+        let mut state = self.state.lock().await;
+        state.set_agent_seen(id, seen_at)?;
+        self.persist(&state).await
+    }
+
+    async fn set_human_seen(&self, id: Uuid, seen_at: DateTime<Utc>) -> Result<(), StoreError> {
+        let mut state = self.state.lock().await;
+        state.set_human_seen(id, seen_at)?;
+        self.persist(&state).await
     }
 
     async fn delete_review(&self, id: Uuid) -> Result<(), StoreError> {
         let mut state = self.state.lock().await;
-        if state.reviews.remove(&id).is_none() {
-            return Err(StoreError::ReviewNotFound(id));
-        }
-        state.threads.retain(|_, t| t.review_id != id);
-        state.revisions.retain(|_, r| r.review_id != id);
-        self.persist(&state).await?;
-        Ok(())
+        state.delete_review(id)?;
+        self.persist(&state).await
     }
 
     async fn delete_closed_reviews(&self) -> Result<Vec<Uuid>, StoreError> {
         let mut state = self.state.lock().await;
-        let closed_ids: Vec<Uuid> = state
-            .reviews
-            .values()
-            .filter(|r| r.status == ReviewStatus::Closed)
-            .map(|r| r.id)
-            .collect();
-        if closed_ids.is_empty() {
-            return Ok(vec![]);
-        }
-        for id in &closed_ids {
-            state.reviews.remove(id);
-            state.threads.retain(|_, t| t.review_id != *id);
-            state.revisions.retain(|_, r| r.review_id != *id);
+        let deleted = state.delete_closed_reviews();
+        if !deleted.is_empty() {
+            self.persist(&state).await?;
         }
+        Ok(deleted)
+    }
+
+    async fn create_thread(
+        &self,
+        input: CreateThreadInput,
+        limit: Option<ThreadLimit>,
+    ) -> Result<CommentThread, StoreError> {
+        let mut state = self.state.lock().await;
+        let thread = state.create_thread(input, limit)?;
         self.persist(&state).await?;
-        Ok(closed_ids)
+        Ok(thread)
     }
 
-    async fn create_thread(&self, input: CreateThreadInput) -> Result<CommentThread, StoreError> {
+    async fn create_threads(
+        &self,
+        review_id: Uuid,
+        inputs: Vec<CreateThreadInput>,
+        limit: Option<ThreadLimit>,
+    ) -> Result<Vec<CommentThread>, StoreError> {
         let mut state = self.state.lock().await;
-        if !state.reviews.contains_key(&input.review_id) {
-            return Err(StoreError::ReviewNotFound(input.review_id));
-        }
-        let now = Utc::now();
-        let initial_comment = Comment {
-            id: Uuid::new_v4(),
-            author_type: input.initial_comment_author,
-            body: input.initial_comment_body,
-            created_at: now,
-        };
-        let thread = CommentThread {
-            id: Uuid::new_v4(),
-            review_id: input.review_id,
-            file_path: input.file_path,
-            line_start: input.line_start,
-            line_end: input.line_end,
-            origin: input.origin,
-            status: ThreadStatus::Open,
-            comments: vec![initial_comment],
-            created_at: now,
-            updated_at: now,
-            revision_number: input.revision_number,
-            content_snippet: input.content_snippet,
-        };
-        state.threads.insert(thread.id, thread.clone());
+        let threads = state.create_threads(review_id, inputs, limit)?;
+        self.persist(&state).await?;
+        Ok(threads)
+    }
+
+    async fn copy_threads(
+        &self,
+        review_id: Uuid,
+        threads: Vec<CommentThread>,
+        limit: Option<ThreadLimit>,
+    ) -> Result<Vec<CommentThread>, StoreError> {
+        let mut state = self.state.lock().await;
+        let copies = state.copy_threads(review_id, threads, limit)?;
+        self.persist(&state).await?;
+        Ok(copies)
+    }
+
+    async fn set_thread_pinned(
+        &self,
+        thread_id: Uuid,
+        pinned: bool,
+    ) -> Result<CommentThread, StoreError> {
+        let mut state = self.state.lock().await;
+        let thread = state.set_thread_pinned(thread_id, pinned)?;
+        self.persist(&state).await?;
+        Ok(thread)
+    }
+
+    async fn link_threads(
+        &self,
+        thread_id: Uuid,
+        related: Vec<Uuid>,
+    ) -> Result<CommentThread, StoreError> {
+        let mut state = self.state.lock().await;
+        let thread = state.link_threads(thread_id, related)?;
         self.persist(&state).await?;
         Ok(thread)
     }
 
     async fn get_thread(&self, thread_id: Uuid) -> Result<CommentThread, StoreError> {
-        let state = self.state.lock().await;
-        state
-            .threads
-            .get(&thread_id)
-            .cloned()
-            .ok_or(StoreError::ThreadNotFound(thread_id))
+        self.state.lock().await.get_thread(thread_id)
     }
 
     async fn get_threads(
@@ -215,93 +226,61 @@ impl ReviewStore for JsonFileStore {
         review_id: Uuid,
         file_path: Option<&str>,
     ) -> Result<Vec<CommentThread>, StoreError> {
-        let state = self.state.lock().await;
-        if !state.reviews.contains_key(&review_id) {
-            return Err(StoreError::ReviewNotFound(review_id));
-        }
-        let threads = state
-            .threads
-            .values()
-            .filter(|t| t.review_id == review_id && file_path.is_none_or(|fp| t.file_path == fp))
-            .cloned()
-            .collect();
-        Ok(threads)
+        self.state.lock().await.get_threads(review_id, file_path)
+    }
+
+    async fn all_threads(&self, filter: &AllThreadsFilter) -> ThreadPage {
+        self.state.lock().await.all_threads(filter)
     }
 
     async fn update_thread_status(
         &self,
         thread_id: Uuid,
         status: ThreadStatus,
+        expected_version: Option<u64>,
+        limit: Option<ThreadLimit>,
     ) -> Result<(), StoreError> {
         let mut state = self.state.lock().await;
-        let thread = state
-            .threads
-            .get_mut(&thread_id)
-            .ok_or(StoreError::ThreadNotFound(thread_id))?;
-        thread.status = status;
-        thread.updated_at = Utc::now();
-        self.persist(&state).await?;
-        Ok(())
+        state.update_thread_status(thread_id, status, expected_version, limit)?;
+        self.persist(&state).await
+    }
+
+    async fn get_comment(&self, thread_id: Uuid, comment_id: Uuid) -> Result<Comment, StoreError> {
+        self.state.lock().await.get_comment(thread_id, comment_id)
+    }
+
+    async fn get_thread_comments(
+        &self,
+        thread_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<Comment>>, StoreError> {
+        self.state.lock().await.get_thread_comments(thread_ids)
     }
 
     async fn add_comment(&self, input: AddCommentInput) -> Result<Comment, StoreError> {
         let mut state = self.state.lock().await;
-        let thread = state
-            .threads
-            .get_mut(&input.thread_id)
-            .ok_or(StoreError::ThreadNotFound(input.thread_id))?;
-        let comment = Comment {
-            id: Uuid::new_v4(),
-            author_type: input.author_type,
-            body: input.body,
-            created_at: Utc::now(),
-        };
-        thread.comments.push(comment.clone());
-        thread.updated_at = Utc::now();
+        let comment = state.add_comment(input)?;
         self.persist(&state).await?;
         Ok(comment)
     }
 
-    async fn create_revision(&self, input: CreateRevisionInput) -> Result<Revision, StoreError> {
+    async fn publish_drafts(&self, review_id: Uuid) -> Result<Vec<(Uuid, Comment)>, StoreError> {
         let mut state = self.state.lock().await;
-        if !state.reviews.contains_key(&input.review_id) {
-            return Err(StoreError::ReviewNotFound(input.review_id));
+        let published = state.publish_drafts(review_id)?;
+        if !published.is_empty() {
+            self.persist(&state).await?;
         }
-        let next_number = state
-            .revisions
-            .values()
-            .filter(|r| r.review_id == input.review_id)
-            .map(|r| r.revision_number)
-            .max()
-            .unwrap_or(0)
-            + 1;
-        let revision = Revision {
-            id: Uuid::new_v4(),
-            review_id: input.review_id,
-            revision_number: next_number,
-            trigger: input.trigger,
-            message: input.message,
-            files: input.files,
-            created_at: Utc::now(),
-        };
-        state.revisions.insert(revision.id, revision.clone());
+        Ok(published)
+    }
+
+    async fn create_revision(&self, input: CreateRevisionInput) -> Result<Revision, StoreError> {
+        let mut state = self.state.lock().await;
+        let revision = state.create_revision(input)?;
         self.persist(&state).await?;
         Ok(revision)
     }
 
     async fn get_revisions(&self, review_id: Uuid) -> Result<Vec<Revision>, StoreError> {
-        let state = self.state.lock().await;
-        if !state.reviews.contains_key(&review_id) {
-            return Err(StoreError::ReviewNotFound(review_id));
-        }
-        let mut revisions: Vec<Revision> = state
-            .revisions
-            .values()
-            .filter(|r| r.review_id == review_id)
-            .cloned()
-            .collect();
-        revisions.sort_by_key(|r| r.revision_number);
-        Ok(revisions)
+        self.state.lock().await.get_revisions(review_id)
     }
 
     async fn get_revision(
@@ -309,37 +288,35 @@ impl ReviewStore for JsonFileStore {
         review_id: Uuid,
         revision_number: u32,
     ) -> Result<Revision, StoreError> {
-        let state = self.state.lock().await;
-        if !state.reviews.contains_key(&review_id) {
-            return Err(StoreError::ReviewNotFound(review_id));
-        }
-        state
-            .revisions
-            .values()
-            .find(|r| r.review_id == review_id && r.revision_number == revision_number)
-            .cloned()
-            .ok_or(StoreError::RevisionNotFound(review_id))
+        self.state
+            .lock()
+            .await
+            .get_revision(review_id, revision_number)
     }
 
     async fn get_latest_revision(&self, review_id: Uuid) -> Result<Revision, StoreError> {
+        self.state.lock().await.get_latest_revision(review_id)
+    }
+
+    async fn snapshot(&self) -> Result<serde_json::Value, StoreError> {
         let state = self.state.lock().await;
-        if !state.reviews.contains_key(&review_id) {
-            return Err(StoreError::ReviewNotFound(review_id));
-        }
-        state
-            .revisions
-            .values()
-            .filter(|r| r.review_id == review_id)
-            .max_by_key(|r| r.revision_number)
-            .cloned()
-            .ok_or(StoreError::RevisionNotFound(review_id))
+        Ok(serde_json::to_value(&*state)?)
+    }
+
+    async fn restore(&self, snapshot: serde_json::Value) -> Result<(), StoreError> {
+        let restored = State::from_snapshot(snapshot)?;
+        let mut state = self.state.lock().await;
+        self.persist(&restored).await?;
+        *state = restored;
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::review::{AuthorType, ThreadOrigin};
+    use crate::review::{AuthorType, LineSide, ThreadOrigin};
+    use crate::store_tests::create_review_with_store;
     use tempfile::TempDir;
 
     async fn test_store() -> (JsonFileStore, TempDir) {
@@ -349,83 +326,7 @@ mod tests {
         (store, dir)
     }
 
-    async fn create_review_with_store(store: &JsonFileStore) -> Review {
-        store
-            .create_review(CreateReviewInput {
-                title: Some("Test".into()),
-                repo_path: "/tmp/test-repo".into(),
-                base_ref: "HEAD".into(),
-            })
-            .await
-            .unwrap()
-    }
-
-    #[tokio::test]
-    async fn test_create_and_get_review() {
-        let (store, _dir) = test_store().await;
-        let review = store
-            .create_review(CreateReviewInput {
-                title: Some("Test review".into()),
-                repo_path: "/tmp/test-repo".into(),
-                base_ref: "HEAD".into(),
-            })
-            .await
-            .unwrap();
-        assert_eq!(review.title.as_deref(), Some("Test review"));
-        assert_eq!(review.status, ReviewStatus::Open);
-        let fetched = store.get_review(review.id).await.unwrap();
-        assert_eq!(fetched.id, review.id);
-    }
-
-    #[tokio::test]
-    async fn test_get_review_not_found() {
-        let (store, _dir) = test_store().await;
-        let result = store.get_review(Uuid::new_v4()).await;
-        assert!(matches!(result, Err(StoreError::ReviewNotFound(_))));
-    }
-
-    #[tokio::test]
-    async fn test_list_reviews() {
-        let (store, _dir) = test_store().await;
-        assert!(store.list_reviews().await.is_empty());
-        store
-            .create_review(CreateReviewInput {
-                title: Some("First".into()),
-                repo_path: "/tmp/repo1".into(),
-                base_ref: "HEAD".into(),
-            })
-            .await
-            .unwrap();
-        store
-            .create_review(CreateReviewInput {
-                title: Some("Second".into()),
-                repo_path: "/tmp/repo2".into(),
-                base_ref: "HEAD".into(),
-            })
-            .await
-            .unwrap();
-        let list = store.list_reviews().await;
-        assert_eq!(list.len(), 2);
-    }
-
-    #[tokio::test]
-    async fn test_update_review_status() {
-        let (store, _dir) = test_store().await;
-        let review = store
-            .create_review(CreateReviewInput {
-                title: None,
-                repo_path: "/tmp/repo".into(),
-                base_ref: "HEAD".into(),
-            })
-            .await
-            .unwrap();
-        store
-            .update_review_status(review.id, ReviewStatus::Closed)
-            .await
-            .unwrap();
-        let updated = store.get_review(review.id).await.unwrap();
-        assert_eq!(updated.status, ReviewStatus::Closed);
-    }
+    crate::store_tests::review_store_tests!(test_store);
 
     #[tokio::test]
     async fn test_persistence_across_instances() {
@@ -436,8 +337,12 @@ mod tests {
             store
                 .create_review(CreateReviewInput {
                     title: Some("Persisted".into()),
-                    repo_path: "/tmp/repo".into(),
+                    repo_path: Some("/tmp/repo".into()),
                     base_ref: "HEAD".into(),
+                    path_prefix: None,
+                    extra_repos: Vec::new(),
+                    labels: Vec::new(),
+                    diff_algorithm: Default::default(),
                 })
                 .await
                 .unwrap();
@@ -451,187 +356,84 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_create_and_get_thread() {
-        let (store, _dir) = test_store().await;
-        let review = create_review_with_store(&store).await;
-        let thread = store
-            .create_thread(CreateThreadInput {
-                review_id: review.id,
-                file_path: "src/main.rs".into(),
-                line_start: 10,
-                line_end: 15,
-                origin: ThreadOrigin::Comment,
-                initial_comment_body: "Looks wrong".into(),
-                initial_comment_author: AuthorType::Human,
-                revision_number: None,
-                content_snippet: None,
-            })
-            .await
-            .unwrap();
-        assert_eq!(thread.file_path, "src/main.rs");
-        assert_eq!(thread.line_start, 10);
-        assert_eq!(thread.line_end, 15);
-        assert_eq!(thread.comments.len(), 1);
-        assert_eq!(thread.comments[0].body, "Looks wrong");
-        assert_eq!(thread.comments[0].author_type, AuthorType::Human);
-    }
-
-    #[tokio::test]
-    async fn test_create_thread_review_not_found() {
-        let (store, _dir) = test_store().await;
-        let result = store
-            .create_thread(CreateThreadInput {
-                review_id: Uuid::new_v4(),
-                file_path: "x".into(),
-                line_start: 1,
-                line_end: 1,
-                origin: ThreadOrigin::Comment,
-                initial_comment_body: "hi".into(),
-                initial_comment_author: AuthorType::Human,
-                revision_number: None,
-                content_snippet: None,
-            })
-            .await;
-        assert!(matches!(result, Err(StoreError::ReviewNotFound(_))));
-    }
-
-    #[tokio::test]
-    async fn test_get_threads_review_not_found() {
-        let (store, _dir) = test_store().await;
-        let result = store.get_threads(Uuid::new_v4(), None).await;
-        assert!(matches!(result, Err(StoreError::ReviewNotFound(_))));
-    }
-
-    #[tokio::test]
-    async fn test_get_threads_filtered_by_file() {
-        let (store, _dir) = test_store().await;
-        let review = create_review_with_store(&store).await;
-        store
-            .create_thread(CreateThreadInput {
-                review_id: review.id,
-                file_path: "src/a.rs".into(),
-                line_start: 1,
-                line_end: 1,
-                origin: ThreadOrigin::Comment,
-                initial_comment_body: "a".into(),
-                initial_comment_author: AuthorType::Human,
-                revision_number: None,
-                content_snippet: None,
-            })
-            .await
-            .unwrap();
-        store
-            .create_thread(CreateThreadInput {
-                review_id: review.id,
-                file_path: "src/b.rs".into(),
-                line_start: 1,
-                line_end: 1,
-                origin: ThreadOrigin::ExplanationRequest,
-                initial_comment_body: "b".into(),
-                initial_comment_author: AuthorType::Human,
-                revision_number: None,
-                content_snippet: None,
-            })
-            .await
-            .unwrap();
-        let all = store.get_threads(review.id, None).await.unwrap();
-        assert_eq!(all.len(), 2);
-        let filtered = store
-            .get_threads(review.id, Some("src/a.rs"))
-            .await
-            .unwrap();
-        assert_eq!(filtered.len(), 1);
-        assert_eq!(filtered[0].file_path, "src/a.rs");
-    }
-
-    #[tokio::test]
-    async fn test_update_thread_status() {
-        let (store, _dir) = test_store().await;
-        let review = create_review_with_store(&store).await;
-        let thread = store
-            .create_thread(CreateThreadInput {
-                review_id: review.id,
-                file_path: "src/main.rs".into(),
-                line_start: 1,
-                line_end: 1,
-                origin: ThreadOrigin::Comment,
-                initial_comment_body: "fix this".into(),
-                initial_comment_author: AuthorType::Human,
-                revision_number: None,
-                content_snippet: None,
-            })
-            .await
-            .unwrap();
-        store
-            .update_thread_status(thread.id, ThreadStatus::Resolved)
-            .await
-            .unwrap();
-        let threads = store.get_threads(review.id, None).await.unwrap();
-        assert_eq!(threads[0].status, ThreadStatus::Resolved);
-    }
-
-    #[tokio::test]
-    async fn test_add_comment_to_thread() {
-        let (store, _dir) = test_store().await;
-        let review = create_review_with_store(&store).await;
-        let thread = store
-            .create_thread(CreateThreadInput {
-                review_id: review.id,
-                file_path: "src/main.rs".into(),
-                line_start: 1,
-                line_end: 1,
-                origin: ThreadOrigin::Comment,
-                initial_comment_body: "why?".into(),
-                initial_comment_author: AuthorType::Human,
-                revision_number: None,
-                content_snippet: None,
-            })
-            .await
-            .unwrap();
-        let comment = store
-            .add_comment(AddCommentInput {
-                thread_id: thread.id,
-                author_type: AuthorType::Agent,
-                body: "because X".into(),
-            })
-            .await
-            .unwrap();
-        assert_eq!(comment.author_type, AuthorType::Agent);
-        assert_eq!(comment.body, "because X");
-        let threads = store.get_threads(review.id, None).await.unwrap();
-        assert_eq!(threads[0].comments.len(), 2);
-    }
+    async fn test_pretty_and_compact_state_load_the_same() {
+        let dir = TempDir::new().unwrap();
+        let mut loaded = Vec::new();
+        for pretty in [false, true] {
+            let path = dir.path().join(format!("state-{pretty}.json"));
+            let store = JsonFileStore::new(&path).await.unwrap().pretty(pretty);
+            let review = create_review_with_store(&store).await;
+            store
+                .create_thread(
+                    CreateThreadInput {
+                        review_id: review.id,
+                        file_path: "src/main.rs".into(),
+                        line_start: 1,
+                        line_end: 2,
+                        side: LineSide::New,
+                        origin: ThreadOrigin::Comment,
+                        initial_comment_body: "Why?".into(),
+                        initial_comment_author: AuthorType::Human,
+                        revision_number: None,
+                        content_snippet: None,
+                        initial_comment_suggestion: None,
+                    },
+                    None,
+                )
+                .await
+                .unwrap();
 
-    #[tokio::test]
-    async fn test_add_comment_thread_not_found() {
-        let (store, _dir) = test_store().await;
-        let result = store
-            .add_comment(AddCommentInput {
-                thread_id: Uuid::new_v4(),
-                author_type: AuthorType::Human,
-                body: "hello".into(),
-            })
-            .await;
-        assert!(matches!(result, Err(StoreError::ThreadNotFound(_))));
+            let data = std::fs::read_to_string(&path).unwrap();
+            assert_eq!(data.contains('\n'), pretty);
+            let reloaded = JsonFileStore::new(&path).await.unwrap();
+            let review = reloaded.get_review(review.id).await.unwrap();
+            let threads = reloaded.get_threads(review.id, None).await.unwrap();
+            loaded.push((
+                review.title,
+                threads.len(),
+                threads[0].comments[0].body.clone(),
+            ));
+        }
+        assert_eq!(loaded[0], loaded[1]);
     }
 
     #[tokio::test]
-    async fn test_create_review_with_repo_path() {
-        let (store, _dir) = test_store().await;
-        let review = store
-            .create_review(CreateReviewInput {
-                title: Some("Repo test".into()),
-                repo_path: "/tmp/fake-repo".into(),
-                base_ref: "HEAD~1".into(),
-            })
-            .await
-            .unwrap();
-        assert_eq!(review.repo_path, "/tmp/fake-repo");
-        assert_eq!(review.base_ref, "HEAD~1");
+    async fn test_concurrent_persists_leave_valid_state() {
+        async fn create_reviews(store: &JsonFileStore) {
+            for i in 0..10 {
+                store
+                    .create_review(CreateReviewInput {
+                        title: Some(format!("Review {i}")),
+                        repo_path: Some("/tmp/repo".into()),
+                        base_ref: "main".into(),
+                        path_prefix: None,
+                        extra_repos: Vec::new(),
+                        labels: Vec::new(),
+                        diff_algorithm: Default::default(),
+                    })
+                    .await
+                    .unwrap();
+            }
+        }
 
-        let fetched = store.get_review(review.id).await.unwrap();
-        assert_eq!(fetched.repo_path, "/tmp/fake-repo");
-        assert_eq!(fetched.base_ref, "HEAD~1");
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("state.json");
+        // Two stores sharing one file race on every write
+        let first = JsonFileStore::new(&path).await.unwrap();
+        let second = JsonFileStore::new(&path).await.unwrap();
+        tokio::join!(create_reviews(&first), create_reviews(&second));
+
+        let reloaded = JsonFileStore::new(&path).await.unwrap();
+        assert_eq!(reloaded.list_reviews().await.len(), 10);
+        let leftovers: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .filter(|name| name != "state.json")
+            .collect();
+        assert!(
+            leftovers.is_empty(),
+            "temp files left behind: {leftovers:?}"
+        );
     }
 
     #[tokio::test]
@@ -644,17 +446,22 @@ mod tests {
             let review = create_review_with_store(&store).await;
             review_id = review.id;
             store
-                .create_thread(CreateThreadInput {
-                    review_id: review.id,
-                    file_path: "src/main.rs".into(),
-                    line_start: 1,
-                    line_end: 1,
-                    origin: ThreadOrigin::AgentExplanation,
-                    initial_comment_body: "This does X".into(),
-                    initial_comment_author: AuthorType::Agent,
-                    revision_number: None,
-                    content_snippet: None,
-                })
+                .create_thread(
+                    CreateThreadInput {
+                        review_id: review.id,
+                        file_path: "src/main.rs".into(),
+                        line_start: 1,
+                        line_end: 1,
+                        origin: ThreadOrigin::AgentExplanation,
+                        initial_comment_body: "This does X".into(),
+                        initial_comment_author: AuthorType::Agent,
+                        revision_number: None,
+                        content_snippet: None,
+                        initial_comment_suggestion: None,
+                        side: LineSide::New,
+                    },
+                    None,
+                )
                 .await
                 .unwrap();
         }
@@ -666,135 +473,6 @@ mod tests {
         }
     }
 
-    #[tokio::test]
-    async fn test_create_and_get_revision() {
-        use crate::diff::{FileDiff, FileStatus};
-        use crate::review::RevisionTrigger;
-
-        let (store, _dir) = test_store().await;
-        let review = create_review_with_store(&store).await;
-        let file = FileDiff {
-            old_path: None,
-            new_path: Some("src/main.rs".into()),
-            status: FileStatus::Added,
-            hunks: vec![],
-        };
-        let revision = store
-            .create_revision(CreateRevisionInput {
-                review_id: review.id,
-                trigger: RevisionTrigger::Agent,
-                message: Some("Initial diff".into()),
-                files: vec![file],
-            })
-            .await
-            .unwrap();
-        assert_eq!(revision.revision_number, 1);
-        assert_eq!(revision.review_id, review.id);
-        assert_eq!(revision.files.len(), 1);
-        assert_eq!(revision.message.as_deref(), Some("Initial diff"));
-
-        let fetched = store.get_revision(review.id, 1).await.unwrap();
-        assert_eq!(fetched.id, revision.id);
-        assert_eq!(fetched.revision_number, 1);
-    }
-
-    #[tokio::test]
-    async fn test_revision_numbers_increment() {
-        use crate::review::RevisionTrigger;
-
-        let (store, _dir) = test_store().await;
-        let review = create_review_with_store(&store).await;
-        let r1 = store
-            .create_revision(CreateRevisionInput {
-                review_id: review.id,
-                trigger: RevisionTrigger::Agent,
-                message: None,
-                files: vec![],
-            })
-            .await
-            .unwrap();
-        let r2 = store
-            .create_revision(CreateRevisionInput {
-                review_id: review.id,
-                trigger: RevisionTrigger::Manual,
-                message: None,
-                files: vec![],
-            })
-            .await
-            .unwrap();
-        assert_eq!(r1.revision_number, 1);
-        assert_eq!(r2.revision_number, 2);
-    }
-
-    #[tokio::test]
-    async fn test_get_revisions_sorted() {
-        use crate::review::RevisionTrigger;
-
-        let (store, _dir) = test_store().await;
-        let review = create_review_with_store(&store).await;
-        for _ in 0..3 {
-            store
-                .create_revision(CreateRevisionInput {
-                    review_id: review.id,
-                    trigger: RevisionTrigger::Agent,
-                    message: None,
-                    files: vec![],
-                })
-                .await
-                .unwrap();
-        }
-        let revisions = store.get_revisions(review.id).await.unwrap();
-        assert_eq!(revisions.len(), 3);
-        assert_eq!(revisions[0].revision_number, 1);
-        assert_eq!(revisions[1].revision_number, 2);
-        assert_eq!(revisions[2].revision_number, 3);
-    }
-
-    #[tokio::test]
-    async fn test_get_latest_revision() {
-        use crate::review::RevisionTrigger;
-
-        let (store, _dir) = test_store().await;
-        let review = create_review_with_store(&store).await;
-        store
-            .create_revision(CreateRevisionInput {
-                review_id: review.id,
-                trigger: RevisionTrigger::Agent,
-                message: Some("first".into()),
-                files: vec![],
-            })
-            .await
-            .unwrap();
-        store
-            .create_revision(CreateRevisionInput {
-                review_id: review.id,
-                trigger: RevisionTrigger::Manual,
-                message: Some("second".into()),
-                files: vec![],
-            })
-            .await
-            .unwrap();
-        let latest = store.get_latest_revision(review.id).await.unwrap();
-        assert_eq!(latest.revision_number, 2);
-        assert_eq!(latest.message.as_deref(), Some("second"));
-    }
-
-    #[tokio::test]
-    async fn test_create_revision_review_not_found() {
-        use crate::review::RevisionTrigger;
-
-        let (store, _dir) = test_store().await;
-        let result = store
-            .create_revision(CreateRevisionInput {
-                review_id: Uuid::new_v4(),
-                trigger: RevisionTrigger::Agent,
-                message: None,
-                files: vec![],
-            })
-            .await;
-        assert!(matches!(result, Err(StoreError::ReviewNotFound(_))));
-    }
-
     #[tokio::test]
     async fn test_corrupted_state_file_returns_error() {
         let dir = TempDir::new().unwrap();
@@ -807,59 +485,30 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_list_reviews_open_thread_count() {
-        let (store, _dir) = test_store().await;
-        let review = create_review_with_store(&store).await;
-        // Create two threads
-        for body in ["first", "second"] {
-            store
-                .create_thread(CreateThreadInput {
-                    review_id: review.id,
-                    file_path: "src/main.rs".into(),
-                    line_start: 1,
-                    line_end: 1,
-                    origin: ThreadOrigin::Comment,
-                    initial_comment_body: body.into(),
-                    initial_comment_author: AuthorType::Human,
-                    revision_number: None,
-                    content_snippet: None,
-                })
-                .await
-                .unwrap();
-        }
-        // Both open
-        let list = store.list_reviews().await;
-        assert_eq!(list[0].thread_count, 2);
-        assert_eq!(list[0].open_thread_count, 2);
-
-        // Resolve one
-        let threads = store.get_threads(review.id, None).await.unwrap();
-        store
-            .update_thread_status(threads[0].id, ThreadStatus::Resolved)
-            .await
-            .unwrap();
-        let list = store.list_reviews().await;
-        assert_eq!(list[0].thread_count, 2);
-        assert_eq!(list[0].open_thread_count, 1);
-
-        // AgentExplanation threads should not count as open
-        store
-            .create_thread(CreateThreadInput {
-                review_id: review.id,
-                file_path: "src/main.rs".into(),
-                line_start: 1,
-                line_end: 1,
-                origin: ThreadOrigin::AgentExplanation,
-                initial_comment_body: "This does X".into(),
-                initial_comment_author: AuthorType::Agent,
-                revision_number: None,
-                content_snippet: None,
-            })
-            .await
-            .unwrap();
-        let list = store.list_reviews().await;
-        assert_eq!(list[0].thread_count, 3);
-        assert_eq!(list[0].open_thread_count, 1); // still 1, AgentExplanation excluded
+    async fn test_loads_state_saved_before_labels() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("state.json");
+        let id = Uuid::new_v4();
+        let state = serde_json::json!({
+            "reviews": {
+                id.to_string(): {
+                    "id": id,
+                    "title": "Old",
+                    "status": "Open",
+                    "created_at": "2025-01-01T00:00:00Z",
+                    "updated_at": "2025-01-01T00:00:00Z",
+                    "repo_path": "/tmp/repo",
+                    "base_ref": "HEAD"
+                }
+            },
+            "threads": {}
+        });
+        tokio::fs::write(&path, state.to_string()).await.unwrap();
+
+        let store = JsonFileStore::new(&path).await.unwrap();
+        let review = store.get_review(id).await.unwrap();
+        assert_eq!(review.title.as_deref(), Some("Old"));
+        assert!(review.labels.is_empty());
     }
 
     #[tokio::test]
@@ -873,109 +522,6 @@ mod tests {
         assert!(store.list_reviews().await.is_empty());
     }
 
-    #[tokio::test]
-    async fn test_delete_review_removes_review_and_associated_data() {
-        use crate::review::{AuthorType, RevisionTrigger, ThreadOrigin};
-
-        let (store, _dir) = test_store().await;
-        let review = create_review_with_store(&store).await;
-
-        store
-            .create_thread(CreateThreadInput {
-                review_id: review.id,
-                file_path: "src/main.rs".into(),
-                line_start: 1,
-                line_end: 1,
-                origin: ThreadOrigin::Comment,
-                initial_comment_body: "test".into(),
-                initial_comment_author: AuthorType::Human,
-                revision_number: None,
-                content_snippet: None,
-            })
-            .await
-            .unwrap();
-        store
-            .create_revision(CreateRevisionInput {
-                review_id: review.id,
-                trigger: RevisionTrigger::Agent,
-                message: None,
-                files: vec![],
-            })
-            .await
-            .unwrap();
-
-        store.delete_review(review.id).await.unwrap();
-
-        assert!(matches!(
-            store.get_review(review.id).await,
-            Err(StoreError::ReviewNotFound(_))
-        ));
-        assert!(matches!(
-            store.get_threads(review.id, None).await,
-            Err(StoreError::ReviewNotFound(_))
-        ));
-        assert!(matches!(
-            store.get_revisions(review.id).await,
-            Err(StoreError::ReviewNotFound(_))
-        ));
-        assert!(store.list_reviews().await.is_empty());
-    }
-
-    #[tokio::test]
-    async fn test_delete_review_not_found() {
-        let (store, _dir) = test_store().await;
-        let result = store.delete_review(Uuid::new_v4()).await;
-        assert!(matches!(result, Err(StoreError::ReviewNotFound(_))));
-    }
-
-    #[tokio::test]
-    async fn test_delete_review_does_not_affect_other_reviews() {
-        let (store, _dir) = test_store().await;
-        let review1 = create_review_with_store(&store).await;
-        let review2 = create_review_with_store(&store).await;
-
-        store.delete_review(review1.id).await.unwrap();
-
-        assert!(store.get_review(review2.id).await.is_ok());
-        assert_eq!(store.list_reviews().await.len(), 1);
-    }
-
-    #[tokio::test]
-    async fn test_delete_closed_reviews() {
-        let (store, _dir) = test_store().await;
-        let r1 = create_review_with_store(&store).await;
-        let r2 = create_review_with_store(&store).await;
-        let r3 = create_review_with_store(&store).await;
-
-        store
-            .update_review_status(r1.id, ReviewStatus::Closed)
-            .await
-            .unwrap();
-        store
-            .update_review_status(r2.id, ReviewStatus::Closed)
-            .await
-            .unwrap();
-
-        let deleted = store.delete_closed_reviews().await.unwrap();
-        assert_eq!(deleted.len(), 2);
-        assert!(deleted.contains(&r1.id));
-        assert!(deleted.contains(&r2.id));
-
-        let remaining = store.list_reviews().await;
-        assert_eq!(remaining.len(), 1);
-        assert_eq!(remaining[0].id, r3.id);
-    }
-
-    #[tokio::test]
-    async fn test_delete_closed_reviews_none_closed() {
-        let (store, _dir) = test_store().await;
-        create_review_with_store(&store).await;
-
-        let deleted = store.delete_closed_reviews().await.unwrap();
-        assert!(deleted.is_empty());
-        assert_eq!(store.list_reviews().await.len(), 1);
-    }
-
     #[tokio::test]
     async fn test_delete_review_persists() {
         let dir = TempDir::new().unwrap();
@@ -996,4 +542,23 @@ mod tests {
             ));
         }
     }
+
+    #[tokio::test]
+    async fn test_restore_snapshot_replaces_state_and_persists() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("state.json");
+        let store = JsonFileStore::new(&path).await.unwrap();
+        let kept = create_review_with_store(&store).await;
+        let snapshot = store.snapshot().await.unwrap();
+
+        store.delete_review(kept.id).await.unwrap();
+        let dropped = create_review_with_store(&store).await;
+        store.restore(snapshot).await.unwrap();
+
+        assert_eq!(store.get_review(kept.id).await.unwrap().id, kept.id);
+        assert!(store.get_review(dropped.id).await.is_err());
+        let reopened = JsonFileStore::new(&path).await.unwrap();
+        assert_eq!(reopened.list_reviews().await.len(), 1);
+        assert!(reopened.get_review(kept.id).await.is_ok());
+    }
 }
@@ -4,7 +4,15 @@ pub mod git_diff;
 pub mod highlight;
 pub mod interdiff;
 pub mod json_store;
+pub mod markdown;
+pub mod memory_store;
 pub mod parser;
+pub mod renames;
 pub mod review;
 pub mod store;
+#[cfg(test)]
+mod store_tests;
+pub mod trivial;
+pub mod unified_diff;
+pub mod whitespace;
 pub mod ws;
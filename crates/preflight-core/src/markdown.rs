@@ -0,0 +1,99 @@
+use pulldown_cmark::{CowStr, Event, Options, Parser, Tag};
+
+/// URL schemes links and images may use. Anything else (`javascript:`,
+/// `data:`, ...) is dropped; relative URLs are kept.
+const ALLOWED_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// Render a comment body from Markdown to HTML that is safe to insert into a
+/// page: raw HTML in the body is shown as text rather than passed through,
+/// and link and image URLs with scripting schemes are removed.
+pub fn render_html(markdown: &str) -> String {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let events = Parser::new_ext(markdown, options).map(|event| match event {
+        Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+        Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Link {
+            link_type,
+            dest_url: safe_url(dest_url),
+            title,
+            id,
+        }),
+        Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Image {
+            link_type,
+            dest_url: safe_url(dest_url),
+            title,
+            id,
+        }),
+        event => event,
+    });
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, events);
+    html
+}
+
+fn safe_url(url: CowStr<'_>) -> CowStr<'_> {
+    // Browsers ignore whitespace and control characters inside a scheme, so
+    // `java\tscript:` must be caught too
+    let normalized: String = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_control())
+        .collect();
+    // A scheme is everything before the first ':' if no '/', '?' or '#'
+    // comes earlier; otherwise the URL is relative
+    let scheme = normalized
+        .split_once(':')
+        .map(|(scheme, _)| scheme)
+        .filter(|s| !s.contains(['/', '?', '#']));
+    match scheme {
+        Some(scheme)
+            if !ALLOWED_SCHEMES
+                .iter()
+                .any(|allowed| scheme.eq_ignore_ascii_case(allowed)) =>
+        {
+            CowStr::Borrowed("")
+        }
+        _ => url,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renders_markdown() {
+        assert_eq!(
+            render_html("Use `foo()` **here**"),
+            "<p>Use <code>foo()</code> <strong>here</strong></p>\n"
+        );
+    }
+
+    #[test]
+    fn test_raw_html_is_escaped() {
+        let html = render_html("hi <script>alert(1)</script>\n\n<img src=x onerror=alert(1)>");
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("<img"));
+        assert!(html.contains("&lt;script&gt;"));
+    }
+
+    #[test]
+    fn test_script_urls_are_removed() {
+        let html = render_html(
+            "[click](javascript:alert(1)) [tab](<java\tscript:alert(1)>) \
+             [ok](https://example.com) [rel](src/main.rs)",
+        );
+        assert!(!html.contains("alert"));
+        assert!(html.contains(r#"href="https://example.com""#));
+        assert!(html.contains(r#"href="src/main.rs""#));
+    }
+}
@@ -0,0 +1,866 @@
+use std::collections::{HashMap, HashSet};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::review::{
+    Comment, CommentThread, Review, ReviewDecision, ReviewStatus, Revision, ThreadStatus,
+};
+use crate::store::{
+    AddCommentInput, AllThreadsFilter, CreateReviewInput, CreateRevisionInput, CreateThreadInput,
+    ReviewStore, ReviewSummary, ReviewThread, StoreError, ThreadLimit, ThreadPage,
+};
+
+/// The whole contents of a store. Both `MemoryStore` and `JsonFileStore`
+/// keep one of these behind a lock; the JSON store also writes it to disk
+/// after every change.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub(crate) struct State {
+    reviews: HashMap<Uuid, Review>,
+    threads: HashMap<Uuid, CommentThread>,
+    #[serde(default)]
+    revisions: HashMap<Uuid, Revision>,
+}
+
+impl State {
+    /// The highest-numbered revision of a review.
+    fn latest_revision(&self, review_id: Uuid) -> Option<&Revision> {
+        self.revisions
+            .values()
+            .filter(|r| r.review_id == review_id)
+            .max_by_key(|r| r.revision_number)
+    }
+
+    /// The number the next revision of a review gets. Only valid while the
+    /// state lock is held through the insert.
+    fn next_revision_number(&self, review_id: Uuid) -> u32 {
+        self.latest_revision(review_id)
+            .map_or(0, |r| r.revision_number)
+            + 1
+    }
+
+    /// A review's listing fields along with its thread, file and revision
+    /// counts.
+    fn review_summary(&self, review: &Review) -> ReviewSummary {
+        let review_threads: Vec<_> = self
+            .threads
+            .values()
+            .filter(|t| t.review_id == review.id)
+            .collect();
+        let thread_count = review_threads.len();
+        let open_count = |count_explanations| {
+            review_threads
+                .iter()
+                .filter(|t| t.counts_as_open(count_explanations))
+                .count()
+        };
+        let file_count = self
+            .latest_revision(review.id)
+            .map(|r| r.files.len())
+            .unwrap_or(0);
+        let revision_count = self
+            .revisions
+            .values()
+            .filter(|r| r.review_id == review.id)
+            .count();
+        ReviewSummary {
+            id: review.id,
+            title: review.title.clone(),
+            status: review.status.clone(),
+            thread_count,
+            open_thread_count: open_count(false),
+            open_thread_count_with_explanations: open_count(true),
+            file_count,
+            revision_count,
+            path_prefix: review.path_prefix.clone(),
+            extra_repos: review.extra_repos.clone(),
+            labels: review.labels.clone(),
+            agent_seen_at: review.agent_seen_at,
+            decision: review.decision,
+            created_at: review.created_at,
+            updated_at: review.updated_at,
+        }
+    }
+
+    fn ensure_review(&self, id: Uuid) -> Result<(), StoreError> {
+        if self.reviews.contains_key(&id) {
+            Ok(())
+        } else {
+            Err(StoreError::ReviewNotFound(id))
+        }
+    }
+
+    fn review_mut(&mut self, id: Uuid) -> Result<&mut Review, StoreError> {
+        self.reviews
+            .get_mut(&id)
+            .ok_or(StoreError::ReviewNotFound(id))
+    }
+
+    fn thread_mut(&mut self, thread_id: Uuid) -> Result<&mut CommentThread, StoreError> {
+        self.threads
+            .get_mut(&thread_id)
+            .ok_or(StoreError::ThreadNotFound(thread_id))
+    }
+
+    pub(crate) fn create_review(&mut self, input: CreateReviewInput) -> Review {
+        let now = Utc::now();
+        let review = Review {
+            id: Uuid::new_v4(),
+            title: input.title,
+            status: ReviewStatus::Open,
+            created_at: now,
+            updated_at: now,
+            status_changed_at: None,
+            repo_path: input.repo_path,
+            base_ref: input.base_ref,
+            path_prefix: input.path_prefix,
+            agent_seen_at: None,
+            human_seen_at: None,
+            extra_repos: input.extra_repos,
+            labels: input.labels,
+            diff_algorithm: input.diff_algorithm,
+            decision: None,
+        };
+        self.reviews.insert(review.id, review.clone());
+        review
+    }
+
+    pub(crate) fn get_review(&self, id: Uuid) -> Result<Review, StoreError> {
+        self.reviews
+            .get(&id)
+            .cloned()
+            .ok_or(StoreError::ReviewNotFound(id))
+    }
+
+    // TODO: O(R*T) — pre-build a thread count map if this becomes a hot path
+    pub(crate) fn list_reviews(&self) -> Vec<ReviewSummary> {
+        self.reviews
+            .values()
+            .map(|review| self.review_summary(review))
+            .collect()
+    }
+
+    pub(crate) fn list_reviews_by_status(&self, status: ReviewStatus) -> Vec<ReviewSummary> {
+        self.reviews
+            .values()
+            .filter(|review| review.status == status)
+            .map(|review| self.review_summary(review))
+            .collect()
+    }
+
+    pub(crate) fn update_review_status(
+        &mut self,
+        id: Uuid,
+        status: ReviewStatus,
+    ) -> Result<(), StoreError> {
+        let review = self.review_mut(id)?;
+        let now = Utc::now();
+        review.status = status;
+        review.updated_at = now;
+        review.status_changed_at = Some(now);
+        Ok(())
+    }
+
+    pub(crate) fn set_labels(&mut self, id: Uuid, labels: Vec<String>) -> Result<(), StoreError> {
+        let review = self.review_mut(id)?;
+        review.labels = labels;
+        review.updated_at = Utc::now();
+        Ok(())
+    }
+
+    pub(crate) fn set_decision(
+        &mut self,
+        id: Uuid,
+        decision: Option<ReviewDecision>,
+    ) -> Result<(), StoreError> {
+        let review = self.review_mut(id)?;
+        review.decision = decision;
+        review.updated_at = Utc::now();
+        Ok(())
+    }
+
+    pub(crate) fn set_agent_seen(
+        &mut self,
+        id: Uuid,
+        seen_at: Option<DateTime<Utc>>,
+    ) -> Result<(), StoreError> {
+        self.review_mut(id)?.agent_seen_at = seen_at;
+        Ok(())
+    }
+
+    pub(crate) fn set_human_seen(
+        &mut self,
+        id: Uuid,
+        seen_at: DateTime<Utc>,
+    ) -> Result<(), StoreError> {
+        self.review_mut(id)?.human_seen_at = Some(seen_at);
+        Ok(())
+    }
+
+    pub(crate) fn delete_review(&mut self, id: Uuid) -> Result<(), StoreError> {
+        if self.reviews.remove(&id).is_none() {
+            return Err(StoreError::ReviewNotFound(id));
+        }
+        self.threads.retain(|_, t| t.review_id != id);
+        self.revisions.retain(|_, r| r.review_id != id);
+        Ok(())
+    }
+
+    pub(crate) fn delete_closed_reviews(&mut self) -> Vec<Uuid> {
+        // Check each status in the same pass that removes the review, so
+        // nothing reopened since the caller last looked is caught
+        let mut closed_ids = HashSet::new();
+        self.reviews.retain(|id, r| {
+            let closed = r.status == ReviewStatus::Closed;
+            if closed {
+                closed_ids.insert(*id);
+            }
+            !closed
+        });
+        if closed_ids.is_empty() {
+            return vec![];
+        }
+        self.threads
+            .retain(|_, t| !closed_ids.contains(&t.review_id));
+        self.revisions
+            .retain(|_, r| !closed_ids.contains(&r.review_id));
+        closed_ids.into_iter().collect()
+    }
+
+    /// Fail if adding `adding` to a review's threads would take it past
+    /// `limit`. Only valid while the state lock is held through the insert.
+    fn check_thread_limit(
+        &self,
+        review_id: Uuid,
+        adding: &[&CommentThread],
+        limit: Option<ThreadLimit>,
+    ) -> Result<(), StoreError> {
+        let Some(limit) = limit else {
+            return Ok(());
+        };
+        let counts = |t: &CommentThread| t.counts_as_open(limit.count_explanations);
+        let adding = adding.iter().filter(|t| counts(t)).count();
+        if adding == 0 {
+            return Ok(());
+        }
+        let open = self
+            .threads
+            .values()
+            .filter(|t| t.review_id == review_id && counts(t))
+            .count();
+        if open + adding > limit.max_open {
+            return Err(StoreError::ThreadLimitReached {
+                review_id,
+                open,
+                limit: limit.max_open,
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn create_thread(
+        &mut self,
+        input: CreateThreadInput,
+        limit: Option<ThreadLimit>,
+    ) -> Result<CommentThread, StoreError> {
+        self.ensure_review(input.review_id)?;
+        let thread = new_thread(input, Utc::now());
+        self.check_thread_limit(thread.review_id, &[&thread], limit)?;
+        self.threads.insert(thread.id, thread.clone());
+        Ok(thread)
+    }
+
+    pub(crate) fn create_threads(
+        &mut self,
+        review_id: Uuid,
+        inputs: Vec<CreateThreadInput>,
+        limit: Option<ThreadLimit>,
+    ) -> Result<Vec<CommentThread>, StoreError> {
+        self.ensure_review(review_id)?;
+        let now = Utc::now();
+        // Listings sort by creation time, so step it to keep the batch's order
+        let threads: Vec<CommentThread> = inputs
+            .into_iter()
+            .zip(0..)
+            .map(|(input, i)| {
+                let created_at = now + chrono::TimeDelta::nanoseconds(i);
+                new_thread(CreateThreadInput { review_id, ..input }, created_at)
+            })
+            .collect();
+        self.check_thread_limit(review_id, &threads.iter().collect::<Vec<_>>(), limit)?;
+        for thread in &threads {
+            self.threads.insert(thread.id, thread.clone());
+        }
+        Ok(threads)
+    }
+
+    pub(crate) fn copy_threads(
+        &mut self,
+        review_id: Uuid,
+        threads: Vec<CommentThread>,
+        limit: Option<ThreadLimit>,
+    ) -> Result<Vec<CommentThread>, StoreError> {
+        self.ensure_review(review_id)?;
+        self.check_thread_limit(review_id, &threads.iter().collect::<Vec<_>>(), limit)?;
+        let thread_ids: HashMap<Uuid, Uuid> =
+            threads.iter().map(|t| (t.id, Uuid::new_v4())).collect();
+        let copies: Vec<CommentThread> = threads
+            .into_iter()
+            .map(|thread| {
+                let new_ids: HashMap<Uuid, Uuid> = thread
+                    .comments
+                    .iter()
+                    .map(|c| (c.id, Uuid::new_v4()))
+                    .collect();
+                let comments = thread
+                    .comments
+                    .into_iter()
+                    .map(|c| Comment {
+                        id: new_ids[&c.id],
+                        in_reply_to: c.in_reply_to.and_then(|id| new_ids.get(&id).copied()),
+                        ..c
+                    })
+                    .collect();
+                let related_threads = thread
+                    .related_threads
+                    .iter()
+                    .filter_map(|id| thread_ids.get(id).copied())
+                    .collect();
+                CommentThread {
+                    id: thread_ids[&thread.id],
+                    review_id,
+                    comments,
+                    related_threads,
+                    version: 0,
+                    ..thread
+                }
+            })
+            .collect();
+        for thread in &copies {
+            self.threads.insert(thread.id, thread.clone());
+        }
+        Ok(copies)
+    }
+
+    pub(crate) fn set_thread_pinned(
+        &mut self,
+        thread_id: Uuid,
+        pinned: bool,
+    ) -> Result<CommentThread, StoreError> {
+        let thread = self.thread_mut(thread_id)?;
+        if thread.pinned != pinned {
+            thread.pinned = pinned;
+            thread.touch(Utc::now());
+        }
+        Ok(thread.clone())
+    }
+
+    pub(crate) fn link_threads(
+        &mut self,
+        thread_id: Uuid,
+        related: Vec<Uuid>,
+    ) -> Result<CommentThread, StoreError> {
+        let review_id = self
+            .threads
+            .get(&thread_id)
+            .ok_or(StoreError::ThreadNotFound(thread_id))?
+            .review_id;
+        // Validate every id before touching anything
+        for &related_id in &related {
+            let other = self
+                .threads
+                .get(&related_id)
+                .ok_or(StoreError::ThreadNotFound(related_id))?;
+            if other.review_id != review_id {
+                return Err(StoreError::LinkAcrossReviews {
+                    thread_id,
+                    related_id,
+                });
+            }
+        }
+
+        let now = Utc::now();
+        for related_id in related {
+            if related_id == thread_id {
+                continue;
+            }
+            for (from, to) in [(thread_id, related_id), (related_id, thread_id)] {
+                let thread = self.threads.get_mut(&from).expect("validated above");
+                if !thread.related_threads.contains(&to) {
+                    thread.related_threads.push(to);
+                    thread.touch(now);
+                }
+            }
+        }
+        Ok(self.threads[&thread_id].clone())
+    }
+
+    pub(crate) fn get_thread(&self, thread_id: Uuid) -> Result<CommentThread, StoreError> {
+        self.threads
+            .get(&thread_id)
+            .cloned()
+            .ok_or(StoreError::ThreadNotFound(thread_id))
+    }
+
+    pub(crate) fn get_threads(
+        &self,
+        review_id: Uuid,
+        file_path: Option<&str>,
+    ) -> Result<Vec<CommentThread>, StoreError> {
+        self.ensure_review(review_id)?;
+        let mut threads: Vec<CommentThread> = self
+            .threads
+            .values()
+            .filter(|t| t.review_id == review_id && file_path.is_none_or(|fp| t.file_path == fp))
+            .cloned()
+            .collect();
+        threads.sort_by_key(|t| (t.created_at, t.id));
+        Ok(threads)
+    }
+
+    pub(crate) fn all_threads(&self, filter: &AllThreadsFilter) -> ThreadPage {
+        let mut threads: Vec<&CommentThread> = self
+            .threads
+            .values()
+            .filter(|t| filter.status.as_ref().is_none_or(|s| t.status == *s))
+            .filter(|t| filter.origin.as_ref().is_none_or(|o| t.origin == *o))
+            .collect();
+        threads.sort_by_key(|t| (t.created_at, t.id));
+        let total = threads.len();
+        let threads = threads
+            .into_iter()
+            .skip(filter.offset)
+            .take(filter.limit.unwrap_or(usize::MAX))
+            .map(|thread| ReviewThread {
+                review_title: self
+                    .reviews
+                    .get(&thread.review_id)
+                    .and_then(|r| r.title.clone()),
+                human_seen_at: self
+                    .reviews
+                    .get(&thread.review_id)
+                    .and_then(|r| r.human_seen_at),
+                thread: thread.clone(),
+            })
+            .collect();
+        ThreadPage { threads, total }
+    }
+
+    pub(crate) fn update_thread_status(
+        &mut self,
+        thread_id: Uuid,
+        status: ThreadStatus,
+        expected_version: Option<u64>,
+        limit: Option<ThreadLimit>,
+    ) -> Result<(), StoreError> {
+        let thread = self.get_thread(thread_id)?;
+        if let Some(expected) = expected_version
+            && expected != thread.version
+        {
+            return Err(StoreError::VersionConflict {
+                thread_id,
+                expected,
+                actual: thread.version,
+            });
+        }
+        if let Some(limit) = limit
+            && !thread.counts_as_open(limit.count_explanations)
+        {
+            let reopened = CommentThread {
+                status: status.clone(),
+                ..thread.clone()
+            };
+            self.check_thread_limit(thread.review_id, &[&reopened], Some(limit))?;
+        }
+        let thread = self.thread_mut(thread_id)?;
+        thread.status = status;
+        thread.touch(Utc::now());
+        Ok(())
+    }
+
+    pub(crate) fn get_comment(
+        &self,
+        thread_id: Uuid,
+        comment_id: Uuid,
+    ) -> Result<Comment, StoreError> {
+        let thread = self
+            .threads
+            .get(&thread_id)
+            .ok_or(StoreError::ThreadNotFound(thread_id))?;
+        thread
+            .comments
+            .iter()
+            .find(|c| c.id == comment_id)
+            .cloned()
+            .ok_or(StoreError::CommentNotFound {
+                thread_id,
+                comment_id,
+            })
+    }
+
+    pub(crate) fn get_thread_comments(
+        &self,
+        thread_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<Comment>>, StoreError> {
+        thread_ids
+            .iter()
+            .map(|&id| {
+                let thread = self
+                    .threads
+                    .get(&id)
+                    .ok_or(StoreError::ThreadNotFound(id))?;
+                Ok((id, thread.comments.clone()))
+            })
+            .collect()
+    }
+
+    pub(crate) fn add_comment(&mut self, input: AddCommentInput) -> Result<Comment, StoreError> {
+        let thread = self.thread_mut(input.thread_id)?;
+        if let Some(target) = input.in_reply_to
+            && !thread.comments.iter().any(|c| c.id == target)
+        {
+            return Err(StoreError::ReplyTargetNotFound {
+                thread_id: input.thread_id,
+                comment_id: target,
+            });
+        }
+        let comment = Comment {
+            id: Uuid::new_v4(),
+            author_type: input.author_type,
+            body: input.body,
+            created_at: Utc::now(),
+            draft: input.draft,
+            in_reply_to: input.in_reply_to,
+            suggestion: input.suggestion,
+            attachments: input.attachments,
+        };
+        thread.comments.push(comment.clone());
+        thread.touch(Utc::now());
+        Ok(comment)
+    }
+
+    pub(crate) fn publish_drafts(
+        &mut self,
+        review_id: Uuid,
+    ) -> Result<Vec<(Uuid, Comment)>, StoreError> {
+        self.ensure_review(review_id)?;
+        let now = Utc::now();
+        let mut published = Vec::new();
+        for thread in self
+            .threads
+            .values_mut()
+            .filter(|t| t.review_id == review_id)
+        {
+            let mut touched = false;
+            for comment in thread.comments.iter_mut().filter(|c| c.draft) {
+                comment.draft = false;
+                published.push((thread.id, comment.clone()));
+                touched = true;
+            }
+            if touched {
+                thread.touch(now);
+            }
+        }
+        published.sort_by_key(|(_, c)| c.created_at);
+        Ok(published)
+    }
+
+    pub(crate) fn create_revision(
+        &mut self,
+        input: CreateRevisionInput,
+    ) -> Result<Revision, StoreError> {
+        self.ensure_review(input.review_id)?;
+        let revision = Revision {
+            id: Uuid::new_v4(),
+            review_id: input.review_id,
+            revision_number: self.next_revision_number(input.review_id),
+            trigger: input.trigger,
+            message: input.message,
+            files: input.files,
+            base_snapshots: input.base_snapshots,
+            created_at: Utc::now(),
+        };
+        self.revisions.insert(revision.id, revision.clone());
+        Ok(revision)
+    }
+
+    pub(crate) fn get_revisions(&self, review_id: Uuid) -> Result<Vec<Revision>, StoreError> {
+        self.ensure_review(review_id)?;
+        let mut revisions: Vec<Revision> = self
+            .revisions
+            .values()
+            .filter(|r| r.review_id == review_id)
+            .cloned()
+            .collect();
+        revisions.sort_by_key(|r| r.revision_number);
+        Ok(revisions)
+    }
+
+    pub(crate) fn get_revision(
+        &self,
+        review_id: Uuid,
+        revision_number: u32,
+    ) -> Result<Revision, StoreError> {
+        self.ensure_review(review_id)?;
+        self.revisions
+            .values()
+            .find(|r| r.review_id == review_id && r.revision_number == revision_number)
+            .cloned()
+            .ok_or(StoreError::RevisionNotFound(review_id))
+    }
+
+    pub(crate) fn get_latest_revision(&self, review_id: Uuid) -> Result<Revision, StoreError> {
+        self.ensure_review(review_id)?;
+        self.latest_revision(review_id)
+            .cloned()
+            .ok_or(StoreError::RevisionNotFound(review_id))
+    }
+
+    pub(crate) fn from_snapshot(snapshot: serde_json::Value) -> Result<Self, StoreError> {
+        serde_json::from_value(snapshot).map_err(|e| StoreError::InvalidSnapshot(e.to_string()))
+    }
+}
+
+/// Build a new open thread with its initial comment.
+fn new_thread(input: CreateThreadInput, now: chrono::DateTime<Utc>) -> CommentThread {
+    let initial_comment = Comment {
+        id: Uuid::new_v4(),
+        author_type: input.initial_comment_author,
+        body: input.initial_comment_body,
+        created_at: now,
+        draft: false,
+        in_reply_to: None,
+        suggestion: input.initial_comment_suggestion,
+        attachments: Vec::new(),
+    };
+    CommentThread {
+        id: Uuid::new_v4(),
+        review_id: input.review_id,
+        file_path: input.file_path,
+        line_start: input.line_start,
+        line_end: input.line_end,
+        side: input.side,
+        origin: input.origin,
+        status: ThreadStatus::Open,
+        comments: vec![initial_comment],
+        created_at: now,
+        updated_at: now,
+        revision_number: input.revision_number,
+        content_snippet: input.content_snippet,
+        related_threads: Vec::new(),
+        pinned: false,
+        version: 0,
+    }
+}
+
+/// A store that keeps everything in memory and never touches the disk.
+/// Used by tests and by `preflight serve --memory`.
+#[derive(Default)]
+pub struct MemoryStore {
+    state: Mutex<State>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ReviewStore for MemoryStore {
+    async fn create_review(&self, input: CreateReviewInput) -> Result<Review, StoreError> {
+        Ok(self.state.lock().await.create_review(input))
+    }
+
+    async fn get_review(&self, id: Uuid) -> Result<Review, StoreError> {
+        self.state.lock().await.get_review(id)
+    }
+
+    async fn list_reviews(&self) -> Vec<ReviewSummary> {
+        self.state.lock().await.list_reviews()
+    }
+
+    async fn list_reviews_by_status(&self, status: ReviewStatus) -> Vec<ReviewSummary> {
+        self.state.lock().await.list_reviews_by_status(status)
+    }
+
+    async fn update_review_status(&self, id: Uuid, status: ReviewStatus) -> Result<(), StoreError> {
+        self.state.lock().await.update_review_status(id, status)
+    }
+
+    async fn set_labels(&self, id: Uuid, labels: Vec<String>) -> Result<(), StoreError> {
+        self.state.lock().await.set_labels(id, labels)
+    }
+
+    async fn set_decision(
+        &self,
+        id: Uuid,
+        decision: Option<ReviewDecision>,
+    ) -> Result<(), StoreError> {
+        self.state.lock().await.set_decision(id, decision)
+    }
+
+    async fn set_agent_seen(
+        &self,
+        id: Uuid,
+        seen_at: Option<DateTime<Utc>>,
+    ) -> Result<(), StoreError> {
+        self.state.lock().await.set_agent_seen(id, seen_at)
+    }
+
+    async fn set_human_seen(&self, id: Uuid, seen_at: DateTime<Utc>) -> Result<(), StoreError> {
+        self.state.lock().await.set_human_seen(id, seen_at)
+    }
+
+    async fn delete_review(&self, id: Uuid) -> Result<(), StoreError> {
+        self.state.lock().await.delete_review(id)
+    }
+
+    async fn delete_closed_reviews(&self) -> Result<Vec<Uuid>, StoreError> {
+        Ok(self.state.lock().await.delete_closed_reviews())
+    }
+
+    async fn create_thread(
+        &self,
+        input: CreateThreadInput,
+        limit: Option<ThreadLimit>,
+    ) -> Result<CommentThread, StoreError> {
+        self.state.lock().await.create_thread(input, limit)
+    }
+
+    async fn create_threads(
+        &self,
+        review_id: Uuid,
+        inputs: Vec<CreateThreadInput>,
+        limit: Option<ThreadLimit>,
+    ) -> Result<Vec<CommentThread>, StoreError> {
+        self.state
+            .lock()
+            .await
+            .create_threads(review_id, inputs, limit)
+    }
+
+    async fn copy_threads(
+        &self,
+        review_id: Uuid,
+        threads: Vec<CommentThread>,
+        limit: Option<ThreadLimit>,
+    ) -> Result<Vec<CommentThread>, StoreError> {
+        self.state
+            .lock()
+            .await
+            .copy_threads(review_id, threads, limit)
+    }
+
+    async fn set_thread_pinned(
+        &self,
+        thread_id: Uuid,
+        pinned: bool,
+    ) -> Result<CommentThread, StoreError> {
+        self.state.lock().await.set_thread_pinned(thread_id, pinned)
+    }
+
+    async fn link_threads(
+        &self,
+        thread_id: Uuid,
+        related: Vec<Uuid>,
+    ) -> Result<CommentThread, StoreError> {
+        self.state.lock().await.link_threads(thread_id, related)
+    }
+
+    async fn get_thread(&self, thread_id: Uuid) -> Result<CommentThread, StoreError> {
+        self.state.lock().await.get_thread(thread_id)
+    }
+
+    async fn get_threads(
+        &self,
+        review_id: Uuid,
+        file_path: Option<&str>,
+    ) -> Result<Vec<CommentThread>, StoreError> {
+        self.state.lock().await.get_threads(review_id, file_path)
+    }
+
+    async fn all_threads(&self, filter: &AllThreadsFilter) -> ThreadPage {
+        self.state.lock().await.all_threads(filter)
+    }
+
+    async fn update_thread_status(
+        &self,
+        thread_id: Uuid,
+        status: ThreadStatus,
+        expected_version: Option<u64>,
+        limit: Option<ThreadLimit>,
+    ) -> Result<(), StoreError> {
+        self.state
+            .lock()
+            .await
+            .update_thread_status(thread_id, status, expected_version, limit)
+    }
+
+    async fn get_comment(&self, thread_id: Uuid, comment_id: Uuid) -> Result<Comment, StoreError> {
+        self.state.lock().await.get_comment(thread_id, comment_id)
+    }
+
+    async fn get_thread_comments(
+        &self,
+        thread_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<Comment>>, StoreError> {
+        self.state.lock().await.get_thread_comments(thread_ids)
+    }
+
+    async fn add_comment(&self, input: AddCommentInput) -> Result<Comment, StoreError> {
+        self.state.lock().await.add_comment(input)
+    }
+
+    async fn publish_drafts(&self, review_id: Uuid) -> Result<Vec<(Uuid, Comment)>, StoreError> {
+        self.state.lock().await.publish_drafts(review_id)
+    }
+
+    async fn create_revision(&self, input: CreateRevisionInput) -> Result<Revision, StoreError> {
+        self.state.lock().await.create_revision(input)
+    }
+
+    async fn get_revisions(&self, review_id: Uuid) -> Result<Vec<Revision>, StoreError> {
+        self.state.lock().await.get_revisions(review_id)
+    }
+
+    async fn get_revision(
+        &self,
+        review_id: Uuid,
+        revision_number: u32,
+    ) -> Result<Revision, StoreError> {
+        self.state
+            .lock()
+            .await
+            .get_revision(review_id, revision_number)
+    }
+
+    async fn get_latest_revision(&self, review_id: Uuid) -> Result<Revision, StoreError> {
+        self.state.lock().await.get_latest_revision(review_id)
+    }
+
+    async fn snapshot(&self) -> Result<serde_json::Value, StoreError> {
+        let state = self.state.lock().await;
+        Ok(serde_json::to_value(&*state)?)
+    }
+
+    async fn restore(&self, snapshot: serde_json::Value) -> Result<(), StoreError> {
+        let restored = State::from_snapshot(snapshot)?;
+        *self.state.lock().await = restored;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn test_store() -> (MemoryStore, ()) {
+        (MemoryStore::new(), ())
+    }
+
+    crate::store_tests::review_store_tests!(test_store);
+}
@@ -1,4 +1,4 @@
-use crate::diff::{DiffLine, FileDiff, FileStatus, Hunk, LineKind};
+use crate::diff::{DiffLine, FileDiff, FileStatus, Hunk, LineKind, ModeChange};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ParseError {
@@ -60,6 +60,9 @@ fn parse_file_block(block: &[&str], global_offset: usize) -> Result<FileDiff, Pa
     let mut status = FileStatus::Modified;
     let mut hunks = Vec::new();
     let mut is_binary = false;
+    let mut is_symlink = false;
+    let mut old_mode: Option<String> = None;
+    let mut new_mode: Option<String> = None;
 
     let mut i = 1; // skip the "diff --git" line itself
 
@@ -83,10 +86,20 @@ fn parse_file_block(block: &[&str], global_offset: usize) -> Result<FileDiff, Pa
             } else {
                 Some(strip_ab_prefix(path))
             };
-        } else if line.starts_with("new file mode") {
+        } else if let Some(mode) = line.strip_prefix("new file mode ") {
             status = FileStatus::Added;
-        } else if line.starts_with("deleted file mode") {
+            is_symlink |= mode == SYMLINK_MODE;
+        } else if let Some(mode) = line.strip_prefix("deleted file mode ") {
             status = FileStatus::Deleted;
+            is_symlink |= mode == SYMLINK_MODE;
+        } else if let Some(mode) = line.strip_prefix("old mode ") {
+            is_symlink |= mode == SYMLINK_MODE;
+            old_mode = Some(mode.to_string());
+        } else if let Some(mode) = line.strip_prefix("new mode ") {
+            is_symlink |= mode == SYMLINK_MODE;
+            new_mode = Some(mode.to_string());
+        } else if line.starts_with("index ") && line.ends_with(SYMLINK_MODE) {
+            is_symlink = true;
         } else if let Some(from) = line.strip_prefix("rename from ") {
             status = FileStatus::Renamed;
             old_path = Some(from.to_string());
@@ -102,8 +115,26 @@ fn parse_file_block(block: &[&str], global_offset: usize) -> Result<FileDiff, Pa
 
     if is_binary {
         status = FileStatus::Binary;
+    } else if is_symlink {
+        status = FileStatus::Symlink;
     }
 
+    // Mode-only changes have no `---`/`+++` lines, so fall back to the
+    // paths in the `diff --git` header.
+    if old_path.is_none()
+        && new_path.is_none()
+        && status == FileStatus::Modified
+        && let Some((a, b)) = parse_git_header_paths(block[0])
+    {
+        old_path = Some(a);
+        new_path = Some(b);
+    }
+
+    let mode_change = match (old_mode, new_mode) {
+        (Some(old_mode), Some(new_mode)) => Some(ModeChange { old_mode, new_mode }),
+        _ => None,
+    };
+
     // Parse hunks
     while i < block.len() {
         if block[i].starts_with("@@ ") {
@@ -120,11 +151,30 @@ fn parse_file_block(block: &[&str], global_offset: usize) -> Result<FileDiff, Pa
         new_path,
         status,
         hunks,
+        mode_change,
     })
 }
 
-/// Strip the `a/` or `b/` prefix from a diff path.
+/// Git's file mode for symbolic links.
+const SYMLINK_MODE: &str = "120000";
+
+/// Extract the old and new paths from a `diff --git a/<old> b/<new>` line.
+///
+/// The header is only consulted when the paths are the same (a mode-only
+/// change), so it is split at its middle: searching for ` b/` would cut a
+/// path like `a/x b/y.rs` short.
+fn parse_git_header_paths(line: &str) -> Option<(String, String)> {
+    let rest = line.strip_prefix("diff --git ")?;
+    let mid = rest.len() / 2;
+    let old = rest.get(..mid)?.strip_prefix("a/")?;
+    let new = rest.get(mid..)?.strip_prefix(" b/")?;
+    (old == new).then(|| (old.to_string(), new.to_string()))
+}
+
+/// Strip the `a/` or `b/` prefix from a diff path, along with the tab git
+/// appends after paths that contain spaces.
 fn strip_ab_prefix(path: &str) -> String {
+    let path = path.strip_suffix('\t').unwrap_or(path);
     if let Some(stripped) = path.strip_prefix("a/").or_else(|| path.strip_prefix("b/")) {
         stripped.to_string()
     } else {
@@ -170,6 +220,7 @@ fn parse_hunk(
                 old_line_no: Some(old_line),
                 new_line_no: Some(new_line),
                 highlighted: None,
+                whitespace_only: false,
             });
             old_line += 1;
             new_line += 1;
@@ -185,6 +236,7 @@ fn parse_hunk(
                         old_line_no: Some(old_line),
                         new_line_no: Some(new_line),
                         highlighted: None,
+                        whitespace_only: false,
                     });
                     old_line += 1;
                     new_line += 1;
@@ -196,6 +248,7 @@ fn parse_hunk(
                         old_line_no: None,
                         new_line_no: Some(new_line),
                         highlighted: None,
+                        whitespace_only: false,
                     });
                     new_line += 1;
                 }
@@ -206,6 +259,7 @@ fn parse_hunk(
                         old_line_no: Some(old_line),
                         new_line_no: None,
                         highlighted: None,
+                        whitespace_only: false,
                     });
                     old_line += 1;
                 }
@@ -334,6 +388,22 @@ index abc1234..def5678 100644
         assert_eq!(result[0].status, FileStatus::Modified);
     }
 
+    #[test]
+    fn test_path_with_space_drops_trailing_tab() {
+        let input = "\
+diff --git a/my notes.md b/my notes.md
+index abc1234..def5678 100644
+--- a/my notes.md\t
++++ b/my notes.md\t
+@@ -1 +1 @@
+-old
++new
+";
+        let result = parse_diff(input).unwrap();
+        assert_eq!(result[0].old_path.as_deref(), Some("my notes.md"));
+        assert_eq!(result[0].new_path.as_deref(), Some("my notes.md"));
+    }
+
     #[test]
     fn test_new_file_status() {
         let input = "\
@@ -409,6 +479,61 @@ Binary files /dev/null and b/image.png differ
         assert!(result[0].hunks.is_empty());
     }
 
+    #[test]
+    fn test_mode_only_change() {
+        let input = "\
+diff --git a/scripts/build.sh b/scripts/build.sh
+old mode 100644
+new mode 100755
+";
+        let result = parse_diff(input).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].status, FileStatus::Modified);
+        assert_eq!(result[0].old_path.as_deref(), Some("scripts/build.sh"));
+        assert_eq!(result[0].new_path.as_deref(), Some("scripts/build.sh"));
+        assert!(result[0].hunks.is_empty());
+        assert_eq!(
+            result[0].mode_change,
+            Some(ModeChange {
+                old_mode: "100644".into(),
+                new_mode: "100755".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_mode_only_change_to_path_containing_b_slash() {
+        let input = "\
+diff --git a/x b/y.rs b/x b/y.rs
+old mode 100644
+new mode 100755
+";
+        let result = parse_diff(input).unwrap();
+        assert_eq!(result[0].old_path.as_deref(), Some("x b/y.rs"));
+        assert_eq!(result[0].new_path.as_deref(), Some("x b/y.rs"));
+    }
+
+    #[test]
+    fn test_symlink_creation() {
+        let input = "\
+diff --git a/current b/current
+new file mode 120000
+index 0000000..5e1c309
+--- /dev/null
++++ b/current
+@@ -0,0 +1 @@
++releases/v2
+\\ No newline at end of file
+";
+        let result = parse_diff(input).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].status, FileStatus::Symlink);
+        assert_eq!(result[0].old_path, None);
+        assert_eq!(result[0].new_path.as_deref(), Some("current"));
+        assert_eq!(result[0].hunks[0].lines[0].content, "releases/v2");
+        assert_eq!(result[0].mode_change, None);
+    }
+
     #[test]
     fn test_multiple_files() {
         let input = "\
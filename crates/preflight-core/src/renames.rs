@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+
+use crate::diff::FileStatus;
+use crate::review::Revision;
+
+/// Map every path a file has been shown under in a review to the path it has
+/// in the latest revision.
+///
+/// Each revision diffs against the review's base, so a renamed file keeps its
+/// base path as `old_path`. Files are linked across revisions through that
+/// base path, which lets a chain like `a.rs` (rev 1) → `b.rs` (rev 2) →
+/// `c.rs` (rev 3) map both `a.rs` and `b.rs` to `c.rs`. Paths that never
+/// moved are omitted.
+pub fn build_rename_map(revisions: &[Revision]) -> HashMap<String, String> {
+    let Some(latest) = revisions.iter().max_by_key(|r| r.revision_number) else {
+        return HashMap::new();
+    };
+
+    // Base path -> path in the latest revision
+    let current: HashMap<&str, &str> = latest
+        .files
+        .iter()
+        .filter(|f| f.status == FileStatus::Renamed)
+        .filter_map(|f| Some((f.old_path.as_deref()?, f.new_path.as_deref()?)))
+        .collect();
+
+    let mut map = HashMap::new();
+    for file in revisions.iter().flat_map(|r| &r.files) {
+        let Some(shown) = file.new_path.as_deref().or(file.old_path.as_deref()) else {
+            continue;
+        };
+        let base = file.old_path.as_deref().unwrap_or(shown);
+        if let Some(&latest_path) = current.get(base)
+            && latest_path != shown
+        {
+            map.insert(shown.to_string(), latest_path.to_string());
+        }
+    }
+    map
+}
+
+/// Resolve a path through a rename map, returning it unchanged if it never moved.
+pub fn resolve_path<'a>(map: &'a HashMap<String, String>, path: &'a str) -> &'a str {
+    map.get(path).map_or(path, String::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::FileDiff;
+    use crate::review::RevisionTrigger;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn file(old: Option<&str>, new: Option<&str>, status: FileStatus) -> FileDiff {
+        FileDiff {
+            old_path: old.map(String::from),
+            new_path: new.map(String::from),
+            status,
+            hunks: vec![],
+            mode_change: None,
+        }
+    }
+
+    fn revision(number: u32, files: Vec<FileDiff>) -> Revision {
+        Revision {
+            id: Uuid::new_v4(),
+            review_id: Uuid::nil(),
+            revision_number: number,
+            trigger: RevisionTrigger::Agent,
+            message: None,
+            files,
+            base_snapshots: Default::default(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn follows_rename_chain_to_latest_path() {
+        let revisions = vec![
+            revision(
+                1,
+                vec![file(
+                    Some("src/a.rs"),
+                    Some("src/a.rs"),
+                    FileStatus::Modified,
+                )],
+            ),
+            revision(
+                2,
+                vec![file(
+                    Some("src/a.rs"),
+                    Some("src/b.rs"),
+                    FileStatus::Renamed,
+                )],
+            ),
+            revision(
+                3,
+                vec![file(
+                    Some("src/a.rs"),
+                    Some("src/c.rs"),
+                    FileStatus::Renamed,
+                )],
+            ),
+        ];
+        let map = build_rename_map(&revisions);
+        assert_eq!(resolve_path(&map, "src/a.rs"), "src/c.rs");
+        assert_eq!(resolve_path(&map, "src/b.rs"), "src/c.rs");
+        assert_eq!(resolve_path(&map, "src/c.rs"), "src/c.rs");
+    }
+
+    #[test]
+    fn unrenamed_files_are_not_mapped() {
+        let revisions = vec![
+            revision(
+                1,
+                vec![file(Some("x.rs"), Some("x.rs"), FileStatus::Modified)],
+            ),
+            revision(2, vec![file(None, Some("y.rs"), FileStatus::Added)]),
+        ];
+        assert!(build_rename_map(&revisions).is_empty());
+        assert!(build_rename_map(&[]).is_empty());
+    }
+}
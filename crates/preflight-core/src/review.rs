@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -6,13 +8,25 @@ use crate::diff::FileDiff;
 
 #[non_exhaustive]
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum ReviewStatus {
     Open,
     Closed,
 }
 
+/// A reviewer's verdict on a review, separate from whether it is open.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum ReviewDecision {
+    Approved,
+    ChangesRequested,
+    Commented,
+}
+
 #[non_exhaustive]
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum ThreadOrigin {
     Comment,
     ExplanationRequest,
@@ -21,6 +35,7 @@ pub enum ThreadOrigin {
 
 #[non_exhaustive]
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum ThreadStatus {
     Open,
     Resolved,
@@ -28,6 +43,7 @@ pub enum ThreadStatus {
 
 #[non_exhaustive]
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum AuthorType {
     Human,
     Agent,
@@ -35,6 +51,7 @@ pub enum AuthorType {
 
 #[non_exhaustive]
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum AgentStatus {
     Seen,
     Researching,
@@ -43,18 +60,97 @@ pub enum AgentStatus {
 
 #[non_exhaustive]
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum RevisionTrigger {
     Agent,
     Manual,
 }
 
+/// Another repository whose changes are part of a review, e.g. a submodule
+/// or a sibling worktree. Its files appear in the review under `prefix`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ExtraRepo {
+    pub repo_path: String,
+    pub base_ref: String,
+    pub prefix: String,
+}
+
+/// Where one of a review's files lives on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileLocation<'a> {
+    pub repo_path: &'a str,
+    pub base_ref: &'a str,
+    /// The file's path relative to `repo_path`.
+    pub path: &'a str,
+}
+
+/// Which version of a file a thread's line numbers refer to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum LineSide {
+    /// Lines of the file before the change, for commenting on deleted code.
+    Old,
+    /// Lines of the file after the change.
+    #[default]
+    New,
+}
+
+/// Context lines kept on each side of a thread's lines in its snippet.
+pub const SNIPPET_CONTEXT_LINES: u32 = 3;
+
+/// The code a thread was anchored to when it was created.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct ContentSnippet {
     pub lines: Vec<String>,
     pub context_before: Vec<String>,
     pub context_after: Vec<String>,
 }
 
+impl ContentSnippet {
+    /// Capture lines `line_start..=line_end` of `side` of `file` from its
+    /// hunks, with up to [`SNIPPET_CONTEXT_LINES`] of context each side.
+    /// None if any of those lines are outside the diff.
+    pub fn from_diff(
+        file: &FileDiff,
+        side: LineSide,
+        line_start: u32,
+        line_end: u32,
+    ) -> Option<Self> {
+        let side_lines: std::collections::BTreeMap<u32, &str> = file
+            .hunks
+            .iter()
+            .flat_map(|h| &h.lines)
+            .filter_map(|l| {
+                let line_no = match side {
+                    LineSide::Old => l.old_line_no,
+                    LineSide::New => l.new_line_no,
+                };
+                Some((line_no?, l.content.as_str()))
+            })
+            .collect();
+        let lines = (line_start..=line_end)
+            .map(|n| side_lines.get(&n).map(|l| l.to_string()))
+            .collect::<Option<Vec<_>>>()?;
+        let context = |range: std::ops::Range<u32>| {
+            range
+                .filter_map(|n| side_lines.get(&n).map(|l| l.to_string()))
+                .collect()
+        };
+        Some(Self {
+            lines,
+            context_before: context(line_start.saturating_sub(SNIPPET_CONTEXT_LINES)..line_start),
+            context_after: context(line_end + 1..line_end + 1 + SNIPPET_CONTEXT_LINES),
+        })
+    }
+
+    /// The anchored lines as one block of text.
+    pub fn anchor_text(&self) -> String {
+        self.lines.join("\n")
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Revision {
     pub id: Uuid,
@@ -63,6 +159,11 @@ pub struct Revision {
     pub trigger: RevisionTrigger,
     pub message: Option<String>,
     pub files: Vec<FileDiff>,
+    /// Base-side content of the changed files, by old path, captured when
+    /// the revision was made so it can be shown after the repo changes or
+    /// goes away. Empty unless snapshotting is turned on.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub base_snapshots: HashMap<String, String>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -73,8 +174,64 @@ pub struct Review {
     pub status: ReviewStatus,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
-    pub repo_path: String,
+    /// When the status last changed, for the activity feed. Other edits,
+    /// like labels or a decision, only move `updated_at`.
+    #[serde(default)]
+    pub status_changed_at: Option<DateTime<Utc>>,
+    /// `None` for reviews created from a pasted or piped diff, which have no
+    /// repository to read files from.
+    pub repo_path: Option<String>,
     pub base_ref: String,
+    #[serde(default)]
+    pub path_prefix: Option<String>,
+    /// When the agent last said it had read the whole review. Cleared by the
+    /// next human comment.
+    #[serde(default)]
+    pub agent_seen_at: Option<DateTime<Utc>>,
+    /// When the human last marked the review as seen. Comments after it are
+    /// unread.
+    #[serde(default)]
+    pub human_seen_at: Option<DateTime<Utc>>,
+    /// Repositories diffed alongside `repo_path`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_repos: Vec<ExtraRepo>,
+    /// Free-form tags from integrators, e.g. `pr:123` or a model name.
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Used for every diff of the review, so revisions stay comparable.
+    #[serde(default)]
+    pub diff_algorithm: crate::git_diff::DiffAlgorithm,
+    /// The reviewer's verdict, if they have given one.
+    #[serde(default)]
+    pub decision: Option<ReviewDecision>,
+}
+
+impl Review {
+    /// Find the repository a review path belongs to. Paths under an extra
+    /// repository's prefix belong to it (the longest prefix wins); everything
+    /// else belongs to the main repository, or to none if the review has no
+    /// repository.
+    pub fn locate<'a>(&'a self, path: &'a str) -> Option<FileLocation<'a>> {
+        self.extra_repos
+            .iter()
+            .filter_map(|repo| {
+                let rest = path.strip_prefix(repo.prefix.as_str())?.strip_prefix('/')?;
+                Some((repo, rest))
+            })
+            .max_by_key(|(repo, _)| repo.prefix.len())
+            .map(|(repo, rest)| FileLocation {
+                repo_path: &repo.repo_path,
+                base_ref: &repo.base_ref,
+                path: rest,
+            })
+            .or_else(|| {
+                Some(FileLocation {
+                    repo_path: self.repo_path.as_deref()?,
+                    base_ref: &self.base_ref,
+                    path,
+                })
+            })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -83,6 +240,45 @@ pub struct Comment {
     pub author_type: AuthorType,
     pub body: String,
     pub created_at: DateTime<Utc>,
+    /// Stored but not yet announced; published by `ReviewStore::publish_drafts`.
+    #[serde(default)]
+    pub draft: bool,
+    /// An earlier comment in the same thread that this one replies to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub in_reply_to: Option<Uuid>,
+    /// Proposed replacement text for the thread's line range.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+    /// Structured content for clients that can render it; `body` stays the
+    /// plain-text fallback.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+}
+
+/// Structured content attached to a comment.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(tag = "type")]
+pub enum Attachment {
+    /// A block of code, highlighted as `language` (e.g. `rust`) when given.
+    CodeBlock {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        language: Option<String>,
+        code: String,
+    },
+}
+
+impl Attachment {
+    /// Bytes of text the attachment carries, which count toward a comment's
+    /// size limit along with its body.
+    pub fn byte_len(&self) -> usize {
+        match self {
+            Attachment::CodeBlock { language, code } => {
+                language.as_deref().map_or(0, str::len) + code.len()
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -92,6 +288,9 @@ pub struct CommentThread {
     pub file_path: String,
     pub line_start: u32,
     pub line_end: u32,
+    /// Which version of the file `line_start`/`line_end` number.
+    #[serde(default)]
+    pub side: LineSide,
     pub origin: ThreadOrigin,
     pub status: ThreadStatus,
     pub comments: Vec<Comment>,
@@ -101,4 +300,31 @@ pub struct CommentThread {
     pub revision_number: Option<u32>,
     #[serde(default)]
     pub content_snippet: Option<ContentSnippet>,
+    /// Threads in the same review discussing the same thing elsewhere.
+    /// Links are always recorded on both threads.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related_threads: Vec<Uuid>,
+    /// Kept at the top of thread listings so blocking issues stay visible.
+    #[serde(default)]
+    pub pinned: bool,
+    /// Bumped on every change to the thread, so a client can make a status
+    /// update conditional on the thread being as it last read it.
+    #[serde(default)]
+    pub version: u64,
+}
+
+impl CommentThread {
+    /// Record a change made at `now`.
+    pub(crate) fn touch(&mut self, now: DateTime<Utc>) {
+        self.updated_at = now;
+        self.version += 1;
+    }
+
+    /// Whether the thread counts toward a review's or file's open thread
+    /// count. Agent explanations need no answer, so they only count when
+    /// `count_explanations` is set.
+    pub fn counts_as_open(&self, count_explanations: bool) -> bool {
+        self.status == ThreadStatus::Open
+            && (count_explanations || self.origin != ThreadOrigin::AgentExplanation)
+    }
 }
@@ -1,24 +1,47 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 
-use crate::review::{AuthorType, CommentThread, Review, ReviewStatus, ThreadOrigin, ThreadStatus};
+use crate::review::{
+    AuthorType, CommentThread, ExtraRepo, LineSide, Review, ReviewDecision, ReviewStatus,
+    ThreadOrigin, ThreadStatus,
+};
 use uuid::Uuid;
 
-/// Summary of a review for listing.
+/// Summary of a review for listing. Carries everything a listing shows, so
+/// callers don't need to fetch each review.
 #[derive(Debug, Clone)]
 pub struct ReviewSummary {
     pub id: Uuid,
     pub title: Option<String>,
     pub status: ReviewStatus,
     pub thread_count: usize,
+    /// Open threads, not counting agent explanations.
     pub open_thread_count: usize,
+    /// Open threads including agent explanations.
+    pub open_thread_count_with_explanations: usize,
     pub file_count: usize,
+    pub revision_count: usize,
+    pub path_prefix: Option<String>,
+    pub extra_repos: Vec<ExtraRepo>,
+    pub labels: Vec<String>,
+    pub agent_seen_at: Option<DateTime<Utc>>,
+    pub decision: Option<ReviewDecision>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
 }
 
 /// Input for creating a new review.
 pub struct CreateReviewInput {
     pub title: Option<String>,
-    pub repo_path: String,
+    pub repo_path: Option<String>,
     pub base_ref: String,
+    pub path_prefix: Option<String>,
+    /// Further repositories whose changes belong to the review.
+    pub extra_repos: Vec<ExtraRepo>,
+    pub labels: Vec<String>,
+    pub diff_algorithm: crate::git_diff::DiffAlgorithm,
 }
 
 /// Input for creating a new comment thread.
@@ -32,6 +55,19 @@ pub struct CreateThreadInput {
     pub initial_comment_author: AuthorType,
     pub revision_number: Option<u32>,
     pub content_snippet: Option<crate::review::ContentSnippet>,
+    /// Replacement text the initial comment proposes for the line range.
+    pub initial_comment_suggestion: Option<String>,
+    /// Which version of the file the line numbers refer to.
+    pub side: LineSide,
+}
+
+/// Cap on a review's unresolved threads, checked by the store while it
+/// holds its lock so concurrent requests can't both squeeze under it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThreadLimit {
+    pub max_open: usize,
+    /// See [`CommentThread::counts_as_open`](crate::review::CommentThread::counts_as_open).
+    pub count_explanations: bool,
 }
 
 /// Input for creating a new revision.
@@ -40,6 +76,8 @@ pub struct CreateRevisionInput {
     pub trigger: crate::review::RevisionTrigger,
     pub message: Option<String>,
     pub files: Vec<crate::diff::FileDiff>,
+    /// See [`Revision::base_snapshots`](crate::review::Revision::base_snapshots).
+    pub base_snapshots: HashMap<String, String>,
 }
 
 /// Input for adding a comment to a thread.
@@ -47,6 +85,40 @@ pub struct AddCommentInput {
     pub thread_id: Uuid,
     pub author_type: AuthorType,
     pub body: String,
+    pub draft: bool,
+    /// Must name a comment already in the same thread.
+    pub in_reply_to: Option<Uuid>,
+    /// Replacement text proposed for the thread's line range.
+    pub suggestion: Option<String>,
+    pub attachments: Vec<crate::review::Attachment>,
+}
+
+/// Which threads `all_threads` returns, across every review.
+#[derive(Debug, Clone, Default)]
+pub struct AllThreadsFilter {
+    pub status: Option<ThreadStatus>,
+    pub origin: Option<ThreadOrigin>,
+    /// Matching threads to skip before the page starts.
+    pub offset: usize,
+    /// Most threads in the page; `None` returns them all.
+    pub limit: Option<usize>,
+}
+
+/// A thread along with the title of the review it belongs to.
+#[derive(Debug, Clone)]
+pub struct ReviewThread {
+    pub review_title: Option<String>,
+    /// The review's `human_seen_at`, for counting the thread's unread comments.
+    pub human_seen_at: Option<DateTime<Utc>>,
+    pub thread: CommentThread,
+}
+
+/// One page of `all_threads`.
+#[derive(Debug, Clone)]
+pub struct ThreadPage {
+    pub threads: Vec<ReviewThread>,
+    /// Matching threads across all pages.
+    pub total: usize,
 }
 
 #[non_exhaustive]
@@ -55,6 +127,35 @@ pub enum StoreError {
     ReviewNotFound(Uuid),
     ThreadNotFound(Uuid),
     RevisionNotFound(Uuid),
+    /// `in_reply_to` names a comment that is not in the thread.
+    ReplyTargetNotFound {
+        thread_id: Uuid,
+        comment_id: Uuid,
+    },
+    /// The comment is not in the thread.
+    CommentNotFound {
+        thread_id: Uuid,
+        comment_id: Uuid,
+    },
+    /// A thread cannot be linked to a thread in another review.
+    LinkAcrossReviews {
+        thread_id: Uuid,
+        related_id: Uuid,
+    },
+    /// The thread changed since the version the caller expected.
+    VersionConflict {
+        thread_id: Uuid,
+        expected: u64,
+        actual: u64,
+    },
+    /// The change would take the review past its [`ThreadLimit`].
+    ThreadLimitReached {
+        review_id: Uuid,
+        open: usize,
+        limit: usize,
+    },
+    /// A snapshot passed to `restore` could not be read as store state.
+    InvalidSnapshot(String),
     PersistenceError(String),
 }
 
@@ -64,6 +165,38 @@ impl std::fmt::Display for StoreError {
             StoreError::ReviewNotFound(id) => write!(f, "review not found: {id}"),
             StoreError::ThreadNotFound(id) => write!(f, "thread not found: {id}"),
             StoreError::RevisionNotFound(id) => write!(f, "revision not found: {id}"),
+            StoreError::ReplyTargetNotFound {
+                thread_id,
+                comment_id,
+            } => write!(f, "comment {comment_id} not found in thread {thread_id}"),
+            StoreError::CommentNotFound {
+                thread_id,
+                comment_id,
+            } => write!(f, "comment not found: {comment_id} (thread {thread_id})"),
+            StoreError::LinkAcrossReviews {
+                thread_id,
+                related_id,
+            } => write!(
+                f,
+                "thread {related_id} is not in the same review as thread {thread_id}"
+            ),
+            StoreError::VersionConflict {
+                thread_id,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "thread {thread_id} is at version {actual}, not {expected}; it changed since it was read"
+            ),
+            StoreError::ThreadLimitReached {
+                review_id,
+                open,
+                limit,
+            } => write!(
+                f,
+                "review {review_id} already has {open} open threads; the limit is {limit}"
+            ),
+            StoreError::InvalidSnapshot(msg) => write!(f, "invalid snapshot: {msg}"),
             StoreError::PersistenceError(msg) => write!(f, "persistence error: {msg}"),
         }
     }
@@ -88,28 +221,122 @@ pub trait ReviewStore: Send + Sync {
     async fn create_review(&self, input: CreateReviewInput) -> Result<Review, StoreError>;
     async fn get_review(&self, id: Uuid) -> Result<Review, StoreError>;
     async fn list_reviews(&self) -> Vec<ReviewSummary>;
+    /// Like `list_reviews`, but only reviews with the given status.
+    async fn list_reviews_by_status(&self, status: ReviewStatus) -> Vec<ReviewSummary>;
     async fn update_review_status(&self, id: Uuid, status: ReviewStatus) -> Result<(), StoreError>;
+    /// Replace a review's labels.
+    async fn set_labels(&self, id: Uuid, labels: Vec<String>) -> Result<(), StoreError>;
+    /// Record the reviewer's verdict, or clear it with `None`.
+    async fn set_decision(
+        &self,
+        id: Uuid,
+        decision: Option<ReviewDecision>,
+    ) -> Result<(), StoreError>;
+    /// Record when the agent last saw the whole review, or clear it with `None`.
+    async fn set_agent_seen(
+        &self,
+        id: Uuid,
+        seen_at: Option<DateTime<Utc>>,
+    ) -> Result<(), StoreError>;
+    /// Record when the human last saw the review, so later comments count as
+    /// unread. Doesn't touch `updated_at`.
+    async fn set_human_seen(&self, id: Uuid, seen_at: DateTime<Utc>) -> Result<(), StoreError>;
     async fn delete_review(&self, id: Uuid) -> Result<(), StoreError>;
+    /// Delete every closed review. Each status is checked as the review is
+    /// removed, atomically, so a concurrent reopen either lands first and
+    /// keeps the review or fails with `ReviewNotFound`.
     async fn delete_closed_reviews(&self) -> Result<Vec<Uuid>, StoreError>;
 
-    async fn create_thread(&self, input: CreateThreadInput) -> Result<CommentThread, StoreError>;
+    /// Create a thread, failing with `ThreadLimitReached` if it would take
+    /// the review past `limit`.
+    async fn create_thread(
+        &self,
+        input: CreateThreadInput,
+        limit: Option<ThreadLimit>,
+    ) -> Result<CommentThread, StoreError>;
+    /// Create several threads on one review atomically: either all are
+    /// created or, if the review does not exist or the batch would take it
+    /// past `limit`, none are.
+    async fn create_threads(
+        &self,
+        review_id: Uuid,
+        inputs: Vec<CreateThreadInput>,
+        limit: Option<ThreadLimit>,
+    ) -> Result<Vec<CommentThread>, StoreError>;
+    /// Copy threads into a review, giving the threads and their comments new
+    /// ids (with `in_reply_to` and `related_threads` remapped; links to
+    /// threads not being copied are dropped) and keeping everything else.
+    /// Nothing is copied if the copies would take the review past `limit`.
+    async fn copy_threads(
+        &self,
+        review_id: Uuid,
+        threads: Vec<CommentThread>,
+        limit: Option<ThreadLimit>,
+    ) -> Result<Vec<CommentThread>, StoreError>;
+    /// Pin or unpin a thread. Returns the updated thread.
+    async fn set_thread_pinned(
+        &self,
+        thread_id: Uuid,
+        pinned: bool,
+    ) -> Result<CommentThread, StoreError>;
+    /// Link a thread to other threads in the same review, recording the link
+    /// on both sides. Existing links and self-links are ignored. Returns the
+    /// updated thread.
+    async fn link_threads(
+        &self,
+        thread_id: Uuid,
+        related: Vec<Uuid>,
+    ) -> Result<CommentThread, StoreError>;
     async fn get_thread(&self, thread_id: Uuid) -> Result<CommentThread, StoreError>;
+    /// A review's threads, oldest first (ties broken by id).
     async fn get_threads(
         &self,
         review_id: Uuid,
         file_path: Option<&str>,
     ) -> Result<Vec<CommentThread>, StoreError>;
+    /// Threads from every review that match `filter`, oldest first (ties
+    /// broken by id), in one pass over the store.
+    async fn all_threads(&self, filter: &AllThreadsFilter) -> ThreadPage;
+    /// Set a thread's status. With `expected_version`, fails with
+    /// `VersionConflict` unless the thread is still at that version, so a
+    /// client acting on a stale read can't undo a newer change. Reopening
+    /// fails with `ThreadLimitReached` if the review is already at `limit`.
     async fn update_thread_status(
         &self,
         thread_id: Uuid,
         status: ThreadStatus,
+        expected_version: Option<u64>,
+        limit: Option<ThreadLimit>,
     ) -> Result<(), StoreError>;
 
+    async fn get_comment(
+        &self,
+        thread_id: Uuid,
+        comment_id: Uuid,
+    ) -> Result<crate::review::Comment, StoreError>;
+    /// The comments of each of `thread_ids`, keyed by thread id, read under
+    /// one lock so they are consistent with each other. Fails if any thread
+    /// is missing.
+    async fn get_thread_comments(
+        &self,
+        thread_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<crate::review::Comment>>, StoreError>;
     async fn add_comment(
         &self,
         input: AddCommentInput,
     ) -> Result<crate::review::Comment, StoreError>;
+    /// Mark every draft comment in a review as published.
+    /// Returns the published comments paired with their thread ids.
+    async fn publish_drafts(
+        &self,
+        review_id: Uuid,
+    ) -> Result<Vec<(Uuid, crate::review::Comment)>, StoreError>;
 
+    /// Store a new revision, numbering it one past the review's latest.
+    ///
+    /// The store owns revision numbering: numbers start at 1 and are unique
+    /// and gap-free per review, even when called concurrently. Implementations
+    /// must allocate the number and insert the revision atomically.
     async fn create_revision(
         &self,
         input: CreateRevisionInput,
@@ -127,4 +354,9 @@ pub trait ReviewStore: Send + Sync {
         &self,
         review_id: Uuid,
     ) -> Result<crate::review::Revision, StoreError>;
+
+    /// The whole store contents as JSON, in a form `restore` accepts.
+    async fn snapshot(&self) -> Result<serde_json::Value, StoreError>;
+    /// Replace the whole store contents with a snapshot taken by `snapshot`.
+    async fn restore(&self, snapshot: serde_json::Value) -> Result<(), StoreError>;
 }
@@ -0,0 +1,1461 @@
+//! Tests every `ReviewStore` implementation must pass, run against each
+//! store with `review_store_tests!`.
+
+use uuid::Uuid;
+
+use crate::review::{AuthorType, CommentThread, LineSide, Review, ThreadOrigin};
+use crate::store::{CreateReviewInput, CreateThreadInput, ReviewStore};
+
+pub(crate) async fn create_review_with_store(store: &dyn ReviewStore) -> Review {
+    store
+        .create_review(CreateReviewInput {
+            title: Some("Test".into()),
+            repo_path: Some("/tmp/test-repo".into()),
+            base_ref: "HEAD".into(),
+            path_prefix: None,
+            extra_repos: Vec::new(),
+            labels: Vec::new(),
+            diff_algorithm: Default::default(),
+        })
+        .await
+        .unwrap()
+}
+
+pub(crate) async fn create_thread_on(store: &dyn ReviewStore, review_id: Uuid) -> CommentThread {
+    store
+        .create_thread(
+            CreateThreadInput {
+                review_id,
+                file_path: "src/a.rs".into(),
+                line_start: 1,
+                line_end: 1,
+                origin: ThreadOrigin::Comment,
+                initial_comment_body: "same pattern here".into(),
+                initial_comment_author: AuthorType::Human,
+                revision_number: None,
+                content_snippet: None,
+                initial_comment_suggestion: None,
+                side: LineSide::New,
+            },
+            None,
+        )
+        .await
+        .unwrap()
+}
+
+/// Expand the shared battery into a `review_store` module. `$make_store` names
+/// an async fn in the calling module returning the store under test along with
+/// anything that has to outlive it (e.g. its temp dir).
+macro_rules! review_store_tests {
+    ($make_store:ident) => {
+        mod review_store {
+            use chrono::Utc;
+            use uuid::Uuid;
+
+            use super::$make_store;
+            use $crate::review::{AuthorType, LineSide, ReviewStatus, ThreadOrigin, ThreadStatus};
+            use $crate::store::{
+                AddCommentInput, AllThreadsFilter, CreateReviewInput, CreateRevisionInput,
+                CreateThreadInput, ReviewStore, StoreError, ThreadLimit,
+            };
+            use $crate::store_tests::{create_review_with_store, create_thread_on};
+
+            #[tokio::test]
+            async fn test_create_and_get_review() {
+                let (store, _guard) = $make_store().await;
+                let review = store
+                    .create_review(CreateReviewInput {
+                        title: Some("Test review".into()),
+                        repo_path: Some("/tmp/test-repo".into()),
+                        base_ref: "HEAD".into(),
+                        path_prefix: None,
+                        extra_repos: Vec::new(),
+                        labels: Vec::new(),
+                        diff_algorithm: Default::default(),
+                    })
+                    .await
+                    .unwrap();
+                assert_eq!(review.title.as_deref(), Some("Test review"));
+                assert_eq!(review.status, ReviewStatus::Open);
+                let fetched = store.get_review(review.id).await.unwrap();
+                assert_eq!(fetched.id, review.id);
+            }
+
+            #[tokio::test]
+            async fn test_get_review_not_found() {
+                let (store, _guard) = $make_store().await;
+                let result = store.get_review(Uuid::new_v4()).await;
+                assert!(matches!(result, Err(StoreError::ReviewNotFound(_))));
+            }
+
+            #[tokio::test]
+            async fn test_list_reviews() {
+                let (store, _guard) = $make_store().await;
+                assert!(store.list_reviews().await.is_empty());
+                store
+                    .create_review(CreateReviewInput {
+                        title: Some("First".into()),
+                        repo_path: Some("/tmp/repo1".into()),
+                        base_ref: "HEAD".into(),
+                        path_prefix: None,
+                        extra_repos: Vec::new(),
+                        labels: Vec::new(),
+                        diff_algorithm: Default::default(),
+                    })
+                    .await
+                    .unwrap();
+                store
+                    .create_review(CreateReviewInput {
+                        title: Some("Second".into()),
+                        repo_path: Some("/tmp/repo2".into()),
+                        base_ref: "HEAD".into(),
+                        path_prefix: None,
+                        extra_repos: Vec::new(),
+                        labels: Vec::new(),
+                        diff_algorithm: Default::default(),
+                    })
+                    .await
+                    .unwrap();
+                let list = store.list_reviews().await;
+                assert_eq!(list.len(), 2);
+            }
+
+            #[tokio::test]
+            async fn test_list_reviews_carries_timestamps() {
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                store
+                    .update_review_status(review.id, ReviewStatus::Closed)
+                    .await
+                    .unwrap();
+                let updated = store.get_review(review.id).await.unwrap();
+
+                let list = store.list_reviews().await;
+                assert_eq!(list[0].created_at, review.created_at);
+                assert_eq!(list[0].updated_at, updated.updated_at);
+                assert!(list[0].updated_at > list[0].created_at);
+            }
+
+            #[tokio::test]
+            async fn test_only_status_changes_set_status_changed_at() {
+                use crate::review::ReviewDecision;
+
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                assert!(review.status_changed_at.is_none());
+
+                store
+                    .set_labels(review.id, vec!["pr:1".into()])
+                    .await
+                    .unwrap();
+                store
+                    .set_decision(review.id, Some(ReviewDecision::Approved))
+                    .await
+                    .unwrap();
+                let updated = store.get_review(review.id).await.unwrap();
+                assert!(updated.updated_at > review.updated_at);
+                assert!(updated.status_changed_at.is_none());
+
+                store
+                    .update_review_status(review.id, ReviewStatus::Closed)
+                    .await
+                    .unwrap();
+                let updated = store.get_review(review.id).await.unwrap();
+                assert_eq!(updated.status_changed_at, Some(updated.updated_at));
+            }
+
+            #[tokio::test]
+            async fn test_list_reviews_by_status() {
+                let (store, _guard) = $make_store().await;
+                let open = create_review_with_store(&store).await;
+                let closed = create_review_with_store(&store).await;
+                store
+                    .update_review_status(closed.id, ReviewStatus::Closed)
+                    .await
+                    .unwrap();
+                store
+                    .create_thread(CreateThreadInput {
+                        review_id: open.id,
+                        file_path: "src/main.rs".into(),
+                        line_start: 1,
+                        line_end: 1,
+                        origin: ThreadOrigin::Comment,
+                        initial_comment_body: "hmm".into(),
+                        initial_comment_author: AuthorType::Human,
+                        revision_number: None,
+                        content_snippet: None,
+                        initial_comment_suggestion: None,
+                        side: LineSide::New,
+                    }, None)
+                    .await
+                    .unwrap();
+
+                let list = store.list_reviews_by_status(ReviewStatus::Open).await;
+                assert_eq!(list.len(), 1);
+                assert_eq!(list[0].id, open.id);
+                assert_eq!(list[0].thread_count, 1);
+                assert_eq!(list[0].open_thread_count, 1);
+
+                let list = store.list_reviews_by_status(ReviewStatus::Closed).await;
+                assert_eq!(list.len(), 1);
+                assert_eq!(list[0].id, closed.id);
+                assert_eq!(list[0].thread_count, 0);
+            }
+
+            #[tokio::test]
+            async fn test_update_review_status() {
+                let (store, _guard) = $make_store().await;
+                let review = store
+                    .create_review(CreateReviewInput {
+                        title: None,
+                        repo_path: Some("/tmp/repo".into()),
+                        base_ref: "HEAD".into(),
+                        path_prefix: None,
+                        extra_repos: Vec::new(),
+                        labels: Vec::new(),
+                        diff_algorithm: Default::default(),
+                    })
+                    .await
+                    .unwrap();
+                store
+                    .update_review_status(review.id, ReviewStatus::Closed)
+                    .await
+                    .unwrap();
+                let updated = store.get_review(review.id).await.unwrap();
+                assert_eq!(updated.status, ReviewStatus::Closed);
+            }
+
+            #[tokio::test]
+            async fn test_set_agent_seen() {
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                assert!(review.agent_seen_at.is_none());
+
+                let seen_at = Utc::now();
+                store
+                    .set_agent_seen(review.id, Some(seen_at))
+                    .await
+                    .unwrap();
+                let updated = store.get_review(review.id).await.unwrap();
+                assert_eq!(updated.agent_seen_at, Some(seen_at));
+                assert_eq!(updated.updated_at, review.updated_at);
+
+                store.set_agent_seen(review.id, None).await.unwrap();
+                assert!(
+                    store
+                        .get_review(review.id)
+                        .await
+                        .unwrap()
+                        .agent_seen_at
+                        .is_none()
+                );
+
+                assert!(matches!(
+                    store.set_agent_seen(Uuid::new_v4(), None).await,
+                    Err(StoreError::ReviewNotFound(_))
+                ));
+            }
+
+            #[tokio::test]
+            async fn test_set_human_seen() {
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                assert!(review.human_seen_at.is_none());
+
+                let seen_at = Utc::now();
+                store.set_human_seen(review.id, seen_at).await.unwrap();
+                let updated = store.get_review(review.id).await.unwrap();
+                assert_eq!(updated.human_seen_at, Some(seen_at));
+                assert_eq!(updated.updated_at, review.updated_at);
+
+                assert!(matches!(
+                    store.set_human_seen(Uuid::new_v4(), seen_at).await,
+                    Err(StoreError::ReviewNotFound(_))
+                ));
+            }
+
+            #[tokio::test]
+            async fn test_create_and_get_thread() {
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                let thread = store
+                    .create_thread(CreateThreadInput {
+                        review_id: review.id,
+                        file_path: "src/main.rs".into(),
+                        line_start: 10,
+                        line_end: 15,
+                        origin: ThreadOrigin::Comment,
+                        initial_comment_body: "Looks wrong".into(),
+                        initial_comment_author: AuthorType::Human,
+                        revision_number: None,
+                        content_snippet: None,
+                        initial_comment_suggestion: None,
+                        side: LineSide::New,
+                    }, None)
+                    .await
+                    .unwrap();
+                assert_eq!(thread.file_path, "src/main.rs");
+                assert_eq!(thread.line_start, 10);
+                assert_eq!(thread.line_end, 15);
+                assert_eq!(thread.comments.len(), 1);
+                assert_eq!(thread.comments[0].body, "Looks wrong");
+                assert_eq!(thread.comments[0].author_type, AuthorType::Human);
+            }
+
+            #[tokio::test]
+            async fn test_create_thread_review_not_found() {
+                let (store, _guard) = $make_store().await;
+                let result = store
+                    .create_thread(CreateThreadInput {
+                        review_id: Uuid::new_v4(),
+                        file_path: "x".into(),
+                        line_start: 1,
+                        line_end: 1,
+                        origin: ThreadOrigin::Comment,
+                        initial_comment_body: "hi".into(),
+                        initial_comment_author: AuthorType::Human,
+                        revision_number: None,
+                        content_snippet: None,
+                        initial_comment_suggestion: None,
+                        side: LineSide::New,
+                    }, None)
+                    .await;
+                assert!(matches!(result, Err(StoreError::ReviewNotFound(_))));
+            }
+
+            #[tokio::test]
+            async fn test_create_threads_batch() {
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                let inputs = ["src/a.rs", "src/b.rs"]
+                    .into_iter()
+                    .map(|path| CreateThreadInput {
+                        review_id: review.id,
+                        file_path: path.into(),
+                        line_start: 1,
+                        line_end: 2,
+                        origin: ThreadOrigin::Comment,
+                        initial_comment_body: "batch".into(),
+                        initial_comment_author: AuthorType::Agent,
+                        revision_number: None,
+                        content_snippet: None,
+                        initial_comment_suggestion: None,
+                        side: LineSide::New,
+                    })
+                    .collect();
+                let threads = store.create_threads(review.id, inputs, None).await.unwrap();
+                assert_eq!(threads.len(), 2);
+                assert_eq!(store.get_threads(review.id, None).await.unwrap().len(), 2);
+
+                let result = store.create_threads(Uuid::new_v4(), Vec::new(), None).await;
+                assert!(matches!(result, Err(StoreError::ReviewNotFound(_))));
+            }
+
+            #[tokio::test]
+            async fn test_thread_limit() {
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                let limit = Some(ThreadLimit {
+                    max_open: 1,
+                    count_explanations: false,
+                });
+                let input = |origin| CreateThreadInput {
+                    review_id: review.id,
+                    file_path: "src/a.rs".into(),
+                    line_start: 1,
+                    line_end: 1,
+                    origin,
+                    initial_comment_body: "limit".into(),
+                    initial_comment_author: AuthorType::Agent,
+                    revision_number: None,
+                    content_snippet: None,
+                    initial_comment_suggestion: None,
+                    side: LineSide::New,
+                };
+                let first = store
+                    .create_thread(input(ThreadOrigin::Comment), limit)
+                    .await
+                    .unwrap();
+                let result = store
+                    .create_thread(input(ThreadOrigin::Comment), limit)
+                    .await;
+                assert!(matches!(
+                    result,
+                    Err(StoreError::ThreadLimitReached { open: 1, limit: 1, .. })
+                ));
+                let result = store
+                    .create_threads(review.id, vec![input(ThreadOrigin::Comment)], limit)
+                    .await;
+                assert!(matches!(result, Err(StoreError::ThreadLimitReached { .. })));
+                // Explanations only count when asked to
+                let explanation = store
+                    .create_thread(input(ThreadOrigin::AgentExplanation), limit)
+                    .await
+                    .unwrap();
+                let counting = Some(ThreadLimit {
+                    max_open: 1,
+                    count_explanations: true,
+                });
+                let result = store
+                    .create_thread(input(ThreadOrigin::AgentExplanation), counting)
+                    .await;
+                assert!(matches!(result, Err(StoreError::ThreadLimitReached { .. })));
+
+                // Reopening is refused while the review is full...
+                store
+                    .update_thread_status(first.id, ThreadStatus::Resolved, None, limit)
+                    .await
+                    .unwrap();
+                store
+                    .create_thread(input(ThreadOrigin::Comment), limit)
+                    .await
+                    .unwrap();
+                let result = store
+                    .update_thread_status(first.id, ThreadStatus::Open, None, limit)
+                    .await;
+                assert!(matches!(result, Err(StoreError::ThreadLimitReached { .. })));
+                // ...but resolving and touching already-open threads isn't
+                store
+                    .update_thread_status(explanation.id, ThreadStatus::Open, None, limit)
+                    .await
+                    .unwrap();
+
+                // Copies are all-or-nothing
+                let target = create_review_with_store(&store).await;
+                let open = store.get_threads(review.id, None).await.unwrap();
+                let result = store.copy_threads(target.id, open, counting).await;
+                assert!(matches!(result, Err(StoreError::ThreadLimitReached { .. })));
+                assert!(store.get_threads(target.id, None).await.unwrap().is_empty());
+
+                assert_eq!(store.get_threads(review.id, None).await.unwrap().len(), 3);
+            }
+
+            #[tokio::test]
+            async fn test_create_threads_keeps_batch_order() {
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                let paths: Vec<String> = (0..20).map(|i| format!("src/{i}.rs")).collect();
+                let inputs = paths
+                    .iter()
+                    .map(|path| CreateThreadInput {
+                        review_id: review.id,
+                        file_path: path.clone(),
+                        line_start: 1,
+                        line_end: 1,
+                        origin: ThreadOrigin::Comment,
+                        initial_comment_body: "batch".into(),
+                        initial_comment_author: AuthorType::Agent,
+                        revision_number: None,
+                        content_snippet: None,
+                        initial_comment_suggestion: None,
+                        side: LineSide::New,
+                    })
+                    .collect();
+                store.create_threads(review.id, inputs, None).await.unwrap();
+                let listed: Vec<String> = store
+                    .get_threads(review.id, None)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|t| t.file_path)
+                    .collect();
+                assert_eq!(listed, paths);
+            }
+
+            #[tokio::test]
+            async fn test_copy_threads_into_another_review() {
+                let (store, _guard) = $make_store().await;
+                let source = create_review_with_store(&store).await;
+                let target = create_review_with_store(&store).await;
+                let thread = store
+                    .create_thread(CreateThreadInput {
+                        review_id: source.id,
+                        file_path: "src/a.rs".into(),
+                        line_start: 3,
+                        line_end: 4,
+                        origin: ThreadOrigin::Comment,
+                        initial_comment_body: "why?".into(),
+                        initial_comment_author: AuthorType::Human,
+                        revision_number: Some(1),
+                        content_snippet: None,
+                        initial_comment_suggestion: None,
+                        side: LineSide::New,
+                    }, None)
+                    .await
+                    .unwrap();
+                store
+                    .add_comment(AddCommentInput {
+                        thread_id: thread.id,
+                        author_type: AuthorType::Agent,
+                        body: "because".into(),
+                        draft: false,
+                        in_reply_to: Some(thread.comments[0].id),
+                        suggestion: None,
+                        attachments: Vec::new(),
+                    })
+                    .await
+                    .unwrap();
+                let original = store.get_thread(thread.id).await.unwrap();
+
+                let copies = store
+                    .copy_threads(target.id, vec![original.clone()], None)
+                    .await
+                    .unwrap();
+                assert_eq!(copies.len(), 1);
+                let copy = &copies[0];
+                assert_ne!(copy.id, original.id);
+                assert_eq!(copy.review_id, target.id);
+                assert_eq!(copy.file_path, "src/a.rs");
+                assert_eq!(copy.comments.len(), 2);
+                assert_ne!(copy.comments[0].id, original.comments[0].id);
+                assert_eq!(copy.comments[1].in_reply_to, Some(copy.comments[0].id));
+                // The source review is untouched
+                assert_eq!(store.get_threads(source.id, None).await.unwrap().len(), 1);
+                assert_eq!(store.get_threads(target.id, None).await.unwrap().len(), 1);
+            }
+
+            #[tokio::test]
+            async fn test_get_comment() {
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                let thread = create_thread_on(&store, review.id).await;
+                let other = create_thread_on(&store, review.id).await;
+                let comment_id = thread.comments[0].id;
+
+                let comment = store.get_comment(thread.id, comment_id).await.unwrap();
+                assert_eq!(comment.body, "same pattern here");
+                let result = store.get_comment(other.id, comment_id).await;
+                assert!(matches!(result, Err(StoreError::CommentNotFound { .. })));
+            }
+
+            #[tokio::test]
+            async fn test_get_thread_comments() {
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                let a = create_thread_on(&store, review.id).await;
+                let b = create_thread_on(&store, review.id).await;
+                store
+                    .add_comment(AddCommentInput {
+                        thread_id: b.id,
+                        author_type: AuthorType::Agent,
+                        body: "fixed".into(),
+                        draft: false,
+                        in_reply_to: None,
+                        suggestion: None,
+                        attachments: Vec::new(),
+                    })
+                    .await
+                    .unwrap();
+
+                let comments = store.get_thread_comments(&[a.id, b.id]).await.unwrap();
+                assert_eq!(comments.len(), 2);
+                assert_eq!(comments[&a.id].len(), 1);
+                assert_eq!(comments[&b.id].len(), 2);
+                assert_eq!(comments[&b.id][1].body, "fixed");
+
+                let result = store.get_thread_comments(&[a.id, Uuid::new_v4()]).await;
+                assert!(matches!(result, Err(StoreError::ThreadNotFound(_))));
+            }
+
+            #[tokio::test]
+            async fn test_link_threads_is_bidirectional() {
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                let a = create_thread_on(&store, review.id).await;
+                let b = create_thread_on(&store, review.id).await;
+                let c = create_thread_on(&store, review.id).await;
+
+                let linked = store.link_threads(a.id, vec![b.id, c.id]).await.unwrap();
+                assert_eq!(linked.related_threads, vec![b.id, c.id]);
+                // Linking again (and to itself) changes nothing
+                let linked = store.link_threads(b.id, vec![a.id, b.id]).await.unwrap();
+                assert_eq!(linked.related_threads, vec![a.id]);
+                let c = store.get_thread(c.id).await.unwrap();
+                assert_eq!(c.related_threads, vec![a.id]);
+            }
+
+            #[tokio::test]
+            async fn test_set_thread_pinned() {
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                let thread = create_thread_on(&store, review.id).await;
+                assert!(!thread.pinned);
+
+                let pinned = store.set_thread_pinned(thread.id, true).await.unwrap();
+                assert!(pinned.pinned);
+                assert!(store.get_thread(thread.id).await.unwrap().pinned);
+                let unpinned = store.set_thread_pinned(thread.id, false).await.unwrap();
+                assert!(!unpinned.pinned);
+
+                let result = store.set_thread_pinned(Uuid::new_v4(), true).await;
+                assert!(matches!(result, Err(StoreError::ThreadNotFound(_))));
+            }
+
+            #[tokio::test]
+            async fn test_link_threads_rejects_other_review() {
+                let (store, _guard) = $make_store().await;
+                let first = create_review_with_store(&store).await;
+                let second = create_review_with_store(&store).await;
+                let a = create_thread_on(&store, first.id).await;
+                let b = create_thread_on(&store, first.id).await;
+                let other = create_thread_on(&store, second.id).await;
+
+                let result = store.link_threads(a.id, vec![b.id, other.id]).await;
+                assert!(matches!(
+                    result,
+                    Err(StoreError::LinkAcrossReviews { related_id, .. }) if related_id == other.id
+                ));
+                // Nothing was linked
+                assert!(
+                    store
+                        .get_thread(a.id)
+                        .await
+                        .unwrap()
+                        .related_threads
+                        .is_empty()
+                );
+                assert!(
+                    store
+                        .get_thread(b.id)
+                        .await
+                        .unwrap()
+                        .related_threads
+                        .is_empty()
+                );
+            }
+
+            #[tokio::test]
+            async fn test_get_threads_review_not_found() {
+                let (store, _guard) = $make_store().await;
+                let result = store.get_threads(Uuid::new_v4(), None).await;
+                assert!(matches!(result, Err(StoreError::ReviewNotFound(_))));
+            }
+
+            #[tokio::test]
+            async fn test_get_threads_filtered_by_file() {
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                store
+                    .create_thread(CreateThreadInput {
+                        review_id: review.id,
+                        file_path: "src/a.rs".into(),
+                        line_start: 1,
+                        line_end: 1,
+                        origin: ThreadOrigin::Comment,
+                        initial_comment_body: "a".into(),
+                        initial_comment_author: AuthorType::Human,
+                        revision_number: None,
+                        content_snippet: None,
+                        initial_comment_suggestion: None,
+                        side: LineSide::New,
+                    }, None)
+                    .await
+                    .unwrap();
+                store
+                    .create_thread(CreateThreadInput {
+                        review_id: review.id,
+                        file_path: "src/b.rs".into(),
+                        line_start: 1,
+                        line_end: 1,
+                        origin: ThreadOrigin::ExplanationRequest,
+                        initial_comment_body: "b".into(),
+                        initial_comment_author: AuthorType::Human,
+                        revision_number: None,
+                        content_snippet: None,
+                        initial_comment_suggestion: None,
+                        side: LineSide::New,
+                    }, None)
+                    .await
+                    .unwrap();
+                let all = store.get_threads(review.id, None).await.unwrap();
+                assert_eq!(all.len(), 2);
+                let filtered = store
+                    .get_threads(review.id, Some("src/a.rs"))
+                    .await
+                    .unwrap();
+                assert_eq!(filtered.len(), 1);
+                assert_eq!(filtered[0].file_path, "src/a.rs");
+            }
+
+            #[tokio::test]
+            async fn test_update_thread_status() {
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                let thread = store
+                    .create_thread(CreateThreadInput {
+                        review_id: review.id,
+                        file_path: "src/main.rs".into(),
+                        line_start: 1,
+                        line_end: 1,
+                        origin: ThreadOrigin::Comment,
+                        initial_comment_body: "fix this".into(),
+                        initial_comment_author: AuthorType::Human,
+                        revision_number: None,
+                        content_snippet: None,
+                        initial_comment_suggestion: None,
+                        side: LineSide::New,
+                    }, None)
+                    .await
+                    .unwrap();
+                store
+                    .update_thread_status(thread.id, ThreadStatus::Resolved, None, None)
+                    .await
+                    .unwrap();
+                let threads = store.get_threads(review.id, None).await.unwrap();
+                assert_eq!(threads[0].status, ThreadStatus::Resolved);
+            }
+
+            #[tokio::test]
+            async fn test_update_thread_status_rejects_stale_version() {
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                let thread = create_thread_on(&store, review.id).await;
+                let stale = thread.version;
+
+                // Another client resolves and reopens the thread
+                store
+                    .update_thread_status(thread.id, ThreadStatus::Resolved, Some(stale), None)
+                    .await
+                    .unwrap();
+                let current = store.get_thread(thread.id).await.unwrap().version;
+                assert!(current > stale);
+                store
+                    .update_thread_status(thread.id, ThreadStatus::Open, Some(current), None)
+                    .await
+                    .unwrap();
+
+                // A resolve based on the first read must not clobber the reopen
+                let result = store
+                    .update_thread_status(thread.id, ThreadStatus::Resolved, Some(stale), None)
+                    .await;
+                assert!(matches!(
+                    result,
+                    Err(StoreError::VersionConflict { expected, .. }) if expected == stale
+                ));
+                let thread = store.get_thread(thread.id).await.unwrap();
+                assert_eq!(thread.status, ThreadStatus::Open);
+            }
+
+            #[tokio::test]
+            async fn test_add_comment_to_thread() {
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                let thread = store
+                    .create_thread(CreateThreadInput {
+                        review_id: review.id,
+                        file_path: "src/main.rs".into(),
+                        line_start: 1,
+                        line_end: 1,
+                        origin: ThreadOrigin::Comment,
+                        initial_comment_body: "why?".into(),
+                        initial_comment_author: AuthorType::Human,
+                        revision_number: None,
+                        content_snippet: None,
+                        initial_comment_suggestion: None,
+                        side: LineSide::New,
+                    }, None)
+                    .await
+                    .unwrap();
+                let comment = store
+                    .add_comment(AddCommentInput {
+                        thread_id: thread.id,
+                        author_type: AuthorType::Agent,
+                        body: "because X".into(),
+                        draft: false,
+                        in_reply_to: None,
+                        suggestion: None,
+                        attachments: Vec::new(),
+                    })
+                    .await
+                    .unwrap();
+                assert_eq!(comment.author_type, AuthorType::Agent);
+                assert_eq!(comment.body, "because X");
+                let threads = store.get_threads(review.id, None).await.unwrap();
+                assert_eq!(threads[0].comments.len(), 2);
+            }
+
+            #[tokio::test]
+            async fn test_add_comment_reply_target_must_be_in_thread() {
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                let thread = store
+                    .create_thread(CreateThreadInput {
+                        review_id: review.id,
+                        file_path: "src/main.rs".into(),
+                        line_start: 1,
+                        line_end: 1,
+                        origin: ThreadOrigin::Comment,
+                        initial_comment_body: "why?".into(),
+                        initial_comment_author: AuthorType::Human,
+                        revision_number: None,
+                        content_snippet: None,
+                        initial_comment_suggestion: None,
+                        side: LineSide::New,
+                    }, None)
+                    .await
+                    .unwrap();
+                let reply = store
+                    .add_comment(AddCommentInput {
+                        thread_id: thread.id,
+                        author_type: AuthorType::Agent,
+                        body: "because X".into(),
+                        draft: false,
+                        in_reply_to: Some(thread.comments[0].id),
+                        suggestion: None,
+                        attachments: Vec::new(),
+                    })
+                    .await
+                    .unwrap();
+                assert_eq!(reply.in_reply_to, Some(thread.comments[0].id));
+
+                let result = store
+                    .add_comment(AddCommentInput {
+                        thread_id: thread.id,
+                        author_type: AuthorType::Agent,
+                        body: "dangling".into(),
+                        draft: false,
+                        in_reply_to: Some(Uuid::new_v4()),
+                        suggestion: None,
+                        attachments: Vec::new(),
+                    })
+                    .await;
+                assert!(matches!(
+                    result,
+                    Err(StoreError::ReplyTargetNotFound { .. })
+                ));
+                let threads = store.get_threads(review.id, None).await.unwrap();
+                assert_eq!(threads[0].comments.len(), 2);
+            }
+
+            #[tokio::test]
+            async fn test_add_comment_thread_not_found() {
+                let (store, _guard) = $make_store().await;
+                let result = store
+                    .add_comment(AddCommentInput {
+                        thread_id: Uuid::new_v4(),
+                        author_type: AuthorType::Human,
+                        body: "hello".into(),
+                        draft: false,
+                        in_reply_to: None,
+                        suggestion: None,
+                        attachments: Vec::new(),
+                    })
+                    .await;
+                assert!(matches!(result, Err(StoreError::ThreadNotFound(_))));
+            }
+
+            #[tokio::test]
+            async fn test_publish_drafts() {
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                let thread = store
+                    .create_thread(CreateThreadInput {
+                        review_id: review.id,
+                        file_path: "src/main.rs".into(),
+                        line_start: 1,
+                        line_end: 1,
+                        origin: ThreadOrigin::Comment,
+                        initial_comment_body: "why?".into(),
+                        initial_comment_author: AuthorType::Human,
+                        revision_number: None,
+                        content_snippet: None,
+                        initial_comment_suggestion: None,
+                        side: LineSide::New,
+                    }, None)
+                    .await
+                    .unwrap();
+                for body in ["first", "second"] {
+                    store
+                        .add_comment(AddCommentInput {
+                            thread_id: thread.id,
+                            author_type: AuthorType::Human,
+                            body: body.into(),
+                            draft: true,
+                            in_reply_to: None,
+                            suggestion: None,
+                            attachments: Vec::new(),
+                        })
+                        .await
+                        .unwrap();
+                }
+
+                let published = store.publish_drafts(review.id).await.unwrap();
+                assert_eq!(published.len(), 2);
+                assert!(published.iter().all(|(id, c)| *id == thread.id && !c.draft));
+                assert_eq!(published[0].1.body, "first");
+
+                let thread = store.get_thread(thread.id).await.unwrap();
+                assert!(thread.comments.iter().all(|c| !c.draft));
+                assert!(store.publish_drafts(review.id).await.unwrap().is_empty());
+            }
+
+            #[tokio::test]
+            async fn test_all_threads_spans_reviews() {
+                let (store, _guard) = $make_store().await;
+                let first = create_review_with_store(&store).await;
+                let second = create_review_with_store(&store).await;
+                let a = create_thread_on(&store, first.id).await;
+                let b = create_thread_on(&store, second.id).await;
+                let resolved = create_thread_on(&store, second.id).await;
+                store
+                    .update_thread_status(resolved.id, ThreadStatus::Resolved, None, None)
+                    .await
+                    .unwrap();
+
+                let open = AllThreadsFilter {
+                    status: Some(ThreadStatus::Open),
+                    ..Default::default()
+                };
+                let page = store.all_threads(&open).await;
+                assert_eq!(page.total, 2);
+                let ids: Vec<Uuid> = page.threads.iter().map(|t| t.thread.id).collect();
+                assert_eq!(ids, [a.id, b.id]);
+                assert!(
+                    page.threads
+                        .iter()
+                        .all(|t| t.review_title.as_deref() == Some("Test"))
+                );
+
+                let page = store
+                    .all_threads(&AllThreadsFilter {
+                        offset: 1,
+                        limit: Some(1),
+                        ..open
+                    })
+                    .await;
+                assert_eq!(page.total, 2);
+                assert_eq!(page.threads.len(), 1);
+                assert_eq!(page.threads[0].thread.id, b.id);
+            }
+
+            #[tokio::test]
+            async fn test_create_review_with_repo_path() {
+                let (store, _guard) = $make_store().await;
+                let review = store
+                    .create_review(CreateReviewInput {
+                        title: Some("Repo test".into()),
+                        repo_path: Some("/tmp/fake-repo".into()),
+                        base_ref: "HEAD~1".into(),
+                        path_prefix: None,
+                        extra_repos: Vec::new(),
+                        labels: Vec::new(),
+                        diff_algorithm: Default::default(),
+                    })
+                    .await
+                    .unwrap();
+                assert_eq!(review.repo_path.as_deref(), Some("/tmp/fake-repo"));
+                assert_eq!(review.base_ref, "HEAD~1");
+
+                let fetched = store.get_review(review.id).await.unwrap();
+                assert_eq!(fetched.repo_path.as_deref(), Some("/tmp/fake-repo"));
+                assert_eq!(fetched.base_ref, "HEAD~1");
+            }
+
+            #[tokio::test]
+            async fn test_create_review_without_repo_path() {
+                let (store, _guard) = $make_store().await;
+                let review = store
+                    .create_review(CreateReviewInput {
+                        title: Some("Piped diff".into()),
+                        repo_path: None,
+                        base_ref: "HEAD".into(),
+                        path_prefix: None,
+                        extra_repos: Vec::new(),
+                        labels: Vec::new(),
+                        diff_algorithm: Default::default(),
+                    })
+                    .await
+                    .unwrap();
+                let fetched = store.get_review(review.id).await.unwrap();
+                assert!(fetched.repo_path.is_none());
+                assert!(fetched.locate("src/main.rs").is_none());
+            }
+
+            #[tokio::test]
+            async fn test_create_and_get_revision() {
+                use crate::diff::{FileDiff, FileStatus};
+                use crate::review::RevisionTrigger;
+
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                let file = FileDiff {
+                    old_path: None,
+                    new_path: Some("src/main.rs".into()),
+                    status: FileStatus::Added,
+                    hunks: vec![],
+                    mode_change: None,
+                };
+                let revision = store
+                    .create_revision(CreateRevisionInput {
+                        review_id: review.id,
+                        trigger: RevisionTrigger::Agent,
+                        message: Some("Initial diff".into()),
+                        files: vec![file],
+                        base_snapshots: Default::default(),
+                    })
+                    .await
+                    .unwrap();
+                assert_eq!(revision.revision_number, 1);
+                assert_eq!(revision.review_id, review.id);
+                assert_eq!(revision.files.len(), 1);
+                assert_eq!(revision.message.as_deref(), Some("Initial diff"));
+
+                let fetched = store.get_revision(review.id, 1).await.unwrap();
+                assert_eq!(fetched.id, revision.id);
+                assert_eq!(fetched.revision_number, 1);
+            }
+
+            #[tokio::test]
+            async fn test_revision_numbers_increment() {
+                use crate::review::RevisionTrigger;
+
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                let r1 = store
+                    .create_revision(CreateRevisionInput {
+                        review_id: review.id,
+                        trigger: RevisionTrigger::Agent,
+                        message: None,
+                        files: vec![],
+                        base_snapshots: Default::default(),
+                    })
+                    .await
+                    .unwrap();
+                let r2 = store
+                    .create_revision(CreateRevisionInput {
+                        review_id: review.id,
+                        trigger: RevisionTrigger::Manual,
+                        message: None,
+                        files: vec![],
+                        base_snapshots: Default::default(),
+                    })
+                    .await
+                    .unwrap();
+                assert_eq!(r1.revision_number, 1);
+                assert_eq!(r2.revision_number, 2);
+                assert_eq!(store.list_reviews().await[0].revision_count, 2);
+            }
+
+            #[tokio::test]
+            async fn test_concurrent_revisions_get_unique_numbers() {
+                use crate::review::RevisionTrigger;
+
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                let store = std::sync::Arc::new(store);
+                let handles: Vec<_> = (0..16)
+                    .map(|_| {
+                        let store = store.clone();
+                        tokio::spawn(async move {
+                            store
+                                .create_revision(CreateRevisionInput {
+                                    review_id: review.id,
+                                    trigger: RevisionTrigger::Agent,
+                                    message: None,
+                                    files: vec![],
+                                    base_snapshots: Default::default(),
+                                })
+                                .await
+                                .unwrap()
+                                .revision_number
+                        })
+                    })
+                    .collect();
+                let mut numbers = Vec::new();
+                for handle in handles {
+                    numbers.push(handle.await.unwrap());
+                }
+                numbers.sort();
+                assert_eq!(numbers, (1..=16).collect::<Vec<u32>>());
+            }
+
+            #[tokio::test]
+            async fn test_get_revisions_sorted() {
+                use crate::review::RevisionTrigger;
+
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                for _ in 0..3 {
+                    store
+                        .create_revision(CreateRevisionInput {
+                            review_id: review.id,
+                            trigger: RevisionTrigger::Agent,
+                            message: None,
+                            files: vec![],
+                            base_snapshots: Default::default(),
+                        })
+                        .await
+                        .unwrap();
+                }
+                let revisions = store.get_revisions(review.id).await.unwrap();
+                assert_eq!(revisions.len(), 3);
+                assert_eq!(revisions[0].revision_number, 1);
+                assert_eq!(revisions[1].revision_number, 2);
+                assert_eq!(revisions[2].revision_number, 3);
+            }
+
+            #[tokio::test]
+            async fn test_get_latest_revision() {
+                use crate::review::RevisionTrigger;
+
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                store
+                    .create_revision(CreateRevisionInput {
+                        review_id: review.id,
+                        trigger: RevisionTrigger::Agent,
+                        message: Some("first".into()),
+                        files: vec![],
+                        base_snapshots: Default::default(),
+                    })
+                    .await
+                    .unwrap();
+                store
+                    .create_revision(CreateRevisionInput {
+                        review_id: review.id,
+                        trigger: RevisionTrigger::Manual,
+                        message: Some("second".into()),
+                        files: vec![],
+                        base_snapshots: Default::default(),
+                    })
+                    .await
+                    .unwrap();
+                let latest = store.get_latest_revision(review.id).await.unwrap();
+                assert_eq!(latest.revision_number, 2);
+                assert_eq!(latest.message.as_deref(), Some("second"));
+            }
+
+            #[tokio::test]
+            async fn test_create_revision_review_not_found() {
+                use crate::review::RevisionTrigger;
+
+                let (store, _guard) = $make_store().await;
+                let result = store
+                    .create_revision(CreateRevisionInput {
+                        review_id: Uuid::new_v4(),
+                        trigger: RevisionTrigger::Agent,
+                        message: None,
+                        files: vec![],
+                        base_snapshots: Default::default(),
+                    })
+                    .await;
+                assert!(matches!(result, Err(StoreError::ReviewNotFound(_))));
+            }
+
+            #[tokio::test]
+            async fn test_set_labels() {
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                assert!(review.labels.is_empty());
+
+                store
+                    .set_labels(review.id, vec!["pr:42".into(), "ticket:ENG-7".into()])
+                    .await
+                    .unwrap();
+                let updated = store.get_review(review.id).await.unwrap();
+                assert_eq!(updated.labels, ["pr:42", "ticket:ENG-7"]);
+                assert_eq!(
+                    store.list_reviews().await[0].labels,
+                    ["pr:42", "ticket:ENG-7"]
+                );
+
+                assert!(matches!(
+                    store.set_labels(Uuid::new_v4(), Vec::new()).await,
+                    Err(StoreError::ReviewNotFound(_))
+                ));
+            }
+
+            #[tokio::test]
+            async fn test_set_decision() {
+                use crate::review::ReviewDecision;
+
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                assert!(review.decision.is_none());
+
+                for decision in [
+                    ReviewDecision::Approved,
+                    ReviewDecision::ChangesRequested,
+                    ReviewDecision::Commented,
+                ] {
+                    store.set_decision(review.id, Some(decision)).await.unwrap();
+                    let updated = store.get_review(review.id).await.unwrap();
+                    assert_eq!(updated.decision, Some(decision));
+                    assert_eq!(store.list_reviews().await[0].decision, Some(decision));
+                }
+                store.set_decision(review.id, None).await.unwrap();
+                assert!(store.get_review(review.id).await.unwrap().decision.is_none());
+
+                assert!(matches!(
+                    store.set_decision(Uuid::new_v4(), None).await,
+                    Err(StoreError::ReviewNotFound(_))
+                ));
+            }
+
+            #[tokio::test]
+            async fn test_list_reviews_open_thread_count() {
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                // Create two threads
+                for body in ["first", "second"] {
+                    store
+                        .create_thread(CreateThreadInput {
+                            review_id: review.id,
+                            file_path: "src/main.rs".into(),
+                            line_start: 1,
+                            line_end: 1,
+                            origin: ThreadOrigin::Comment,
+                            initial_comment_body: body.into(),
+                            initial_comment_author: AuthorType::Human,
+                            revision_number: None,
+                            content_snippet: None,
+                            initial_comment_suggestion: None,
+                            side: LineSide::New,
+                        }, None)
+                        .await
+                        .unwrap();
+                }
+                // Both open
+                let list = store.list_reviews().await;
+                assert_eq!(list[0].thread_count, 2);
+                assert_eq!(list[0].open_thread_count, 2);
+
+                // Resolve one
+                let threads = store.get_threads(review.id, None).await.unwrap();
+                store
+                    .update_thread_status(threads[0].id, ThreadStatus::Resolved, None, None)
+                    .await
+                    .unwrap();
+                let list = store.list_reviews().await;
+                assert_eq!(list[0].thread_count, 2);
+                assert_eq!(list[0].open_thread_count, 1);
+
+                // AgentExplanation threads should not count as open
+                store
+                    .create_thread(CreateThreadInput {
+                        review_id: review.id,
+                        file_path: "src/main.rs".into(),
+                        line_start: 1,
+                        line_end: 1,
+                        origin: ThreadOrigin::AgentExplanation,
+                        initial_comment_body: "This does X".into(),
+                        initial_comment_author: AuthorType::Agent,
+                        revision_number: None,
+                        content_snippet: None,
+                        initial_comment_suggestion: None,
+                        side: LineSide::New,
+                    }, None)
+                    .await
+                    .unwrap();
+                let list = store.list_reviews().await;
+                assert_eq!(list[0].thread_count, 3);
+                assert_eq!(list[0].open_thread_count, 1); // still 1, AgentExplanation excluded
+                assert_eq!(list[0].open_thread_count_with_explanations, 2);
+            }
+
+            #[tokio::test]
+            async fn test_delete_review_removes_review_and_associated_data() {
+                use crate::review::{AuthorType, RevisionTrigger, ThreadOrigin};
+
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+
+                store
+                    .create_thread(CreateThreadInput {
+                        review_id: review.id,
+                        file_path: "src/main.rs".into(),
+                        line_start: 1,
+                        line_end: 1,
+                        origin: ThreadOrigin::Comment,
+                        initial_comment_body: "test".into(),
+                        initial_comment_author: AuthorType::Human,
+                        revision_number: None,
+                        content_snippet: None,
+                        initial_comment_suggestion: None,
+                        side: LineSide::New,
+                    }, None)
+                    .await
+                    .unwrap();
+                store
+                    .create_revision(CreateRevisionInput {
+                        review_id: review.id,
+                        trigger: RevisionTrigger::Agent,
+                        message: None,
+                        files: vec![],
+                        base_snapshots: Default::default(),
+                    })
+                    .await
+                    .unwrap();
+
+                store.delete_review(review.id).await.unwrap();
+
+                assert!(matches!(
+                    store.get_review(review.id).await,
+                    Err(StoreError::ReviewNotFound(_))
+                ));
+                assert!(matches!(
+                    store.get_threads(review.id, None).await,
+                    Err(StoreError::ReviewNotFound(_))
+                ));
+                assert!(matches!(
+                    store.get_revisions(review.id).await,
+                    Err(StoreError::ReviewNotFound(_))
+                ));
+                assert!(store.list_reviews().await.is_empty());
+            }
+
+            #[tokio::test]
+            async fn test_delete_review_not_found() {
+                let (store, _guard) = $make_store().await;
+                let result = store.delete_review(Uuid::new_v4()).await;
+                assert!(matches!(result, Err(StoreError::ReviewNotFound(_))));
+            }
+
+            #[tokio::test]
+            async fn test_delete_review_does_not_affect_other_reviews() {
+                let (store, _guard) = $make_store().await;
+                let review1 = create_review_with_store(&store).await;
+                let review2 = create_review_with_store(&store).await;
+
+                store.delete_review(review1.id).await.unwrap();
+
+                assert!(store.get_review(review2.id).await.is_ok());
+                assert_eq!(store.list_reviews().await.len(), 1);
+            }
+
+            #[tokio::test]
+            async fn test_delete_closed_reviews() {
+                let (store, _guard) = $make_store().await;
+                let r1 = create_review_with_store(&store).await;
+                let r2 = create_review_with_store(&store).await;
+                let r3 = create_review_with_store(&store).await;
+
+                store
+                    .update_review_status(r1.id, ReviewStatus::Closed)
+                    .await
+                    .unwrap();
+                store
+                    .update_review_status(r2.id, ReviewStatus::Closed)
+                    .await
+                    .unwrap();
+
+                let deleted = store.delete_closed_reviews().await.unwrap();
+                assert_eq!(deleted.len(), 2);
+                assert!(deleted.contains(&r1.id));
+                assert!(deleted.contains(&r2.id));
+
+                let remaining = store.list_reviews().await;
+                assert_eq!(remaining.len(), 1);
+                assert_eq!(remaining[0].id, r3.id);
+            }
+
+            #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+            async fn test_reopen_racing_delete_closed_reviews() {
+                let (store, _guard) = $make_store().await;
+                let store = std::sync::Arc::new(store);
+                for _ in 0..20 {
+                    let id = create_review_with_store(&*store).await.id;
+                    store
+                        .update_review_status(id, ReviewStatus::Closed)
+                        .await
+                        .unwrap();
+
+                    let reopen = tokio::spawn({
+                        let store = store.clone();
+                        async move { store.update_review_status(id, ReviewStatus::Open).await }
+                    });
+                    let delete = tokio::spawn({
+                        let store = store.clone();
+                        async move { store.delete_closed_reviews().await.unwrap() }
+                    });
+                    let reopened = reopen.await.unwrap();
+                    let deleted = delete.await.unwrap();
+
+                    // Whichever ran first wins; a successful reopen is never lost
+                    match reopened {
+                        Ok(()) => {
+                            assert!(!deleted.contains(&id));
+                            let kept = store.get_review(id).await.unwrap();
+                            assert_eq!(kept.status, ReviewStatus::Open);
+                        }
+                        Err(StoreError::ReviewNotFound(_)) => assert_eq!(deleted, vec![id]),
+                        Err(e) => panic!("unexpected error: {e}"),
+                    }
+                }
+            }
+
+            #[tokio::test]
+            async fn test_delete_closed_reviews_none_closed() {
+                let (store, _guard) = $make_store().await;
+                create_review_with_store(&store).await;
+
+                let deleted = store.delete_closed_reviews().await.unwrap();
+                assert!(deleted.is_empty());
+                assert_eq!(store.list_reviews().await.len(), 1);
+            }
+
+            #[tokio::test]
+            async fn test_get_threads_in_creation_order() {
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+                let mut created = Vec::new();
+                for line in 1..=8 {
+                    let thread = store
+                        .create_thread(CreateThreadInput {
+                            review_id: review.id,
+                            file_path: "src/main.rs".into(),
+                            line_start: line,
+                            line_end: line,
+                            origin: ThreadOrigin::Comment,
+                            initial_comment_body: format!("comment {line}"),
+                            initial_comment_author: AuthorType::Human,
+                            revision_number: None,
+                            content_snippet: None,
+                            initial_comment_suggestion: None,
+                            side: LineSide::New,
+                        }, None)
+                        .await
+                        .unwrap();
+                    created.push(thread.id);
+                }
+
+                for _ in 0..3 {
+                    let ids: Vec<Uuid> = store
+                        .get_threads(review.id, None)
+                        .await
+                        .unwrap()
+                        .iter()
+                        .map(|t| t.id)
+                        .collect();
+                    assert_eq!(ids, created);
+                }
+            }
+
+            #[tokio::test]
+            async fn test_restore_snapshot_replaces_state() {
+                let (store, _guard) = $make_store().await;
+                let kept = create_review_with_store(&store).await;
+                let snapshot = store.snapshot().await.unwrap();
+
+                store.delete_review(kept.id).await.unwrap();
+                let dropped = create_review_with_store(&store).await;
+                store.restore(snapshot).await.unwrap();
+
+                assert_eq!(store.get_review(kept.id).await.unwrap().id, kept.id);
+                assert!(store.get_review(dropped.id).await.is_err());
+            }
+
+            #[tokio::test]
+            async fn test_restore_rejects_invalid_snapshot() {
+                let (store, _guard) = $make_store().await;
+                let review = create_review_with_store(&store).await;
+
+                let result = store.restore(serde_json::json!({ "reviews": 3 })).await;
+                assert!(matches!(result, Err(StoreError::InvalidSnapshot(_))));
+                assert!(store.get_review(review.id).await.is_ok());
+            }
+        }
+    };
+}
+
+pub(crate) use review_store_tests;
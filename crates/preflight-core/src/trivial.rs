@@ -0,0 +1,285 @@
+use crate::diff::{DiffLine, FileDiff, FileStatus, LineKind};
+
+/// Whether a file's changes are mechanical enough that a reviewer can skim
+/// past it: they only reindent or rewrap lines, or only touch imports,
+/// comments and blank lines. However small, any other change counts. Import
+/// and comment lines are recognized for a handful of languages by file
+/// extension.
+pub fn is_trivial(file: &FileDiff) -> bool {
+    if file.status == FileStatus::Binary {
+        return false;
+    }
+    let changed: Vec<&DiffLine> = file
+        .hunks
+        .iter()
+        .flat_map(|h| &h.lines)
+        .filter(|l| l.kind != LineKind::Context)
+        .collect();
+    if is_whitespace_only(&changed) {
+        return true;
+    }
+    let path = file
+        .new_path
+        .as_deref()
+        .or(file.old_path.as_deref())
+        .unwrap_or_default();
+    let Some(is_import) = import_matcher(path) else {
+        return false;
+    };
+    let comments = comment_prefixes(path);
+    changed.iter().all(|l| {
+        let line = l.content.trim();
+        line.is_empty()
+            || is_import(line)
+            || comments
+                .iter()
+                .any(|c| line.starts_with(c) || line == c.trim_end())
+    })
+}
+
+/// Whether the removed and added lines read the same once whitespace,
+/// including line breaks, is ignored.
+fn is_whitespace_only(changed: &[&DiffLine]) -> bool {
+    let squeeze = |kind: LineKind| -> String {
+        changed
+            .iter()
+            .filter(|l| l.kind == kind)
+            .flat_map(|l| l.content.chars())
+            .filter(|c| !c.is_whitespace())
+            .collect()
+    };
+    squeeze(LineKind::Removed) == squeeze(LineKind::Added)
+}
+
+/// A check for import lines in the language `path`'s extension suggests.
+fn import_matcher(path: &str) -> Option<fn(&str) -> bool> {
+    let ext = path.rsplit_once('.')?.1;
+    let matcher: fn(&str) -> bool = match ext {
+        "rs" => |l| {
+            let l = l.strip_prefix("pub ").unwrap_or(l);
+            let l = l.strip_prefix("pub(crate) ").unwrap_or(l);
+            l.starts_with("use ") || l.starts_with("extern crate ")
+        },
+        "py" => |l| l.starts_with("import ") || (l.starts_with("from ") && l.contains(" import ")),
+        "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "svelte" => |l| {
+            l.starts_with("import ")
+                || (l.starts_with("export ") && l.contains(" from "))
+                || is_require(l)
+        },
+        "go" | "java" | "kt" | "scala" | "swift" => |l| l.starts_with("import "),
+        "c" | "h" | "cc" | "cpp" | "hpp" => |l| l.starts_with("#include "),
+        "cs" => |l| l.starts_with("using ") && l.ends_with(';'),
+        _ => return None,
+    };
+    Some(matcher)
+}
+
+/// A CommonJS import: `const x = require(...)` and the like, but not a
+/// `require(` call anywhere else on the line.
+fn is_require(line: &str) -> bool {
+    let Some(rest) = ["const ", "let ", "var "]
+        .iter()
+        .find_map(|kw| line.strip_prefix(kw))
+    else {
+        return false;
+    };
+    let Some((name, value)) = rest.split_once('=') else {
+        return false;
+    };
+    let name = name.trim();
+    !name.is_empty()
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+        && value.trim_start().starts_with("require(")
+}
+
+/// How line comments start in the language `path`'s extension suggests.
+/// Lines inside a block comment count when they start with `* ` or are
+/// just `*`.
+fn comment_prefixes(path: &str) -> &'static [&'static str] {
+    let ext = path.rsplit_once('.').map_or("", |(_, ext)| ext);
+    match ext {
+        "py" => &["#"],
+        "svelte" => &["//", "/*", "* ", "*/", "<!--"],
+        "rs" | "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "go" | "java" | "kt" | "scala"
+        | "swift" | "c" | "h" | "cc" | "cpp" | "hpp" | "cs" => &["//", "/*", "* ", "*/"],
+        _ => &[],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::Hunk;
+
+    fn file(path: &str, lines: &[(LineKind, &str)]) -> FileDiff {
+        let lines = lines
+            .iter()
+            .map(|(kind, content)| DiffLine {
+                kind: kind.clone(),
+                content: content.to_string(),
+                old_line_no: None,
+                new_line_no: None,
+                highlighted: None,
+                whitespace_only: false,
+            })
+            .collect();
+        FileDiff {
+            old_path: Some(path.into()),
+            new_path: Some(path.into()),
+            status: FileStatus::Modified,
+            hunks: vec![Hunk {
+                old_start: 1,
+                old_count: 1,
+                new_start: 1,
+                new_count: 1,
+                context: None,
+                lines,
+            }],
+            mode_change: None,
+        }
+    }
+
+    #[test]
+    fn test_single_edited_line_is_not_trivial() {
+        let diff = file(
+            "src/main.rs",
+            &[
+                (LineKind::Context, "fn main() {"),
+                (LineKind::Removed, "    run(1);"),
+                (LineKind::Added, "    run(2);"),
+                (LineKind::Context, "}"),
+            ],
+        );
+        assert!(!is_trivial(&diff));
+
+        // Small changes in files whose language isn't recognized count too
+        let diff = file("config.toml", &[(LineKind::Added, "debug = true")]);
+        assert!(!is_trivial(&diff));
+    }
+
+    #[test]
+    fn test_whitespace_only_changes_are_trivial() {
+        let diff = file(
+            "config.toml",
+            &[
+                (LineKind::Removed, "debug=true"),
+                (LineKind::Added, "  debug = true"),
+            ],
+        );
+        assert!(is_trivial(&diff));
+
+        // Rewrapping a call across lines
+        let diff = file(
+            "src/main.rs",
+            &[
+                (LineKind::Removed, "    run(a, b);"),
+                (LineKind::Added, "    run("),
+                (LineKind::Added, "        a, b,"),
+                (LineKind::Added, "    );"),
+            ],
+        );
+        assert!(!is_trivial(&diff), "the trailing comma is a change");
+    }
+
+    #[test]
+    fn test_comment_only_changes_are_trivial() {
+        let diff = file(
+            "src/main.rs",
+            &[
+                (LineKind::Removed, "// Runs the thing"),
+                (LineKind::Added, "/// Runs the thing twice."),
+                (LineKind::Added, "/*"),
+                (LineKind::Added, " * More detail."),
+                (LineKind::Added, " */"),
+            ],
+        );
+        assert!(is_trivial(&diff));
+
+        let diff = file("app.py", &[(LineKind::Added, "# TODO: retry")]);
+        assert!(is_trivial(&diff));
+
+        // A pointer dereference isn't a comment line
+        let diff = file("main.c", &[(LineKind::Added, "*count = 0;")]);
+        assert!(!is_trivial(&diff));
+    }
+
+    #[test]
+    fn test_several_changed_lines_are_not_trivial() {
+        let diff = file(
+            "src/main.rs",
+            &[
+                (LineKind::Added, "fn helper() {"),
+                (LineKind::Added, "    run(2);"),
+                (LineKind::Added, "}"),
+            ],
+        );
+        assert!(!is_trivial(&diff));
+    }
+
+    #[test]
+    fn test_import_only_changes_are_trivial() {
+        let diff = file(
+            "src/main.rs",
+            &[
+                (LineKind::Removed, "use std::io;"),
+                (LineKind::Added, "use std::fs;"),
+                (LineKind::Added, "pub use crate::review::Review;"),
+                (LineKind::Added, ""),
+            ],
+        );
+        assert!(is_trivial(&diff));
+
+        let diff = file(
+            "app.py",
+            &[
+                (LineKind::Added, "import os"),
+                (LineKind::Added, "from pathlib import Path"),
+                (LineKind::Added, "import sys"),
+            ],
+        );
+        assert!(is_trivial(&diff));
+    }
+
+    #[test]
+    fn test_require_only_counts_as_an_import_declaration() {
+        let diff = file(
+            "index.js",
+            &[
+                (LineKind::Added, "const fs = require(\"fs\");"),
+                (LineKind::Added, "let path = require('path');"),
+            ],
+        );
+        assert!(is_trivial(&diff));
+
+        for line in [
+            "const config = loadConfig(require(\"./defaults\"));",
+            "module.exports = require(\"./impl\");",
+            "const { a } = require(\"./a\");",
+            "if (x) require(\"./side-effect\");",
+        ] {
+            let diff = file("index.js", &[(LineKind::Added, line)]);
+            assert!(!is_trivial(&diff), "{line}");
+        }
+    }
+
+    #[test]
+    fn test_imports_in_unknown_languages_are_not_trivial() {
+        let diff = file(
+            "notes.txt",
+            &[
+                (LineKind::Added, "import os"),
+                (LineKind::Added, "import sys"),
+                (LineKind::Added, "import re"),
+            ],
+        );
+        assert!(!is_trivial(&diff));
+    }
+
+    #[test]
+    fn test_binary_file_is_not_trivial() {
+        let mut diff = file("logo.png", &[]);
+        diff.status = FileStatus::Binary;
+        diff.hunks.clear();
+        assert!(!is_trivial(&diff));
+    }
+}
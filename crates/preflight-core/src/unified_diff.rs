@@ -0,0 +1,147 @@
+use std::fmt::Write;
+
+use crate::diff::{FileDiff, FileStatus, LineKind};
+
+/// Serialize parsed file diffs back into `git diff` style unified diff text.
+///
+/// This is the inverse of `parser::parse_diff` for the information `FileDiff`
+/// keeps; index lines and "No newline at end of file" markers are not retained.
+pub fn to_unified_diff(files: &[FileDiff]) -> String {
+    let mut out = String::new();
+    for file in files {
+        write_file(&mut out, file);
+    }
+    out
+}
+
+fn write_file(out: &mut String, file: &FileDiff) {
+    let old_path = file.old_path.as_deref();
+    let new_path = file.new_path.as_deref();
+    let header_old = old_path.or(new_path).unwrap_or_default();
+    let header_new = new_path.or(old_path).unwrap_or_default();
+    let _ = writeln!(out, "diff --git a/{header_old} b/{header_new}");
+
+    let file_mode = if file.status == FileStatus::Symlink {
+        "120000"
+    } else {
+        "100644"
+    };
+    if old_path.is_none() {
+        let _ = writeln!(out, "new file mode {file_mode}");
+    } else if new_path.is_none() {
+        let _ = writeln!(out, "deleted file mode {file_mode}");
+    }
+    if let Some(mode) = &file.mode_change {
+        let _ = writeln!(out, "old mode {}", mode.old_mode);
+        let _ = writeln!(out, "new mode {}", mode.new_mode);
+    }
+    if file.status == FileStatus::Renamed {
+        let _ = writeln!(out, "rename from {header_old}");
+        let _ = writeln!(out, "rename to {header_new}");
+    }
+
+    let old_label = old_path.map_or("/dev/null".to_string(), |p| format!("a/{p}"));
+    let new_label = new_path.map_or("/dev/null".to_string(), |p| format!("b/{p}"));
+    if file.status == FileStatus::Binary {
+        let _ = writeln!(out, "Binary files {old_label} and {new_label} differ");
+        return;
+    }
+    if file.hunks.is_empty() {
+        return;
+    }
+    let _ = writeln!(out, "--- {old_label}");
+    let _ = writeln!(out, "+++ {new_label}");
+
+    for hunk in &file.hunks {
+        let _ = write!(
+            out,
+            "@@ -{},{} +{},{} @@",
+            hunk.old_start, hunk.old_count, hunk.new_start, hunk.new_count
+        );
+        match &hunk.context {
+            Some(context) => {
+                let _ = writeln!(out, " {context}");
+            }
+            None => out.push('\n'),
+        }
+        for line in &hunk.lines {
+            let prefix = match line.kind {
+                LineKind::Added => '+',
+                LineKind::Removed => '-',
+                LineKind::Context => ' ',
+            };
+            out.push(prefix);
+            out.push_str(&line.content);
+            out.push('\n');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_diff;
+
+    #[test]
+    fn round_trips_modified_file() {
+        let input = "\
+diff --git a/src/main.rs b/src/main.rs
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1,3 +1,4 @@ fn main() {
+ line1
+-old
++new
++added
+ line3
+";
+        let files = parse_diff(input).unwrap();
+        assert_eq!(to_unified_diff(&files), input);
+    }
+
+    #[test]
+    fn round_trips_added_deleted_and_renamed_files() {
+        let input = "\
+diff --git a/new.rs b/new.rs
+new file mode 100644
+--- /dev/null
++++ b/new.rs
+@@ -0,0 +1,1 @@
++fn new() {}
+diff --git a/gone.rs b/gone.rs
+deleted file mode 100644
+--- a/gone.rs
++++ /dev/null
+@@ -1,1 +0,0 @@
+-fn gone() {}
+diff --git a/old.rs b/renamed.rs
+rename from old.rs
+rename to renamed.rs
+--- a/old.rs
++++ b/renamed.rs
+@@ -1,1 +1,1 @@
+-fn old() {}
++fn renamed() {}
+";
+        let files = parse_diff(input).unwrap();
+        let output = to_unified_diff(&files);
+        assert_eq!(output, input);
+        assert_eq!(parse_diff(&output).unwrap(), files);
+    }
+
+    #[test]
+    fn round_trips_mode_only_change() {
+        let input = "\
+diff --git a/run.sh b/run.sh
+old mode 100644
+new mode 100755
+";
+        let files = parse_diff(input).unwrap();
+        assert_eq!(to_unified_diff(&files), input);
+    }
+
+    #[test]
+    fn empty_input_produces_empty_output() {
+        assert_eq!(to_unified_diff(&[]), "");
+    }
+}
@@ -0,0 +1,213 @@
+use crate::diff::{DiffLine, Hunk, LineKind};
+
+/// Whether two lines are equal once all whitespace is ignored, matching
+/// `git diff -w`.
+fn equal_ignoring_whitespace(a: &str, b: &str) -> bool {
+    a.chars()
+        .filter(|c| !c.is_whitespace())
+        .eq(b.chars().filter(|c| !c.is_whitespace()))
+}
+
+/// Split a hunk's lines into runs: context lines on their own, and each block
+/// of removed lines together with the added lines directly following it.
+fn change_runs(lines: &[DiffLine]) -> Vec<std::ops::Range<usize>> {
+    let mut runs = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let start = i;
+        if lines[i].kind == LineKind::Context {
+            i += 1;
+        } else {
+            while i < lines.len() && lines[i].kind == LineKind::Removed {
+                i += 1;
+            }
+            while i < lines.len() && lines[i].kind == LineKind::Added {
+                i += 1;
+            }
+        }
+        runs.push(start..i);
+    }
+    runs
+}
+
+/// The removed and added halves of a change run.
+fn split_run(run: &[DiffLine]) -> (&[DiffLine], &[DiffLine]) {
+    let removed = run
+        .iter()
+        .take_while(|l| l.kind == LineKind::Removed)
+        .count();
+    run.split_at(removed)
+}
+
+/// Flag removed/added line pairs that differ only in whitespace.
+///
+/// Within each block of removed lines followed by added lines, the n-th
+/// removed line is paired with the n-th added line.
+pub fn mark_whitespace_only(hunks: &mut [Hunk]) {
+    for hunk in hunks {
+        for run in change_runs(&hunk.lines) {
+            let lines = &mut hunk.lines[run];
+            let (removed, added) = split_run(lines);
+            let flags: Vec<bool> = removed
+                .iter()
+                .zip(added)
+                .map(|(r, a)| equal_ignoring_whitespace(&r.content, &a.content))
+                .collect();
+            let removed_len = removed.len();
+            for (i, flag) in flags.into_iter().enumerate() {
+                lines[i].whitespace_only = flag;
+                lines[removed_len + i].whitespace_only = flag;
+            }
+        }
+    }
+}
+
+/// Hide whitespace-only changes, like viewing the diff with `git diff -w`.
+///
+/// A block whose removed and added lines all pair up as whitespace-only is
+/// folded into context lines showing the new content; blocks that mix real
+/// and whitespace changes are kept as-is so line order stays intact. Hunks
+/// left without any changes are dropped. Expects [`mark_whitespace_only`]
+/// to have run first.
+pub fn suppress_whitespace_only(hunks: Vec<Hunk>) -> Vec<Hunk> {
+    hunks
+        .into_iter()
+        .filter_map(|hunk| {
+            let mut lines = Vec::with_capacity(hunk.lines.len());
+            for run in change_runs(&hunk.lines) {
+                let block = &hunk.lines[run];
+                let (removed, added) = split_run(block);
+                let foldable = !removed.is_empty()
+                    && removed.len() == added.len()
+                    && block.iter().all(|l| l.whitespace_only);
+                if foldable {
+                    lines.extend(removed.iter().zip(added).map(|(r, a)| DiffLine {
+                        kind: LineKind::Context,
+                        old_line_no: r.old_line_no,
+                        whitespace_only: false,
+                        ..a.clone()
+                    }));
+                } else {
+                    lines.extend_from_slice(block);
+                }
+            }
+            if lines.iter().all(|l| l.kind == LineKind::Context) {
+                return None;
+            }
+            // Lines on each side, not counting the other side's
+            let count = |skip| lines.iter().filter(|l| l.kind != skip).count() as u32;
+            let (old_count, new_count) = (count(LineKind::Added), count(LineKind::Removed));
+            Some(Hunk {
+                old_count,
+                new_count,
+                lines,
+                ..hunk
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(kind: LineKind, content: &str, old: Option<u32>, new: Option<u32>) -> DiffLine {
+        DiffLine {
+            kind,
+            content: content.into(),
+            old_line_no: old,
+            new_line_no: new,
+            highlighted: None,
+            whitespace_only: false,
+        }
+    }
+
+    fn hunk(lines: Vec<DiffLine>) -> Hunk {
+        Hunk {
+            old_start: 1,
+            old_count: 3,
+            new_start: 1,
+            new_count: 3,
+            context: None,
+            lines,
+        }
+    }
+
+    #[test]
+    fn test_reindented_line_is_flagged() {
+        let mut hunks = vec![hunk(vec![
+            line(LineKind::Context, "fn main() {", Some(1), Some(1)),
+            line(LineKind::Removed, "  let x = 1;", Some(2), None),
+            line(LineKind::Added, "    let x = 1;", None, Some(2)),
+            line(LineKind::Context, "}", Some(3), Some(3)),
+        ])];
+        mark_whitespace_only(&mut hunks);
+        let flags: Vec<bool> = hunks[0].lines.iter().map(|l| l.whitespace_only).collect();
+        assert_eq!(flags, vec![false, true, true, false]);
+    }
+
+    #[test]
+    fn test_real_change_is_not_flagged() {
+        let mut hunks = vec![hunk(vec![
+            line(LineKind::Removed, "let x = 1;", Some(1), None),
+            line(LineKind::Added, "let x = 2;", None, Some(1)),
+            line(LineKind::Added, "let y = 3;", None, Some(2)),
+        ])];
+        mark_whitespace_only(&mut hunks);
+        assert!(hunks[0].lines.iter().all(|l| !l.whitespace_only));
+    }
+
+    #[test]
+    fn test_suppress_folds_whitespace_only_hunk_away() {
+        let mut hunks = vec![hunk(vec![
+            line(LineKind::Context, "fn main() {", Some(1), Some(1)),
+            line(LineKind::Removed, "\tlet x=1;", Some(2), None),
+            line(LineKind::Added, "    let x = 1;", None, Some(2)),
+            line(LineKind::Context, "}", Some(3), Some(3)),
+        ])];
+        mark_whitespace_only(&mut hunks);
+        assert!(suppress_whitespace_only(hunks).is_empty());
+    }
+
+    #[test]
+    fn test_suppress_keeps_real_changes_and_folds_the_rest() {
+        let mut hunks = vec![hunk(vec![
+            line(LineKind::Removed, "a", Some(1), None),
+            line(LineKind::Added, " a", None, Some(1)),
+            line(LineKind::Context, "b", Some(2), Some(2)),
+            line(LineKind::Removed, "c", Some(3), None),
+            line(LineKind::Added, "d", None, Some(3)),
+        ])];
+        mark_whitespace_only(&mut hunks);
+        let hunks = suppress_whitespace_only(hunks);
+        assert_eq!(hunks.len(), 1);
+        let lines = &hunks[0].lines;
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0].kind, LineKind::Context);
+        assert_eq!(lines[0].content, " a");
+        assert_eq!(lines[0].old_line_no, Some(1));
+        assert_eq!(lines[0].new_line_no, Some(1));
+        assert_eq!(lines[2].kind, LineKind::Removed);
+        assert_eq!(lines[3].kind, LineKind::Added);
+        assert_eq!((hunks[0].old_count, hunks[0].new_count), (3, 3));
+    }
+
+    #[test]
+    fn test_suppress_recounts_the_hunk() {
+        // Counts that don't match the lines must not survive the rebuild
+        let mut hunks = vec![Hunk {
+            old_count: 0,
+            new_count: 0,
+            ..hunk(vec![
+                line(LineKind::Removed, "a", Some(1), None),
+                line(LineKind::Added, "  a", None, Some(1)),
+                line(LineKind::Removed, "b", Some(2), None),
+                line(LineKind::Added, "c", None, Some(2)),
+                line(LineKind::Added, "d", None, Some(3)),
+            ])
+        }];
+        mark_whitespace_only(&mut hunks);
+        let hunks = suppress_whitespace_only(hunks);
+        assert_eq!((hunks[0].old_count, hunks[0].new_count), (2, 3));
+    }
+}
@@ -14,6 +14,7 @@ pub struct WsEvent {
 pub enum WsEventType {
     ReviewCreated,
     ReviewStatusChanged,
+    ReviewDecisionChanged,
     ReviewDeleted,
     RevisionCreated,
     ThreadCreated,
@@ -21,6 +22,10 @@ pub enum WsEventType {
     ThreadStatusChanged,
     ThreadAcknowledged,
     ThreadPoked,
+    ThreadsLinked,
+    ThreadPinned,
     RevisionRequested,
     AgentPresenceChanged,
+    HumanPresenceChanged,
+    AgentSeenChanged,
 }
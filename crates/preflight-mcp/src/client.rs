@@ -1,13 +1,22 @@
-use futures_util::StreamExt;
-use preflight_core::ws::WsEvent;
+use std::sync::Arc;
+
+use futures_util::{SinkExt, StreamExt};
+use preflight_core::ws::{WsEvent, WsEventType};
 use reqwest::Client;
 use serde::de::DeserializeOwned;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, watch};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Consecutive unparseable WebSocket messages before the event stream is
+/// reported as degraded.
+const PARSE_FAILURE_THRESHOLD: u32 = 3;
 
 #[derive(Debug, Clone)]
 pub struct PreflightClient {
     http: Client,
     base_url: String,
+    /// Set to a warning while WebSocket events are failing to parse.
+    event_warning: Arc<watch::Sender<Option<String>>>,
 }
 
 #[derive(Debug)]
@@ -18,6 +27,8 @@ pub enum ClientError {
     ApiError { status: u16, body: String },
     /// Failed to deserialize the response.
     DeserializeError(String),
+    /// WebSocket events are arriving but cannot be parsed.
+    EventStreamDegraded { failures: u32, last_error: String },
 }
 
 impl std::fmt::Display for ClientError {
@@ -31,6 +42,15 @@ impl std::fmt::Display for ClientError {
                 write!(f, "API error (HTTP {status}): {body}")
             }
             ClientError::DeserializeError(msg) => write!(f, "failed to parse response: {msg}"),
+            ClientError::EventStreamDegraded {
+                failures,
+                last_error,
+            } => write!(
+                f,
+                "events are not being delivered: {failures} consecutive WebSocket messages \
+                 failed to parse (last error: {last_error}) — the MCP server and preflight \
+                 server may be different versions"
+            ),
         }
     }
 }
@@ -40,6 +60,7 @@ impl PreflightClient {
         Self {
             http: Client::new(),
             base_url: format!("http://127.0.0.1:{port}"),
+            event_warning: Arc::new(watch::channel(None).0),
         }
     }
 
@@ -47,6 +68,24 @@ impl PreflightClient {
         &self.base_url
     }
 
+    /// The current event stream warning, if WebSocket events are failing to parse.
+    pub fn event_warning(&self) -> Option<String> {
+        self.event_warning.borrow().clone()
+    }
+
+    /// Subscribe to changes in the event stream warning.
+    pub fn subscribe_event_warning(&self) -> watch::Receiver<Option<String>> {
+        self.event_warning.subscribe()
+    }
+
+    /// Check that the preflight server answers on `/api/health`, so a missing
+    /// server is reported up front rather than through WebSocket retries.
+    pub async fn check_health(&self) -> Result<(), ClientError> {
+        self.get::<serde_json::Value>("/api/health")
+            .await
+            .map(|_| ())
+    }
+
     pub async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, ClientError> {
         let url = format!("{}{path}", self.base_url);
         let response = self
@@ -163,36 +202,39 @@ impl PreflightClient {
         );
         let url = format!("{ws_url}/api/ws");
         let tx_clone = tx.clone();
+        let warning = self.event_warning.clone();
 
         tokio::spawn(async move {
             let mut backoff = std::time::Duration::from_secs(1);
             let max_backoff = std::time::Duration::from_secs(30);
+            let mut health = EventStreamHealth::new(warning);
 
             loop {
                 match tokio_tungstenite::connect_async(&url).await {
                     Ok((ws_stream, _)) => {
                         eprintln!("[mcp] connected to WebSocket at {url}");
                         backoff = std::time::Duration::from_secs(1); // Reset on success
-                        let (_write, mut read) = ws_stream.split();
+                        let (mut write, mut read) = ws_stream.split();
 
                         while let Some(msg) = read.next().await {
                             match msg {
-                                Ok(tokio_tungstenite::tungstenite::Message::Text(text)) => {
-                                    match serde_json::from_str::<WsEvent>(&text) {
-                                        Ok(event) => {
-                                            let _ = tx_clone.send(event);
-                                        }
-                                        Err(e) => {
-                                            eprintln!("[mcp] failed to parse WS event: {e}");
-                                        }
+                                Ok(Message::Text(text)) => {
+                                    if let Some(event) = health.record(parse_ws_event(&text)) {
+                                        let _ = tx_clone.send(event);
+                                    }
+                                }
+                                Ok(Message::Ping(data)) => {
+                                    if let Err(e) = write.send(Message::Pong(data)).await {
+                                        eprintln!("[mcp] failed to answer WebSocket ping: {e}");
+                                        break;
                                     }
                                 }
-                                Ok(tokio_tungstenite::tungstenite::Message::Close(_)) => break,
+                                Ok(Message::Close(_)) => break,
                                 Err(e) => {
                                     eprintln!("[mcp] WebSocket error: {e}");
                                     break;
                                 }
-                                _ => {} // Ignore ping/pong/binary
+                                _ => {} // Ignore pong/binary
                             }
                         }
 
@@ -215,10 +257,88 @@ impl PreflightClient {
     }
 }
 
+/// Parse a WebSocket text message, checking the event type tag first so that
+/// schema drift between client and server is reported as such.
+fn parse_ws_event(text: &str) -> Result<WsEvent, ClientError> {
+    let value: serde_json::Value = serde_json::from_str(text)
+        .map_err(|e| ClientError::DeserializeError(format!("malformed event JSON: {e}")))?;
+    let Some(tag) = value.get("event_type") else {
+        return Err(ClientError::DeserializeError(
+            "event has no event_type tag".to_string(),
+        ));
+    };
+    serde_json::from_value::<WsEventType>(tag.clone())
+        .map_err(|_| ClientError::DeserializeError(format!("unknown event type {tag}")))?;
+    serde_json::from_value(value).map_err(|e| ClientError::DeserializeError(e.to_string()))
+}
+
+/// Tracks consecutive parse failures on the event stream and publishes a
+/// warning once they cross [`PARSE_FAILURE_THRESHOLD`].
+struct EventStreamHealth {
+    consecutive_failures: u32,
+    warning: Arc<watch::Sender<Option<String>>>,
+}
+
+impl EventStreamHealth {
+    fn new(warning: Arc<watch::Sender<Option<String>>>) -> Self {
+        Self {
+            consecutive_failures: 0,
+            warning,
+        }
+    }
+
+    /// Record the outcome of parsing one message, returning the event on success.
+    fn record(&mut self, result: Result<WsEvent, ClientError>) -> Option<WsEvent> {
+        match result {
+            Ok(event) => {
+                if self.consecutive_failures > 0 {
+                    self.consecutive_failures = 0;
+                    self.warning.send_if_modified(|w| w.take().is_some());
+                }
+                Some(event)
+            }
+            Err(e) => {
+                self.consecutive_failures += 1;
+                eprintln!("[mcp] failed to parse WS event: {e}");
+                if self.consecutive_failures >= PARSE_FAILURE_THRESHOLD {
+                    let degraded = ClientError::EventStreamDegraded {
+                        failures: self.consecutive_failures,
+                        last_error: e.to_string(),
+                    };
+                    self.warning.send_replace(Some(degraded.to_string()));
+                }
+                None
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn check_health_fails_when_no_server_is_listening() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let err = PreflightClient::new(port).check_health().await.unwrap_err();
+        assert!(matches!(err, ClientError::ConnectionFailed(_)));
+        assert!(err.to_string().contains("preflight serve"));
+    }
+
+    #[tokio::test]
+    async fn check_health_succeeds_against_running_server() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let store = preflight_core::memory_store::MemoryStore::new();
+        let app = preflight_server::app(Arc::new(store));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        PreflightClient::new(port).check_health().await.unwrap();
+    }
+
     #[tokio::test]
     async fn connect_ws_returns_broadcast_sender() {
         // Start a minimal WS server
@@ -276,4 +396,113 @@ mod tests {
         assert_eq!(event.review_id, "test-123");
         assert!(matches!(event.event_type, WsEventType::CommentAdded));
     }
+
+    #[tokio::test]
+    async fn connect_ws_skips_malformed_message_and_delivers_next() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws_stream = tokio_tungstenite::accept_async(stream).await.unwrap();
+
+            ws_stream
+                .send(Message::Text("{not json".into()))
+                .await
+                .unwrap();
+            let event = WsEvent {
+                event_type: WsEventType::ThreadCreated,
+                review_id: "after-malformed".to_string(),
+                payload: serde_json::json!({}),
+                timestamp: chrono::Utc::now(),
+            };
+            let json = serde_json::to_string(&event).unwrap();
+            ws_stream.send(Message::Text(json.into())).await.unwrap();
+            while ws_stream.next().await.is_some() {}
+        });
+
+        let client = PreflightClient::new(port);
+        let tx = client.connect_ws().await;
+        let mut rx = tx.subscribe();
+
+        let event = tokio::time::timeout(std::time::Duration::from_secs(5), rx.recv())
+            .await
+            .expect("timed out")
+            .expect("recv error");
+
+        assert_eq!(event.review_id, "after-malformed");
+        // A single bad message is not enough to flag the stream.
+        assert!(client.event_warning().is_none());
+    }
+
+    #[tokio::test]
+    async fn connect_ws_answers_ping_with_pong() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (pong_tx, pong_rx) = tokio::sync::oneshot::channel();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws_stream = tokio_tungstenite::accept_async(stream).await.unwrap();
+            ws_stream
+                .send(Message::Ping(b"keepalive".to_vec().into()))
+                .await
+                .unwrap();
+            while let Some(Ok(msg)) = ws_stream.next().await {
+                if let Message::Pong(data) = msg {
+                    let _ = pong_tx.send(data.to_vec());
+                    break;
+                }
+            }
+        });
+
+        let client = PreflightClient::new(port);
+        let _tx = client.connect_ws().await;
+
+        let data = tokio::time::timeout(std::time::Duration::from_secs(5), pong_rx)
+            .await
+            .expect("timed out")
+            .expect("server task dropped");
+        assert_eq!(data, b"keepalive");
+    }
+
+    #[test]
+    fn parse_ws_event_rejects_unknown_event_type() {
+        let err = parse_ws_event(
+            r#"{"event_type":"review_exploded","review_id":"r","payload":{},"timestamp":"2026-01-01T00:00:00Z"}"#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("unknown event type"), "{err}");
+
+        let err = parse_ws_event(r#"{"review_id":"r"}"#).unwrap_err();
+        assert!(err.to_string().contains("no event_type"), "{err}");
+    }
+
+    #[test]
+    fn repeated_parse_failures_raise_warning_until_recovery() {
+        let (sender, mut warnings) = watch::channel(None);
+        let mut health = EventStreamHealth::new(Arc::new(sender));
+
+        for _ in 0..PARSE_FAILURE_THRESHOLD - 1 {
+            assert!(health.record(parse_ws_event("garbage")).is_none());
+        }
+        assert!(warnings.borrow_and_update().is_none());
+
+        assert!(health.record(parse_ws_event("garbage")).is_none());
+        let warning = warnings.borrow_and_update().clone().unwrap();
+        assert!(
+            warning.contains("events are not being delivered"),
+            "{warning}"
+        );
+
+        let valid = WsEvent {
+            event_type: WsEventType::ReviewCreated,
+            review_id: "r1".to_string(),
+            payload: serde_json::json!({}),
+            timestamp: chrono::Utc::now(),
+        };
+        let json = serde_json::to_string(&valid).unwrap();
+        assert!(health.record(parse_ws_event(&json)).is_some());
+        assert!(warnings.borrow_and_update().is_none());
+    }
 }
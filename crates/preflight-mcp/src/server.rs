@@ -1,3 +1,7 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use preflight_core::diff::{FileDiff, FileStatus, Hunk, ModeChange};
 use preflight_core::ws::{WsEvent, WsEventType};
 use rmcp::{
     ServerHandler,
@@ -5,7 +9,7 @@ use rmcp::{
     model::*,
     schemars, tool, tool_handler, tool_router,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tokio::sync::broadcast;
 
 use crate::client::{ClientError, PreflightClient};
@@ -15,6 +19,117 @@ pub struct PreflightMcp {
     client: PreflightClient,
     tool_router: ToolRouter<Self>,
     pub ws_tx: broadcast::Sender<WsEvent>,
+    review_counts: Arc<Mutex<ReviewCounts>>,
+    /// Reviews this session has registered agent presence on, so each is
+    /// registered only once and can be released when the session ends.
+    present_reviews: Arc<Mutex<HashSet<String>>>,
+}
+
+/// Per-review state tracked for the `get_info` summary.
+#[derive(Debug, Default)]
+struct ReviewTally {
+    open: bool,
+    /// Non-explanation threads, keyed by thread ID, mapped to whether they are unresolved.
+    threads: HashMap<String, bool>,
+}
+
+/// Cached review counts, seeded from the API once and then kept current from
+/// WebSocket events so that the synchronous `get_info` can report them.
+#[derive(Debug, Default)]
+pub struct ReviewCounts {
+    reviews: HashMap<String, ReviewTally>,
+}
+
+impl ReviewCounts {
+    /// Number of reviews with status Open.
+    pub fn open_reviews(&self) -> usize {
+        self.reviews.values().filter(|r| r.open).count()
+    }
+
+    /// Number of open reviews that have at least one unresolved thread.
+    pub fn reviews_with_unresolved_threads(&self) -> usize {
+        self.reviews
+            .values()
+            .filter(|r| r.open && r.threads.values().any(|&unresolved| unresolved))
+            .count()
+    }
+
+    fn apply(&mut self, event: &WsEvent) {
+        let payload = &event.payload;
+        match event.event_type {
+            WsEventType::ReviewCreated | WsEventType::ReviewStatusChanged => {
+                let tally = self.reviews.entry(event.review_id.clone()).or_default();
+                tally.open = payload["status"].as_str() == Some("Open");
+            }
+            WsEventType::ReviewDeleted => {
+                self.reviews.remove(&event.review_id);
+            }
+            WsEventType::ThreadCreated => {
+                if payload["origin"].as_str() == Some("AgentExplanation") {
+                    return;
+                }
+                let Some(thread_id) = payload["id"].as_str() else {
+                    return;
+                };
+                let tally = self
+                    .reviews
+                    .entry(event.review_id.clone())
+                    .or_insert_with(|| ReviewTally {
+                        open: true,
+                        ..Default::default()
+                    });
+                tally.threads.insert(
+                    thread_id.to_string(),
+                    payload["status"].as_str() == Some("Open"),
+                );
+            }
+            WsEventType::ThreadStatusChanged => {
+                let Some(thread_id) = payload["thread_id"].as_str() else {
+                    return;
+                };
+                if let Some(unresolved) = self
+                    .reviews
+                    .get_mut(&event.review_id)
+                    .and_then(|r| r.threads.get_mut(thread_id))
+                {
+                    *unresolved = payload["status"].as_str() == Some("Open");
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Build counts from the current API state.
+    async fn fetch(client: &PreflightClient) -> Result<Self, ClientError> {
+        let reviews: serde_json::Value = client.get("/api/reviews").await?;
+        let mut counts = Self::default();
+        for review in reviews.as_array().into_iter().flatten() {
+            let Some(id) = review["id"].as_str() else {
+                continue;
+            };
+            let mut tally = ReviewTally {
+                open: review["status"].as_str() == Some("Open"),
+                ..Default::default()
+            };
+            if review["thread_count"].as_u64().unwrap_or(0) > 0 {
+                let threads: serde_json::Value =
+                    client.get(&format!("/api/reviews/{id}/threads")).await?;
+                for thread in threads.as_array().into_iter().flatten() {
+                    if thread["origin"].as_str() == Some("AgentExplanation") {
+                        continue;
+                    }
+                    if let Some(thread_id) = thread["id"].as_str() {
+                        tally.threads.insert(
+                            thread_id.to_string(),
+                            thread["status"].as_str() == Some("Open"),
+                        );
+                    }
+                }
+            }
+            counts.reviews.insert(id.to_string(), tally);
+        }
+        Ok(counts)
+    }
 }
 
 // --- Tool input schemas ---
@@ -34,6 +149,40 @@ pub struct GetDiffInput {
     pub review_id: String,
     #[schemars(description = "Path of the file within the review (e.g. src/main.rs)")]
     pub file_path: String,
+    #[serde(default)]
+    #[schemars(
+        description = "Output format: \"unified\" (default) for compact +/- diff text, or \"json\" for the server's full response including syntax highlighting"
+    )]
+    pub format: DiffFormat,
+}
+
+/// How `get_diff` renders a file's diff.
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffFormat {
+    #[default]
+    Unified,
+    Json,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetChangedLinesInput {
+    #[schemars(description = "UUID of the review")]
+    pub review_id: String,
+    #[schemars(description = "Path of the file within the review (e.g. src/main.rs)")]
+    pub file_path: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetFullDiffInput {
+    #[schemars(description = "UUID of the review")]
+    pub review_id: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetSuggestionsInput {
+    #[schemars(description = "UUID of the review")]
+    pub review_id: String,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -42,6 +191,14 @@ pub struct GetCommentsInput {
     pub review_id: String,
     #[schemars(description = "Optional file path to filter comments by")]
     pub file_path: Option<String>,
+    #[schemars(
+        description = "Optional RFC 3339 timestamp; only comments created after it are returned, and threads without newer comments are left out. Pass the latest created_at you have seen to poll for new replies."
+    )]
+    pub since: Option<String>,
+    #[schemars(
+        description = "Optional author filter: 'human' or 'agent'. Threads without matching comments are left out."
+    )]
+    pub author: Option<String>,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -50,6 +207,10 @@ pub struct RespondToCommentInput {
     pub thread_id: String,
     #[schemars(description = "The response text")]
     pub body: String,
+    #[schemars(
+        description = "Optional UUID of an earlier comment in the same thread that this replies to"
+    )]
+    pub in_reply_to: Option<String>,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -66,8 +227,40 @@ pub struct CreateReviewInput {
     pub repo_path: String,
     #[schemars(description = "Optional title for the review")]
     pub title: Option<String>,
-    #[schemars(description = "Git ref to diff against (e.g. HEAD, main). Defaults to HEAD.")]
+    #[schemars(
+        description = "Git ref to diff against (e.g. HEAD, main). If omitted or 'auto', uses the merge-base with the default branch."
+    )]
+    pub base_ref: Option<String>,
+    #[schemars(
+        description = "Review only the changes this commit introduced instead of uncommitted changes. Don't combine with base_ref."
+    )]
+    pub commit: Option<String>,
+    #[schemars(
+        description = "Optional repo-relative directory to limit the review to (e.g. packages/foo)"
+    )]
+    pub path_prefix: Option<String>,
+    #[schemars(
+        description = "Optional further repositories to review together with this one, such as a submodule or a sibling worktree"
+    )]
+    pub extra_repos: Option<Vec<ExtraRepoInput>>,
+    #[schemars(
+        description = "Optional labels to tag the review with, e.g. a PR number ('pr:123') or ticket id"
+    )]
+    pub labels: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct ExtraRepoInput {
+    #[schemars(description = "Absolute path to the git repository or worktree")]
+    pub repo_path: String,
+    #[schemars(
+        description = "Git ref to diff this repository against. If omitted, uses the merge-base with its default branch."
+    )]
     pub base_ref: Option<String>,
+    #[schemars(
+        description = "Directory its files are listed under in the review (e.g. the submodule's path)"
+    )]
+    pub prefix: String,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -79,7 +272,7 @@ pub struct FindOrCreateReviewInput {
     )]
     pub title: Option<String>,
     #[schemars(
-        description = "Git ref to diff against (e.g. HEAD, main). If omitted, auto-detects the merge-base with the default branch."
+        description = "Git ref to diff against (e.g. HEAD, main). If omitted or 'auto', uses the merge-base with the default branch."
     )]
     pub base_ref: Option<String>,
 }
@@ -100,6 +293,35 @@ pub struct CreateThreadInput {
         description = "Thread origin: 'Comment' for regular comments, 'AgentExplanation' for proactive code explanations. Defaults to 'Comment'."
     )]
     pub origin: Option<String>,
+    #[serde(default)]
+    #[schemars(
+        description = "Which version of the file the line numbers refer to: 'New' (default) for the changed file, 'Old' for lines that were deleted"
+    )]
+    pub side: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct NewThread {
+    #[schemars(description = "Path of the file to comment on (e.g. src/main.rs)")]
+    pub file_path: String,
+    #[schemars(description = "Starting line number")]
+    pub line_start: u32,
+    #[schemars(description = "Ending line number")]
+    pub line_end: u32,
+    #[schemars(description = "The comment text")]
+    pub body: String,
+    #[schemars(
+        description = "Thread origin: 'Comment' or 'AgentExplanation'. Defaults to 'Comment'."
+    )]
+    pub origin: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct CreateThreadsInput {
+    #[schemars(description = "UUID of the review")]
+    pub review_id: String,
+    #[schemars(description = "Threads to create. If any entry is invalid, none are created.")]
+    pub threads: Vec<NewThread>,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -110,6 +332,24 @@ pub struct UpdateReviewStatusInput {
     pub status: String,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetReviewPresenceInput {
+    #[schemars(description = "UUID of the review")]
+    pub review_id: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetReviewDecisionInput {
+    #[schemars(description = "UUID of the review")]
+    pub review_id: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct MarkReviewSeenInput {
+    #[schemars(description = "UUID of the review")]
+    pub review_id: String,
+}
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct DeleteReviewInput {
     #[schemars(description = "UUID of the review to delete")]
@@ -139,21 +379,120 @@ pub struct WaitForEventInput {
     )]
     pub review_id: Option<String>,
     #[schemars(
-        description = "Optional list of event types to filter. Valid values: review_created, review_status_changed, revision_created, thread_created, comment_added, thread_status_changed, thread_acknowledged, thread_poked, revision_requested, agent_presence_changed. If omitted, matches any event type."
+        description = "Optional list of event types to filter. Valid values: review_created, review_status_changed, review_decision_changed, revision_created, thread_created, comment_added, thread_status_changed, thread_acknowledged, thread_poked, threads_linked, thread_pinned, revision_requested, agent_presence_changed, human_presence_changed, agent_seen_changed. If omitted, matches any event type."
     )]
     pub event_types: Option<Vec<String>>,
     #[schemars(description = "Timeout in seconds. Defaults to 300 (5 minutes). Max 600.")]
     pub timeout_secs: Option<u64>,
+    #[schemars(
+        description = "Gather every matching event until the timeout (or max_events) instead of returning the first, and return them as an array in arrival order. Defaults to false."
+    )]
+    pub collect: Option<bool>,
+    #[schemars(
+        description = "Return once this many matching events are collected. Implies collect. Max 100."
+    )]
+    pub max_events: Option<usize>,
 }
 
+/// Most events `wait_for_event` collects in one call.
+const MAX_COLLECTED_EVENTS: usize = 100;
+
 fn format_error(e: ClientError) -> String {
     e.to_string()
 }
 
+/// Upper bound on the text returned by `get_full_diff`, to keep it within an agent's context.
+const MAX_FULL_DIFF_BYTES: usize = 200_000;
+
+/// The subset of the server's file diff response needed to rebuild a `FileDiff`.
+#[derive(Debug, Deserialize)]
+struct FileDiffBody {
+    path: String,
+    old_path: Option<String>,
+    status: FileStatus,
+    hunks: Vec<Hunk>,
+    #[serde(default)]
+    mode_change: Option<ModeChange>,
+    /// Set by the whole-review diff on files past its size limit, which
+    /// come back without hunks.
+    #[serde(default)]
+    truncated: bool,
+}
+
+impl From<FileDiffBody> for FileDiff {
+    fn from(body: FileDiffBody) -> Self {
+        let new_path = (body.status != FileStatus::Deleted).then_some(body.path);
+        FileDiff {
+            old_path: body.old_path,
+            new_path,
+            status: body.status,
+            hunks: body.hunks,
+            mode_change: body.mode_change,
+        }
+    }
+}
+
+/// Cut `diff` at the last line boundary within `limit` bytes, noting the truncation.
+fn truncate_diff(mut diff: String, limit: usize) -> String {
+    if diff.len() <= limit {
+        return diff;
+    }
+    let total = diff.len();
+    let mut cut = limit;
+    while !diff.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    let cut = diff[..cut].rfind('\n').map_or(0, |i| i + 1);
+    diff.truncate(cut);
+    diff.push_str(&format!(
+        "[diff truncated: showing {cut} of {total} bytes; use get_diff for individual files]\n"
+    ));
+    diff
+}
+
+/// Whether `event` passes `wait_for_event`'s review and event type filters.
+fn event_matches(input: &WaitForEventInput, event: &WsEvent) -> bool {
+    if let Some(ref rid) = input.review_id
+        && &event.review_id != rid
+    {
+        return false;
+    }
+    input.event_types.as_ref().is_none_or(|types| {
+        types
+            .iter()
+            .any(|t| event_type_matches(&event.event_type, t))
+    })
+}
+
+/// An event as `wait_for_event` returns it.
+fn event_json(event: &WsEvent) -> serde_json::Value {
+    serde_json::json!({
+        "event_type": event.event_type,
+        "review_id": event.review_id,
+        "payload": event.payload,
+        "timestamp": event.timestamp,
+    })
+}
+
+/// Whether two `event_json` values describe the same event. Comment events
+/// are the same when they carry the same comment, since a catch-up event and
+/// the live one differ in timestamp and the `catch_up` flag.
+fn same_event(a: &serde_json::Value, b: &serde_json::Value) -> bool {
+    if a["event_type"] != b["event_type"] || a["review_id"] != b["review_id"] {
+        return false;
+    }
+    let comment_id = |e: &serde_json::Value| e["payload"]["comment"]["id"].clone();
+    match (comment_id(a), comment_id(b)) {
+        (serde_json::Value::Null, _) | (_, serde_json::Value::Null) => a["payload"] == b["payload"],
+        (a, b) => a == b,
+    }
+}
+
 fn event_type_matches(event_type: &WsEventType, filter: &str) -> bool {
     match filter {
         "review_created" => matches!(event_type, WsEventType::ReviewCreated),
         "review_status_changed" => matches!(event_type, WsEventType::ReviewStatusChanged),
+        "review_decision_changed" => matches!(event_type, WsEventType::ReviewDecisionChanged),
         "review_deleted" => matches!(event_type, WsEventType::ReviewDeleted),
         "revision_created" => matches!(event_type, WsEventType::RevisionCreated),
         "thread_created" => matches!(event_type, WsEventType::ThreadCreated),
@@ -161,24 +500,105 @@ fn event_type_matches(event_type: &WsEventType, filter: &str) -> bool {
         "thread_status_changed" => matches!(event_type, WsEventType::ThreadStatusChanged),
         "thread_acknowledged" => matches!(event_type, WsEventType::ThreadAcknowledged),
         "thread_poked" => matches!(event_type, WsEventType::ThreadPoked),
+        "threads_linked" => matches!(event_type, WsEventType::ThreadsLinked),
+        "thread_pinned" => matches!(event_type, WsEventType::ThreadPinned),
         "revision_requested" => matches!(event_type, WsEventType::RevisionRequested),
         "agent_presence_changed" => matches!(event_type, WsEventType::AgentPresenceChanged),
+        "human_presence_changed" => matches!(event_type, WsEventType::HumanPresenceChanged),
+        "agent_seen_changed" => matches!(event_type, WsEventType::AgentSeenChanged),
         _ => false,
     }
 }
 
 impl PreflightMcp {
     pub fn new(client: PreflightClient, ws_tx: broadcast::Sender<WsEvent>) -> Self {
+        let review_counts = Arc::new(Mutex::new(ReviewCounts::default()));
+        Self::spawn_count_tracker(client.clone(), ws_tx.subscribe(), review_counts.clone());
         Self {
             client,
             tool_router: Self::tool_router(),
             ws_tx,
+            review_counts,
+            present_reviews: Arc::default(),
+        }
+    }
+
+    /// Register agent presence on a review the first time a tool touches it.
+    /// Registration is best effort; a failed attempt is retried on the next call.
+    async fn register_presence(&self, review_id: &str) {
+        if !self
+            .present_reviews
+            .lock()
+            .unwrap()
+            .insert(review_id.to_string())
+        {
+            return;
+        }
+        let body = serde_json::json!({ "connected": true });
+        if self
+            .client
+            .put(&format!("/api/reviews/{review_id}/agent-presence"), &body)
+            .await
+            .is_err()
+        {
+            self.present_reviews.lock().unwrap().remove(review_id);
+        }
+    }
+
+    /// Deregister presence on every review this session registered on.
+    /// Call once the MCP connection has closed.
+    pub async fn release_presence(&self) {
+        let reviews: Vec<String> = self.present_reviews.lock().unwrap().drain().collect();
+        let body = serde_json::json!({ "connected": false });
+        for review_id in reviews {
+            let _ = self
+                .client
+                .put(&format!("/api/reviews/{review_id}/agent-presence"), &body)
+                .await;
+        }
+    }
+
+    /// Seed the review counts from the API, then keep them current from WebSocket events.
+    fn spawn_count_tracker(
+        client: PreflightClient,
+        mut rx: broadcast::Receiver<WsEvent>,
+        counts: Arc<Mutex<ReviewCounts>>,
+    ) {
+        tokio::spawn(async move {
+            match ReviewCounts::fetch(&client).await {
+                Ok(seed) => *counts.lock().unwrap() = seed,
+                Err(e) => eprintln!("[mcp] failed to load review counts: {e}"),
+            }
+            loop {
+                match rx.recv().await {
+                    Ok(event) => counts.lock().unwrap().apply(&event),
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        eprintln!("[mcp] review count tracker: skipped {n} events (lagged)");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    /// One-line summary of the cached review counts for the `get_info` instructions.
+    fn review_summary(&self) -> String {
+        let counts = self.review_counts.lock().unwrap();
+        let open = counts.open_reviews();
+        let noun = if open == 1 { "review" } else { "reviews" };
+        let mut summary = format!(
+            "Currently {open} open {noun}, {} with unresolved threads.",
+            counts.reviews_with_unresolved_threads()
+        );
+        if let Some(warning) = self.client.event_warning() {
+            summary.push_str(&format!("\n\nWarning: {warning}"));
         }
+        summary
     }
 
     /// Check for threads that need agent attention (catch-up for missed events).
-    /// Returns a synthetic comment_added event JSON string if a pending thread is found.
-    async fn check_pending_threads(&self, review_id: &str) -> Option<String> {
+    /// Returns a synthetic comment_added event if a pending thread is found.
+    async fn check_pending_threads(&self, review_id: &str) -> Option<serde_json::Value> {
         let threads: serde_json::Value = self
             .client
             .get(&format!("/api/reviews/{review_id}/threads"))
@@ -195,7 +615,8 @@ impl PreflightMcp {
             let Some(comments) = thread["comments"].as_array() else {
                 continue;
             };
-            let Some(last) = comments.last() else {
+            // Drafts are the reviewer's unsent notes, not something to answer
+            let Some(last) = comments.iter().rev().find(|c| c["draft"] != true) else {
                 continue;
             };
             if last["author_type"].as_str() != Some("Human") {
@@ -212,7 +633,7 @@ impl PreflightMcp {
                 },
                 "timestamp": chrono::Utc::now(),
             });
-            return serde_json::to_string_pretty(&event).ok();
+            return Some(event);
         }
         None
     }
@@ -239,6 +660,7 @@ impl PreflightMcp {
         &self,
         Parameters(input): Parameters<GetReviewInput>,
     ) -> Result<String, String> {
+        self.register_presence(&input.review_id).await;
         let review: serde_json::Value = self
             .client
             .get(&format!("/api/reviews/{}", input.review_id))
@@ -259,11 +681,14 @@ impl PreflightMcp {
         serde_json::to_string_pretty(&combined).map_err(|e| e.to_string())
     }
 
-    #[tool(description = "Get the diff content for a specific file in a review")]
+    #[tool(
+        description = "Get the diff for a specific file in a review, as unified diff text by default or the server's JSON with format=json"
+    )]
     async fn get_diff(
         &self,
         Parameters(input): Parameters<GetDiffInput>,
     ) -> Result<String, String> {
+        self.register_presence(&input.review_id).await;
         let encoded_path = urlencoding::encode(&input.file_path);
         let diff: serde_json::Value = self
             .client
@@ -274,7 +699,61 @@ impl PreflightMcp {
             .await
             .map_err(format_error)?;
 
-        serde_json::to_string_pretty(&diff).map_err(|e| e.to_string())
+        match input.format {
+            DiffFormat::Json => serde_json::to_string_pretty(&diff).map_err(|e| e.to_string()),
+            DiffFormat::Unified => {
+                let body: FileDiffBody = serde_json::from_value(diff).map_err(|e| e.to_string())?;
+                Ok(preflight_core::unified_diff::to_unified_diff(&[
+                    FileDiff::from(body),
+                ]))
+            }
+        }
+    }
+
+    #[tool(
+        description = "Get only the added lines of a file in the latest revision, each with its line number in the new file. Cheaper than get_diff when you only need the new code."
+    )]
+    async fn get_changed_lines(
+        &self,
+        Parameters(input): Parameters<GetChangedLinesInput>,
+    ) -> Result<String, String> {
+        self.register_presence(&input.review_id).await;
+        let encoded_path = urlencoding::encode(&input.file_path);
+        let lines: serde_json::Value = self
+            .client
+            .get(&format!(
+                "/api/reviews/{}/changed-lines/{encoded_path}",
+                input.review_id
+            ))
+            .await
+            .map_err(format_error)?;
+        serde_json::to_string_pretty(&lines).map_err(|e| e.to_string())
+    }
+
+    #[tool(
+        description = "Get the whole review (all files in the latest revision) as one unified diff. Output is truncated if very large."
+    )]
+    async fn get_full_diff(
+        &self,
+        Parameters(input): Parameters<GetFullDiffInput>,
+    ) -> Result<String, String> {
+        self.register_presence(&input.review_id).await;
+        let bodies: Vec<FileDiffBody> = self
+            .client
+            .get(&format!("/api/reviews/{}/diff", input.review_id))
+            .await
+            .map_err(format_error)?;
+
+        let (omitted, files): (Vec<_>, Vec<_>) = bodies.into_iter().partition(|b| b.truncated);
+        let files: Vec<FileDiff> = files.into_iter().map(FileDiff::from).collect();
+        let mut diff = preflight_core::unified_diff::to_unified_diff(&files);
+        if !omitted.is_empty() {
+            diff.push_str(&format!(
+                "[{} more files omitted from this large review; use get_diff for individual files]\n",
+                omitted.len()
+            ));
+        }
+        Ok(truncate_diff(diff, MAX_FULL_DIFF_BYTES))
     }
 
     #[tool(description = "Get comment threads on a review, optionally filtered by file path")]
@@ -282,20 +761,66 @@ impl PreflightMcp {
         &self,
         Parameters(input): Parameters<GetCommentsInput>,
     ) -> Result<String, String> {
-        let path = match &input.file_path {
-            Some(file) => format!(
-                "/api/reviews/{}/threads?file={}",
-                input.review_id,
-                urlencoding::encode(file)
-            ),
-            None => format!("/api/reviews/{}/threads", input.review_id),
-        };
+        self.register_presence(&input.review_id).await;
+        let mut query = Vec::new();
+        if let Some(file) = &input.file_path {
+            query.push(format!("file={}", urlencoding::encode(file)));
+        }
+        if let Some(since) = &input.since {
+            query.push(format!("since={}", urlencoding::encode(since)));
+        }
+        if let Some(author) = &input.author {
+            query.push(format!("author={}", urlencoding::encode(author)));
+        }
+        let mut path = format!("/api/reviews/{}/threads", input.review_id);
+        if !query.is_empty() {
+            path.push('?');
+            path.push_str(&query.join("&"));
+        }
 
         let threads: serde_json::Value = self.client.get(&path).await.map_err(format_error)?;
 
         serde_json::to_string_pretty(&threads).map_err(|e| e.to_string())
     }
 
+    #[tool(
+        description = "Get the suggested replacements left on open threads of a review, each with its thread, file path and line range"
+    )]
+    async fn get_suggestions(
+        &self,
+        Parameters(input): Parameters<GetSuggestionsInput>,
+    ) -> Result<String, String> {
+        self.register_presence(&input.review_id).await;
+        let threads: Vec<serde_json::Value> = self
+            .client
+            .get(&format!("/api/reviews/{}/threads", input.review_id))
+            .await
+            .map_err(format_error)?;
+
+        let suggestions: Vec<serde_json::Value> = threads
+            .iter()
+            .filter(|thread| thread["status"] == "Open")
+            .flat_map(|thread| {
+                let comments = thread["comments"].as_array().into_iter().flatten();
+                comments
+                    .filter(|comment| comment["suggestion"].is_string())
+                    .map(move |comment| {
+                        serde_json::json!({
+                            "thread_id": thread["id"],
+                            "comment_id": comment["id"],
+                            "file_path": thread["file_path"],
+                            "line_start": thread["line_start"],
+                            "line_end": thread["line_end"],
+                            "suggestion": comment["suggestion"],
+                            "body": comment["body"],
+                        })
+                    })
+            })
+            .collect();
+
+        serde_json::to_string_pretty(&suggestions).map_err(|e| e.to_string())
+    }
+
     #[tool(description = "Reply to a comment thread as the AI agent")]
     async fn respond_to_comment(
         &self,
@@ -304,6 +829,7 @@ impl PreflightMcp {
         let body = serde_json::json!({
             "author_type": "Agent",
             "body": input.body,
+            "in_reply_to": input.in_reply_to,
         });
 
         let comment: serde_json::Value = self
@@ -322,6 +848,7 @@ impl PreflightMcp {
         &self,
         Parameters(input): Parameters<SubmitRevisionInput>,
     ) -> Result<String, String> {
+        self.register_presence(&input.review_id).await;
         let body = serde_json::json!({
             "trigger": "Agent",
             "message": input.message,
@@ -339,18 +866,21 @@ impl PreflightMcp {
         serde_json::to_string_pretty(&revision).map_err(|e| e.to_string())
     }
 
-    #[tool(description = "Create a new code review from a git repository's uncommitted changes")]
+    #[tool(
+        description = "Create a new code review from a git repository's uncommitted changes, or from a single commit"
+    )]
     async fn create_review(
         &self,
         Parameters(input): Parameters<CreateReviewInput>,
     ) -> Result<String, String> {
-        let base_ref = input.base_ref.unwrap_or_else(|| {
-            preflight_core::git_diff::detect_default_base(std::path::Path::new(&input.repo_path))
-        });
         let body = serde_json::json!({
             "repo_path": input.repo_path,
             "title": input.title,
-            "base_ref": base_ref,
+            "base_ref": input.base_ref,
+            "commit": input.commit,
+            "path_prefix": input.path_prefix,
+            "extra_repos": input.extra_repos.unwrap_or_default(),
+            "labels": input.labels.unwrap_or_default(),
         });
 
         let review: serde_json::Value = self
@@ -358,6 +888,9 @@ impl PreflightMcp {
             .post("/api/reviews", &body)
             .await
             .map_err(format_error)?;
+        if let Some(id) = review["id"].as_str() {
+            self.register_presence(id).await;
+        }
 
         serde_json::to_string_pretty(&review).map_err(|e| e.to_string())
     }
@@ -380,6 +913,9 @@ impl PreflightMcp {
             .post("/api/reviews/find-or-create", &body)
             .await
             .map_err(format_error)?;
+        if let Some(id) = review["id"].as_str() {
+            self.register_presence(id).await;
+        }
 
         serde_json::to_string_pretty(&review).map_err(|e| e.to_string())
     }
@@ -391,11 +927,14 @@ impl PreflightMcp {
         &self,
         Parameters(input): Parameters<CreateThreadInput>,
     ) -> Result<String, String> {
+        self.register_presence(&input.review_id).await;
         let origin = input.origin.unwrap_or_else(|| "Comment".to_string());
+        let side = input.side.unwrap_or_else(|| "New".to_string());
         let body = serde_json::json!({
             "file_path": input.file_path,
             "line_start": input.line_start,
             "line_end": input.line_end,
+            "side": side,
             "origin": origin,
             "body": input.body,
             "author_type": "Agent",
@@ -410,11 +949,47 @@ impl PreflightMcp {
         serde_json::to_string_pretty(&thread).map_err(|e| e.to_string())
     }
 
+    #[tool(
+        description = "Create several comment threads in one call. All threads are created together or, if any entry is invalid, not at all. Returns the created thread IDs."
+    )]
+    async fn create_threads(
+        &self,
+        Parameters(input): Parameters<CreateThreadsInput>,
+    ) -> Result<String, String> {
+        self.register_presence(&input.review_id).await;
+        let body: Vec<serde_json::Value> = input
+            .threads
+            .into_iter()
+            .map(|thread| {
+                serde_json::json!({
+                    "file_path": thread.file_path,
+                    "line_start": thread.line_start,
+                    "line_end": thread.line_end,
+                    "origin": thread.origin.unwrap_or_else(|| "Comment".to_string()),
+                    "body": thread.body,
+                    "author_type": "Agent",
+                })
+            })
+            .collect();
+
+        let created: serde_json::Value = self
+            .client
+            .post(
+                &format!("/api/reviews/{}/threads/batch", input.review_id),
+                &serde_json::Value::Array(body),
+            )
+            .await
+            .map_err(format_error)?;
+
+        serde_json::to_string_pretty(&created).map_err(|e| e.to_string())
+    }
+
     #[tool(description = "Update a review's status (open or close it)")]
     async fn update_review_status(
         &self,
         Parameters(input): Parameters<UpdateReviewStatusInput>,
     ) -> Result<String, String> {
+        self.register_presence(&input.review_id).await;
         let body = serde_json::json!({ "status": input.status });
 
         self.client
@@ -429,40 +1004,107 @@ impl PreflightMcp {
     }
 
     #[tool(
-        description = "Delete a review and all its associated data (threads, revisions, comments)"
+        description = "Check who is present on a review: whether an agent is connected and whether a human is viewing it live. Use this to decide whether to act now or wait for the human."
     )]
-    async fn delete_review(
+    async fn get_review_presence(
         &self,
-        Parameters(input): Parameters<DeleteReviewInput>,
+        Parameters(input): Parameters<GetReviewPresenceInput>,
     ) -> Result<String, String> {
-        self.client
-            .delete(&format!("/api/reviews/{}", input.review_id))
+        let agent: serde_json::Value = self
+            .client
+            .get(&format!("/api/reviews/{}/agent-status", input.review_id))
             .await
             .map_err(format_error)?;
 
-        Ok(format!("Review {} deleted", input.review_id))
+        let human: serde_json::Value = self
+            .client
+            .get(&format!("/api/reviews/{}/human-status", input.review_id))
+            .await
+            .map_err(format_error)?;
+
+        let presence = serde_json::json!({
+            "agent_connected": agent["connected"],
+            "human_viewing": human["connected"],
+        });
+
+        serde_json::to_string_pretty(&presence).map_err(|e| e.to_string())
     }
 
-    #[tool(description = "Resolve or reopen a comment thread")]
-    async fn resolve_thread(
+    #[tool(
+        description = "Read the reviewer's verdict on a review: 'Approved', 'ChangesRequested', 'Commented', or null if none was given yet. Unlike the review's status, this says whether your changes were accepted."
+    )]
+    async fn get_review_decision(
         &self,
-        Parameters(input): Parameters<ResolveThreadInput>,
+        Parameters(input): Parameters<GetReviewDecisionInput>,
     ) -> Result<String, String> {
-        let body = serde_json::json!({ "status": input.status });
-
-        self.client
-            .patch(&format!("/api/threads/{}/status", input.thread_id), &body)
+        let review: serde_json::Value = self
+            .client
+            .get(&format!("/api/reviews/{}", input.review_id))
             .await
             .map_err(format_error)?;
 
-        Ok(format!(
-            "Thread {} status updated to {}",
-            input.thread_id, input.status
-        ))
+        let decision = serde_json::json!({
+            "decision": review["decision"],
+            "status": review["status"],
+        });
+
+        serde_json::to_string_pretty(&decision).map_err(|e| e.to_string())
     }
 
     #[tool(
-        description = "Acknowledge a comment thread to signal that the agent has seen it or is working on it. Use 'seen' when you first read a comment, 'researching' when you begin investigating the code, 'working' when you begin composing a response."
+        description = "Mark the whole review as seen once you have read every comment on it. The human sees this until they add a new comment."
+    )]
+    async fn mark_review_seen(
+        &self,
+        Parameters(input): Parameters<MarkReviewSeenInput>,
+    ) -> Result<String, String> {
+        self.register_presence(&input.review_id).await;
+        self.client
+            .put(
+                &format!("/api/reviews/{}/agent-seen", input.review_id),
+                &serde_json::json!({}),
+            )
+            .await
+            .map_err(format_error)?;
+
+        Ok(format!("Review {} marked as seen", input.review_id))
+    }
+
+    #[tool(
+        description = "Delete a review and all its associated data (threads, revisions, comments)"
+    )]
+    async fn delete_review(
+        &self,
+        Parameters(input): Parameters<DeleteReviewInput>,
+    ) -> Result<String, String> {
+        self.client
+            .delete(&format!("/api/reviews/{}", input.review_id))
+            .await
+            .map_err(format_error)?;
+
+        Ok(format!("Review {} deleted", input.review_id))
+    }
+
+    #[tool(description = "Resolve or reopen a comment thread")]
+    async fn resolve_thread(
+        &self,
+        Parameters(input): Parameters<ResolveThreadInput>,
+    ) -> Result<String, String> {
+        let body = serde_json::json!({ "status": input.status });
+
+        self.client
+            .patch(&format!("/api/threads/{}/status", input.thread_id), &body)
+            .await
+            .map_err(format_error)?;
+
+        Ok(format!(
+            "Thread {} status updated to {}",
+            input.thread_id, input.status
+        ))
+    }
+
+    #[tool(
+        description = "Acknowledge a comment thread to signal that the agent has seen it or is working on it. Use 'seen' when you first read a comment, 'researching' when you begin investigating the code, 'working' when you begin composing a response."
     )]
     async fn acknowledge_thread(
         &self,
@@ -496,7 +1138,7 @@ impl PreflightMcp {
     }
 
     #[tool(
-        description = "Wait for a real-time event (new comment, thread created, etc). Blocks until a matching event arrives or timeout. Use this from a background task to monitor a review for activity."
+        description = "Wait for a real-time event (new comment, thread created, etc). Blocks until a matching event arrives or timeout. Use this from a background task to monitor a review for activity. Set collect (or max_events) to instead gather every matching event until the timeout or the cap and get them back as an array, in arrival order."
     )]
     async fn wait_for_event(
         &self,
@@ -504,7 +1146,14 @@ impl PreflightMcp {
     ) -> Result<String, String> {
         let timeout_secs = input.timeout_secs.unwrap_or(300).min(600);
         let timeout = std::time::Duration::from_secs(timeout_secs);
+        let max_events = match (input.collect.unwrap_or(false), input.max_events) {
+            (_, Some(max)) => max.clamp(1, MAX_COLLECTED_EVENTS),
+            (true, None) => MAX_COLLECTED_EVENTS,
+            (false, None) => 1,
+        };
+        let collect = input.collect.unwrap_or(false) || input.max_events.is_some();
         let mut rx = self.ws_tx.subscribe();
+        let mut events = Vec::new();
 
         // Register agent presence if review_id is provided
         if let Some(ref rid) = input.review_id {
@@ -515,47 +1164,44 @@ impl PreflightMcp {
                 .await;
 
             // Catch-up: check for threads needing attention before blocking
-            if let Some(event_json) = self.check_pending_threads(rid).await {
-                let body = serde_json::json!({ "connected": false });
-                let _ = self
-                    .client
-                    .put(&format!("/api/reviews/{rid}/agent-presence"), &body)
-                    .await;
-                return Ok(event_json);
+            if let Some(event) = self.check_pending_threads(rid).await {
+                events.push(event);
             }
         }
 
-        let result = tokio::time::timeout(timeout, async {
-            loop {
-                match rx.recv().await {
-                    Ok(event) => {
-                        // Filter by review_id if specified
-                        if let Some(ref rid) = input.review_id
-                            && &event.review_id != rid
-                        {
-                            continue;
+        let result = if events.len() >= max_events {
+            Ok(Ok(()))
+        } else {
+            tokio::time::timeout(timeout, async {
+                loop {
+                    match rx.recv().await {
+                        Ok(event) => {
+                            if !event_matches(&input, &event) {
+                                continue;
+                            }
+                            let event = event_json(&event);
+                            // A comment made while catching up is both
+                            // caught up on and broadcast
+                            if events.iter().any(|e| same_event(e, &event)) {
+                                continue;
+                            }
+                            events.push(event);
+                            if events.len() >= max_events {
+                                return Ok(());
+                            }
                         }
-                        // Filter by event type if specified
-                        if let Some(ref types) = input.event_types
-                            && !types
-                                .iter()
-                                .any(|t| event_type_matches(&event.event_type, t))
-                        {
+                        Err(broadcast::error::RecvError::Lagged(n)) => {
+                            eprintln!("[mcp] wait_for_event: skipped {n} events (lagged)");
                             continue;
                         }
-                        return Ok(event);
-                    }
-                    Err(broadcast::error::RecvError::Lagged(n)) => {
-                        eprintln!("[mcp] wait_for_event: skipped {n} events (lagged)");
-                        continue;
-                    }
-                    Err(broadcast::error::RecvError::Closed) => {
-                        return Err("Event channel closed".to_string());
+                        Err(broadcast::error::RecvError::Closed) => {
+                            return Err("Event channel closed".to_string());
+                        }
                     }
                 }
-            }
-        })
-        .await;
+            })
+            .await
+        };
 
         // Deregister agent presence if review_id is provided
         if let Some(ref rid) = input.review_id {
@@ -566,25 +1212,25 @@ impl PreflightMcp {
                 .await;
         }
 
-        match result {
-            Ok(Ok(event)) => {
-                let output = serde_json::json!({
-                    "event_type": event.event_type,
-                    "review_id": event.review_id,
-                    "payload": event.payload,
-                    "timestamp": event.timestamp,
-                });
-                serde_json::to_string_pretty(&output).map_err(|e| e.to_string())
-            }
-            Ok(Err(e)) => Err(e),
-            Err(_) => {
-                let output = serde_json::json!({
-                    "timeout": true,
-                    "message": format!("No matching events within {timeout_secs}s"),
-                });
-                serde_json::to_string_pretty(&output).map_err(|e| e.to_string())
+        if let Ok(Err(e)) = result {
+            return Err(e);
+        }
+        if events.is_empty() {
+            let mut output = serde_json::json!({
+                "timeout": true,
+                "message": format!("No matching events within {timeout_secs}s"),
+            });
+            if let Some(warning) = self.client.event_warning() {
+                output["warning"] = serde_json::Value::String(warning);
             }
+            return serde_json::to_string_pretty(&output).map_err(|e| e.to_string());
         }
+        let output = if collect {
+            serde_json::Value::Array(events)
+        } else {
+            events.swap_remove(0)
+        };
+        serde_json::to_string_pretty(&output).map_err(|e| e.to_string())
     }
 }
 
@@ -599,18 +1245,25 @@ impl ServerHandler for PreflightMcp {
                 version: env!("CARGO_PKG_VERSION").into(),
                 ..Default::default()
             },
-            instructions: Some(
+            instructions: Some(format!(
                 "Preflight is a local code review tool. Use these tools to participate in code reviews.\n\n\
+                 {}\n\n\
                  Core loop: list_reviews → get_review → get_diff → get_comments → respond_to_comment\n\n\
+                 Use get_full_diff to read every file in a review as one patch, \
+                 get_changed_lines for just the added lines of a file, \
+                 and get_suggestions to collect proposed replacements on open threads.\n\n\
                  Agent actions: find_or_create_review (idempotent review setup), create_review (start a review), \
                  create_thread (comment on code or explain it with origin 'AgentExplanation'), \
+                 create_threads (several threads at once, all or nothing), \
                  submit_revision (after making changes)\n\n\
-                 Activity: acknowledge_thread to signal 'seen' or 'working' on a thread\n\n\
-                 Lifecycle: update_review_status (open/close), resolve_thread (resolve/reopen)\n\n\
+                 Activity: acknowledge_thread to signal 'seen' or 'working' on a thread, \
+                 mark_review_seen once you have read the whole review\n\n\
+                 Lifecycle: update_review_status (open/close), resolve_thread (resolve/reopen), \
+                 get_review_decision (whether the reviewer approved or requested changes)\n\n\
                  Notifications: Use wait_for_event from a background task to monitor for new comments, \
-                 threads, or status changes. It blocks until a matching event arrives or times out."
-                    .to_string(),
-            ),
+                 threads, or status changes. It blocks until a matching event arrives or times out.",
+                self.review_summary()
+            )),
         }
     }
 }
@@ -648,6 +1301,8 @@ mod tests {
                 review_id: Some("test-review".to_string()),
                 event_types: Some(vec!["comment_added".to_string()]),
                 timeout_secs: Some(5),
+                collect: None,
+                max_events: None,
             }))
             .await
             .unwrap();
@@ -657,6 +1312,80 @@ mod tests {
         assert_eq!(parsed["review_id"], "test-review");
     }
 
+    /// Send a `comment_added` event for `comment_id` on review `r1`.
+    fn send_comment(ws_tx: &broadcast::Sender<WsEvent>, comment_id: &str) {
+        let _ = ws_tx.send(WsEvent {
+            event_type: WsEventType::CommentAdded,
+            review_id: "r1".to_string(),
+            payload: serde_json::json!({"thread_id": "t1", "comment": {"id": comment_id}}),
+            timestamp: chrono::Utc::now(),
+        });
+    }
+
+    #[tokio::test]
+    async fn wait_for_event_collects_every_matching_event() {
+        let mcp = test_mcp();
+        let ws_tx = mcp.ws_tx.clone();
+
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            send_comment(&ws_tx, "c1");
+            send_comment(&ws_tx, "c2");
+            // Redelivered comment, collected once
+            send_comment(&ws_tx, "c1");
+            send_comment(&ws_tx, "c3");
+        });
+
+        let result = mcp
+            .wait_for_event(Parameters(WaitForEventInput {
+                review_id: None,
+                event_types: Some(vec!["comment_added".to_string()]),
+                timeout_secs: Some(1),
+                collect: Some(true),
+                max_events: None,
+            }))
+            .await
+            .unwrap();
+
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        let ids: Vec<_> = parsed
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["payload"]["comment"]["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, ["c1", "c2", "c3"]);
+    }
+
+    #[tokio::test]
+    async fn wait_for_event_returns_once_max_events_collected() {
+        let mcp = test_mcp();
+        let ws_tx = mcp.ws_tx.clone();
+
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            send_comment(&ws_tx, "c1");
+            send_comment(&ws_tx, "c2");
+            send_comment(&ws_tx, "c3");
+        });
+
+        let started = std::time::Instant::now();
+        let result = mcp
+            .wait_for_event(Parameters(WaitForEventInput {
+                review_id: None,
+                event_types: None,
+                timeout_secs: Some(5),
+                collect: None,
+                max_events: Some(2),
+            }))
+            .await
+            .unwrap();
+
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(parsed.as_array().unwrap().len(), 2);
+    }
+
     #[tokio::test]
     async fn wait_for_event_times_out() {
         let mcp = test_mcp();
@@ -666,6 +1395,8 @@ mod tests {
                 review_id: None,
                 event_types: None,
                 timeout_secs: Some(1),
+                collect: None,
+                max_events: None,
             }))
             .await
             .unwrap();
@@ -703,6 +1434,8 @@ mod tests {
                 review_id: Some("my-review".to_string()),
                 event_types: None,
                 timeout_secs: Some(5),
+                collect: None,
+                max_events: None,
             }))
             .await
             .unwrap();
@@ -740,6 +1473,8 @@ mod tests {
                 review_id: None,
                 event_types: Some(vec!["thread_created".to_string()]),
                 timeout_secs: Some(5),
+                collect: None,
+                max_events: None,
             }))
             .await
             .unwrap();
@@ -768,6 +1503,8 @@ mod tests {
                 review_id: None,
                 event_types: Some(vec!["thread_acknowledged".to_string()]),
                 timeout_secs: Some(5),
+                collect: None,
+                max_events: None,
             }))
             .await
             .unwrap();
@@ -796,6 +1533,8 @@ mod tests {
                 review_id: None,
                 event_types: Some(vec!["thread_poked".to_string()]),
                 timeout_secs: Some(5),
+                collect: None,
+                max_events: None,
             }))
             .await
             .unwrap();
@@ -804,15 +1543,47 @@ mod tests {
         assert_eq!(parsed["event_type"], "thread_poked");
     }
 
+    #[tokio::test]
+    async fn review_counts_update_after_review_created() {
+        let mcp = test_mcp();
+        assert!(
+            mcp.get_info()
+                .instructions
+                .unwrap()
+                .contains("Currently 0 open reviews")
+        );
+
+        let _ = mcp.ws_tx.send(WsEvent {
+            event_type: WsEventType::ReviewCreated,
+            review_id: "r1".to_string(),
+            payload: serde_json::json!({"id": "r1", "status": "Open"}),
+            timestamp: chrono::Utc::now(),
+        });
+        let _ = mcp.ws_tx.send(WsEvent {
+            event_type: WsEventType::ThreadCreated,
+            review_id: "r1".to_string(),
+            payload: serde_json::json!({"id": "t1", "status": "Open", "origin": "Comment"}),
+            timestamp: chrono::Utc::now(),
+        });
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        loop {
+            let instructions = mcp.get_info().instructions.unwrap();
+            if instructions.contains("Currently 1 open review, 1 with unresolved threads.") {
+                break;
+            }
+            assert!(
+                std::time::Instant::now() < deadline,
+                "counts never updated: {instructions}"
+            );
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+    }
+
     // --- Integration tests for catch-up behavior (real HTTP server) ---
 
     async fn start_test_server() -> u16 {
-        let dir = tempfile::TempDir::new().unwrap();
-        let path = dir.path().join("state.json");
-        let store = preflight_core::json_store::JsonFileStore::new(&path)
-            .await
-            .unwrap();
-        Box::leak(Box::new(dir));
+        let store = preflight_core::memory_store::MemoryStore::new();
 
         let app = preflight_server::app(std::sync::Arc::new(store));
         let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
@@ -918,6 +1689,324 @@ mod tests {
         (port, review_id)
     }
 
+    #[tokio::test]
+    async fn get_review_presence_reports_agent_connection() {
+        let (port, review_id) = setup_server_with_review().await;
+        let client = crate::client::PreflightClient::new(port);
+        let (ws_tx, _) = broadcast::channel(64);
+        let mcp = PreflightMcp::new(client, ws_tx);
+        let presence = || {
+            mcp.get_review_presence(Parameters(GetReviewPresenceInput {
+                review_id: review_id.clone(),
+            }))
+        };
+
+        let parsed: serde_json::Value = serde_json::from_str(&presence().await.unwrap()).unwrap();
+        assert_eq!(parsed["agent_connected"], false);
+        assert_eq!(parsed["human_viewing"], false);
+
+        let resp = reqwest::Client::new()
+            .put(format!(
+                "http://127.0.0.1:{port}/api/reviews/{review_id}/agent-presence"
+            ))
+            .json(&serde_json::json!({ "connected": true }))
+            .send()
+            .await
+            .unwrap();
+        assert!(resp.status().is_success());
+
+        let parsed: serde_json::Value = serde_json::from_str(&presence().await.unwrap()).unwrap();
+        assert_eq!(parsed["agent_connected"], true);
+    }
+
+    #[tokio::test]
+    async fn get_review_decision_reads_each_decision() {
+        let (port, review_id) = setup_server_with_review().await;
+        let client = crate::client::PreflightClient::new(port);
+        let (ws_tx, _) = broadcast::channel(64);
+        let mcp = PreflightMcp::new(client, ws_tx);
+        let decision = || async {
+            let result = mcp
+                .get_review_decision(Parameters(GetReviewDecisionInput {
+                    review_id: review_id.clone(),
+                }))
+                .await
+                .unwrap();
+            serde_json::from_str::<serde_json::Value>(&result).unwrap()["decision"].clone()
+        };
+
+        assert!(decision().await.is_null());
+        for verdict in ["Approved", "ChangesRequested", "Commented"] {
+            let resp = reqwest::Client::new()
+                .post(format!(
+                    "http://127.0.0.1:{port}/api/reviews/{review_id}/decision"
+                ))
+                .json(&serde_json::json!({ "decision": verdict }))
+                .send()
+                .await
+                .unwrap();
+            assert!(resp.status().is_success());
+            assert_eq!(decision().await, verdict);
+        }
+    }
+
+    #[tokio::test]
+    async fn review_tools_register_agent_presence_once() {
+        let (port, review_id) = setup_server_with_review().await;
+        let client = crate::client::PreflightClient::new(port);
+        let (ws_tx, _) = broadcast::channel(64);
+        let mcp = PreflightMcp::new(client, ws_tx);
+        let status_url = format!("http://127.0.0.1:{port}/api/reviews/{review_id}/agent-status");
+        let http = reqwest::Client::new();
+
+        let status: serde_json::Value = http
+            .get(&status_url)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(status["connected"], false);
+
+        for _ in 0..2 {
+            mcp.get_review(Parameters(GetReviewInput {
+                review_id: review_id.clone(),
+            }))
+            .await
+            .unwrap();
+        }
+
+        let status: serde_json::Value = http
+            .get(&status_url)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(status["connected"], true);
+        assert_eq!(mcp.present_reviews.lock().unwrap().len(), 1);
+
+        // Unknown reviews are not remembered, so nothing is left to release
+        let _ = mcp
+            .get_review(Parameters(GetReviewInput {
+                review_id: "00000000-0000-0000-0000-000000000000".to_string(),
+            }))
+            .await;
+        assert_eq!(mcp.present_reviews.lock().unwrap().len(), 1);
+
+        mcp.release_presence().await;
+        assert!(mcp.present_reviews.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn get_suggestions_returns_open_suggestions_with_anchors() {
+        let (port, review_id) = setup_server_with_review().await;
+        let client = crate::client::PreflightClient::new(port);
+        let (ws_tx, _) = broadcast::channel(64);
+        let mcp = PreflightMcp::new(client, ws_tx);
+        let http = reqwest::Client::new();
+        let base_url = format!("http://127.0.0.1:{port}");
+
+        let post_thread = |line: u32, suggestion: Option<&str>| {
+            http.post(format!("{base_url}/api/reviews/{review_id}/threads"))
+                .json(&serde_json::json!({
+                    "file_path": "src/main.rs",
+                    "line_start": line,
+                    "line_end": line,
+                    "origin": "Comment",
+                    "body": "try this",
+                    "author_type": "Human",
+                    "suggestion": suggestion,
+                }))
+                .send()
+        };
+        let open: serde_json::Value = post_thread(1, Some("fn main() {}"))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(open["comments"][0]["suggestion"], "fn main() {}");
+        post_thread(2, None).await.unwrap();
+        let resolved: serde_json::Value = post_thread(3, Some("// gone"))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let resp = http
+            .patch(format!(
+                "{base_url}/api/threads/{}/status",
+                resolved["id"].as_str().unwrap()
+            ))
+            .json(&serde_json::json!({ "status": "Resolved" }))
+            .send()
+            .await
+            .unwrap();
+        assert!(resp.status().is_success());
+
+        let result = mcp
+            .get_suggestions(Parameters(GetSuggestionsInput {
+                review_id: review_id.clone(),
+            }))
+            .await
+            .unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        let suggestions = parsed.as_array().unwrap();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0]["thread_id"], open["id"]);
+        assert_eq!(suggestions[0]["comment_id"], open["comments"][0]["id"]);
+        assert_eq!(suggestions[0]["file_path"], "src/main.rs");
+        assert_eq!(suggestions[0]["line_start"], 1);
+        assert_eq!(suggestions[0]["line_end"], 1);
+        assert_eq!(suggestions[0]["suggestion"], "fn main() {}");
+    }
+
+    #[tokio::test]
+    async fn create_threads_creates_all_or_none() {
+        let (port, review_id) = setup_server_with_review().await;
+        let client = crate::client::PreflightClient::new(port);
+        let (ws_tx, _) = broadcast::channel(64);
+        let mcp = PreflightMcp::new(client, ws_tx);
+        let thread = |line_start, line_end| NewThread {
+            file_path: "src/main.rs".to_string(),
+            line_start,
+            line_end,
+            body: "batched".to_string(),
+            origin: None,
+        };
+
+        let result = mcp
+            .create_threads(Parameters(CreateThreadsInput {
+                review_id: review_id.clone(),
+                threads: vec![thread(1, 1), thread(2, 3)],
+            }))
+            .await
+            .unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(parsed["thread_ids"].as_array().unwrap().len(), 2);
+
+        let err = mcp
+            .create_threads(Parameters(CreateThreadsInput {
+                review_id: review_id.clone(),
+                threads: vec![thread(1, 1), thread(5, 2)],
+            }))
+            .await
+            .unwrap_err();
+        assert!(err.contains("threads[1]"), "{err}");
+
+        let threads: serde_json::Value = reqwest::get(format!(
+            "http://127.0.0.1:{port}/api/reviews/{review_id}/threads"
+        ))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+        assert_eq!(threads.as_array().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn get_changed_lines_returns_added_lines() {
+        let port = start_test_server().await;
+        let http = reqwest::Client::new();
+        let base_url = format!("http://127.0.0.1:{port}");
+        let repo_path = setup_test_repo();
+        let review_id = http_create_review(&http, &base_url, &repo_path).await;
+
+        let client = crate::client::PreflightClient::new(port);
+        let (ws_tx, _) = broadcast::channel(64);
+        let mcp = PreflightMcp::new(client, ws_tx);
+        let text = mcp
+            .get_changed_lines(Parameters(GetChangedLinesInput {
+                review_id,
+                file_path: "src/main.rs".into(),
+            }))
+            .await
+            .unwrap();
+
+        let lines: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert!(
+            lines
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|l| l["content"] == "    println!(\"hello\");")
+        );
+    }
+
+    #[tokio::test]
+    async fn get_full_diff_covers_all_files() {
+        let port = start_test_server().await;
+        let http = reqwest::Client::new();
+        let base_url = format!("http://127.0.0.1:{port}");
+        let repo_path = setup_test_repo();
+        let p = std::path::Path::new(&repo_path);
+        std::fs::write(p.join("src/lib.rs"), "pub fn lib() {}\n").unwrap();
+        std::process::Command::new("git")
+            .args(["add", "src/lib.rs"])
+            .current_dir(p)
+            .output()
+            .unwrap();
+        let review_id = http_create_review(&http, &base_url, &repo_path).await;
+
+        let client = crate::client::PreflightClient::new(port);
+        let (ws_tx, _) = broadcast::channel(64);
+        let mcp = PreflightMcp::new(client, ws_tx);
+        let diff = mcp
+            .get_full_diff(Parameters(GetFullDiffInput { review_id }))
+            .await
+            .unwrap();
+
+        assert!(diff.contains("diff --git a/src/main.rs b/src/main.rs"));
+        assert!(diff.contains("+    println!(\"hello\");"));
+        assert!(diff.contains("diff --git a/src/lib.rs b/src/lib.rs\nnew file mode 100644"));
+        assert!(diff.contains("+pub fn lib() {}"));
+        let files = preflight_core::parser::parse_diff(&diff).unwrap();
+        assert_eq!(files.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn get_diff_defaults_to_unified_text() {
+        let (port, review_id) = setup_server_with_review().await;
+        let client = crate::client::PreflightClient::new(port);
+        let (ws_tx, _) = broadcast::channel(64);
+        let mcp = PreflightMcp::new(client, ws_tx);
+        let input: GetDiffInput = serde_json::from_value(serde_json::json!({
+            "review_id": review_id,
+            "file_path": "src/main.rs",
+        }))
+        .unwrap();
+
+        let diff = mcp.get_diff(Parameters(input)).await.unwrap();
+        assert!(diff.contains("--- a/src/main.rs\n+++ b/src/main.rs"));
+        assert!(diff.contains("\n-fn main() {}\n"));
+        assert!(diff.contains("\n+use std::io;\n"));
+        assert!(!diff.contains("<span"));
+
+        let diff = mcp
+            .get_diff(Parameters(GetDiffInput {
+                review_id,
+                file_path: "src/main.rs".into(),
+                format: DiffFormat::Json,
+            }))
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&diff).unwrap();
+        assert_eq!(json["path"], "src/main.rs");
+    }
+
+    #[test]
+    fn truncate_diff_cuts_at_line_boundary() {
+        let diff = "line one\nline two\nline three\n".to_string();
+        assert_eq!(truncate_diff(diff.clone(), 100), diff);
+
+        let truncated = truncate_diff(diff, 14);
+        assert!(truncated.starts_with("line one\n[diff truncated: showing 9 of 29 bytes"));
+    }
+
     #[tokio::test]
     async fn wait_for_event_returns_pending_thread_immediately() {
         let (port, review_id) = setup_server_with_review().await;
@@ -936,6 +2025,8 @@ mod tests {
                 review_id: Some(review_id.clone()),
                 event_types: None,
                 timeout_secs: Some(10),
+                collect: None,
+                max_events: None,
             }))
             .await
             .unwrap();
@@ -977,6 +2068,8 @@ mod tests {
                 review_id: Some(review_id.clone()),
                 event_types: None,
                 timeout_secs: Some(1),
+                collect: None,
+                max_events: None,
             }))
             .await
             .unwrap();
@@ -985,6 +2078,57 @@ mod tests {
         assert_eq!(parsed["timeout"], true);
     }
 
+    #[tokio::test]
+    async fn wait_for_event_ignores_draft_replies() {
+        let (port, review_id) = setup_server_with_review().await;
+        let http = reqwest::Client::new();
+        let base_url = format!("http://127.0.0.1:{port}");
+        let thread_id = http_create_thread(&http, &base_url, &review_id).await;
+        for (author, draft) in [("Agent", false), ("Human", true)] {
+            let resp = http
+                .post(format!("{base_url}/api/threads/{thread_id}/comments"))
+                .json(&serde_json::json!({
+                    "author_type": author,
+                    "body": "reply",
+                    "draft": draft
+                }))
+                .send()
+                .await
+                .unwrap();
+            assert!(resp.status().is_success());
+        }
+
+        let client = crate::client::PreflightClient::new(port);
+        let (ws_tx, _) = broadcast::channel(64);
+        let mcp = PreflightMcp::new(client, ws_tx);
+
+        // The agent already answered; the unsent draft needs no reply yet
+        let result = mcp
+            .wait_for_event(Parameters(WaitForEventInput {
+                review_id: Some(review_id.clone()),
+                event_types: None,
+                timeout_secs: Some(1),
+                collect: None,
+                max_events: None,
+            }))
+            .await
+            .unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(parsed["timeout"], true);
+
+        let comments = mcp
+            .get_comments(Parameters(GetCommentsInput {
+                review_id,
+                file_path: None,
+                since: None,
+                author: None,
+            }))
+            .await
+            .unwrap();
+        let threads: serde_json::Value = serde_json::from_str(&comments).unwrap();
+        assert_eq!(threads[0]["comments"].as_array().unwrap().len(), 2);
+    }
+
     #[tokio::test]
     async fn wait_for_event_skips_resolved_threads() {
         let (port, review_id) = setup_server_with_review().await;
@@ -1011,6 +2155,8 @@ mod tests {
                 review_id: Some(review_id.clone()),
                 event_types: None,
                 timeout_secs: Some(1),
+                collect: None,
+                max_events: None,
             }))
             .await
             .unwrap();
@@ -1048,6 +2194,8 @@ mod tests {
                 review_id: Some(review_id.clone()),
                 event_types: None,
                 timeout_secs: Some(1),
+                collect: None,
+                max_events: None,
             }))
             .await
             .unwrap();
@@ -4,12 +4,7 @@ use tokio::net::TcpListener;
 
 /// Spin up an ephemeral preflight server and return its port.
 async fn start_server() -> u16 {
-    let dir = tempfile::TempDir::new().unwrap();
-    let path = dir.path().join("state.json");
-    let store = preflight_core::json_store::JsonFileStore::new(&path)
-        .await
-        .unwrap();
-    Box::leak(Box::new(dir));
+    let store = preflight_core::memory_store::MemoryStore::new();
 
     let app = preflight_server::app(Arc::new(store));
     let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
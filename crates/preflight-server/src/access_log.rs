@@ -0,0 +1,159 @@
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{MatchedPath, Request},
+    http::HeaderValue,
+    middleware::Next,
+    response::Response,
+};
+
+/// Git-bound requests slower than this are flagged in the log.
+pub const SLOW_GIT_REQUEST: Duration = Duration::from_millis(500);
+
+/// Route patterns whose handlers shell out to git, so their timings are the
+/// ones worth watching.
+const GIT_ROUTES: &[&str] = &[
+    "/api/reviews",
+    "/api/reviews/find-or-create",
+    "/api/reviews/preview",
+    "/api/validate-repo",
+    "/api/reviews/{id}/files",
+    "/api/reviews/{id}/files/{*path}",
+    "/api/reviews/{id}/changed-lines/{*path}",
+    "/api/reviews/{id}/diff",
+    "/api/reviews/{id}/content/{*path}",
+    "/api/reviews/{id}/raw/{*path}",
+    "/api/reviews/{id}/interdiff/{*path}",
+    "/api/reviews/{id}/revisions",
+    "/api/reviews/{id}/revisions/{n}/diff",
+    "/api/reviews/{id}/drift",
+    "/api/reviews/{id}/rebase-preview",
+];
+
+/// Middleware printing one line per request to stderr with its method, path,
+/// status and duration, and reporting the duration to the client in a
+/// `Server-Timing` header. Only mounted when `--access-log` is set.
+pub async fn log_requests(
+    matched_path: Option<MatchedPath>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let route = matched_path.map(|p| p.as_str().to_string());
+    let start = Instant::now();
+    let mut response = next.run(request).await;
+    let elapsed = start.elapsed();
+
+    let git_bound = route.as_deref().is_some_and(|r| GIT_ROUTES.contains(&r));
+    eprintln!(
+        "{}",
+        format_line(
+            method.as_str(),
+            &path,
+            response.status().as_u16(),
+            elapsed,
+            git_bound
+        )
+    );
+    if let Ok(value) = HeaderValue::from_str(&format!("app;dur={:.1}", millis(elapsed))) {
+        response.headers_mut().insert("server-timing", value);
+    }
+    response
+}
+
+fn format_line(
+    method: &str,
+    path: &str,
+    status: u16,
+    elapsed: Duration,
+    git_bound: bool,
+) -> String {
+    let mut line = format!("{method} {path} {status} {:.1}ms", millis(elapsed));
+    if git_bound && elapsed > SLOW_GIT_REQUEST {
+        line.push_str(" SLOW (git)");
+    }
+    line
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::body::Body;
+    use axum::http::Request;
+    use preflight_core::memory_store::MemoryStore;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::state::ServerConfig;
+
+    #[test]
+    fn slow_git_requests_are_flagged() {
+        let slow = SLOW_GIT_REQUEST + Duration::from_millis(1);
+        assert_eq!(
+            format_line("GET", "/api/reviews/1/diff", 200, slow, true),
+            "GET /api/reviews/1/diff 200 501.0ms SLOW (git)"
+        );
+        assert_eq!(
+            format_line("GET", "/api/threads", 200, slow, false),
+            "GET /api/threads 200 501.0ms"
+        );
+        assert_eq!(
+            format_line(
+                "GET",
+                "/api/reviews/1/diff",
+                404,
+                Duration::from_millis(3),
+                true
+            ),
+            "GET /api/reviews/1/diff 404 3.0ms"
+        );
+    }
+
+    async fn server_timing(config: ServerConfig) -> Option<String> {
+        let app = crate::app_with_config(Arc::new(MemoryStore::new()), config);
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/health")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        response
+            .headers()
+            .get("server-timing")
+            .map(|v| v.to_str().unwrap().to_string())
+    }
+
+    #[tokio::test]
+    async fn access_log_adds_server_timing_only_when_enabled() {
+        let timing = server_timing(ServerConfig {
+            access_log: true,
+            ..Default::default()
+        })
+        .await
+        .expect("server-timing header");
+        assert!(timing.starts_with("app;dur="), "{timing}");
+
+        assert_eq!(server_timing(ServerConfig::default()).await, None);
+    }
+
+    #[test]
+    fn git_routes_match_mounted_patterns() {
+        for route in GIT_ROUTES {
+            assert!(
+                crate::API_ROUTES
+                    .iter()
+                    .any(|(path, _)| *path == route.replace("{*path}", "{path}")),
+                "{route} is not an API route"
+            );
+        }
+    }
+}
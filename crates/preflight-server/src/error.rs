@@ -1,28 +1,121 @@
 use axum::{
-    http::StatusCode,
+    http::{StatusCode, header},
     response::{IntoResponse, Response},
 };
-use serde_json::json;
+use serde::Serialize;
+
+/// Stable machine-readable error codes, sent alongside the message so clients
+/// don't have to match on free text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    ReviewNotFound,
+    ThreadNotFound,
+    RevisionNotFound,
+    FileNotFound,
+    CommentNotFound,
+    ReplyTargetNotFound,
+    LinkAcrossReviews,
+    BadRepoPath,
+    NoRepository,
+    RepositoryMissing,
+    BadRef,
+    GitError,
+    NoChanges,
+    ReviewNotOpen,
+    ThreadLimitReached,
+    VersionConflict,
+    InvalidRequest,
+    InvalidJson,
+    ConfirmationRequired,
+    Unauthorized,
+    PayloadTooLarge,
+    RateLimited,
+    InternalError,
+}
+
+/// JSON body of every error response.
+#[derive(Debug, Serialize)]
+pub struct ErrorBody {
+    pub code: ErrorCode,
+    pub error: String,
+}
 
 /// API error type that converts to appropriate HTTP responses.
 #[derive(Debug)]
 pub enum ApiError {
-    NotFound(String),
-    BadRequest(String),
+    NotFound(ErrorCode, String),
+    BadRequest(ErrorCode, String),
+    Conflict(ErrorCode, String),
+    Unprocessable(ErrorCode, String),
+    Unauthorized(String),
+    PayloadTooLarge(String),
+    /// Too many requests; the client may retry after this many seconds.
+    RateLimited(u64),
     Internal(String),
 }
 
+impl ApiError {
+    /// A 400 for request bodies that fail validation.
+    pub fn invalid_request(msg: impl Into<String>) -> Self {
+        ApiError::BadRequest(ErrorCode::InvalidRequest, msg.into())
+    }
+
+    /// A 400 for git operations on a review created from a diff, which has no
+    /// repository.
+    pub fn no_repository(review_id: uuid::Uuid) -> Self {
+        ApiError::BadRequest(
+            ErrorCode::NoRepository,
+            format!("review {review_id} was created from a diff and has no repository"),
+        )
+    }
+
+    /// A 404 for reading files of a review whose repository has since been
+    /// deleted or moved.
+    pub fn repository_missing(repo_path: &std::path::Path) -> Self {
+        ApiError::NotFound(
+            ErrorCode::RepositoryMissing,
+            format!(
+                "the repository for this review no longer exists at {}",
+                repo_path.display()
+            ),
+        )
+    }
+}
+
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
-        let (status, message) = match self {
-            ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
-            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
-            ApiError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+        let (status, code, error) = match self {
+            ApiError::NotFound(code, msg) => (StatusCode::NOT_FOUND, code, msg),
+            ApiError::BadRequest(code, msg) => (StatusCode::BAD_REQUEST, code, msg),
+            ApiError::Conflict(code, msg) => (StatusCode::CONFLICT, code, msg),
+            ApiError::Unprocessable(code, msg) => (StatusCode::UNPROCESSABLE_ENTITY, code, msg),
+            ApiError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, ErrorCode::Unauthorized, msg),
+            ApiError::PayloadTooLarge(msg) => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                ErrorCode::PayloadTooLarge,
+                msg,
+            ),
+            ApiError::RateLimited(retry_after) => {
+                let body = ErrorBody {
+                    code: ErrorCode::RateLimited,
+                    error: format!("rate limit exceeded; retry in {retry_after}s"),
+                };
+                return (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    [(header::RETRY_AFTER, retry_after.to_string())],
+                    axum::Json(body),
+                )
+                    .into_response();
+            }
+            ApiError::Internal(msg) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorCode::InternalError,
+                msg,
+            ),
         };
 
-        let body = json!({ "error": message });
-
-        (status, axum::Json(body)).into_response()
+        (status, axum::Json(ErrorBody { code, error })).into_response()
     }
 }
 
@@ -30,11 +123,33 @@ impl From<preflight_core::store::StoreError> for ApiError {
     fn from(err: preflight_core::store::StoreError) -> Self {
         use preflight_core::store::StoreError;
         match err {
-            StoreError::ReviewNotFound(id) => ApiError::NotFound(format!("review not found: {id}")),
-            StoreError::ThreadNotFound(id) => ApiError::NotFound(format!("thread not found: {id}")),
-            StoreError::RevisionNotFound(id) => {
-                ApiError::NotFound(format!("revision not found: {id}"))
+            StoreError::ReviewNotFound(id) => {
+                ApiError::NotFound(ErrorCode::ReviewNotFound, format!("review not found: {id}"))
+            }
+            StoreError::ThreadNotFound(id) => {
+                ApiError::NotFound(ErrorCode::ThreadNotFound, format!("thread not found: {id}"))
+            }
+            StoreError::RevisionNotFound(id) => ApiError::NotFound(
+                ErrorCode::RevisionNotFound,
+                format!("revision not found: {id}"),
+            ),
+            StoreError::CommentNotFound { .. } => {
+                ApiError::NotFound(ErrorCode::CommentNotFound, err.to_string())
             }
+            StoreError::ReplyTargetNotFound { .. } => {
+                ApiError::BadRequest(ErrorCode::ReplyTargetNotFound, err.to_string())
+            }
+            StoreError::LinkAcrossReviews { .. } => {
+                ApiError::BadRequest(ErrorCode::LinkAcrossReviews, err.to_string())
+            }
+            StoreError::VersionConflict { .. } => {
+                ApiError::Conflict(ErrorCode::VersionConflict, err.to_string())
+            }
+            StoreError::ThreadLimitReached { open, limit, .. } => ApiError::BadRequest(
+                ErrorCode::ThreadLimitReached,
+                format!("review already has {open} open threads; the limit is {limit}"),
+            ),
+            StoreError::InvalidSnapshot(_) => ApiError::invalid_request(err.to_string()),
             StoreError::PersistenceError(msg) => {
                 ApiError::Internal(format!("persistence error: {msg}"))
             }
@@ -43,26 +158,85 @@ impl From<preflight_core::store::StoreError> for ApiError {
     }
 }
 
+impl From<axum::extract::rejection::JsonRejection> for ApiError {
+    /// Well-formed JSON of the wrong shape is a 422; anything that isn't JSON
+    /// at all is a 400. axum's message names the offending field.
+    fn from(rejection: axum::extract::rejection::JsonRejection) -> Self {
+        use axum::extract::rejection::JsonRejection;
+        let msg = rejection.body_text();
+        match rejection {
+            JsonRejection::JsonDataError(_) => ApiError::Unprocessable(ErrorCode::InvalidJson, msg),
+            _ if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+                ApiError::PayloadTooLarge(msg)
+            }
+            _ => ApiError::BadRequest(ErrorCode::InvalidJson, msg),
+        }
+    }
+}
+
+impl From<preflight_core::git_diff::GitDiffError> for ApiError {
+    fn from(err: preflight_core::git_diff::GitDiffError) -> Self {
+        use preflight_core::git_diff::GitDiffError;
+        match err {
+            GitDiffError::NotAGitRepo => {
+                ApiError::BadRequest(ErrorCode::BadRepoPath, err.to_string())
+            }
+            GitDiffError::BadRef(_) => ApiError::BadRequest(ErrorCode::BadRef, err.to_string()),
+            _ => ApiError::BadRequest(ErrorCode::GitError, err.to_string()),
+        }
+    }
+}
+
+impl From<preflight_core::file_reader::FileReadError> for ApiError {
+    fn from(err: preflight_core::file_reader::FileReadError) -> Self {
+        use preflight_core::file_reader::FileReadError;
+        match err {
+            FileReadError::TooLarge { .. } => ApiError::PayloadTooLarge(err.to_string()),
+            _ => ApiError::NotFound(ErrorCode::FileNotFound, err.to_string()),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use axum::response::IntoResponse;
     use uuid::Uuid;
 
-    #[test]
-    fn not_found_produces_404() {
-        let err = ApiError::NotFound("missing".into());
+    #[tokio::test]
+    async fn not_found_produces_404() {
+        let err = ApiError::NotFound(ErrorCode::ReviewNotFound, "missing".into());
         let response = err.into_response();
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let bytes = http_body_util::BodyExt::collect(response.into_body())
+            .await
+            .unwrap()
+            .to_bytes();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["code"], "review_not_found");
     }
 
     #[test]
     fn bad_request_produces_400() {
-        let err = ApiError::BadRequest("invalid input".into());
+        let err = ApiError::invalid_request("invalid input");
         let response = err.into_response();
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
 
+    #[test]
+    fn unauthorized_produces_401() {
+        let err = ApiError::Unauthorized("missing token".into());
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn payload_too_large_produces_413() {
+        let err = ApiError::PayloadTooLarge("too big".into());
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
     #[test]
     fn internal_produces_500() {
         let err = ApiError::Internal("something broke".into());
@@ -75,7 +249,10 @@ mod tests {
         let id = Uuid::new_v4();
         let store_err = preflight_core::store::StoreError::ReviewNotFound(id);
         let api_err: ApiError = store_err.into();
-        assert!(matches!(api_err, ApiError::NotFound(_)));
+        assert!(matches!(
+            api_err,
+            ApiError::NotFound(ErrorCode::ReviewNotFound, _)
+        ));
     }
 
     #[test]
@@ -83,7 +260,29 @@ mod tests {
         let id = Uuid::new_v4();
         let store_err = preflight_core::store::StoreError::ThreadNotFound(id);
         let api_err: ApiError = store_err.into();
-        assert!(matches!(api_err, ApiError::NotFound(_)));
+        assert!(matches!(
+            api_err,
+            ApiError::NotFound(ErrorCode::ThreadNotFound, _)
+        ));
+    }
+
+    #[test]
+    fn from_store_comment_not_found_differs_from_reply_target() {
+        use preflight_core::store::StoreError;
+        let (thread_id, comment_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let missing = StoreError::CommentNotFound {
+            thread_id,
+            comment_id,
+        };
+        let bad_reply = StoreError::ReplyTargetNotFound {
+            thread_id,
+            comment_id,
+        };
+        assert_ne!(missing.to_string(), bad_reply.to_string());
+        assert!(matches!(
+            missing.into(),
+            ApiError::NotFound(ErrorCode::CommentNotFound, _)
+        ));
     }
 
     #[test]
@@ -93,4 +292,37 @@ mod tests {
         let api_err: ApiError = store_err.into();
         assert!(matches!(api_err, ApiError::Internal(_)));
     }
+
+    #[test]
+    fn body_includes_code_and_message() {
+        let body = ErrorBody {
+            code: ErrorCode::NoChanges,
+            error: "no changes detected since last revision".into(),
+        };
+        assert_eq!(
+            serde_json::to_value(body).unwrap(),
+            serde_json::json!({
+                "code": "no_changes",
+                "error": "no changes detected since last revision"
+            })
+        );
+    }
+
+    #[test]
+    fn from_git_diff_not_a_repo_is_bad_repo_path() {
+        let api_err: ApiError = preflight_core::git_diff::GitDiffError::NotAGitRepo.into();
+        assert!(matches!(
+            api_err,
+            ApiError::BadRequest(ErrorCode::BadRepoPath, _)
+        ));
+    }
+
+    #[test]
+    fn from_git_diff_bad_ref_is_bad_ref() {
+        let api_err: ApiError = preflight_core::git_diff::GitDiffError::BadRef("v9".into()).into();
+        assert!(matches!(
+            api_err,
+            ApiError::BadRequest(ErrorCode::BadRef, _)
+        ));
+    }
 }
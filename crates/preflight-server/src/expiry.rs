@@ -0,0 +1,194 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use preflight_core::review::ReviewStatus;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::state::AppState;
+
+/// How often inactive reviews are looked for.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Periodically expire reviews that have been inactive for longer than
+/// `config.expire_after`. Does nothing when that is unset.
+pub fn spawn(state: AppState) {
+    if state.config.expire_after.is_none() {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            sweep(&state, Utc::now()).await;
+        }
+    });
+}
+
+/// Close (or with `expire_delete`, delete) every review with no activity in
+/// the `expire_after` before `now`, announcing each like a manual change.
+/// Closing only looks at open reviews; deleting removes closed ones too.
+/// A review that fails is logged and left for the next sweep. Returns the ids
+/// of the expired reviews.
+pub async fn sweep(state: &AppState, now: DateTime<Utc>) -> Vec<Uuid> {
+    let Some(expire_after) = state.config.expire_after else {
+        return Vec::new();
+    };
+    let cutoff = now - expire_after;
+    let candidates = if state.config.expire_delete {
+        state.store.list_reviews().await
+    } else {
+        state.store.list_reviews_by_status(ReviewStatus::Open).await
+    };
+
+    let mut expired = Vec::new();
+    for summary in candidates {
+        match expire_if_inactive(state, summary.id, cutoff).await {
+            Ok(true) => expired.push(summary.id),
+            Ok(false) => {}
+            Err(e) => eprintln!("failed to expire review {}: {e:?}", summary.id),
+        }
+    }
+    expired
+}
+
+/// Expire the review if it has had no activity since `cutoff`, returning
+/// whether it was.
+async fn expire_if_inactive(
+    state: &AppState,
+    id: Uuid,
+    cutoff: DateTime<Utc>,
+) -> Result<bool, ApiError> {
+    if last_activity(state, id).await? >= cutoff {
+        return Ok(false);
+    }
+    if state.config.expire_delete {
+        crate::routes::reviews::remove_review(state, id).await?;
+    } else {
+        crate::routes::reviews::set_review_status(state, id, ReviewStatus::Closed).await?;
+    }
+    Ok(true)
+}
+
+/// The latest of the review's own `updated_at`, its revisions, and its
+/// threads and their comments.
+async fn last_activity(state: &AppState, id: Uuid) -> Result<DateTime<Utc>, ApiError> {
+    let review = state.store.get_review(id).await?;
+    let revisions = state.store.get_revisions(id).await?;
+    let threads = state.store.get_threads(id, None).await?;
+    let latest = revisions
+        .iter()
+        .map(|r| r.created_at)
+        .chain(threads.iter().map(|t| t.updated_at))
+        .chain(
+            threads
+                .iter()
+                .flat_map(|t| &t.comments)
+                .map(|c| c.created_at),
+        )
+        .fold(review.updated_at, DateTime::max);
+    Ok(latest)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::{TimeDelta, Utc};
+    use preflight_core::memory_store::MemoryStore;
+    use preflight_core::review::ReviewStatus;
+    use preflight_core::store::CreateReviewInput;
+    use tokio::sync::broadcast;
+
+    use crate::state::{AppState, PresenceTracker, ServerConfig};
+    use crate::ws::WsEventType;
+
+    fn test_state(config: ServerConfig) -> (AppState, broadcast::Receiver<crate::ws::WsEvent>) {
+        let (ws_tx, ws_rx) = broadcast::channel(64);
+        let state = AppState {
+            store: Arc::new(MemoryStore::new()),
+            highlighter: Arc::new(preflight_core::highlight::Highlighter::new()),
+            ws_tx: ws_tx.clone(),
+            agent_status: Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
+            agent_presence: Arc::new(PresenceTracker::agents(ws_tx.clone())),
+            human_presence: Arc::new(PresenceTracker::humans(ws_tx)),
+            config: Arc::new(config),
+            metrics: Arc::new(crate::metrics::Metrics::default()),
+        };
+        (state, ws_rx)
+    }
+
+    async fn create_review(state: &AppState) -> uuid::Uuid {
+        state
+            .store
+            .create_review(CreateReviewInput {
+                title: None,
+                repo_path: Some("/tmp/repo".into()),
+                base_ref: "HEAD".into(),
+                path_prefix: None,
+                extra_repos: Vec::new(),
+                labels: Vec::new(),
+                diff_algorithm: Default::default(),
+            })
+            .await
+            .unwrap()
+            .id
+    }
+
+    #[tokio::test]
+    async fn inactive_review_is_closed_once_expired() {
+        let (state, mut rx) = test_state(ServerConfig {
+            expire_after: Some(TimeDelta::days(30)),
+            ..Default::default()
+        });
+        let id = create_review(&state).await;
+
+        let expired = super::sweep(&state, Utc::now() + TimeDelta::days(29)).await;
+        assert!(expired.is_empty());
+        assert_eq!(
+            state.store.get_review(id).await.unwrap().status,
+            ReviewStatus::Open
+        );
+
+        let expired = super::sweep(&state, Utc::now() + TimeDelta::days(31)).await;
+        assert_eq!(expired, vec![id]);
+        assert_eq!(
+            state.store.get_review(id).await.unwrap().status,
+            ReviewStatus::Closed
+        );
+        let event = rx.try_recv().unwrap();
+        assert_eq!(event.event_type, WsEventType::ReviewStatusChanged);
+        assert_eq!(event.review_id, id.to_string());
+    }
+
+    #[tokio::test]
+    async fn expiry_is_off_by_default() {
+        let (state, _rx) = test_state(ServerConfig::default());
+        let id = create_review(&state).await;
+
+        let expired = super::sweep(&state, Utc::now() + TimeDelta::days(3650)).await;
+        assert!(expired.is_empty());
+        assert_eq!(
+            state.store.get_review(id).await.unwrap().status,
+            ReviewStatus::Open
+        );
+    }
+
+    #[tokio::test]
+    async fn expire_delete_removes_the_review() {
+        let (state, mut rx) = test_state(ServerConfig {
+            expire_after: Some(TimeDelta::days(30)),
+            expire_delete: true,
+            ..Default::default()
+        });
+        let id = create_review(&state).await;
+
+        let expired = super::sweep(&state, Utc::now() + TimeDelta::days(31)).await;
+        assert_eq!(expired, vec![id]);
+        assert!(state.store.get_review(id).await.is_err());
+        assert_eq!(
+            rx.try_recv().unwrap().event_type,
+            WsEventType::ReviewDeleted
+        );
+    }
+}
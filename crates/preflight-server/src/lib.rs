@@ -6,13 +6,22 @@ use axum::{
     response::{Html, IntoResponse, Response},
     routing::get,
 };
+use preflight_core::highlight::Highlighter;
 use preflight_core::store::ReviewStore;
 use rust_embed::RustEmbed;
 
+pub mod access_log;
 pub mod error;
+pub mod expiry;
+pub mod extract;
+pub mod metrics;
+pub mod openapi;
+pub mod rate_limit;
 pub mod routes;
+pub mod sse;
 pub mod state;
 pub mod types;
+pub mod webhooks;
 pub mod ws;
 
 #[derive(RustEmbed)]
@@ -20,17 +29,53 @@ pub mod ws;
 struct Assets;
 
 pub fn app(store: Arc<dyn ReviewStore>) -> Router {
+    app_with_config(store, state::ServerConfig::default())
+}
+
+pub fn app_with_config(store: Arc<dyn ReviewStore>, config: state::ServerConfig) -> Router {
     let (ws_tx, _) = tokio::sync::broadcast::channel(64);
-    let agent_presence = Arc::new(state::AgentPresenceTracker::new(ws_tx.clone()));
+    let agent_presence = Arc::new(state::PresenceTracker::agents(ws_tx.clone()));
+    let human_presence = Arc::new(state::PresenceTracker::humans(ws_tx.clone()));
+    let serve_metrics = config.metrics;
+    let access_log = config.access_log;
+    let rate_limit = config.rate_limit;
+    if !config.webhook_urls.is_empty() {
+        webhooks::spawn(
+            &ws_tx,
+            config.webhook_urls.clone(),
+            config.webhook_secret.clone(),
+        );
+    }
+    let highlighter = match &config.syntax_dir {
+        Some(dir) => Highlighter::with_syntax_dir(dir).unwrap_or_else(|e| {
+            eprintln!(
+                "warning: failed to load syntaxes from '{}': {e}; using the built-in ones",
+                dir.display()
+            );
+            Highlighter::new()
+        }),
+        None => Highlighter::new(),
+    };
     let state = state::AppState {
         store,
-        highlighter: Arc::new(preflight_core::highlight::Highlighter::new()),
+        highlighter: Arc::new(highlighter),
         ws_tx,
         agent_status: Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
         agent_presence,
+        human_presence,
+        config: Arc::new(config),
+        metrics: Arc::new(metrics::Metrics::default()),
     };
-    Router::new()
+    expiry::spawn(state.clone());
+    let mut router = Router::new()
+        .route("/api", get(api_index))
         .route("/api/health", get(health))
+        .route("/api/openapi.json", get(openapi::openapi_json))
+        .route(
+            "/api/validate-repo",
+            axum::routing::post(routes::reviews::validate_repo),
+        )
+        .nest("/api/admin", routes::admin::router(state.clone()))
         .nest("/api/reviews", routes::reviews::router())
         .nest("/api/reviews", routes::files::router())
         .nest("/api/reviews", routes::files::content_router())
@@ -39,9 +84,27 @@ pub fn app(store: Arc<dyn ReviewStore>) -> Router {
         .nest("/api/reviews", routes::threads::review_router())
         .nest("/api/threads", routes::threads::thread_router())
         .nest("/api/threads", routes::comments::router())
+        .nest("/api/reviews", routes::comments::review_router())
         .route("/api/ws", get(ws::ws_handler))
-        .fallback(static_handler)
-        .with_state(state)
+        .route("/api/events", get(sse::sse_handler))
+        .fallback(static_handler);
+    if serve_metrics {
+        router = router.route("/metrics", get(metrics::metrics_handler));
+    }
+    if let Some(per_minute) = rate_limit {
+        router = router.layer(axum::middleware::from_fn_with_state(
+            Arc::new(rate_limit::RateLimiter::new(per_minute)),
+            rate_limit::limit_writes,
+        ));
+    }
+    router = router.layer(axum::middleware::from_fn_with_state(
+        state.clone(),
+        metrics::track_requests,
+    ));
+    if access_log {
+        router = router.layer(axum::middleware::from_fn(access_log::log_requests));
+    }
+    router.with_state(state)
 }
 
 async fn health() -> axum::Json<serde_json::Value> {
@@ -51,6 +114,71 @@ async fn health() -> axum::Json<serde_json::Value> {
     }))
 }
 
+/// Every API route and its methods, for `GET /api`. Kept by hand alongside
+/// the routers above; `/api/openapi.json` has the full schemas.
+const API_ROUTES: &[(&str, &[&str])] = &[
+    ("/api/health", &["GET"]),
+    ("/api/openapi.json", &["GET"]),
+    ("/api/validate-repo", &["POST"]),
+    ("/api/admin/backup", &["GET"]),
+    ("/api/admin/restore", &["POST"]),
+    ("/api/reviews", &["GET", "POST", "DELETE"]),
+    ("/api/reviews/from-diff", &["POST"]),
+    ("/api/reviews/find-or-create", &["POST"]),
+    ("/api/reviews/preview", &["POST"]),
+    ("/api/reviews/{id}", &["GET", "PATCH", "DELETE"]),
+    ("/api/reviews/{id}/full", &["GET"]),
+    ("/api/reviews/{id}/fork", &["POST"]),
+    ("/api/reviews/{id}/status", &["PATCH"]),
+    ("/api/reviews/{id}/decision", &["POST"]),
+    ("/api/reviews/{id}/agent-status", &["GET"]),
+    ("/api/reviews/{id}/human-status", &["GET"]),
+    ("/api/reviews/{id}/agent-presence", &["PUT"]),
+    ("/api/reviews/{id}/agent-seen", &["PUT"]),
+    ("/api/reviews/{id}/mark-seen", &["POST"]),
+    ("/api/reviews/{id}/heartbeat", &["POST"]),
+    ("/api/reviews/{id}/request-revision", &["POST"]),
+    ("/api/reviews/{id}/activity", &["GET"]),
+    ("/api/reviews/{id}/files", &["GET"]),
+    ("/api/reviews/{id}/files/{path}", &["GET"]),
+    ("/api/reviews/{id}/changed-lines/{path}", &["GET"]),
+    ("/api/reviews/{id}/diff", &["GET"]),
+    ("/api/reviews/{id}/content/{path}", &["GET"]),
+    ("/api/reviews/{id}/raw/{path}", &["GET"]),
+    ("/api/reviews/{id}/interdiff/{path}", &["GET"]),
+    ("/api/reviews/{id}/revisions", &["GET", "POST"]),
+    ("/api/reviews/{id}/revisions/{n}/diff", &["GET"]),
+    ("/api/reviews/{id}/drift", &["GET"]),
+    ("/api/reviews/{id}/rebase-preview", &["GET"]),
+    ("/api/reviews/{id}/threads", &["GET", "POST"]),
+    ("/api/reviews/{id}/threads/batch", &["POST"]),
+    ("/api/reviews/{id}/threads/from-hunk", &["POST"]),
+    ("/api/reviews/{id}/submit-drafts", &["POST"]),
+    ("/api/threads", &["GET"]),
+    ("/api/threads/{id}/status", &["PATCH"]),
+    ("/api/threads/{id}/agent-status", &["PUT"]),
+    ("/api/threads/{id}/poke", &["POST"]),
+    ("/api/threads/{id}/link", &["POST"]),
+    ("/api/threads/{id}/pin", &["PATCH"]),
+    ("/api/threads/comments/batch", &["POST"]),
+    ("/api/threads/{id}/comments", &["POST"]),
+    ("/api/threads/{id}/comments/{comment_id}", &["GET"]),
+    ("/api/ws", &["GET"]),
+    ("/api/events", &["GET"]),
+];
+
+async fn api_index() -> axum::Json<serde_json::Value> {
+    let routes: Vec<_> = API_ROUTES
+        .iter()
+        .map(|(path, methods)| serde_json::json!({ "path": path, "methods": methods }))
+        .collect();
+    axum::Json(serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "openapi": "/api/openapi.json",
+        "routes": routes
+    }))
+}
+
 async fn static_handler(uri: axum::http::Uri) -> Response {
     let path = uri.path().trim_start_matches('/');
 
@@ -58,23 +186,74 @@ async fn static_handler(uri: axum::http::Uri) -> Response {
     if !path.is_empty()
         && let Some(file) = Assets::get(path)
     {
-        let mime = mime_guess::from_path(path).first_or_octet_stream();
         return (
             StatusCode::OK,
-            [(header::CONTENT_TYPE, mime.as_ref())],
+            [(header::CONTENT_TYPE, content_type(path))],
             file.data,
         )
             .into_response();
     }
 
     // SPA fallback: serve index.html for any unmatched route
-    match Assets::get("index.html") {
+    index_response(Assets::get("index.html"))
+}
+
+/// Content type for an embedded asset or a raw repository file.
+/// `mime_guess` is overridden for extensions it gets wrong or misses, and
+/// text types get an explicit UTF-8 charset since Vite emits UTF-8 (as
+/// most source files are).
+pub(crate) fn content_type(path: &str) -> String {
+    let extension = std::path::Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default();
+    let mime = match extension {
+        "wasm" => "application/wasm".to_string(),
+        "js" | "mjs" => "text/javascript".to_string(),
+        _ => mime_guess::from_path(path)
+            .first_or_octet_stream()
+            .essence_str()
+            .to_string(),
+    };
+    let is_text = mime.starts_with("text/")
+        || matches!(
+            mime.as_str(),
+            "application/json" | "application/manifest+json" | "image/svg+xml"
+        );
+    if is_text {
+        format!("{mime}; charset=utf-8")
+    } else {
+        mime
+    }
+}
+
+/// Whether the frontend build was embedded into this binary.
+pub fn frontend_bundled() -> bool {
+    Assets::get("index.html").is_some()
+}
+
+/// Shown instead of the UI when the binary was built without `frontend/dist`.
+const MISSING_FRONTEND_PAGE: &str = r#"<!doctype html>
+<html lang="en">
+<head><meta charset="utf-8"><title>Preflight</title></head>
+<body>
+<h1>Preflight</h1>
+<p>The server is running, but this build does not include the web UI.
+Build the frontend (<code>cd frontend &amp;&amp; npm install &amp;&amp; npm run build</code>)
+and rebuild the server to bundle it.</p>
+<ul>
+<li><a href="/api/health">/api/health</a></li>
+<li><a href="/api">/api</a> (route index)</li>
+<li><a href="/api/openapi.json">/api/openapi.json</a> (API description)</li>
+</ul>
+</body>
+</html>
+"#;
+
+fn index_response(index: Option<rust_embed::EmbeddedFile>) -> Response {
+    match index {
         Some(file) => Html(file.data).into_response(),
-        None => (
-            StatusCode::NOT_FOUND,
-            "index.html not found in embedded assets",
-        )
-            .into_response(),
+        None => Html(MISSING_FRONTEND_PAGE).into_response(),
     }
 }
 
@@ -84,11 +263,149 @@ mod tests {
 
     #[tokio::test]
     async fn test_app_builds() {
-        let dir = tempfile::TempDir::new().unwrap();
-        let path = dir.path().join("state.json");
-        let store = preflight_core::json_store::JsonFileStore::new(&path)
-            .await
-            .unwrap();
+        let store = preflight_core::memory_store::MemoryStore::new();
         let _app = app(std::sync::Arc::new(store));
     }
+
+    #[test]
+    fn test_asset_content_types() {
+        assert_eq!(content_type("assets/app.wasm"), "application/wasm");
+        assert_eq!(
+            content_type("assets/index-abc.mjs"),
+            "text/javascript; charset=utf-8"
+        );
+        assert_eq!(
+            content_type("assets/index-abc.js"),
+            "text/javascript; charset=utf-8"
+        );
+        assert_eq!(
+            content_type("assets/index-abc.css"),
+            "text/css; charset=utf-8"
+        );
+        assert_eq!(content_type("favicon.svg"), "image/svg+xml; charset=utf-8");
+        assert_eq!(content_type("logo.png"), "image/png");
+        assert_eq!(content_type("unknown.xyz123"), "application/octet-stream");
+    }
+
+    #[tokio::test]
+    async fn test_spa_fallback_has_utf8_charset() {
+        let store = preflight_core::memory_store::MemoryStore::new();
+        let response = tower::ServiceExt::oneshot(
+            app(Arc::new(store)),
+            axum::http::Request::builder()
+                .uri("/reviews/some-id")
+                .body(axum::body::Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/html; charset=utf-8"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_frontend_serves_fallback_page() {
+        use http_body_util::BodyExt;
+
+        let response = index_response(None);
+        assert_eq!(response.status(), StatusCode::OK);
+        let content_type = response.headers()[header::CONTENT_TYPE].to_str().unwrap();
+        assert!(content_type.starts_with("text/html"), "{content_type}");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("does not include the web UI"));
+        assert!(body.contains("href=\"/api/health\""));
+    }
+
+    async fn get(app: &Router, uri: &str) -> Response {
+        tower::ServiceExt::oneshot(
+            app.clone(),
+            axum::http::Request::builder()
+                .uri(uri)
+                .body(axum::body::Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint_exposes_request_counter() {
+        use http_body_util::BodyExt;
+
+        let store = preflight_core::memory_store::MemoryStore::new();
+        let config = state::ServerConfig {
+            metrics: true,
+            ..Default::default()
+        };
+        let app = app_with_config(Arc::new(store), config);
+
+        assert_eq!(get(&app, "/api/health").await.status(), StatusCode::OK);
+        let response = get(&app, "/metrics").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(
+            response.headers()[header::CONTENT_TYPE]
+                .to_str()
+                .unwrap()
+                .starts_with("text/plain; version=0.0.4")
+        );
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+
+        // Every sample line is `name{labels} value` with a numeric value
+        for line in body.lines().filter(|l| !l.starts_with('#')) {
+            let (series, value) = line.rsplit_once(' ').unwrap();
+            assert!(value.parse::<f64>().is_ok(), "bad sample: {line}");
+            assert!(series.starts_with("preflight_"), "bad sample: {line}");
+        }
+        assert!(
+            body.contains(
+                "preflight_http_requests_total{method=\"GET\",route=\"/api/health\",status=\"200\"} 1\n"
+            ),
+            "{body}"
+        );
+        assert!(body.contains("preflight_store_reviews 0\n"));
+    }
+
+    #[tokio::test]
+    async fn test_api_index_lists_routes() {
+        use http_body_util::BodyExt;
+
+        let store = preflight_core::memory_store::MemoryStore::new();
+        let response = get(&app(Arc::new(store)), "/api").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let routes = json["routes"].as_array().unwrap();
+        let methods = |path: &str| {
+            routes
+                .iter()
+                .find(|r| r["path"] == path)
+                .map(|r| r["methods"].clone())
+        };
+        assert_eq!(
+            methods("/api/reviews"),
+            Some(serde_json::json!(["GET", "POST", "DELETE"]))
+        );
+        assert_eq!(
+            methods("/api/reviews/{id}/threads"),
+            Some(serde_json::json!(["GET", "POST"]))
+        );
+        assert_eq!(methods("/api/threads"), Some(serde_json::json!(["GET"])));
+        assert_eq!(methods("/api/ws"), Some(serde_json::json!(["GET"])));
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint_is_opt_in() {
+        let store = preflight_core::memory_store::MemoryStore::new();
+        let response = get(&app(Arc::new(store)), "/metrics").await;
+        // Falls through to the SPA
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/html; charset=utf-8"
+        );
+    }
 }
@@ -2,63 +2,273 @@ use std::process;
 use std::sync::Arc;
 
 use clap::Parser;
+use preflight_core::file_reader::DEFAULT_MAX_FILE_SIZE;
+use preflight_core::git_diff::DEFAULT_RENAME_THRESHOLD;
 use preflight_core::json_store::JsonFileStore;
+use preflight_core::memory_store::MemoryStore;
+use preflight_core::store::ReviewStore;
 use preflight_mcp::client::PreflightClient;
 use preflight_mcp::server::PreflightMcp;
+use preflight_server::state::{DEFAULT_MAX_COMMENT_BYTES, DEFAULT_MAX_OPEN_THREADS};
 use rmcp::{ServiceExt, transport::stdio};
 use tokio::net::TcpListener;
 
 const STATE_FILE: &str = "preflight-state.json";
+const DEFAULT_HOST: &str = "127.0.0.1";
 
 #[derive(Parser)]
 #[command(
     name = "preflight",
-    about = "Local code review tool for AI-generated changes"
+    about = "Local code review tool for AI-generated changes",
+    args_conflicts_with_subcommands = true
 )]
 struct Cli {
     #[command(subcommand)]
     command: Option<Command>,
+
+    /// Options for `serve`, which runs when no subcommand is given
+    #[command(flatten)]
+    serve: ServeArgs,
 }
 
 #[derive(clap::Subcommand)]
 enum Command {
-    /// Start the web server
-    Serve {
-        /// Port to listen on
+    /// Start the web server (the default command)
+    Serve(ServeArgs),
+    /// Start the MCP stdio server
+    Mcp {
+        /// Port of the running preflight web server to connect to
         #[arg(long, default_value = "3000", env = "PREFLIGHT_PORT")]
         port: u16,
+    },
+    /// Create a review on a running preflight server
+    Review {
+        /// Unified diff to review, read from a file or `-` for stdin
+        #[arg(long, value_name = "FILE")]
+        from_diff: String,
 
-        /// Discard existing state and start fresh
+        /// Title for the new review
         #[arg(long)]
-        fresh: bool,
-    },
-    /// Start the MCP stdio server
-    Mcp {
+        title: Option<String>,
+
         /// Port of the running preflight web server to connect to
         #[arg(long, default_value = "3000", env = "PREFLIGHT_PORT")]
         port: u16,
     },
 }
 
+#[derive(clap::Args)]
+struct ServeArgs {
+    /// Port to listen on
+    #[arg(long, default_value = "3000", env = "PREFLIGHT_PORT")]
+    port: u16,
+
+    /// Address to bind to. Use 0.0.0.0 to accept connections from other machines
+    #[arg(long, default_value = DEFAULT_HOST, env = "PREFLIGHT_HOST", value_parser = parse_host)]
+    host: String,
+
+    /// Discard existing state and start fresh
+    #[arg(long)]
+    fresh: bool,
+
+    /// Keep state in memory only; nothing is read from or written to disk
+    #[arg(long, conflicts_with = "fresh")]
+    memory: bool,
+
+    /// Indent the state file for reading by hand; it is written compact otherwise
+    #[arg(long, conflicts_with = "memory", env = "PREFLIGHT_PRETTY_STATE")]
+    pretty_state: bool,
+
+    /// Largest file (in bytes) the file content endpoint will serve
+    #[arg(
+        long,
+        default_value_t = DEFAULT_MAX_FILE_SIZE,
+        env = "PREFLIGHT_MAX_FILE_SIZE"
+    )]
+    max_file_size: u64,
+
+    /// Serve Prometheus metrics at /metrics
+    #[arg(long, env = "PREFLIGHT_METRICS")]
+    metrics: bool,
+
+    /// Log each request's method, path, status and duration to stderr
+    #[arg(long, env = "PREFLIGHT_ACCESS_LOG")]
+    access_log: bool,
+
+    /// How similar (in percent) a moved file must be to show as a rename
+    #[arg(
+        long,
+        default_value_t = DEFAULT_RENAME_THRESHOLD,
+        env = "PREFLIGHT_RENAME_THRESHOLD",
+        value_parser = clap::value_parser!(u8).range(0..=100)
+    )]
+    rename_threshold: u8,
+
+    /// Require this bearer token on the /api/admin backup and restore routes
+    #[arg(long, env = "PREFLIGHT_ADMIN_TOKEN")]
+    admin_token: Option<String>,
+
+    /// Close a review automatically once all its threads are resolved
+    #[arg(long, env = "PREFLIGHT_AUTO_CLOSE")]
+    auto_close: bool,
+
+    /// Count open agent explanations as open threads in counts and for auto-close
+    #[arg(long, env = "PREFLIGHT_COUNT_EXPLANATIONS")]
+    count_explanations: bool,
+
+    /// Most unresolved threads a single review may hold
+    #[arg(
+        long,
+        default_value_t = DEFAULT_MAX_OPEN_THREADS,
+        env = "PREFLIGHT_MAX_OPEN_THREADS"
+    )]
+    max_open_threads: usize,
+
+    /// Longest comment body accepted, in bytes
+    #[arg(
+        long,
+        value_name = "BYTES",
+        default_value_t = DEFAULT_MAX_COMMENT_BYTES,
+        env = "PREFLIGHT_MAX_COMMENT_SIZE"
+    )]
+    max_comment_size: usize,
+
+    /// Directory of extra .sublime-syntax files for languages not highlighted by default
+    #[arg(long, env = "PREFLIGHT_SYNTAX_DIR")]
+    syntax_dir: Option<std::path::PathBuf>,
+
+    /// POST every review event to this URL; repeat or comma-separate for several
+    #[arg(
+        long = "webhook-url",
+        value_name = "URL",
+        env = "PREFLIGHT_WEBHOOK_URLS",
+        value_delimiter = ','
+    )]
+    webhook_urls: Vec<String>,
+
+    /// Sign webhook bodies with HMAC-SHA256 in the X-Preflight-Signature header
+    #[arg(long, env = "PREFLIGHT_WEBHOOK_SECRET")]
+    webhook_secret: Option<String>,
+
+    /// Close reviews with no activity for this many days
+    #[arg(
+        long,
+        value_name = "DAYS",
+        env = "PREFLIGHT_EXPIRE_AFTER_DAYS",
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    expire_after_days: Option<u32>,
+
+    /// Delete expired reviews instead of closing them
+    #[arg(long, requires = "expire_after_days", env = "PREFLIGHT_EXPIRE_DELETE")]
+    expire_delete: bool,
+
+    /// Store the base version of changed files in each revision so old content survives the repo
+    #[arg(long, env = "PREFLIGHT_SNAPSHOT_BASE")]
+    snapshot_base: bool,
+
+    /// Most threads and comments one client address may create per minute
+    #[arg(long, value_name = "PER_MINUTE", env = "PREFLIGHT_RATE_LIMIT")]
+    rate_limit: Option<u32>,
+}
+
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
 
-    match cli.command.unwrap_or(Command::Serve {
-        port: 3000,
-        fresh: false,
-    }) {
-        Command::Serve { port, fresh } => run_serve(port, fresh).await,
+    match cli.command.unwrap_or(Command::Serve(cli.serve)) {
+        Command::Serve(ServeArgs {
+            port,
+            host,
+            fresh,
+            memory,
+            pretty_state,
+            max_file_size,
+            metrics,
+            access_log,
+            rename_threshold,
+            admin_token,
+            auto_close,
+            count_explanations,
+            max_open_threads,
+            max_comment_size,
+            syntax_dir,
+            webhook_urls,
+            webhook_secret,
+            expire_after_days,
+            expire_delete,
+            snapshot_base,
+            rate_limit,
+        }) => {
+            let config = preflight_server::state::ServerConfig {
+                max_file_size,
+                metrics,
+                access_log,
+                rename_threshold,
+                admin_token,
+                auto_close,
+                max_open_threads,
+                max_comment_bytes: max_comment_size,
+                syntax_dir,
+                webhook_urls,
+                webhook_secret,
+                count_explanations,
+                expire_after: expire_after_days.map(|days| chrono::TimeDelta::days(days.into())),
+                expire_delete,
+                snapshot_base,
+                rate_limit,
+            };
+            run_serve(&host, port, fresh, memory, pretty_state, config).await
+        }
         Command::Mcp { port } => run_mcp(port).await,
+        Command::Review {
+            from_diff,
+            title,
+            port,
+        } => run_review(&from_diff, title, port).await,
     }
 }
 
-async fn run_serve(port: u16, fresh: bool) {
-    let store = if fresh {
-        JsonFileStore::new_empty(STATE_FILE).await
+/// Accept an IP address or a hostname made of dot-separated alphanumeric/hyphen labels.
+fn parse_host(value: &str) -> Result<String, String> {
+    if value.parse::<std::net::IpAddr>().is_ok() {
+        return Ok(value.to_string());
+    }
+    let valid_hostname = !value.is_empty()
+        && value.len() <= 253
+        && value.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        });
+    if valid_hostname {
+        Ok(value.to_string())
+    } else {
+        Err(format!("'{value}' is not a valid IP address or hostname"))
+    }
+}
+
+async fn run_serve(
+    host: &str,
+    port: u16,
+    fresh: bool,
+    memory: bool,
+    pretty_state: bool,
+    config: preflight_server::state::ServerConfig,
+) {
+    let store: Arc<dyn ReviewStore> = if memory {
+        Arc::new(MemoryStore::new())
+    } else if fresh {
+        Arc::new(
+            JsonFileStore::new_empty(STATE_FILE)
+                .await
+                .pretty(pretty_state),
+        )
     } else {
         match JsonFileStore::new(STATE_FILE).await {
-            Ok(store) => store,
+            Ok(store) => Arc::new(store.pretty(pretty_state)),
             Err(e) => {
                 eprintln!("error: failed to load state file '{STATE_FILE}': {e}");
                 eprintln!("hint: run with --fresh to discard existing state and start clean");
@@ -66,17 +276,147 @@ async fn run_serve(port: u16, fresh: bool) {
             }
         }
     };
-    let app = preflight_server::app(Arc::new(store));
-    let addr = format!("127.0.0.1:{port}");
-    let listener = TcpListener::bind(&addr).await.unwrap();
+    let app = preflight_server::app_with_config(store, config);
+    let listener = match TcpListener::bind((host, port)).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("error: failed to bind {host}:{port}: {e}");
+            process::exit(1);
+        }
+    };
+    let addr = listener.local_addr().unwrap();
+    if !preflight_server::frontend_bundled() {
+        eprintln!(
+            "warning: this build does not include the web UI (frontend/dist was empty at build time); only the API is available"
+        );
+    }
     println!("listening on http://{addr}");
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .await
+    .unwrap();
 }
 
 async fn run_mcp(port: u16) {
     let client = PreflightClient::new(port);
+    // Only the first connection is checked; the WebSocket reconnects on its
+    // own if the server goes away later.
+    if let Err(e) = client.check_health().await {
+        eprintln!("error: {e}");
+        process::exit(1);
+    }
     let ws_tx = client.connect_ws().await;
     let server = PreflightMcp::new(client, ws_tx);
-    let service = server.serve(stdio()).await.unwrap();
+    let service = server.clone().serve(stdio()).await.unwrap();
     service.waiting().await.unwrap();
+    server.release_presence().await;
+}
+
+/// Read a unified diff (`-` for stdin) and create a review of it, printing
+/// the new review's URL.
+async fn run_review(from_diff: &str, title: Option<String>, port: u16) {
+    let read = if from_diff == "-" {
+        std::io::read_to_string(std::io::stdin())
+    } else {
+        std::fs::read_to_string(from_diff)
+    };
+    let diff = match read {
+        Ok(diff) => diff,
+        Err(e) => {
+            eprintln!("error: failed to read diff from '{from_diff}': {e}");
+            process::exit(1);
+        }
+    };
+    let client = PreflightClient::new(port);
+    let body = serde_json::json!({ "title": title, "diff": diff });
+    let review: serde_json::Value = match client.post("/api/reviews/from-diff", &body).await {
+        Ok(review) => review,
+        Err(e) => {
+            eprintln!("error: {e}");
+            process::exit(1);
+        }
+    };
+    println!(
+        "http://127.0.0.1:{port}/reviews/{}",
+        review["id"].as_str().unwrap_or_default()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cli_is_well_formed() {
+        use clap::CommandFactory;
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn serve_options_apply_without_subcommand() {
+        let cli = Cli::try_parse_from(["preflight", "--port", "4000", "--memory"]).unwrap();
+        assert!(cli.command.is_none());
+        assert_eq!(cli.serve.port, 4000);
+        assert!(cli.serve.memory);
+
+        // Checked on the definition rather than by setting the variable, so
+        // the test doesn't mutate the process environment
+        use clap::CommandFactory;
+        let command = Cli::command();
+        let arg = command
+            .get_arguments()
+            .find(|a| a.get_id() == "max_open_threads")
+            .unwrap();
+        assert_eq!(
+            arg.get_env(),
+            Some(std::ffi::OsStr::new("PREFLIGHT_MAX_OPEN_THREADS"))
+        );
+    }
+
+    #[test]
+    fn expire_after_days_must_be_positive() {
+        assert!(Cli::try_parse_from(["preflight", "--expire-after-days", "0"]).is_err());
+        let cli = Cli::try_parse_from(["preflight", "--expire-after-days", "1"]).unwrap();
+        assert_eq!(cli.serve.expire_after_days, Some(1));
+    }
+
+    #[test]
+    fn serve_options_before_subcommand_are_rejected() {
+        assert!(Cli::try_parse_from(["preflight", "--port", "4000", "mcp"]).is_err());
+    }
+
+    #[test]
+    fn review_reads_diff_from_stdin_dash() {
+        let cli = Cli::try_parse_from(["preflight", "review", "--from-diff", "-"]).unwrap();
+        let Some(Command::Review {
+            from_diff, title, ..
+        }) = cli.command
+        else {
+            panic!("expected the review subcommand");
+        };
+        assert_eq!(from_diff, "-");
+        assert!(title.is_none());
+    }
+
+    #[test]
+    fn parse_host_accepts_ips_and_hostnames() {
+        for host in [
+            "127.0.0.1",
+            "0.0.0.0",
+            "::1",
+            "localhost",
+            "review-box.local",
+        ] {
+            assert_eq!(parse_host(host).as_deref(), Ok(host));
+        }
+    }
+
+    #[test]
+    fn parse_host_rejects_invalid_values() {
+        for host in ["", "bad host", "-leading.example", "trailing.", "host:3000"] {
+            assert!(parse_host(host).is_err(), "{host} should be rejected");
+        }
+    }
 }
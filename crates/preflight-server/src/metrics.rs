@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::state::AppState;
+
+/// Counters exposed by `GET /metrics` in the Prometheus text format.
+///
+/// Always collected (it's a few map updates per request); the endpoint itself
+/// is only mounted when metrics are enabled in [`crate::state::ServerConfig`].
+#[derive(Default)]
+pub struct Metrics {
+    /// Requests served, keyed by (method, matched route, status).
+    requests: Mutex<BTreeMap<(String, String, u16), u64>>,
+    /// Git subprocess timings, keyed by operation.
+    git: Mutex<BTreeMap<&'static str, GitTiming>>,
+    ws_connections: AtomicI64,
+}
+
+#[derive(Default)]
+struct GitTiming {
+    count: u64,
+    total: Duration,
+}
+
+impl Metrics {
+    pub fn record_request(&self, method: &str, route: &str, status: u16) {
+        let mut requests = self.requests.lock().unwrap();
+        *requests
+            .entry((method.to_string(), route.to_string(), status))
+            .or_default() += 1;
+    }
+
+    /// Run a git operation, recording how long it took under `op`.
+    pub fn time_git<T>(&self, op: &'static str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        let mut git = self.git.lock().unwrap();
+        let timing = git.entry(op).or_default();
+        timing.count += 1;
+        timing.total += start.elapsed();
+        result
+    }
+
+    pub fn ws_connected(&self) {
+        self.ws_connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn ws_disconnected(&self) {
+        self.ws_connections.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Render everything in the Prometheus text exposition format.
+    pub fn render(&self, store_reviews: usize, store_threads: usize) -> String {
+        let mut out = String::new();
+
+        out.push_str("# HELP preflight_http_requests_total HTTP requests served.\n");
+        out.push_str("# TYPE preflight_http_requests_total counter\n");
+        for ((method, route, status), count) in self.requests.lock().unwrap().iter() {
+            let _ = writeln!(
+                out,
+                "preflight_http_requests_total{{method=\"{}\",route=\"{}\",status=\"{status}\"}} {count}",
+                escape_label(method),
+                escape_label(route),
+            );
+        }
+
+        out.push_str("# HELP preflight_git_duration_seconds Time spent in git subprocesses.\n");
+        out.push_str("# TYPE preflight_git_duration_seconds summary\n");
+        for (op, timing) in self.git.lock().unwrap().iter() {
+            let _ = writeln!(
+                out,
+                "preflight_git_duration_seconds_sum{{op=\"{op}\"}} {}",
+                timing.total.as_secs_f64()
+            );
+            let _ = writeln!(
+                out,
+                "preflight_git_duration_seconds_count{{op=\"{op}\"}} {}",
+                timing.count
+            );
+        }
+
+        out.push_str("# HELP preflight_ws_connections Open WebSocket connections.\n");
+        out.push_str("# TYPE preflight_ws_connections gauge\n");
+        let _ = writeln!(
+            out,
+            "preflight_ws_connections {}",
+            self.ws_connections.load(Ordering::Relaxed)
+        );
+
+        out.push_str("# HELP preflight_store_reviews Reviews in the store.\n");
+        out.push_str("# TYPE preflight_store_reviews gauge\n");
+        let _ = writeln!(out, "preflight_store_reviews {store_reviews}");
+        out.push_str("# HELP preflight_store_threads Comment threads in the store.\n");
+        out.push_str("# TYPE preflight_store_threads gauge\n");
+        let _ = writeln!(out, "preflight_store_threads {store_threads}");
+
+        out
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Middleware counting every request by its route pattern (e.g.
+/// `/api/reviews/{id}`), so ids don't blow up the label set.
+pub async fn track_requests(
+    State(state): State<AppState>,
+    matched_path: Option<MatchedPath>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().to_string();
+    let route = matched_path
+        .as_ref()
+        .map(|p| p.as_str())
+        .unwrap_or("unmatched")
+        .to_string();
+    let response = next.run(request).await;
+    state
+        .metrics
+        .record_request(&method, &route, response.status().as_u16());
+    response
+}
+
+pub async fn metrics_handler(State(state): State<AppState>) -> Response {
+    let reviews = state.store.list_reviews().await;
+    let threads = reviews.iter().map(|r| r.thread_count).sum();
+    (
+        [(
+            header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        state.metrics.render(reviews.len(), threads),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_includes_recorded_values() {
+        let metrics = Metrics::default();
+        metrics.record_request("GET", "/api/reviews/{id}", 200);
+        metrics.record_request("GET", "/api/reviews/{id}", 200);
+        metrics.time_git("diff", || ());
+        metrics.ws_connected();
+
+        let text = metrics.render(3, 7);
+        assert!(text.contains(
+            "preflight_http_requests_total{method=\"GET\",route=\"/api/reviews/{id}\",status=\"200\"} 2\n"
+        ));
+        assert!(text.contains("preflight_git_duration_seconds_count{op=\"diff\"} 1\n"));
+        assert!(text.contains("preflight_ws_connections 1\n"));
+        assert!(text.contains("preflight_store_reviews 3\n"));
+        assert!(text.contains("preflight_store_threads 7\n"));
+    }
+
+    #[test]
+    fn escapes_label_values() {
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
@@ -0,0 +1,111 @@
+use axum::Json;
+use utoipa::OpenApi;
+
+use crate::routes::{admin, comments, files, reviews, revisions, threads};
+
+/// OpenAPI description of the `/api` routes, served at `/api/openapi.json`.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Preflight API"),
+    paths(
+        reviews::create_review,
+        reviews::create_review_from_diff,
+        reviews::preview_review,
+        reviews::validate_repo,
+        reviews::fork_review,
+        reviews::find_or_create_review,
+        reviews::list_reviews,
+        reviews::update_review,
+        reviews::get_review,
+        reviews::get_review_full,
+        reviews::get_activity,
+        reviews::update_review_status,
+        reviews::set_review_decision,
+        reviews::request_revision,
+        reviews::update_agent_presence,
+        reviews::agent_heartbeat,
+        reviews::mark_agent_seen,
+        reviews::mark_human_seen,
+        reviews::get_agent_presence,
+        reviews::get_human_presence,
+        reviews::delete_review,
+        reviews::delete_closed_reviews,
+        revisions::create_revision,
+        revisions::list_revisions,
+        revisions::get_drift,
+        revisions::get_rebase_preview,
+        files::list_files,
+        files::get_file_diff,
+        files::get_changed_lines,
+        files::get_review_diff,
+        files::get_revision_diff,
+        files::get_file_interdiff,
+        files::get_file_content,
+        files::get_raw_file,
+        threads::create_thread,
+        threads::create_threads,
+        threads::create_hunk_thread,
+        threads::list_threads,
+        threads::list_all_threads,
+        threads::update_thread_status,
+        threads::set_agent_status,
+        threads::poke_thread,
+        threads::link_threads,
+        threads::pin_thread,
+        comments::get_comment,
+        comments::get_comments_batch,
+        comments::add_comment,
+        comments::submit_drafts,
+        admin::backup,
+        admin::restore,
+    ),
+    tags(
+        (name = "reviews", description = "Review lifecycle"),
+        (name = "revisions", description = "Diff snapshots within a review"),
+        (name = "files", description = "Per-file diffs and content"),
+        (name = "threads", description = "Comment threads and agent status"),
+        (name = "admin", description = "Store backup and restore"),
+    )
+)]
+pub struct ApiDoc;
+
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    async fn test_app() -> axum::Router {
+        let store = preflight_core::memory_store::MemoryStore::new();
+        crate::app(std::sync::Arc::new(store))
+    }
+
+    #[tokio::test]
+    async fn test_openapi_spec_includes_review_and_thread_paths() {
+        let app = test_app().await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/openapi.json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        let paths = json["paths"].as_object().unwrap();
+        assert!(paths.contains_key("/api/reviews"));
+        assert!(paths.contains_key("/api/reviews/{id}/threads"));
+        assert!(paths.contains_key("/api/threads/{id}/comments"));
+        assert!(json["components"]["schemas"]["ReviewResponse"].is_object());
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::{HashMap, VecDeque};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use axum::{
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::error::ApiError;
+
+/// Routes that create threads or comments, the writes an agent stuck in a
+/// loop repeats. Only their POSTs are limited; `/api/threads/comments/batch`
+/// is a POST too but only reads, so it isn't here.
+const LIMITED_ROUTES: &[&str] = &[
+    "/api/reviews/{id}/threads",
+    "/api/reviews/{id}/threads/batch",
+    "/api/reviews/{id}/threads/from-hunk",
+    "/api/threads/{id}/comments",
+];
+
+/// Most clients tracked at once; beyond this the oldest bucket is dropped.
+const MAX_BUCKETS: usize = 4096;
+
+/// A token bucket per client: each holds up to `per_minute` requests and
+/// refills at `per_minute` a minute.
+pub struct RateLimiter {
+    per_minute: u32,
+    buckets: Mutex<Buckets>,
+}
+
+/// Buckets by client, and the clients in the order their buckets were made
+/// so the oldest can be evicted without scanning.
+#[derive(Default)]
+struct Buckets {
+    by_client: HashMap<Client, Bucket>,
+    order: VecDeque<Client>,
+}
+
+/// The address a request came from; `None` when the server wasn't started
+/// with connection info (as in tests).
+type Client = Option<IpAddr>;
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(per_minute: u32) -> Self {
+        Self {
+            per_minute: per_minute.max(1),
+            buckets: Mutex::new(Buckets::default()),
+        }
+    }
+
+    fn per_second(&self) -> f64 {
+        f64::from(self.per_minute) / 60.0
+    }
+
+    /// Take a token for `client`, or return how many seconds until one is
+    /// available.
+    pub fn check(&self, client: Client, now: Instant) -> Result<(), u64> {
+        let capacity = f64::from(self.per_minute);
+        let mut buckets = self.buckets.lock().unwrap();
+        let Buckets { by_client, order } = &mut *buckets;
+        if !by_client.contains_key(&client) {
+            if order.len() >= MAX_BUCKETS
+                && let Some(oldest) = order.pop_front()
+            {
+                by_client.remove(&oldest);
+            }
+            order.push_back(client);
+        }
+        let bucket = by_client.entry(client).or_insert(Bucket {
+            tokens: capacity,
+            refilled_at: now,
+        });
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.per_second()).min(capacity);
+        bucket.refilled_at = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(((1.0 - bucket.tokens) / self.per_second()).ceil() as u64)
+        }
+    }
+}
+
+/// Who a request counts against: the address it came from. The limited
+/// routes take no credentials, so nothing the client sends can pick its
+/// bucket.
+fn client_of(request: &Request) -> Client {
+    request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+}
+
+/// Middleware refusing thread and comment creation with a 429 once a client
+/// exceeds the configured rate. Only mounted when a rate limit is set.
+pub async fn limit_writes(
+    State(limiter): State<Arc<RateLimiter>>,
+    matched_path: Option<MatchedPath>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let limited = request.method() == Method::POST
+        && matched_path.is_some_and(|p| LIMITED_ROUTES.contains(&p.as_str()));
+    if limited && let Err(retry_after) = limiter.check(client_of(&request), Instant::now()) {
+        return ApiError::RateLimited(retry_after).into_response();
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::body::Body;
+    use axum::http::{StatusCode, header};
+    use preflight_core::memory_store::MemoryStore;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::state::ServerConfig;
+
+    #[test]
+    fn bucket_refills_over_time() {
+        let limiter = RateLimiter::new(2);
+        let start = Instant::now();
+        let a = Some(IpAddr::from([10, 0, 0, 1]));
+        let b = Some(IpAddr::from([10, 0, 0, 2]));
+        assert!(limiter.check(a, start).is_ok());
+        assert!(limiter.check(a, start).is_ok());
+        assert_eq!(limiter.check(a, start), Err(30));
+        // Other clients have their own bucket
+        assert!(limiter.check(b, start).is_ok());
+        assert!(limiter.check(a, start + Duration::from_secs(30)).is_ok());
+    }
+
+    #[test]
+    fn oldest_bucket_is_evicted_at_the_cap() {
+        let limiter = RateLimiter::new(1);
+        let now = Instant::now();
+        let first = Some(IpAddr::from([10, 0, 0, 0]));
+        assert!(limiter.check(first, now).is_ok());
+        assert!(limiter.check(first, now).is_err());
+        for i in 1..MAX_BUCKETS as u32 {
+            let _ = limiter.check(Some(IpAddr::from(i.to_be_bytes())), now);
+        }
+        assert_eq!(limiter.buckets.lock().unwrap().by_client.len(), MAX_BUCKETS);
+
+        // One more client pushes out the first, whose bucket starts full again
+        let _ = limiter.check(Some(IpAddr::from([192, 168, 0, 1])), now);
+        let buckets = limiter.buckets.lock().unwrap();
+        assert_eq!(buckets.by_client.len(), MAX_BUCKETS);
+        assert!(!buckets.by_client.contains_key(&first));
+    }
+
+    #[tokio::test]
+    async fn rapid_thread_creation_is_rejected_with_retry_after() {
+        let app = crate::app_with_config(
+            Arc::new(MemoryStore::new()),
+            ServerConfig {
+                rate_limit: Some(3),
+                ..Default::default()
+            },
+        );
+        let response = app
+            .clone()
+            .oneshot(
+                axum::http::Request::builder()
+                    .method("POST")
+                    .uri("/api/reviews/from-diff")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({
+                            "diff": "diff --git a/a.txt b/a.txt\n--- a/a.txt\n+++ b/a.txt\n@@ -1 +1 @@\n-a\n+b\n"
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let bytes = http_body_util::BodyExt::collect(response.into_body())
+            .await
+            .unwrap()
+            .to_bytes();
+        let review: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        let id = review["id"].as_str().unwrap();
+
+        let mut statuses = Vec::new();
+        for i in 0..5 {
+            let response = app
+                .clone()
+                .oneshot(
+                    axum::http::Request::builder()
+                        .method("POST")
+                        .uri(format!("/api/reviews/{id}/threads"))
+                        .header("content-type", "application/json")
+                        // A made-up credential per request must not buy a fresh bucket
+                        .header("authorization", format!("Bearer {i}"))
+                        .body(Body::from(
+                            serde_json::json!({
+                                "file_path": "a.txt",
+                                "line_start": 1,
+                                "line_end": 1,
+                                "origin": "Comment",
+                                "body": "again",
+                                "author_type": "Human"
+                            })
+                            .to_string(),
+                        ))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                assert_eq!(response.headers()[header::RETRY_AFTER], "20");
+            }
+            statuses.push(response.status());
+        }
+        assert_eq!(
+            statuses,
+            [
+                StatusCode::OK,
+                StatusCode::OK,
+                StatusCode::OK,
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::TOO_MANY_REQUESTS,
+            ]
+        );
+
+        // Reads are never limited, even the bulk fetch sent as a POST
+        let response = app
+            .clone()
+            .oneshot(
+                axum::http::Request::builder()
+                    .method("POST")
+                    .uri("/api/threads/comments/batch")
+                    .header("content-type", "application/json")
+                    .body(Body::from("[]"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri(format!("/api/reviews/{id}/threads"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::HashSet;
+
+use axum::{
+    Router,
+    extract::{DefaultBodyLimit, Query, Request, State},
+    http::{StatusCode, header},
+    middleware::{self, Next},
+    response::Response,
+    routing::{get, post},
+};
+use ring::hmac;
+
+use crate::error::{ApiError, ErrorCode};
+use crate::extract::Json;
+use crate::state::AppState;
+use crate::types::RestoreQuery;
+
+/// Largest snapshot `POST /api/admin/restore` accepts. A snapshot holds
+/// every review's diffs, so it easily outgrows the default body limit.
+pub const MAX_SNAPSHOT_BYTES: usize = 512 * 1024 * 1024;
+
+pub fn router(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/backup", get(backup))
+        .route(
+            "/restore",
+            post(restore).layer(DefaultBodyLimit::max(MAX_SNAPSHOT_BYTES)),
+        )
+        // As a layer, the token is checked before a restore body is read
+        .route_layer(middleware::from_fn_with_state(state, require_admin))
+}
+
+/// Reject the request unless it carries `Authorization: Bearer <token>`
+/// matching the configured admin token. Without a configured token the
+/// admin routes are open, like the rest of the API.
+async fn require_admin(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    if let Some(expected) = state.config.admin_token.as_deref() {
+        let given = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+        if !given.is_some_and(|given| tokens_match(given, expected)) {
+            return Err(ApiError::Unauthorized(
+                "missing or wrong admin token".into(),
+            ));
+        }
+    }
+    Ok(next.run(request).await)
+}
+
+/// Compare tokens without the response time revealing how much of a guess
+/// was right: the MACs of both are compared in constant time.
+fn tokens_match(given: &str, expected: &str) -> bool {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, b"preflight admin token");
+    hmac::verify(
+        &key,
+        expected.as_bytes(),
+        hmac::sign(&key, given.as_bytes()).as_ref(),
+    )
+    .is_ok()
+}
+
+/// The whole store as JSON, suitable for `POST /api/admin/restore`.
+#[utoipa::path(
+    get,
+    path = "/api/admin/backup",
+    responses(
+        (status = 200, body = Object, description = "Serialized store state"),
+        (status = 401, description = "Admin token required"),
+    ),
+    tag = "admin"
+)]
+async fn backup(State(state): State<AppState>) -> Result<Json<serde_json::Value>, ApiError> {
+    Ok(Json(state.store.snapshot().await?))
+}
+
+/// Replace the whole store with a snapshot from `GET /api/admin/backup`.
+/// Requires `?confirm=true`. Agent statuses and presence for reviews the
+/// snapshot doesn't have are dropped along with them.
+#[utoipa::path(
+    post,
+    path = "/api/admin/restore",
+    params(RestoreQuery),
+    request_body(content = Object, description = "Snapshot from the backup endpoint"),
+    responses(
+        (status = 204),
+        (status = 400, description = "Not confirmed, or not a valid snapshot"),
+        (status = 401, description = "Admin token required"),
+        (status = 413, description = "Snapshot larger than 512 MiB"),
+    ),
+    tag = "admin"
+)]
+async fn restore(
+    State(state): State<AppState>,
+    Query(query): Query<RestoreQuery>,
+    Json(snapshot): Json<serde_json::Value>,
+) -> Result<StatusCode, ApiError> {
+    if !query.confirm {
+        return Err(ApiError::BadRequest(
+            ErrorCode::ConfirmationRequired,
+            "restoring replaces all reviews; pass ?confirm=true to proceed".into(),
+        ));
+    }
+    state.store.restore(snapshot).await?;
+
+    // Statuses are keyed by thread, and agents re-report them as they work
+    state.agent_status.lock().await.clear();
+    let restored: HashSet<_> = state
+        .store
+        .list_reviews()
+        .await
+        .into_iter()
+        .map(|r| r.id)
+        .collect();
+    state
+        .agent_presence
+        .retain(|id| restored.contains(id))
+        .await;
+    state
+        .human_presence
+        .retain(|id| restored.contains(id))
+        .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    use crate::state::ServerConfig;
+
+    async fn test_app(config: ServerConfig) -> axum::Router {
+        let store = preflight_core::memory_store::MemoryStore::new();
+        crate::app_with_config(Arc::new(store), config)
+    }
+
+    async fn body_json(response: axum::response::Response) -> serde_json::Value {
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    fn setup_test_repo() -> tempfile::TempDir {
+        use std::process::Command;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let p = dir.path();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(p)
+                .output()
+                .unwrap();
+        };
+        git(&["init"]);
+        git(&["config", "user.email", "test@test.com"]);
+        git(&["config", "user.name", "Test"]);
+        std::fs::write(p.join("a.txt"), "one\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "init"]);
+        std::fs::write(p.join("a.txt"), "two\n").unwrap();
+        dir
+    }
+
+    async fn send(
+        app: &axum::Router,
+        method: &str,
+        uri: &str,
+        token: Option<&str>,
+        body: Option<serde_json::Value>,
+    ) -> axum::response::Response {
+        let mut builder = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            builder = builder.header("authorization", format!("Bearer {token}"));
+        }
+        let body = match body {
+            Some(json) => {
+                builder = builder.header("content-type", "application/json");
+                Body::from(json.to_string())
+            }
+            None => Body::empty(),
+        };
+        app.clone()
+            .oneshot(builder.body(body).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_backup_round_trips_through_restore() {
+        let app = test_app(ServerConfig::default()).await;
+        let repo = setup_test_repo();
+        let response = send(
+            &app,
+            "POST",
+            "/api/reviews",
+            None,
+            Some(serde_json::json!({
+                "title": "Keep me",
+                "repo_path": repo.path().to_str().unwrap(),
+                "base_ref": "HEAD"
+            })),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let review_id = body_json(response).await["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let response = send(&app, "GET", "/api/admin/backup", None, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let snapshot = body_json(response).await;
+
+        let uri = format!("/api/reviews/{review_id}");
+        assert_eq!(
+            send(&app, "DELETE", &uri, None, None).await.status(),
+            StatusCode::NO_CONTENT
+        );
+        assert_eq!(
+            send(&app, "GET", &uri, None, None).await.status(),
+            StatusCode::NOT_FOUND
+        );
+
+        // Without confirmation nothing happens
+        let response = send(
+            &app,
+            "POST",
+            "/api/admin/restore",
+            None,
+            Some(snapshot.clone()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(body_json(response).await["code"], "confirmation_required");
+        assert_eq!(
+            send(&app, "GET", &uri, None, None).await.status(),
+            StatusCode::NOT_FOUND
+        );
+
+        let response = send(
+            &app,
+            "POST",
+            "/api/admin/restore?confirm=true",
+            None,
+            Some(snapshot),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let response = send(&app, "GET", &uri, None, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let review = body_json(response).await;
+        assert_eq!(review["title"], "Keep me");
+        assert_eq!(review["revision_count"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_restore_rejects_invalid_snapshot() {
+        let app = test_app(ServerConfig::default()).await;
+        let response = send(
+            &app,
+            "POST",
+            "/api/admin/restore?confirm=true",
+            None,
+            Some(serde_json::json!({ "reviews": [] })),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(body_json(response).await["code"], "invalid_request");
+    }
+
+    #[tokio::test]
+    async fn test_admin_routes_require_configured_token() {
+        let config = ServerConfig {
+            admin_token: Some("s3cret".into()),
+            ..Default::default()
+        };
+        let app = test_app(config).await;
+
+        let response = send(&app, "GET", "/api/admin/backup", None, None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(body_json(response).await["code"], "unauthorized");
+        let response = send(&app, "GET", "/api/admin/backup", Some("wrong"), None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = send(
+            &app,
+            "POST",
+            "/api/admin/restore?confirm=true",
+            None,
+            Some(serde_json::json!({})),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // The token is checked before the body is read, so an unparseable
+        // body from a stranger is refused as unauthorized, not invalid
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/admin/restore?confirm=true")
+                    .header("content-type", "application/json")
+                    .body(Body::from("{not json"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = send(&app, "GET", "/api/admin/backup", Some("s3cret"), None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -1,58 +1,245 @@
-use axum::{
-    Json,
-    extract::{Path, State},
-};
+use std::collections::HashMap;
+
+use axum::extract::{Path, Query, State};
 use chrono::Utc;
 use uuid::Uuid;
 
 use crate::error::ApiError;
+use crate::extract::Json;
 use crate::state::AppState;
-use crate::types::{AddCommentRequest, CommentResponse};
+use crate::types::{
+    AddCommentRequest, CommentResponse, CommentsBatchQuery, RenderFormat, RenderQuery,
+};
 use crate::ws::{WsEvent, WsEventType};
+use preflight_core::review::{Attachment, AuthorType, Comment};
 use preflight_core::store::AddCommentInput;
 
+/// Routes nested under /api/threads
 pub fn router() -> axum::Router<AppState> {
+    use axum::routing::{get, post};
+    axum::Router::new()
+        .route("/comments/batch", post(get_comments_batch))
+        .route("/{id}/comments", post(add_comment))
+        .route("/{id}/comments/{comment_id}", get(get_comment))
+}
+
+/// Routes nested under /api/reviews
+pub fn review_router() -> axum::Router<AppState> {
     use axum::routing::post;
-    axum::Router::new().route("/{id}/comments", post(add_comment))
+    axum::Router::new().route("/{id}/submit-drafts", post(submit_drafts))
+}
+
+pub(crate) fn comment_response(comment: Comment) -> CommentResponse {
+    CommentResponse {
+        id: comment.id,
+        author_type: comment.author_type,
+        body: comment.body,
+        created_at: comment.created_at,
+        draft: comment.draft,
+        in_reply_to: comment.in_reply_to,
+        suggestion: comment.suggestion,
+        attachments: comment.attachments,
+        body_html: None,
+    }
+}
+
+/// Reject a comment body that is blank, or that together with its
+/// attachments is longer than `max_bytes`. Used for replies and for a new
+/// thread's first comment alike.
+pub(crate) fn validate_comment_body(
+    body: &str,
+    attachments: &[Attachment],
+    max_bytes: usize,
+) -> Result<(), String> {
+    if body.trim().is_empty() {
+        return Err("body must not be empty".into());
+    }
+    let attached: usize = attachments.iter().map(Attachment::byte_len).sum();
+    if attached == 0 && body.len() > max_bytes {
+        return Err(format!(
+            "body is {} bytes; the limit is {max_bytes}",
+            body.len()
+        ));
+    }
+    if body.len() + attached > max_bytes {
+        return Err(format!(
+            "body and attachments are {} bytes; the limit is {max_bytes}",
+            body.len() + attached
+        ));
+    }
+    Ok(())
+}
+
+/// Add the renderings of the body asked for with `?render=`.
+pub(crate) fn render_comment(
+    mut response: CommentResponse,
+    render: Option<RenderFormat>,
+) -> CommentResponse {
+    if render == Some(RenderFormat::Html) {
+        response.body_html = Some(preflight_core::markdown::render_html(&response.body));
+    }
+    response
+}
+
+/// Announce a published comment and reset the thread's agent status.
+async fn publish_comment(
+    state: &AppState,
+    review_id: Uuid,
+    thread_id: Uuid,
+    response: &CommentResponse,
+) -> Result<(), ApiError> {
+    // Reset agent status on any new comment:
+    // - Human comment means agent needs to re-acknowledge
+    // - Agent comment means agent finished working
+    state.agent_status.lock().await.remove(&thread_id);
+    if response.author_type == AuthorType::Human {
+        super::reviews::clear_agent_seen(state, review_id).await?;
+    }
+    let (thread_count, open_thread_count) = super::threads::thread_counts(state, review_id).await?;
+    let _ = state.ws_tx.send(WsEvent {
+        event_type: WsEventType::CommentAdded,
+        review_id: review_id.to_string(),
+        payload: serde_json::json!({
+            "thread_id": thread_id.to_string(),
+            "comment": serde_json::to_value(response).unwrap(),
+            "thread_count": thread_count,
+            "open_thread_count": open_thread_count
+        }),
+        timestamp: Utc::now(),
+    });
+    Ok(())
+}
+
+/// Fetch one comment, e.g. to follow a link to it.
+#[utoipa::path(
+    get,
+    path = "/api/threads/{id}/comments/{comment_id}",
+    params(
+        ("id" = Uuid, Path, description = "Thread id"),
+        ("comment_id" = Uuid, Path, description = "Comment id"),
+        RenderQuery,
+    ),
+    responses(
+        (status = 200, body = CommentResponse),
+        (status = 404, description = "Thread not found, or the comment is not in it"),
+    ),
+    tag = "threads"
+)]
+async fn get_comment(
+    State(state): State<AppState>,
+    Path((id, comment_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<RenderQuery>,
+) -> Result<Json<CommentResponse>, ApiError> {
+    let comment = state.store.get_comment(id, comment_id).await?;
+    Ok(Json(render_comment(
+        comment_response(comment),
+        query.render,
+    )))
+}
+
+/// Fetch the comments of several threads in one round-trip, e.g. every
+/// thread on a file, keyed by thread id.
+#[utoipa::path(
+    post,
+    path = "/api/threads/comments/batch",
+    params(CommentsBatchQuery),
+    request_body = Vec<Uuid>,
+    responses(
+        (status = 200, body = HashMap<Uuid, Vec<CommentResponse>>),
+        (status = 404, description = "One of the threads was not found"),
+    ),
+    tag = "threads"
+)]
+async fn get_comments_batch(
+    State(state): State<AppState>,
+    Query(query): Query<CommentsBatchQuery>,
+    Json(thread_ids): Json<Vec<Uuid>>,
+) -> Result<Json<HashMap<Uuid, Vec<CommentResponse>>>, ApiError> {
+    let comments = state.store.get_thread_comments(&thread_ids).await?;
+    Ok(Json(
+        comments
+            .into_iter()
+            .map(|(thread_id, comments)| {
+                let comments = comments
+                    .into_iter()
+                    .filter(|c| query.include_drafts || !c.draft)
+                    .map(|c| render_comment(comment_response(c), query.render))
+                    .collect();
+                (thread_id, comments)
+            })
+            .collect(),
+    ))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/threads/{id}/comments",
+    params(("id" = Uuid, Path, description = "Thread id")),
+    request_body = AddCommentRequest,
+    responses(
+        (status = 200, body = CommentResponse),
+        (status = 400, description = "Empty body, body and attachments over the size limit, or in_reply_to is not a comment in this thread"),
+        (status = 404, description = "Thread not found"),
+    ),
+    tag = "threads"
+)]
 async fn add_comment(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
     Json(request): Json<AddCommentRequest>,
 ) -> Result<Json<CommentResponse>, ApiError> {
+    validate_comment_body(
+        &request.body,
+        &request.attachments,
+        state.config.max_comment_bytes,
+    )
+    .map_err(ApiError::invalid_request)?;
     let comment = state
         .store
         .add_comment(AddCommentInput {
             thread_id: id,
             author_type: request.author_type,
             body: request.body,
+            draft: request.draft,
+            in_reply_to: request.in_reply_to,
+            suggestion: request.suggestion,
+            attachments: request.attachments,
         })
         .await?;
-    // Reset agent status on any new comment:
-    // - Human comment means agent needs to re-acknowledge
-    // - Agent comment means agent finished working
-    state.agent_status.lock().await.remove(&id);
-    let response = CommentResponse {
-        id: comment.id,
-        author_type: comment.author_type,
-        body: comment.body,
-        created_at: comment.created_at,
-    };
-    if let Ok(thread) = state.store.get_thread(id).await {
-        let _ = state.ws_tx.send(WsEvent {
-            event_type: WsEventType::CommentAdded,
-            review_id: thread.review_id.to_string(),
-            payload: serde_json::json!({
-                "thread_id": id.to_string(),
-                "comment": serde_json::to_value(&response).unwrap()
-            }),
-            timestamp: Utc::now(),
-        });
+    let response = comment_response(comment);
+    // Drafts stay quiet until submitted
+    if !response.draft
+        && let Ok(thread) = state.store.get_thread(id).await
+    {
+        publish_comment(&state, thread.review_id, id, &response).await?;
     }
     Ok(Json(response))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/reviews/{id}/submit-drafts",
+    params(("id" = Uuid, Path, description = "Review id")),
+    responses(
+        (status = 200, description = "The comments that were published", body = Vec<CommentResponse>),
+        (status = 404, description = "Review not found"),
+    ),
+    tag = "threads"
+)]
+async fn submit_drafts(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<CommentResponse>>, ApiError> {
+    let published = state.store.publish_drafts(id).await?;
+    let mut responses = Vec::with_capacity(published.len());
+    for (thread_id, comment) in published {
+        let response = comment_response(comment);
+        publish_comment(&state, id, thread_id, &response).await?;
+        responses.push(response);
+    }
+    Ok(Json(responses))
+}
+
 #[cfg(test)]
 mod tests {
     use axum::body::Body;
@@ -61,12 +248,7 @@ mod tests {
     use tower::ServiceExt;
 
     async fn test_app() -> axum::Router {
-        let dir = tempfile::TempDir::new().unwrap();
-        let path = dir.path().join("state.json");
-        let store = preflight_core::json_store::JsonFileStore::new(&path)
-            .await
-            .unwrap();
-        Box::leak(Box::new(dir));
+        let store = preflight_core::memory_store::MemoryStore::new();
         crate::app(std::sync::Arc::new(store))
     }
 
@@ -175,6 +357,89 @@ mod tests {
         json["id"].as_str().unwrap().to_string()
     }
 
+    /// Helper: POST a comment to a thread and return the raw response.
+    async fn post_comment(
+        app: &axum::Router,
+        thread_id: &str,
+        body: serde_json::Value,
+    ) -> axum::response::Response {
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/threads/{thread_id}/comments"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_add_comment_in_reply_to() {
+        let app = test_app().await;
+        let review_id = create_review(&app).await;
+        let thread_id = create_thread(&app, &review_id).await;
+        let other_thread_id = create_thread(&app, &review_id).await;
+
+        let response = post_comment(
+            &app,
+            &thread_id,
+            serde_json::json!({"author_type": "Agent", "body": "Which part?"}),
+        )
+        .await;
+        let question = body_json(response).await;
+        assert!(question.get("in_reply_to").is_none());
+        let question_id = question["id"].as_str().unwrap();
+
+        let response = post_comment(
+            &app,
+            &thread_id,
+            serde_json::json!({"author_type": "Human", "body": "The loop", "in_reply_to": question_id}),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let reply = body_json(response).await;
+        assert_eq!(reply["in_reply_to"], question_id);
+
+        // The reference survives a round trip through the thread listing
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{review_id}/threads"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let threads = body_json(response).await;
+        let thread = threads
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|t| t["id"] == thread_id.as_str())
+            .unwrap();
+        assert_eq!(thread["comments"][2]["in_reply_to"], question_id);
+
+        // Unknown comments and comments from other threads are rejected
+        for target in [uuid::Uuid::new_v4().to_string(), question_id.to_string()] {
+            let thread = if target == question_id {
+                &other_thread_id
+            } else {
+                &thread_id
+            };
+            let response = post_comment(
+                &app,
+                thread,
+                serde_json::json!({"author_type": "Human", "body": "?", "in_reply_to": target}),
+            )
+            .await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+
     #[tokio::test]
     async fn test_add_comment_success() {
         let app = test_app().await;
@@ -208,6 +473,178 @@ mod tests {
         assert!(json["created_at"].is_string());
     }
 
+    #[tokio::test]
+    async fn test_add_comment_rejects_blank_body() {
+        let app = test_app().await;
+        let review_id = create_review(&app).await;
+        let thread_id = create_thread(&app, &review_id).await;
+
+        for body in ["", "   "] {
+            let response = post_comment(
+                &app,
+                &thread_id,
+                serde_json::json!({ "author_type": "Agent", "body": body }),
+            )
+            .await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+            let json = body_json(response).await;
+            assert_eq!(json["code"], "invalid_request");
+            assert_eq!(json["error"], "body must not be empty");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_over_limit_comment_body_is_rejected() {
+        let config = crate::state::ServerConfig {
+            max_comment_bytes: 32,
+            ..Default::default()
+        };
+        let app = crate::app_with_config(
+            std::sync::Arc::new(preflight_core::memory_store::MemoryStore::new()),
+            config,
+        );
+        let review_id = create_review(&app).await;
+        let thread_id = create_thread(&app, &review_id).await;
+        let long_body = "x".repeat(33);
+
+        let response = post_comment(
+            &app,
+            &thread_id,
+            serde_json::json!({ "body": long_body, "author_type": "Agent" }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let json = body_json(response).await;
+        assert_eq!(json["code"], "invalid_request");
+        assert_eq!(json["error"], "body is 33 bytes; the limit is 32");
+
+        // Attachments count toward the limit too
+        let response = post_comment(
+            &app,
+            &thread_id,
+            serde_json::json!({
+                "body": "See below",
+                "author_type": "Agent",
+                "attachments": [{ "type": "CodeBlock", "code": "x".repeat(24) }]
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let json = body_json(response).await;
+        assert_eq!(
+            json["error"],
+            "body and attachments are 33 bytes; the limit is 32"
+        );
+
+        // A new thread's first comment is held to the same limit
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/reviews/{review_id}/threads"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({
+                            "file_path": "file.txt",
+                            "line_start": 1,
+                            "line_end": 1,
+                            "origin": "Comment",
+                            "body": long_body,
+                            "author_type": "Agent"
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = post_comment(
+            &app,
+            &thread_id,
+            serde_json::json!({ "body": "x".repeat(32), "author_type": "Agent" }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_add_comment_with_suggestion_round_trips() {
+        let app = test_app().await;
+        let review_id = create_review(&app).await;
+        let thread_id = create_thread(&app, &review_id).await;
+
+        let response = post_comment(
+            &app,
+            &thread_id,
+            serde_json::json!({
+                "author_type": "Human",
+                "body": "Simpler this way",
+                "suggestion": "line1\nline3\n"
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert_eq!(json["suggestion"], "line1\nline3\n");
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{review_id}/threads"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let threads = body_json(response).await;
+        let comments = threads[0]["comments"].as_array().unwrap();
+        // Plain comments leave the field out entirely
+        assert!(comments[0].get("suggestion").is_none());
+        assert_eq!(comments[1]["suggestion"], "line1\nline3\n");
+    }
+
+    #[tokio::test]
+    async fn test_add_comment_with_code_block_attachment_round_trips() {
+        let app = test_app().await;
+        let review_id = create_review(&app).await;
+        let thread_id = create_thread(&app, &review_id).await;
+        let attachment = serde_json::json!({
+            "type": "CodeBlock",
+            "language": "rust",
+            "code": "fn main() {}"
+        });
+
+        let response = post_comment(
+            &app,
+            &thread_id,
+            serde_json::json!({
+                "author_type": "Agent",
+                "body": "Try this:\n\nfn main() {}",
+                "attachments": [attachment]
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_json(response).await["attachments"][0], attachment);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{review_id}/threads"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let threads = body_json(response).await;
+        let comments = threads[0]["comments"].as_array().unwrap();
+        assert!(comments[0].get("attachments").is_none());
+        assert_eq!(comments[1]["attachments"], serde_json::json!([attachment]));
+    }
+
     #[tokio::test]
     async fn test_human_comment_resets_agent_status() {
         let app = test_app().await;
@@ -344,5 +781,132 @@ mod tests {
             .unwrap();
 
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(response).await["code"], "thread_not_found");
+    }
+
+    #[tokio::test]
+    async fn test_get_comment() {
+        let app = test_app().await;
+        let review_id = create_review(&app).await;
+        let thread_id = create_thread(&app, &review_id).await;
+        let other_thread_id = create_thread(&app, &review_id).await;
+
+        let response = post_comment(
+            &app,
+            &thread_id,
+            serde_json::json!({"author_type": "Agent", "body": "Fixed in the next revision"}),
+        )
+        .await;
+        let posted = body_json(response).await;
+        let comment_id = posted["id"].as_str().unwrap();
+
+        let get = |thread_id: String| {
+            app.clone().oneshot(
+                Request::builder()
+                    .uri(format!("/api/threads/{thread_id}/comments/{comment_id}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+
+        let response = get(thread_id).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert_eq!(json, posted);
+
+        // The comment exists, but not in this thread
+        let response = get(other_thread_id).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let json = body_json(response).await;
+        assert_eq!(json["code"], "comment_not_found");
+    }
+
+    #[tokio::test]
+    async fn test_get_comments_batch() {
+        let app = test_app().await;
+        let review_id = create_review(&app).await;
+        let first = create_thread(&app, &review_id).await;
+        let second = create_thread(&app, &review_id).await;
+        let response = post_comment(
+            &app,
+            &second,
+            serde_json::json!({ "body": "done", "author_type": "Agent" }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/threads/comments/batch")
+                    .header("content-type", "application/json")
+                    .body(Body::from(serde_json::json!([first, second]).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert_eq!(json.as_object().unwrap().len(), 2);
+        assert_eq!(json[&first].as_array().unwrap().len(), 1);
+        assert_eq!(json[&first][0]["body"], "initial comment");
+        let second = json[&second].as_array().unwrap();
+        assert_eq!(second.len(), 2);
+        assert_eq!(second[1]["body"], "done");
+    }
+
+    #[tokio::test]
+    async fn test_comment_body_rendered_as_html_on_request() {
+        let app = test_app().await;
+        let review_id = create_review(&app).await;
+        let thread_id = create_thread(&app, &review_id).await;
+
+        let response = post_comment(
+            &app,
+            &thread_id,
+            serde_json::json!({
+                "author_type": "Agent",
+                "body": "Renamed to `parse` **everywhere**\n\n<script>alert(1)</script>"
+            }),
+        )
+        .await;
+        let posted = body_json(response).await;
+        assert!(posted.get("body_html").is_none());
+        let comment_id = posted["id"].as_str().unwrap();
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!(
+                        "/api/threads/{thread_id}/comments/{comment_id}?render=html"
+                    ))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        let html = json["body_html"].as_str().unwrap();
+        assert!(
+            html.starts_with("<p>Renamed to <code>parse</code> <strong>everywhere</strong></p>")
+        );
+        assert!(!html.contains("<script"));
+        assert!(html.contains("&lt;script&gt;"));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{review_id}/threads?render=html"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let threads = body_json(response).await;
+        let comments = threads[0]["comments"].as_array().unwrap();
+        assert!(comments.iter().all(|c| c["body_html"].is_string()));
     }
 }
@@ -1,37 +1,68 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 
 use axum::{
     Json,
     extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
 };
 use serde::Deserialize;
 use uuid::Uuid;
 
-use crate::error::ApiError;
+use crate::error::{ApiError, ErrorCode};
 use crate::state::AppState;
 use crate::types::{
-    FileContentLine, FileContentResponse, FileDiffResponse, FileListEntry, InterdiffQuery,
-    RevisionQuery,
+    ChangedLine, FileContentLine, FileContentResponse, FileDiffQuery, FileDiffResponse,
+    FileListEntry, FileListQuery, FileSort, HunkResponse, InterdiffQuery, ReviewDiffQuery,
 };
+use preflight_core::diff::FileDiff;
 use preflight_core::diff::{DiffLine, FileStatus, Hunk, LineKind};
 use preflight_core::file_reader;
-use preflight_core::review::{ThreadOrigin, ThreadStatus};
+use preflight_core::git_diff;
+use preflight_core::renames;
+use preflight_core::review::{CommentThread, Review, Revision};
+use preflight_core::trivial;
+use preflight_core::whitespace;
+
+/// Response header naming the revision a file route served, so a client that
+/// asked for the latest knows which one that was.
+pub const REVISION_HEADER: &str = "x-preflight-revision";
+
+fn revision_header(revision: &Revision) -> [(&'static str, String); 1] {
+    [(REVISION_HEADER, revision.revision_number.to_string())]
+}
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
 struct ContentQuery {
     version: Option<String>,
+    /// Highlight as this language (e.g. `Dockerfile`, `python`) instead of
+    /// guessing from the file extension.
+    language: Option<String>,
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+struct RawQuery {
+    /// `old` for the base version; defaults to `new`.
+    version: Option<String>,
 }
 
 pub fn router() -> axum::Router<AppState> {
     use axum::routing::get;
     axum::Router::new()
         .route("/{id}/files", get(list_files))
+        .route("/{id}/diff", get(get_review_diff))
+        .route("/{id}/revisions/{n}/diff", get(get_revision_diff))
         .route("/{id}/files/{*path}", get(get_file_diff))
+        .route("/{id}/changed-lines/{*path}", get(get_changed_lines))
 }
 
 pub fn content_router() -> axum::Router<AppState> {
     use axum::routing::get;
-    axum::Router::new().route("/{id}/content/{*path}", get(get_file_content))
+    axum::Router::new()
+        .route("/{id}/content/{*path}", get(get_file_content))
+        .route("/{id}/raw/{*path}", get(get_raw_file))
 }
 
 pub fn interdiff_router() -> axum::Router<AppState> {
@@ -39,17 +70,99 @@ pub fn interdiff_router() -> axum::Router<AppState> {
     axum::Router::new().route("/{id}/interdiff/{*path}", get(get_file_interdiff))
 }
 
+/// The repo-relative path a wildcard route names, so `./src/main.rs`,
+/// `/src/main.rs` and `src//main.rs` all match `src/main.rs`. Axum has
+/// already percent-decoded it. `..` is left for the file reader to reject.
+fn normalize_file_path(path: &str) -> String {
+    path.split('/')
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/reviews/{id}/files",
+    params(("id" = Uuid, Path, description = "Review id"), FileListQuery),
+    responses(
+        (status = 200, body = Vec<FileListEntry>, headers(
+            ("x-preflight-revision" = u32, description = "Revision the list was built from")
+        )),
+        (status = 404, description = "Review or revision not found"),
+    ),
+    tag = "files"
+)]
 async fn list_files(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-    Query(query): Query<RevisionQuery>,
-) -> Result<Json<Vec<FileListEntry>>, ApiError> {
+    Query(query): Query<FileListQuery>,
+) -> Result<impl IntoResponse, ApiError> {
     let revision = match query.revision {
         Some(n) => state.store.get_revision(id, n).await?,
         None => state.store.get_latest_revision(id).await?,
     };
     let threads = state.store.get_threads(id, None).await?;
-    let entries: Vec<FileListEntry> = revision
+    let renames = renames::build_rename_map(&state.store.get_revisions(id).await?);
+    let entries = file_list(
+        &revision,
+        &threads,
+        &renames,
+        state.config.count_explanations,
+    );
+    let entries = sort_files(entries, &revision.files, query.sort.unwrap_or_default());
+    Ok((revision_header(&revision), Json(entries)))
+}
+
+/// Order a file list built from `files` (entry for entry), breaking ties by
+/// path so the order is stable.
+fn sort_files(
+    entries: Vec<FileListEntry>,
+    files: &[FileDiff],
+    sort: FileSort,
+) -> Vec<FileListEntry> {
+    let mut pairs: Vec<_> = entries.into_iter().zip(files).collect();
+    pairs.sort_by(|(a, a_diff), (b, b_diff)| {
+        let order = match sort {
+            FileSort::Path => std::cmp::Ordering::Equal,
+            FileSort::Status => status_rank(&a.status).cmp(&status_rank(&b.status)),
+            FileSort::Size => changed_line_count(b_diff).cmp(&changed_line_count(a_diff)),
+            FileSort::Threads => b.thread_count.cmp(&a.thread_count),
+        };
+        order.then_with(|| a.path.cmp(&b.path))
+    });
+    pairs.into_iter().map(|(entry, _)| entry).collect()
+}
+
+/// Position of a file status when sorting by status.
+fn status_rank(status: &FileStatus) -> u8 {
+    match status {
+        FileStatus::Added => 0,
+        FileStatus::Modified => 1,
+        FileStatus::Renamed => 2,
+        FileStatus::Deleted => 3,
+        _ => 4,
+    }
+}
+
+/// Added plus removed lines across a file's hunks.
+fn changed_line_count(file: &FileDiff) -> usize {
+    file.hunks
+        .iter()
+        .flat_map(|h| &h.lines)
+        .filter(|l| l.kind != LineKind::Context)
+        .count()
+}
+
+/// The files in a revision with their thread counts, following renames so
+/// threads on a file's old path still count, and whether each is trivial.
+/// `count_explanations` is the server's [`CommentThread::counts_as_open`] setting.
+pub(crate) fn file_list(
+    revision: &Revision,
+    threads: &[CommentThread],
+    renames: &HashMap<String, String>,
+    count_explanations: bool,
+) -> Vec<FileListEntry> {
+    revision
         .files
         .iter()
         .map(|f| {
@@ -57,60 +170,353 @@ async fn list_files(
                 .new_path
                 .clone()
                 .unwrap_or_else(|| f.old_path.clone().unwrap_or_default());
-            let file_threads: Vec<_> = threads.iter().filter(|t| t.file_path == path).collect();
+            let file_threads: Vec<_> = threads
+                .iter()
+                .filter(|t| renames::resolve_path(renames, &t.file_path) == path)
+                .collect();
             let thread_count = file_threads.len();
             let open_thread_count = file_threads
                 .iter()
-                .filter(|t| {
-                    t.status == ThreadStatus::Open && t.origin != ThreadOrigin::AgentExplanation
-                })
+                .filter(|t| t.counts_as_open(count_explanations))
                 .count();
             FileListEntry {
                 path,
                 status: f.status.clone(),
                 thread_count,
                 open_thread_count,
+                trivial: trivial::is_trivial(f),
             }
         })
-        .collect();
-    Ok(Json(entries))
+        .collect()
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/reviews/{id}/files/{path}",
+    params(("id" = Uuid, Path, description = "Review id"), ("path" = String, Path, description = "Repo-relative file path"), FileDiffQuery),
+    responses(
+        (status = 200, body = FileDiffResponse, headers(
+            ("x-preflight-revision" = u32, description = "Revision the diff was taken from")
+        )),
+        (status = 400, description = "since is past the latest revision"),
+        (status = 404, description = "Review, revision or file not found"),
+    ),
+    tag = "files"
+)]
 async fn get_file_diff(
     State(state): State<AppState>,
     Path((id, file_path)): Path<(Uuid, String)>,
-    Query(query): Query<RevisionQuery>,
-) -> Result<Json<FileDiffResponse>, ApiError> {
+    Query(query): Query<FileDiffQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let file_path = normalize_file_path(&file_path);
     let revision = match query.revision {
         Some(n) => state.store.get_revision(id, n).await?,
         None => state.store.get_latest_revision(id).await?,
     };
-    let file_diff = revision
-        .files
-        .iter()
-        .find(|f| {
+    let find_file = |wanted: &str| {
+        revision.files.iter().find(|f| {
             let effective_path = f
                 .new_path
                 .as_deref()
                 .or(f.old_path.as_deref())
                 .unwrap_or_default();
-            effective_path == file_path
+            effective_path == wanted
+        })
+    };
+    let file_diff = match find_file(&file_path) {
+        Some(f) => f,
+        // Follow the file if it was renamed in a later revision
+        None => {
+            let renames = renames::build_rename_map(&state.store.get_revisions(id).await?);
+            find_file(renames::resolve_path(&renames, &file_path)).ok_or_else(|| {
+                ApiError::NotFound(
+                    ErrorCode::FileNotFound,
+                    format!("file not found: {file_path}"),
+                )
+            })?
+        }
+    };
+
+    let review = state.store.get_review(id).await?;
+
+    // With `since`, show only what changed after that revision
+    let source_hunks = match query.since {
+        Some(since) => {
+            let latest = match query.revision {
+                Some(_) => state.store.get_latest_revision(id).await?.revision_number,
+                None => revision.revision_number,
+            };
+            if since > latest {
+                return Err(ApiError::invalid_request(format!(
+                    "since ({since}) is past the latest revision ({latest})"
+                )));
+            }
+            let since_revision = state.store.get_revision(id, since).await?;
+            let from_hunks = since_revision
+                .files
+                .iter()
+                .find(|f| {
+                    let p = f
+                        .new_path
+                        .as_deref()
+                        .or(f.old_path.as_deref())
+                        .unwrap_or_default();
+                    p == file_path
+                })
+                .map(|f| f.hunks.as_slice())
+                .unwrap_or(&[]);
+            let base_content = review
+                .locate(file_diff.old_path.as_deref().unwrap_or(&file_path))
+                .and_then(|base| {
+                    state
+                        .metrics
+                        .time_git("show", || {
+                            file_reader::read_old_file(
+                                std::path::Path::new(base.repo_path),
+                                base.path,
+                                &git_diff::old_content_ref(base.base_ref),
+                                state.config.max_file_size,
+                            )
+                        })
+                        .ok()
+                })
+                .unwrap_or_default();
+            preflight_core::interdiff::compute_interdiff(
+                &base_content,
+                from_hunks,
+                &file_diff.hunks,
+            )
+        }
+        None => file_diff.hunks.clone(),
+    };
+
+    let response = file_diff_response(
+        &state,
+        &review,
+        file_diff,
+        source_hunks,
+        true,
+        query.since.is_none(),
+        query.ignore_whitespace,
+    );
+    Ok((revision_header(&revision), Json(response)))
+}
+
+/// Only the added lines of a file in the latest revision, for feeding an LLM
+/// the new code without diff context or removed lines.
+#[utoipa::path(
+    get,
+    path = "/api/reviews/{id}/changed-lines/{path}",
+    params(("id" = Uuid, Path, description = "Review id"), ("path" = String, Path, description = "Repo-relative file path")),
+    responses(
+        (status = 200, body = Vec<ChangedLine>),
+        (status = 404, description = "Review or file not found"),
+    ),
+    tag = "files"
+)]
+async fn get_changed_lines(
+    State(state): State<AppState>,
+    Path((id, file_path)): Path<(Uuid, String)>,
+) -> Result<Json<Vec<ChangedLine>>, ApiError> {
+    let file_path = normalize_file_path(&file_path);
+    let revision = state.store.get_latest_revision(id).await?;
+    let file = revision
+        .files
+        .iter()
+        .find(|f| f.new_path.as_deref().or(f.old_path.as_deref()) == Some(file_path.as_str()))
+        .ok_or_else(|| {
+            ApiError::NotFound(
+                ErrorCode::FileNotFound,
+                format!("file not found: {file_path}"),
+            )
+        })?;
+    let lines = file
+        .hunks
+        .iter()
+        .flat_map(|hunk| &hunk.lines)
+        .filter(|line| line.kind == LineKind::Added)
+        .filter_map(|line| {
+            Some(ChangedLine {
+                line_no: line.new_line_no?,
+                content: line.content.clone(),
+            })
+        })
+        .collect();
+    Ok(Json(lines))
+}
+
+/// Most diff lines `GET /api/reviews/{id}/diff` returns across all files.
+const MAX_REVIEW_DIFF_LINES: usize = 20_000;
+
+/// Every file of a revision in one response, in file list order. The file
+/// that takes the total past `MAX_REVIEW_DIFF_LINES` diff lines, and every
+/// file after it, comes back without hunks and with `truncated` set; fetch
+/// those one at a time.
+#[utoipa::path(
+    get,
+    path = "/api/reviews/{id}/diff",
+    params(("id" = Uuid, Path, description = "Review id"), ReviewDiffQuery),
+    responses(
+        (status = 200, body = Vec<FileDiffResponse>),
+        (status = 404, description = "Review or revision not found"),
+    ),
+    tag = "files"
+)]
+async fn get_review_diff(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ReviewDiffQuery>,
+) -> Result<Json<Vec<FileDiffResponse>>, ApiError> {
+    let revision = match query.revision {
+        Some(n) => state.store.get_revision(id, n).await?,
+        None => state.store.get_latest_revision(id).await?,
+    };
+    let review = state.store.get_review(id).await?;
+    Ok(Json(revision_diff(
+        &state,
+        &review,
+        &revision,
+        query.highlight,
+        query.ignore_whitespace,
+    )))
+}
+
+/// The complete diff of revision `n`, highlighted, for showing an earlier
+/// revision in full. Truncated like `GET /api/reviews/{id}/diff`.
+#[utoipa::path(
+    get,
+    path = "/api/reviews/{id}/revisions/{n}/diff",
+    params(("id" = Uuid, Path, description = "Review id"), ("n" = u32, Path, description = "Revision number")),
+    responses(
+        (status = 200, body = Vec<FileDiffResponse>),
+        (status = 404, description = "Review or revision not found"),
+    ),
+    tag = "files"
+)]
+async fn get_revision_diff(
+    State(state): State<AppState>,
+    Path((id, n)): Path<(Uuid, u32)>,
+) -> Result<Json<Vec<FileDiffResponse>>, ApiError> {
+    let revision = state.store.get_revision(id, n).await?;
+    let review = state.store.get_review(id).await?;
+    Ok(Json(revision_diff(&state, &review, &revision, true, false)))
+}
+
+/// Every file of `revision`, leaving out hunks from the file that takes the
+/// total past `MAX_REVIEW_DIFF_LINES` onwards.
+fn revision_diff(
+    state: &AppState,
+    review: &Review,
+    revision: &Revision,
+    highlight: bool,
+    ignore_whitespace: bool,
+) -> Vec<FileDiffResponse> {
+    let mut total_lines = 0;
+    let mut truncating = false;
+    revision
+        .files
+        .iter()
+        .map(|file_diff| {
+            let lines: usize = file_diff.hunks.iter().map(|h| h.lines.len()).sum();
+            total_lines += lines;
+            truncating |= total_lines > MAX_REVIEW_DIFF_LINES;
+            let hunks = if truncating {
+                Vec::new()
+            } else {
+                file_diff.hunks.clone()
+            };
+            let mut response = file_diff_response(
+                state,
+                review,
+                file_diff,
+                hunks,
+                highlight,
+                true,
+                ignore_whitespace,
+            );
+            response.truncated = truncating;
+            response
         })
-        .ok_or_else(|| ApiError::NotFound(format!("file not found: {file_path}")))?;
+        .collect()
+}
 
+/// Build the response for one file's hunks. With `highlight`, every line is
+/// highlighted, and with `read_full` as well the old and new files are read
+/// from the repo so lines are highlighted with their full preceding context.
+fn file_diff_response(
+    state: &AppState,
+    review: &Review,
+    file_diff: &FileDiff,
+    source_hunks: Vec<Hunk>,
+    highlight: bool,
+    read_full: bool,
+    ignore_whitespace: bool,
+) -> FileDiffResponse {
     let path = file_diff
         .new_path
         .clone()
         .unwrap_or_else(|| file_diff.old_path.clone().unwrap_or_default());
 
-    // Reconstruct full file contents and highlight them
-    let (old_content, new_content) = reconstruct_file_contents(&file_diff.hunks);
-    let old_highlighted = state.highlighter.highlight_file(&old_content, &path);
-    let new_highlighted = state.highlighter.highlight_file(&new_content, &path);
+    // Highlight the real files where they still match the diff, so every line
+    // is highlighted with its full preceding context. Otherwise fall back to
+    // the sparse contents the hunks provide.
+    let (mut old_content, mut new_content) = reconstruct_file_contents(&source_hunks);
+    if highlight && read_full {
+        let (old_full, new_full) = read_full_contents(state, review, file_diff);
+        if let Some(full) = old_full.filter(|c| matches_hunks(c, &source_hunks, true)) {
+            old_content = full;
+        }
+        if let Some(full) = new_full.filter(|c| matches_hunks(c, &source_hunks, false)) {
+            new_content = full;
+        }
+    }
+    let language = state
+        .highlighter
+        .detect_language(&new_content, &path, None)
+        .map(str::to_string);
+    let mut hunks = if highlight {
+        // A rename can change the extension, so each side picks its own syntax
+        let old_path = file_diff.old_path.as_deref().unwrap_or(&path);
+        highlight_hunks(
+            state,
+            &source_hunks,
+            (&old_content, old_path),
+            (&new_content, &path),
+        )
+    } else {
+        source_hunks
+    };
+    whitespace::mark_whitespace_only(&mut hunks);
+    if ignore_whitespace {
+        hunks = whitespace::suppress_whitespace_only(hunks);
+    }
+
+    FileDiffResponse {
+        path,
+        old_path: file_diff.old_path.clone(),
+        status: file_diff.status.clone(),
+        language,
+        hunks: hunks.into_iter().map(HunkResponse::from).collect(),
+        mode_change: file_diff.mode_change.clone(),
+        truncated: false,
+    }
+}
 
-    // Map over hunks and populate highlighted field on each line
-    let hunks: Vec<Hunk> = file_diff
-        .hunks
+/// Copy `hunks` with every line highlighted from the file contents it came
+/// from, each side given as `(content, path)`.
+fn highlight_hunks(
+    state: &AppState,
+    hunks: &[Hunk],
+    (old_content, old_path): (&str, &str),
+    (new_content, new_path): (&str, &str),
+) -> Vec<Hunk> {
+    let old_highlighted = state
+        .highlighter
+        .highlight_file(old_content, old_path, None);
+    let new_highlighted = state
+        .highlighter
+        .highlight_file(new_content, new_path, None);
+    hunks
         .iter()
         .map(|hunk| Hunk {
             old_start: hunk.old_start,
@@ -140,25 +546,37 @@ async fn get_file_diff(
                         old_line_no: line.old_line_no,
                         new_line_no: line.new_line_no,
                         highlighted,
+                        whitespace_only: false,
                     }
                 })
                 .collect(),
         })
-        .collect();
-
-    Ok(Json(FileDiffResponse {
-        path,
-        old_path: file_diff.old_path.clone(),
-        status: file_diff.status.clone(),
-        hunks,
-    }))
+        .collect()
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/reviews/{id}/interdiff/{path}",
+    params(("id" = Uuid, Path, description = "Review id"), ("path" = String, Path, description = "Repo-relative file path"), InterdiffQuery),
+    responses(
+        (status = 200, body = FileDiffResponse),
+        (status = 400, description = "from is not earlier than to, or the file is missing from one of the revisions"),
+        (status = 404, description = "Review or revision not found, or the file is in neither revision"),
+    ),
+    tag = "files"
+)]
 async fn get_file_interdiff(
     State(state): State<AppState>,
     Path((id, file_path)): Path<(Uuid, String)>,
     Query(query): Query<InterdiffQuery>,
 ) -> Result<Json<FileDiffResponse>, ApiError> {
+    let file_path = normalize_file_path(&file_path);
+    if query.from >= query.to {
+        return Err(ApiError::invalid_request(format!(
+            "from ({}) must be an earlier revision than to ({})",
+            query.from, query.to
+        )));
+    }
     let review = state.store.get_review(id).await?;
     let from_revision = state.store.get_revision(id, query.from).await?;
     let to_revision = state.store.get_revision(id, query.to).await?;
@@ -181,90 +599,189 @@ async fn get_file_interdiff(
         p == file_path
     });
 
-    if to_file.is_none() && from_file.is_none() {
-        return Err(ApiError::NotFound(format!("file not found: {file_path}")));
-    }
-
-    let from_hunks = from_file.map(|f| f.hunks.as_slice()).unwrap_or(&[]);
-    let to_hunks = to_file.map(|f| f.hunks.as_slice()).unwrap_or(&[]);
+    let (from_file, to_file) = match (from_file, to_file) {
+        (Some(from_file), Some(to_file)) => (from_file, to_file),
+        (None, None) => {
+            return Err(ApiError::NotFound(
+                ErrorCode::FileNotFound,
+                format!("file not found: {file_path}"),
+            ));
+        }
+        (from_file, _) => {
+            let missing = if from_file.is_none() {
+                query.from
+            } else {
+                query.to
+            };
+            return Err(ApiError::invalid_request(format!(
+                "{file_path} is not in revision {missing}"
+            )));
+        }
+    };
 
     // Read the base content of the file (at the review's base_ref)
-    let repo_path = std::path::Path::new(&review.repo_path);
-    let base_content =
-        preflight_core::file_reader::read_old_file(repo_path, &file_path, &review.base_ref)
-            .unwrap_or_default();
-
-    let interdiff_hunks =
-        preflight_core::interdiff::compute_interdiff(&base_content, from_hunks, to_hunks);
-
-    let status = if from_file.is_none() {
-        FileStatus::Added
-    } else if to_file.is_none() {
-        FileStatus::Deleted
-    } else {
-        FileStatus::Modified
-    };
+    let base_content = review
+        .locate(&file_path)
+        .and_then(|location| {
+            state
+                .metrics
+                .time_git("show", || {
+                    preflight_core::file_reader::read_old_file(
+                        std::path::Path::new(location.repo_path),
+                        location.path,
+                        &git_diff::old_content_ref(location.base_ref),
+                        state.config.max_file_size,
+                    )
+                })
+                .ok()
+        })
+        .unwrap_or_default();
+
+    let interdiff_hunks = preflight_core::interdiff::compute_interdiff(
+        &base_content,
+        &from_file.hunks,
+        &to_file.hunks,
+    );
+
+    let language = state
+        .highlighter
+        .detect_language(&base_content, &file_path, None)
+        .map(str::to_string);
+    let (from_content, to_content) = reconstruct_file_contents(&interdiff_hunks);
+    let hunks = highlight_hunks(
+        &state,
+        &interdiff_hunks,
+        (&from_content, &file_path),
+        (&to_content, &file_path),
+    );
 
     Ok(Json(FileDiffResponse {
         path: file_path,
         old_path: None,
-        status,
-        hunks: interdiff_hunks,
+        status: FileStatus::Modified,
+        language,
+        hunks: hunks.into_iter().map(HunkResponse::from).collect(),
+        mode_change: to_file.mode_change.clone(),
+        truncated: false,
     }))
 }
 
-async fn get_file_content(
-    State(state): State<AppState>,
-    Path((id, file_path)): Path<(Uuid, String)>,
-    Query(query): Query<ContentQuery>,
-) -> Result<Json<FileContentResponse>, ApiError> {
+/// Read one side (`old` or `new`, the default) of a file in the latest
+/// revision, returning the path it was read from and its bytes.
+async fn read_file_version(
+    state: &AppState,
+    id: Uuid,
+    file_path: &str,
+    version: Option<&str>,
+) -> Result<(String, Vec<u8>), ApiError> {
     let review = state.store.get_review(id).await?;
 
-    let repo_path = std::path::Path::new(&review.repo_path);
-    file_reader::validate_repo_path(repo_path).map_err(|e| ApiError::BadRequest(e.to_string()))?;
-
-    let version = query.version.as_deref().unwrap_or("new");
+    let version = version.unwrap_or("new");
 
     // For looking up old_path on renames, use the revision's file list
     let revision = state.store.get_latest_revision(id).await?;
 
-    let (content, path) = match version {
-        "old" => {
-            let base_ref = &review.base_ref;
+    let read_path = match version {
+        // Check if this is a rename — use the old_path if available
+        "old" => revision
+            .files
+            .iter()
+            .find(|f| {
+                let effective = f
+                    .new_path
+                    .as_deref()
+                    .or(f.old_path.as_deref())
+                    .unwrap_or_default();
+                effective == file_path
+            })
+            .and_then(|f| f.old_path.as_deref())
+            .unwrap_or(file_path),
+        _ => file_path,
+    };
 
-            // Check if this is a rename — use the old_path if available
-            let read_path = revision
-                .files
-                .iter()
-                .find(|f| {
-                    let effective = f
-                        .new_path
-                        .as_deref()
-                        .or(f.old_path.as_deref())
-                        .unwrap_or_default();
-                    effective == file_path
-                })
-                .and_then(|f| f.old_path.as_deref())
-                .unwrap_or(&file_path);
+    // A snapshotted base doesn't need the repository at all
+    if version == "old"
+        && let Some(content) = revision.base_snapshots.get(read_path)
+    {
+        return Ok((read_path.to_string(), content.clone().into_bytes()));
+    }
 
-            let content = file_reader::read_old_file(repo_path, read_path, base_ref)
-                .map_err(|e| ApiError::NotFound(e.to_string()))?;
-            (content, read_path.to_string())
-        }
-        _ => {
-            let content = file_reader::read_new_file(repo_path, &file_path)
-                .map_err(|e| ApiError::NotFound(e.to_string()))?;
-            (content, file_path)
-        }
+    // Files of an extra repository are read from that repository
+    let location = review
+        .locate(read_path)
+        .ok_or_else(|| ApiError::no_repository(id))?;
+    let repo_path = std::path::Path::new(location.repo_path);
+    if !repo_path.exists() {
+        return Err(ApiError::repository_missing(repo_path));
+    }
+    file_reader::validate_repo_path(repo_path)
+        .map_err(|e| ApiError::BadRequest(ErrorCode::BadRepoPath, e.to_string()))?;
+
+    let content = match version {
+        "old" => state.metrics.time_git("show", || {
+            file_reader::read_old_file_bytes(
+                repo_path,
+                location.path,
+                &git_diff::old_content_ref(location.base_ref),
+                state.config.max_file_size,
+            )
+        })?,
+        _ => match git_diff::new_content_ref(location.base_ref) {
+            // A single-commit review shows the file as of that commit
+            Some(commit) => state.metrics.time_git("show", || {
+                file_reader::read_old_file_bytes(
+                    repo_path,
+                    location.path,
+                    commit,
+                    state.config.max_file_size,
+                )
+            })?,
+            None => file_reader::read_new_file_bytes(
+                repo_path,
+                location.path,
+                state.config.max_file_size,
+            )?,
+        },
     };
+    Ok((read_path.to_string(), content))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/reviews/{id}/content/{path}",
+    params(("id" = Uuid, Path, description = "Review id"), ("path" = String, Path, description = "Repo-relative file path"), ContentQuery),
+    responses(
+        (status = 200, body = FileContentResponse),
+        (status = 404, description = "Review, file or the review's repository not found"),
+        (status = 413, description = "File exceeds the configured size limit"),
+    ),
+    tag = "files"
+)]
+async fn get_file_content(
+    State(state): State<AppState>,
+    Path((id, file_path)): Path<(Uuid, String)>,
+    Query(query): Query<ContentQuery>,
+) -> Result<Json<FileContentResponse>, ApiError> {
+    let file_path = normalize_file_path(&file_path);
+    let (path, content) =
+        match read_file_version(&state, id, &file_path, query.version.as_deref()).await {
+            Err(ApiError::BadRequest(ErrorCode::NoRepository, _)) => {
+                return diff_only_content(&state, id, &file_path, &query)
+                    .await
+                    .map(Json);
+            }
+            result => result?,
+        };
+    let content = String::from_utf8(content)
+        .map_err(|e| file_reader::FileReadError::GitError(e.to_string()))?;
 
-    let highlighted_lines = state.highlighter.highlight_file(&content, &path);
+    let language = query.language.as_deref();
+    let highlighted_lines = state.highlighter.highlight_file(&content, &path, language);
 
-    let ext = std::path::Path::new(&path)
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("");
-    let language = state.highlighter.language_name(ext).map(|s| s.to_string());
+    let language = state
+        .highlighter
+        .detect_language(&content, &path, language)
+        .map(|s| s.to_string());
 
     let lines: Vec<FileContentLine> = content
         .lines()
@@ -280,9 +797,202 @@ async fn get_file_content(
         path,
         language,
         lines,
+        partial: false,
     }))
 }
 
+/// The lines of one side of a file that the latest revision's diff shows,
+/// for reviews with no repository to read the whole file from.
+async fn diff_only_content(
+    state: &AppState,
+    id: Uuid,
+    file_path: &str,
+    query: &ContentQuery,
+) -> Result<FileContentResponse, ApiError> {
+    let revision = state.store.get_latest_revision(id).await?;
+    let file = revision
+        .files
+        .iter()
+        .find(|f| f.new_path.as_deref().or(f.old_path.as_deref()) == Some(file_path))
+        .ok_or_else(|| {
+            ApiError::NotFound(
+                ErrorCode::FileNotFound,
+                format!("file not found: {file_path}"),
+            )
+        })?;
+    let old_side = query.version.as_deref() == Some("old");
+    let (old_content, new_content) = reconstruct_file_contents(&file.hunks);
+    let (path, content) = if old_side {
+        (file.old_path.as_deref().unwrap_or(file_path), old_content)
+    } else {
+        (file_path, new_content)
+    };
+
+    let language = query.language.as_deref();
+    let highlighted_lines = state.highlighter.highlight_file(&content, path, language);
+    let language = state
+        .highlighter
+        .detect_language(&content, path, language)
+        .map(str::to_string);
+
+    let content_lines: Vec<&str> = content.lines().collect();
+    let shown: std::collections::BTreeSet<u32> = file
+        .hunks
+        .iter()
+        .flat_map(|h| &h.lines)
+        .filter_map(|l| {
+            if old_side {
+                l.old_line_no
+            } else {
+                l.new_line_no
+            }
+        })
+        .collect();
+    let lines = shown
+        .into_iter()
+        .map(|line_no| {
+            let i = (line_no - 1) as usize;
+            FileContentLine {
+                line_no,
+                content: content_lines
+                    .get(i)
+                    .copied()
+                    .unwrap_or_default()
+                    .to_string(),
+                highlighted: highlighted_lines.as_ref().and_then(|hl| hl.get(i).cloned()),
+            }
+        })
+        .collect();
+
+    Ok(FileContentResponse {
+        path: path.to_string(),
+        language,
+        lines,
+        partial: true,
+    })
+}
+
+/// The file's bytes as they are, for opening or copying elsewhere.
+#[utoipa::path(
+    get,
+    path = "/api/reviews/{id}/raw/{path}",
+    params(("id" = Uuid, Path, description = "Review id"), ("path" = String, Path, description = "Repo-relative file path"), RawQuery),
+    responses(
+        (status = 200, description = "The file's bytes as a download, typed by its extension; HTML, SVG, XML and scripts are sent as text/plain"),
+        (status = 400, description = "The review's repository is gone, or it has none"),
+        (status = 404, description = "Review or file not found"),
+        (status = 413, description = "File exceeds the configured size limit"),
+    ),
+    tag = "files"
+)]
+async fn get_raw_file(
+    State(state): State<AppState>,
+    Path((id, file_path)): Path<(Uuid, String)>,
+    Query(query): Query<RawQuery>,
+) -> Result<Response, ApiError> {
+    let file_path = normalize_file_path(&file_path);
+    let (path, content) =
+        read_file_version(&state, id, &file_path, query.version.as_deref()).await?;
+    let file_name = path
+        .rsplit('/')
+        .next()
+        .unwrap_or(&path)
+        .replace(['"', '\\'], "_");
+    Ok((
+        [
+            (header::CONTENT_TYPE, raw_content_type(&path)),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{file_name}\""),
+            ),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+            (header::CONTENT_SECURITY_POLICY, "sandbox".to_string()),
+        ],
+        content,
+    )
+        .into_response())
+}
+
+/// The type to serve a repository file as. The file comes from whatever is
+/// under review and is served from the app's own origin, so types a browser
+/// would run (HTML, SVG, XML, scripts) go out as plain text.
+fn raw_content_type(path: &str) -> String {
+    let content_type = crate::content_type(path);
+    let essence = content_type.split(';').next().unwrap_or_default();
+    let active = matches!(
+        essence,
+        "text/html"
+            | "application/xhtml+xml"
+            | "image/svg+xml"
+            | "text/xml"
+            | "application/xml"
+            | "text/javascript"
+            | "application/javascript"
+            | "application/wasm"
+    );
+    if active {
+        "text/plain; charset=utf-8".to_string()
+    } else {
+        content_type
+    }
+}
+
+/// Read both sides of a file from its repository: the old side at the
+/// review's base and the new side from the working tree, or from the commit
+/// for single-commit reviews. A side is `None` if it doesn't exist or can't
+/// be read.
+fn read_full_contents(
+    state: &AppState,
+    review: &Review,
+    file_diff: &FileDiff,
+) -> (Option<String>, Option<String>) {
+    let max_size = state.config.max_file_size;
+    let old = file_diff.old_path.as_deref().and_then(|path| {
+        let location = review.locate(path)?;
+        state
+            .metrics
+            .time_git("show", || {
+                file_reader::read_old_file(
+                    std::path::Path::new(location.repo_path),
+                    location.path,
+                    &git_diff::old_content_ref(location.base_ref),
+                    max_size,
+                )
+            })
+            .ok()
+    });
+    let new = file_diff.new_path.as_deref().and_then(|path| {
+        let location = review.locate(path)?;
+        let repo_path = std::path::Path::new(location.repo_path);
+        match git_diff::new_content_ref(location.base_ref) {
+            Some(commit) => state
+                .metrics
+                .time_git("show", || {
+                    file_reader::read_old_file(repo_path, location.path, commit, max_size)
+                })
+                .ok(),
+            None => file_reader::read_new_file(repo_path, location.path, max_size).ok(),
+        }
+    });
+    (old, new)
+}
+
+/// Whether `content` has every line the hunks show for one side of the diff
+/// at the same line number. Fails once the working tree has moved on from
+/// the revision.
+fn matches_hunks(content: &str, hunks: &[Hunk], old_side: bool) -> bool {
+    let lines: Vec<&str> = content.lines().collect();
+    hunks.iter().flat_map(|hunk| &hunk.lines).all(|line| {
+        let line_no = if old_side {
+            line.old_line_no
+        } else {
+            line.new_line_no
+        };
+        line_no
+            .is_none_or(|n| n >= 1 && lines.get((n - 1) as usize) == Some(&line.content.as_str()))
+    })
+}
+
 fn reconstruct_file_contents(hunks: &[Hunk]) -> (String, String) {
     let mut old_lines: BTreeMap<u32, &str> = BTreeMap::new();
     let mut new_lines: BTreeMap<u32, &str> = BTreeMap::new();
@@ -338,13 +1048,7 @@ mod tests {
     use tower::ServiceExt;
 
     async fn test_app() -> axum::Router {
-        let dir = tempfile::TempDir::new().unwrap();
-        let path = dir.path().join("state.json");
-        let store = preflight_core::json_store::JsonFileStore::new(&path)
-            .await
-            .unwrap();
-        // Leak the TempDir so it stays alive for the duration of the test
-        Box::leak(Box::new(dir));
+        let store = preflight_core::memory_store::MemoryStore::new();
         crate::app(std::sync::Arc::new(store))
     }
 
@@ -455,32 +1159,155 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_get_file_diff_returns_hunks() {
+    async fn test_list_files_flags_trivial_changes() {
+        use std::process::Command;
+
         let app = test_app().await;
-        let (_repo_dir, repo_path) = setup_test_repo();
+        let (repo_dir, repo_path) = setup_test_repo();
+        let p = repo_dir.path();
+        std::fs::write(p.join("src/lib.rs"), "pub const LIMIT: u32 = 1;\n").unwrap();
+        Command::new("git")
+            .args(["add", "src/lib.rs"])
+            .current_dir(p)
+            .output()
+            .unwrap();
+        Command::new("git")
+            .args(["commit", "-m", "add lib"])
+            .current_dir(p)
+            .output()
+            .unwrap();
+        std::fs::write(
+            p.join("src/lib.rs"),
+            "/// Most retries.\npub const LIMIT: u32 = 1;\n",
+        )
+        .unwrap();
         let id = create_review_for_test(&app, &repo_path).await;
 
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri(format!("/api/reviews/{id}/files/src/main.rs"))
+                    .uri(format!("/api/reviews/{id}/files"))
                     .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
+        let json = body_json(response).await;
+        let trivial: std::collections::BTreeMap<&str, bool> = json
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| (f["path"].as_str().unwrap(), f["trivial"].as_bool().unwrap()))
+            .collect();
+        assert_eq!(
+            trivial,
+            std::collections::BTreeMap::from([("src/lib.rs", true), ("src/main.rs", false)])
+        );
+    }
+
+    /// Add `a.rs` (deleted in the working copy) and `z.rs` (new, staged) to
+    /// the test repo next to the modified `src/main.rs`.
+    fn add_deleted_and_new_files(repo_dir: &std::path::Path) {
+        use std::process::Command;
+
+        std::fs::write(repo_dir.join("a.rs"), "fn a() {}\n").unwrap();
+        Command::new("git")
+            .args(["add", "a.rs"])
+            .current_dir(repo_dir)
+            .output()
+            .unwrap();
+        Command::new("git")
+            .args(["commit", "-m", "add a"])
+            .current_dir(repo_dir)
+            .output()
+            .unwrap();
+        std::fs::remove_file(repo_dir.join("a.rs")).unwrap();
+        std::fs::write(repo_dir.join("z.rs"), "fn z() {}\n").unwrap();
+        Command::new("git")
+            .args(["add", "z.rs"])
+            .current_dir(repo_dir)
+            .output()
+            .unwrap();
+    }
 
+    async fn list_file_paths(app: &axum::Router, id: &str, query: &str) -> Vec<String> {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/files{query}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
         assert_eq!(response.status(), StatusCode::OK);
+        body_json(response)
+            .await
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["path"].as_str().unwrap().to_string())
+            .collect()
+    }
 
-        let json = body_json(response).await;
-        assert_eq!(json["path"], "src/main.rs");
-        assert_eq!(json["status"], "Modified");
-        assert!(json["hunks"].is_array());
-        assert!(!json["hunks"].as_array().unwrap().is_empty());
+    #[tokio::test]
+    async fn test_list_files_sorts_by_status() {
+        let app = test_app().await;
+        let (repo_dir, repo_path) = setup_test_repo();
+        add_deleted_and_new_files(repo_dir.path());
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        assert_eq!(
+            list_file_paths(&app, &id, "").await,
+            ["a.rs", "src/main.rs", "z.rs"]
+        );
+        assert_eq!(
+            list_file_paths(&app, &id, "?sort=status").await,
+            ["z.rs", "src/main.rs", "a.rs"]
+        );
     }
 
     #[tokio::test]
-    async fn test_get_file_diff_not_found() {
+    async fn test_list_files_sorts_by_thread_count() {
+        let app = test_app().await;
+        let (repo_dir, repo_path) = setup_test_repo();
+        add_deleted_and_new_files(repo_dir.path());
+        let id = create_review_for_test(&app, &repo_path).await;
+        for file_path in ["z.rs", "z.rs", "src/main.rs"] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .uri(format!("/api/reviews/{id}/threads"))
+                        .header("content-type", "application/json")
+                        .body(Body::from(
+                            serde_json::json!({
+                                "file_path": file_path,
+                                "line_start": 1,
+                                "line_end": 1,
+                                "origin": "Comment",
+                                "body": "look here",
+                                "author_type": "Human"
+                            })
+                            .to_string(),
+                        ))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        assert_eq!(
+            list_file_paths(&app, &id, "?sort=threads").await,
+            ["z.rs", "src/main.rs", "a.rs"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_changed_lines_returns_only_added_lines() {
         let app = test_app().await;
         let (_repo_dir, repo_path) = setup_test_repo();
         let id = create_review_for_test(&app, &repo_path).await;
@@ -488,25 +1315,595 @@ mod tests {
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri(format!("/api/reviews/{id}/files/nonexistent.rs"))
+                    .uri(format!("/api/reviews/{id}/changed-lines/src/main.rs"))
                     .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.status(), StatusCode::OK);
+        // "fn main() {}" is removed and everything else is added
+        assert_eq!(
+            body_json(response).await,
+            serde_json::json!([
+                { "line_no": 1, "content": "use std::io;" },
+                { "line_no": 2, "content": "" },
+                { "line_no": 3, "content": "fn main() {" },
+                { "line_no": 4, "content": "    println!(\"hello\");" },
+                { "line_no": 5, "content": "}" },
+            ])
+        );
     }
 
     #[tokio::test]
-    async fn test_list_files_review_not_found() {
+    async fn test_get_file_diff_returns_hunks() {
         let app = test_app().await;
-        let fake_id = uuid::Uuid::new_v4();
+        let (_repo_dir, repo_path) = setup_test_repo();
+        let id = create_review_for_test(&app, &repo_path).await;
 
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri(format!("/api/reviews/{fake_id}/files"))
+                    .uri(format!("/api/reviews/{id}/files/src/main.rs"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let json = body_json(response).await;
+        assert_eq!(json["path"], "src/main.rs");
+        assert_eq!(json["status"], "Modified");
+        assert_eq!(json["language"], "Rust");
+        assert!(json["hunks"].is_array());
+        assert!(!json["hunks"].as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_file_diff_normalizes_path() {
+        let app = test_app().await;
+        let (repo_dir, repo_path) = setup_test_repo();
+        std::fs::write(repo_dir.path().join("src/my notes.md"), "notes\n").unwrap();
+        std::process::Command::new("git")
+            .args(["add", "src/my notes.md"])
+            .current_dir(repo_dir.path())
+            .output()
+            .unwrap();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        for (uri_path, expected) in [
+            ("./src/main.rs", "src/main.rs"),
+            ("src//main.rs", "src/main.rs"),
+            ("src/./main.rs", "src/main.rs"),
+            ("src/my%20notes.md", "src/my notes.md"),
+            ("./src/my%20notes.md", "src/my notes.md"),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .uri(format!("/api/reviews/{id}/files/{uri_path}"))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{uri_path}");
+            assert_eq!(body_json(response).await["path"], expected);
+        }
+
+        // Content reads go through the same normalization
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/content/./src/my%20notes.md"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_json(response).await["path"], "src/my notes.md");
+    }
+
+    #[tokio::test]
+    async fn test_get_file_diff_hunk_line_spans() {
+        let app = test_app().await;
+        let (_repo_dir, repo_path) = setup_test_repo();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/files/src/main.rs"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let json = body_json(response).await;
+        for hunk in json["hunks"].as_array().unwrap() {
+            let lines = hunk["lines"].as_array().unwrap();
+            let new_lines: Vec<u64> = lines
+                .iter()
+                .filter(|l| l["kind"] != "Removed")
+                .map(|l| l["new_line_no"].as_u64().unwrap())
+                .collect();
+            let old_lines: Vec<u64> = lines
+                .iter()
+                .filter(|l| l["kind"] != "Added")
+                .map(|l| l["old_line_no"].as_u64().unwrap())
+                .collect();
+            assert_eq!(hunk["new_line_start"], new_lines[0]);
+            assert_eq!(hunk["new_line_end"], *new_lines.last().unwrap());
+            assert_eq!(hunk["old_line_start"], old_lines[0]);
+            assert_eq!(hunk["old_line_end"], *old_lines.last().unwrap());
+            // Flattened alongside the usual hunk fields
+            assert!(hunk["new_start"].is_u64());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_file_diff_not_found() {
+        let app = test_app().await;
+        let (_repo_dir, repo_path) = setup_test_repo();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/files/nonexistent.rs"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(response).await["code"], "file_not_found");
+    }
+
+    #[tokio::test]
+    async fn test_get_file_diff_since_shows_only_new_changes() {
+        let app = test_app().await;
+        let (repo_dir, repo_path) = setup_test_repo();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        // Revision 2 adds one more line on top of revision 1's changes
+        std::fs::write(
+            repo_dir.path().join("src/main.rs"),
+            "use std::io;\nuse std::fs;\n\nfn main() {\n    println!(\"hello\");\n}\n",
+        )
+        .unwrap();
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/reviews/{id}/revisions"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "trigger": "Manual" }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/files/src/main.rs?since=1"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let json = body_json(response).await;
+        let changed: Vec<&serde_json::Value> = json["hunks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .flat_map(|h| h["lines"].as_array().unwrap())
+            .filter(|l| l["kind"] != "Context")
+            .collect();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0]["kind"], "Added");
+        assert_eq!(changed[0]["content"], "use std::fs;");
+        assert!(changed[0]["highlighted"].is_string());
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/files/src/main.rs?since=3"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(body_json(response).await["code"], "invalid_request");
+    }
+
+    #[tokio::test]
+    async fn test_get_revision_diff_returns_earlier_revision_in_full() {
+        use std::process::Command;
+
+        let app = test_app().await;
+        let (repo_dir, repo_path) = setup_test_repo();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        // Revision 2 changes main.rs again and adds lib.rs
+        let p = repo_dir.path();
+        std::fs::write(
+            p.join("src/main.rs"),
+            "use std::io;\nuse std::fs;\n\nfn main() {\n    println!(\"hello\");\n}\n",
+        )
+        .unwrap();
+        std::fs::write(p.join("src/lib.rs"), "pub fn lib() {}\n").unwrap();
+        Command::new("git")
+            .args(["add", "src/lib.rs"])
+            .current_dir(p)
+            .output()
+            .unwrap();
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/reviews/{id}/revisions"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "trigger": "Manual" }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let get = |n: u32| {
+            app.clone().oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/revisions/{n}/diff"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+        let response = get(1).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        let files = json.as_array().unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0]["path"], "src/main.rs");
+        let lines: Vec<&serde_json::Value> = files[0]["hunks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .flat_map(|h| h["lines"].as_array().unwrap())
+            .collect();
+        assert!(lines.iter().any(|l| l["content"] == "use std::io;"));
+        assert!(!lines.iter().any(|l| l["content"] == "use std::fs;"));
+        assert!(lines.iter().all(|l| l["highlighted"].is_string()));
+
+        let json = body_json(get(2).await.unwrap()).await;
+        assert_eq!(json.as_array().unwrap().len(), 2);
+
+        let response = get(3).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(response).await["code"], "revision_not_found");
+    }
+
+    #[tokio::test]
+    async fn test_get_file_interdiff_shows_line_added_between_revisions() {
+        let app = test_app().await;
+        let (repo_dir, repo_path) = setup_test_repo();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        std::fs::write(
+            repo_dir.path().join("src/main.rs"),
+            "use std::io;\nuse std::fs;\n\nfn main() {\n    println!(\"hello\");\n}\n",
+        )
+        .unwrap();
+        // A file that only the second revision has
+        std::fs::write(repo_dir.path().join("src/lib.rs"), "pub fn lib() {}\n").unwrap();
+        std::process::Command::new("git")
+            .args(["add", "src/lib.rs"])
+            .current_dir(repo_dir.path())
+            .output()
+            .unwrap();
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/reviews/{id}/revisions"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "trigger": "Manual" }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let get_path = |path: &str, query: &str| {
+            app.clone().oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/interdiff/{path}?{query}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+        let get = |query: &str| {
+            app.clone().oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/interdiff/src/main.rs?{query}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+        let response = get("from=1&to=2").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert_eq!(json["status"], "Modified");
+        let changed: Vec<&serde_json::Value> = json["hunks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .flat_map(|h| h["lines"].as_array().unwrap())
+            .filter(|l| l["kind"] != "Context")
+            .collect();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0]["kind"], "Added");
+        assert_eq!(changed[0]["content"], "use std::fs;");
+        assert!(changed[0]["highlighted"].as_str().unwrap().contains("sy-"));
+
+        for query in ["from=2&to=1", "from=2&to=2"] {
+            let response = get(query).await.unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+
+        let response = get_path("src/lib.rs", "from=1&to=2").await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(body_json(response).await["code"], "invalid_request");
+        let response = get_path("src/missing.rs", "from=1&to=2").await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(response).await["code"], "file_not_found");
+    }
+
+    #[tokio::test]
+    async fn test_get_file_diff_highlights_context_with_full_file() {
+        use std::process::Command;
+
+        let app = test_app().await;
+        let (repo_dir, repo_path) = setup_test_repo();
+        // The language is only known from the shebang, which is far outside
+        // the hunk, so highlighting needs the full file
+        let body: String = (1..=10).map(|i| format!("x{i} = {i}\n")).collect();
+        let original = format!("#!/usr/bin/env python\n{body}");
+        std::fs::write(repo_dir.path().join("script"), &original).unwrap();
+        for args in [&["add", "."][..], &["commit", "-m", "script"]] {
+            Command::new("git")
+                .args(args)
+                .current_dir(repo_dir.path())
+                .output()
+                .unwrap();
+        }
+        std::fs::write(
+            repo_dir.path().join("script"),
+            original.replace("x8 = 8\n", "x8 = 'eight'\n"),
+        )
+        .unwrap();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/files/script"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let json = body_json(response).await;
+        assert_eq!(json["language"], "Python");
+        let lines = json["hunks"][0]["lines"].as_array().unwrap();
+        assert!(lines.iter().all(|l| l["old_line_no"] != 1));
+        let context = lines
+            .iter()
+            .find(|l| l["kind"] == "Context" && l["content"] == "x6 = 6")
+            .unwrap();
+        assert!(context["highlighted"].as_str().unwrap().contains("sy-"));
+    }
+
+    #[tokio::test]
+    async fn test_get_review_diff_returns_every_file() {
+        use std::process::Command;
+
+        let app = test_app().await;
+        let (repo_dir, repo_path) = setup_test_repo();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(repo_dir.path())
+                .output()
+                .unwrap();
+        };
+        std::fs::write(repo_dir.path().join("src/lib.rs"), "pub fn a() {}\n").unwrap();
+        git(&["add", "src/lib.rs"]);
+        git(&["commit", "-m", "lib"]);
+        std::fs::write(repo_dir.path().join("src/lib.rs"), "pub fn b() {}\n").unwrap();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        let get = |uri: String| {
+            app.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        };
+        let response = get(format!("/api/reviews/{id}/diff?highlight=true"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        let files = json.as_array().unwrap();
+        let paths: Vec<&str> = files.iter().map(|f| f["path"].as_str().unwrap()).collect();
+        assert_eq!(paths, ["src/lib.rs", "src/main.rs"]);
+        for file in files {
+            assert_eq!(file["language"], "Rust");
+            assert!(file.get("truncated").is_none());
+            let lines = file["hunks"][0]["lines"].as_array().unwrap();
+            assert!(!lines.is_empty());
+            assert!(lines.iter().all(|l| l["highlighted"].is_string()));
+        }
+
+        let response = get(format!("/api/reviews/{id}/diff")).await.unwrap();
+        let json = body_json(response).await;
+        let lines = json[0]["hunks"][0]["lines"].as_array().unwrap();
+        assert!(lines.iter().all(|l| l["highlighted"].is_null()));
+    }
+
+    #[tokio::test]
+    async fn test_get_review_diff_truncates_past_line_limit() {
+        let app = test_app().await;
+        let (repo_dir, repo_path) = setup_test_repo();
+        let big: String = (0..=super::MAX_REVIEW_DIFF_LINES)
+            .map(|i| format!("{i}\n"))
+            .collect();
+        std::fs::write(repo_dir.path().join("zz_big.txt"), big).unwrap();
+        std::process::Command::new("git")
+            .args(["add", "zz_big.txt"])
+            .current_dir(repo_dir.path())
+            .output()
+            .unwrap();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/diff?highlight=true"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let json = body_json(response).await;
+        assert_eq!(json[0]["path"], "src/main.rs");
+        assert!(!json[0]["hunks"].as_array().unwrap().is_empty());
+        assert_eq!(json[1]["path"], "zz_big.txt");
+        assert_eq!(json[1]["truncated"], true);
+        assert!(json[1]["hunks"].as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_file_diff_highlights_each_side_by_its_own_path() {
+        use std::process::Command;
+
+        let app = test_app().await;
+        let (repo_dir, repo_path) = setup_test_repo();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(repo_dir.path())
+                .output()
+                .unwrap();
+        };
+        let original: String = (1..=10).map(|i| format!("x{i} = {i}\n")).collect();
+        std::fs::write(repo_dir.path().join("calc.py"), &original).unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "calc"]);
+        git(&["mv", "calc.py", "calc.rs"]);
+        std::fs::write(
+            repo_dir.path().join("calc.rs"),
+            original.replace("x5 = 5\n", "x5 = 55\n"),
+        )
+        .unwrap();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/files/calc.rs"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let json = body_json(response).await;
+        assert_eq!(json["old_path"], "calc.py");
+        let lines = json["hunks"][0]["lines"].as_array().unwrap();
+        let highlighted = |kind: &str| {
+            lines.iter().find(|l| l["kind"] == kind).unwrap()["highlighted"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        };
+        assert!(highlighted("Removed").contains("sy-python"));
+        assert!(highlighted("Added").contains("sy-rust"));
+    }
+
+    #[tokio::test]
+    async fn test_get_file_diff_whitespace_only_change_flagged_and_suppressed() {
+        let app = test_app().await;
+        let (repo_dir, repo_path) = setup_test_repo();
+        // Only reformat the committed `fn main() {}`
+        std::fs::write(repo_dir.path().join("src/main.rs"), "fn  main()  {}\n").unwrap();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/files/src/main.rs"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        let lines = json["hunks"][0]["lines"].as_array().unwrap();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|l| l["whitespace_only"] == true));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!(
+                        "/api/reviews/{id}/files/src/main.rs?ignore_whitespace=true"
+                    ))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert!(json["hunks"].as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_files_review_not_found() {
+        let app = test_app().await;
+        let fake_id = uuid::Uuid::new_v4();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{fake_id}/files"))
                     .body(Body::empty())
                     .unwrap(),
             )
@@ -514,6 +1911,7 @@ mod tests {
             .unwrap();
 
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(response).await["code"], "review_not_found");
     }
 
     /// Helper: create a temp git repo with a renamed file, return its (TempDir, repo_path_string).
@@ -584,6 +1982,206 @@ mod tests {
         assert_eq!(lines[0]["content"], "use std::io;");
     }
 
+    #[tokio::test]
+    async fn test_deleted_repo_fails_content_but_serves_diff() {
+        let app = test_app().await;
+        let (repo_dir, repo_path) = setup_test_repo();
+        let id = create_review_for_test(&app, &repo_path).await;
+        repo_dir.close().unwrap();
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/content/src/main.rs"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let json = body_json(response).await;
+        assert_eq!(json["code"], "repository_missing");
+        assert_eq!(
+            json["error"],
+            format!("the repository for this review no longer exists at {repo_path}")
+        );
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/files/src/main.rs"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert!(!json["hunks"].as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_raw_file_serves_bytes_with_content_type() {
+        let app = test_app().await;
+        let (repo_dir, repo_path) = setup_test_repo();
+        let png = [0x89, b'P', b'N', b'G', 0xff, 0x00];
+        std::fs::write(repo_dir.path().join("logo.png"), png).unwrap();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        let get = |uri: String| {
+            app.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        };
+        let response = get(format!("/api/reviews/{id}/raw/logo.png"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "image/png");
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&bytes[..], &png[..]);
+
+        let response = get(format!("/api/reviews/{id}/raw/src/main.rs?version=old"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()["content-type"],
+            "text/x-rust; charset=utf-8"
+        );
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&bytes[..], b"fn main() {}\n");
+
+        let response = get(format!("/api/reviews/{id}/raw/missing.txt"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(response).await["code"], "file_not_found");
+    }
+
+    #[tokio::test]
+    async fn test_get_raw_file_does_not_serve_html_inline() {
+        let app = test_app().await;
+        let (repo_dir, repo_path) = setup_test_repo();
+        let page = "<script>fetch('/api/admin/backup')</script>";
+        std::fs::write(repo_dir.path().join("index.html"), page).unwrap();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/raw/index.html"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let headers = response.headers();
+        assert_eq!(headers["content-type"], "text/plain; charset=utf-8");
+        assert_eq!(
+            headers["content-disposition"],
+            "attachment; filename=\"index.html\""
+        );
+        assert_eq!(headers["x-content-type-options"], "nosniff");
+        assert_eq!(headers["content-security-policy"], "sandbox");
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&bytes[..], page.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_get_file_content_language_override() {
+        let app = test_app().await;
+        let (repo_dir, repo_path) = setup_test_repo();
+        std::fs::write(repo_dir.path().join("Dockerfile"), "RUN cargo build\n").unwrap();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        let get = |uri: String| {
+            app.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        };
+
+        let response = get(format!("/api/reviews/{id}/content/Dockerfile"))
+            .await
+            .unwrap();
+        let json = body_json(response).await;
+        assert!(json["language"].is_null());
+        assert!(json["lines"][0]["highlighted"].is_null());
+
+        let response = get(format!(
+            "/api/reviews/{id}/content/Dockerfile?language=Dockerfile"
+        ))
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert_eq!(json["language"], "Dockerfile");
+        assert!(
+            json["lines"][0]["highlighted"]
+                .as_str()
+                .unwrap()
+                .contains("sy-")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_file_content_detects_shebang() {
+        let app = test_app().await;
+        let (repo_dir, repo_path) = setup_test_repo();
+        std::fs::write(
+            repo_dir.path().join("script"),
+            "#!/usr/bin/env python\nprint('hi')\n",
+        )
+        .unwrap();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/content/script"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert_eq!(json["language"], "Python");
+        assert!(
+            json["lines"][1]["highlighted"]
+                .as_str()
+                .unwrap()
+                .contains("sy-")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_file_content_over_size_limit_returns_413() {
+        let store = preflight_core::memory_store::MemoryStore::new();
+        let config = crate::state::ServerConfig {
+            max_file_size: 16,
+            ..Default::default()
+        };
+        let app = crate::app_with_config(std::sync::Arc::new(store), config);
+        let (_repo_dir, repo_path) = setup_test_repo();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/content/src/main.rs"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let json = body_json(response).await;
+        assert!(json["error"].as_str().unwrap().contains("file too large"));
+    }
+
     #[tokio::test]
     async fn test_get_file_content_old_version_from_git() {
         let app = test_app().await;
@@ -718,4 +2316,73 @@ mod tests {
         let json = body_json(response).await;
         assert_eq!(json.as_array().unwrap().len(), 2);
     }
+
+    #[tokio::test]
+    async fn test_old_content_served_from_snapshot_after_repo_deleted() {
+        let app = crate::app_with_config(
+            std::sync::Arc::new(preflight_core::memory_store::MemoryStore::new()),
+            crate::state::ServerConfig {
+                snapshot_base: true,
+                ..Default::default()
+            },
+        );
+        let (repo_dir, repo_path) = setup_test_repo();
+        let id = create_review_for_test(&app, &repo_path).await;
+        drop(repo_dir);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/content/src/main.rs?version=old"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert_eq!(json["lines"][0]["content"], "fn main() {}");
+    }
+
+    #[tokio::test]
+    async fn test_file_routes_report_served_revision() {
+        let app = test_app().await;
+        let (repo_dir, repo_path) = setup_test_repo();
+        let id = create_review_for_test(&app, &repo_path).await;
+        std::fs::write(
+            repo_dir.path().join("src/main.rs"),
+            "fn main() {\n    run();\n}\n",
+        )
+        .unwrap();
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/reviews/{id}/revisions"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "trigger": "Manual" }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let get = |uri: String| {
+            app.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        };
+        for uri in [
+            format!("/api/reviews/{id}/files"),
+            format!("/api/reviews/{id}/files/src/main.rs"),
+        ] {
+            let response = get(uri.clone()).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{uri}");
+            assert_eq!(response.headers()[super::REVISION_HEADER], "2", "{uri}");
+        }
+        let response = get(format!("/api/reviews/{id}/files?revision=1"))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[super::REVISION_HEADER], "1");
+    }
 }
@@ -1,3 +1,4 @@
+pub mod admin;
 pub mod comments;
 pub mod files;
 pub mod reviews;
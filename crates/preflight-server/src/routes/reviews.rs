@@ -1,18 +1,24 @@
 use axum::{
-    Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
 };
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
-use crate::error::ApiError;
+use crate::error::{ApiError, ErrorCode};
+use crate::extract::Json;
 use crate::state::AppState;
 use crate::types::{
-    CreateReviewRequest, FindOrCreateReviewRequest, ReviewResponse, UpdateReviewStatusRequest,
+    ActivityEntry, ActivityKind, ActivityQuery, CreateReviewFromDiffRequest, CreateReviewRequest,
+    DraftsQuery, ExtraRepoRequest, FindOrCreateReviewRequest, ForkReviewRequest,
+    PreviewReviewRequest, ReviewFullResponse, ReviewListQuery, ReviewPreviewResponse,
+    ReviewResponse, SetReviewDecisionRequest, UpdateReviewRequest, UpdateReviewStatusRequest,
+    ValidateRepoRequest, ValidateRepoResponse,
 };
 use crate::ws::{WsEvent, WsEventType};
-use preflight_core::review::{ThreadOrigin, ThreadStatus};
+use preflight_core::diff::LineKind;
+use preflight_core::git_diff;
+use preflight_core::review::{CommentThread, ExtraRepo, Review, ReviewStatus, Revision};
 use preflight_core::store::CreateReviewInput;
 
 pub fn router() -> axum::Router<AppState> {
@@ -24,53 +30,300 @@ pub fn router() -> axum::Router<AppState> {
                 .post(create_review)
                 .delete(delete_closed_reviews),
         )
+        .route("/from-diff", post(create_review_from_diff))
         .route("/find-or-create", post(find_or_create_review))
-        .route("/{id}", get(get_review).delete(delete_review))
+        .route("/preview", post(preview_review))
+        .route("/{id}/fork", post(fork_review))
+        .route(
+            "/{id}",
+            get(get_review).patch(update_review).delete(delete_review),
+        )
+        .route("/{id}/full", get(get_review_full))
         .route("/{id}/status", patch(update_review_status))
+        .route("/{id}/decision", post(set_review_decision))
         .route("/{id}/agent-status", get(get_agent_presence))
+        .route("/{id}/human-status", get(get_human_presence))
         .route("/{id}/agent-presence", put(update_agent_presence))
+        .route("/{id}/agent-seen", put(mark_agent_seen))
+        .route("/{id}/mark-seen", post(mark_human_seen))
+        .route("/{id}/heartbeat", post(agent_heartbeat))
         .route("/{id}/request-revision", post(request_revision))
+        .route("/{id}/activity", get(get_activity))
+}
+
+/// A title that is only whitespace is treated as no title at all.
+fn non_blank_title(title: Option<String>) -> Option<String> {
+    title.filter(|t| !t.trim().is_empty())
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/reviews",
+    request_body = CreateReviewRequest,
+    responses(
+        (status = 200, body = ReviewResponse),
+        (status = 400, description = "Invalid repo path or base ref, or no changes to review"),
+    ),
+    tag = "reviews"
+)]
 async fn create_review(
     State(state): State<AppState>,
     Json(request): Json<CreateReviewRequest>,
 ) -> Result<Json<ReviewResponse>, ApiError> {
     let repo_path = std::path::Path::new(&request.repo_path);
-    let files = preflight_core::git_diff::diff_against_base(repo_path, &request.base_ref)
-        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let path_prefix = request.path_prefix.filter(|p| !p.is_empty());
+    let base_ref = match (request.commit, request.base_ref) {
+        (Some(_), Some(_)) => {
+            return Err(ApiError::invalid_request(
+                "pass either commit or base_ref, not both",
+            ));
+        }
+        (Some(commit), None) => {
+            let sha = state.metrics.time_git("resolve_commit", || {
+                git_diff::resolve_commit(repo_path, &commit)
+            })?;
+            format!("{sha}{}", git_diff::SINGLE_COMMIT_SUFFIX)
+        }
+        (None, base_ref) => state.metrics.time_git("resolve_base_ref", || {
+            git_diff::resolve_base_ref(repo_path, base_ref.as_deref())
+        }),
+    };
+    let extra_repos = resolve_extra_repos(&state, request.extra_repos)?;
+    let options = state.config.diff_options(request.diff_algorithm);
+    let mut files = state.metrics.time_git("diff", || {
+        git_diff::diff_against_base_with_options(
+            repo_path,
+            &base_ref,
+            path_prefix.as_deref(),
+            &options,
+        )
+    })?;
+    files.extend(state.metrics.time_git("diff", || {
+        git_diff::diff_extra_repos(&extra_repos, &options)
+    })?);
+    if files.is_empty() && !request.allow_empty {
+        return Err(ApiError::BadRequest(
+            ErrorCode::NoChanges,
+            format!("no changes to review against {base_ref}"),
+        ));
+    }
+
+    let input = CreateReviewInput {
+        title: non_blank_title(request.title),
+        repo_path: Some(request.repo_path),
+        base_ref,
+        path_prefix,
+        extra_repos,
+        labels: normalize_labels(request.labels),
+        diff_algorithm: request.diff_algorithm,
+    };
+    create_review_with_files(&state, input, files).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/reviews/from-diff",
+    request_body = CreateReviewFromDiffRequest,
+    responses(
+        (status = 200, body = ReviewResponse),
+        (status = 400, description = "The diff could not be parsed or changes no files"),
+    ),
+    tag = "reviews"
+)]
+async fn create_review_from_diff(
+    State(state): State<AppState>,
+    Json(request): Json<CreateReviewFromDiffRequest>,
+) -> Result<Json<ReviewResponse>, ApiError> {
+    let files = preflight_core::parser::parse_diff(&request.diff)
+        .map_err(|e| ApiError::invalid_request(format!("invalid diff: {e}")))?;
+    if files.is_empty() {
+        return Err(ApiError::invalid_request("diff changes no files"));
+    }
+    let input = CreateReviewInput {
+        title: non_blank_title(request.title),
+        repo_path: None,
+        base_ref: request.base_ref.unwrap_or_else(|| "HEAD".into()),
+        path_prefix: None,
+        extra_repos: Vec::new(),
+        labels: normalize_labels(request.labels),
+        diff_algorithm: Default::default(),
+    };
+    create_review_with_files(&state, input, files).await
+}
+
+/// Store a new review with `files` as its first revision and announce it.
+async fn create_review_with_files(
+    state: &AppState,
+    input: CreateReviewInput,
+    files: Vec<preflight_core::diff::FileDiff>,
+) -> Result<Json<ReviewResponse>, ApiError> {
+    let review = state.store.create_review(input).await?;
+
+    let base_snapshots = super::revisions::base_snapshots(state, &review, &files);
+    let revision = state
+        .store
+        .create_revision(preflight_core::store::CreateRevisionInput {
+            review_id: review.id,
+            trigger: preflight_core::review::RevisionTrigger::Manual,
+            message: None,
+            files,
+            base_snapshots,
+        })
+        .await?;
+
+    let threads = state.store.get_threads(review.id, None).await?;
+    let response = review_response(
+        review,
+        &threads,
+        std::slice::from_ref(&revision),
+        state.config.count_explanations,
+    );
+    let _ = state.ws_tx.send(WsEvent {
+        event_type: WsEventType::ReviewCreated,
+        review_id: response.id.to_string(),
+        payload: serde_json::to_value(&response).unwrap(),
+        timestamp: Utc::now(),
+    });
+    Ok(Json(response))
+}
+
+/// Check the requested extra repositories and resolve each one's base ref
+/// in that repository.
+fn resolve_extra_repos(
+    state: &AppState,
+    requests: Vec<ExtraRepoRequest>,
+) -> Result<Vec<ExtraRepo>, ApiError> {
+    let mut repos: Vec<ExtraRepo> = Vec::with_capacity(requests.len());
+    for request in requests {
+        let prefix = request.prefix.trim_matches('/').to_string();
+        if prefix.is_empty() {
+            return Err(ApiError::invalid_request(
+                "extra repo prefix must not be empty",
+            ));
+        }
+        if repos.iter().any(|r| r.prefix == prefix) {
+            return Err(ApiError::invalid_request(format!(
+                "duplicate extra repo prefix: {prefix}"
+            )));
+        }
+        let base_ref = state.metrics.time_git("resolve_base_ref", || {
+            git_diff::resolve_base_ref(
+                std::path::Path::new(&request.repo_path),
+                request.base_ref.as_deref(),
+            )
+        });
+        repos.push(ExtraRepo {
+            repo_path: request.repo_path,
+            base_ref,
+            prefix,
+        });
+    }
+    Ok(repos)
+}
+
+/// Start a new review of the same repo, e.g. after a rebase, with a fresh
+/// diff and copies of the original's unresolved threads. Resolved threads are
+/// dropped. Threads follow files renamed during the original review; line
+/// numbers are kept as-is.
+#[utoipa::path(
+    post,
+    path = "/api/reviews/{id}/fork",
+    params(("id" = Uuid, Path, description = "Review id to fork")),
+    request_body = ForkReviewRequest,
+    responses(
+        (status = 200, description = "The new review", body = ReviewResponse),
+        (status = 400, description = "Invalid base ref, or the review has no repository"),
+        (status = 404, description = "Review not found"),
+    ),
+    tag = "reviews"
+)]
+async fn fork_review(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<ForkReviewRequest>,
+) -> Result<Json<ReviewResponse>, ApiError> {
+    let original = state.store.get_review(id).await?;
+    let repo_path = original
+        .repo_path
+        .as_deref()
+        .ok_or_else(|| ApiError::no_repository(id))?;
+    let repo_path = std::path::Path::new(repo_path);
+    let base_ref = match request.base_ref {
+        Some(base_ref) => state.metrics.time_git("resolve_base_ref", || {
+            git_diff::resolve_base_ref(repo_path, Some(&base_ref))
+        }),
+        None => original.base_ref.clone(),
+    };
+    let options = state.config.diff_options(original.diff_algorithm);
+    let mut files = state.metrics.time_git("diff", || {
+        git_diff::diff_against_base_with_options(
+            repo_path,
+            &base_ref,
+            original.path_prefix.as_deref(),
+            &options,
+        )
+    })?;
+    files.extend(state.metrics.time_git("diff", || {
+        git_diff::diff_extra_repos(&original.extra_repos, &options)
+    })?);
+
+    let renames = preflight_core::renames::build_rename_map(&state.store.get_revisions(id).await?);
+    let unresolved: Vec<_> = state
+        .store
+        .get_threads(id, None)
+        .await?
+        .into_iter()
+        .filter(|t| t.counts_as_open(state.config.count_explanations))
+        .map(|t| preflight_core::review::CommentThread {
+            file_path: preflight_core::renames::resolve_path(&renames, &t.file_path).to_string(),
+            // Revision numbers belong to the original review
+            revision_number: None,
+            ..t
+        })
+        .collect();
 
     let review = state
         .store
         .create_review(CreateReviewInput {
-            title: request.title,
-            repo_path: request.repo_path,
-            base_ref: request.base_ref,
+            title: non_blank_title(request.title).or(original.title),
+            repo_path: original.repo_path,
+            base_ref,
+            path_prefix: original.path_prefix,
+            extra_repos: original.extra_repos,
+            labels: original.labels,
+            diff_algorithm: original.diff_algorithm,
         })
         .await?;
-
+    let base_snapshots = super::revisions::base_snapshots(&state, &review, &files);
     let revision = state
         .store
         .create_revision(preflight_core::store::CreateRevisionInput {
             review_id: review.id,
             trigger: preflight_core::review::RevisionTrigger::Manual,
-            message: None,
+            message: Some(format!("Forked from review {id}")),
             files,
+            base_snapshots,
         })
         .await?;
-
-    let thread_count = state.store.get_threads(review.id, None).await?.len();
-    let response = ReviewResponse {
-        id: review.id,
-        title: review.title,
-        status: review.status,
-        file_count: revision.files.len(),
-        thread_count,
-        open_thread_count: 0,
-        revision_count: 1,
-        created_at: review.created_at,
-        updated_at: review.updated_at,
+    let threads = match state
+        .store
+        .copy_threads(review.id, unresolved, state.config.thread_limit())
+        .await
+    {
+        Ok(threads) => threads,
+        Err(err) => {
+            // Don't leave a half-made fork behind
+            let _ = state.store.delete_review(review.id).await;
+            return Err(err.into());
+        }
     };
+
+    let response = review_response(
+        review,
+        &threads,
+        std::slice::from_ref(&revision),
+        state.config.count_explanations,
+    );
     let _ = state.ws_tx.send(WsEvent {
         event_type: WsEventType::ReviewCreated,
         review_id: response.id.to_string(),
@@ -80,70 +333,165 @@ async fn create_review(
     Ok(Json(response))
 }
 
+/// Show what a review of the repo would contain, without creating it.
+#[utoipa::path(
+    post,
+    path = "/api/reviews/preview",
+    request_body = PreviewReviewRequest,
+    responses(
+        (status = 200, body = ReviewPreviewResponse),
+        (status = 400, description = "Invalid repo path or base ref"),
+    ),
+    tag = "reviews"
+)]
+async fn preview_review(
+    State(state): State<AppState>,
+    Json(request): Json<PreviewReviewRequest>,
+) -> Result<Json<ReviewPreviewResponse>, ApiError> {
+    let repo_path = std::path::Path::new(&request.repo_path);
+    let path_prefix = request.path_prefix.filter(|p| !p.is_empty());
+    let base_ref = state.metrics.time_git("resolve_base_ref", || {
+        git_diff::resolve_base_ref(repo_path, request.base_ref.as_deref())
+    });
+    let options = state.config.diff_options(request.diff_algorithm);
+    let files = state.metrics.time_git("diff", || {
+        git_diff::diff_against_base_with_options(
+            repo_path,
+            &base_ref,
+            path_prefix.as_deref(),
+            &options,
+        )
+    })?;
+    let lines = || files.iter().flat_map(|f| &f.hunks).flat_map(|h| &h.lines);
+    let additions = lines().filter(|l| l.kind == LineKind::Added).count();
+    let deletions = lines().filter(|l| l.kind == LineKind::Removed).count();
+    Ok(Json(ReviewPreviewResponse {
+        file_count: files.len(),
+        additions,
+        deletions,
+        files,
+    }))
+}
+
+/// Check a repo path a review is about to be created for, and report the
+/// base it would default to. A path that isn't a repository is not an error.
+#[utoipa::path(
+    post,
+    path = "/api/validate-repo",
+    request_body = ValidateRepoRequest,
+    responses((status = 200, body = ValidateRepoResponse)),
+    tag = "reviews"
+)]
+pub(crate) async fn validate_repo(
+    State(state): State<AppState>,
+    Json(request): Json<ValidateRepoRequest>,
+) -> Json<ValidateRepoResponse> {
+    let repo_path = std::path::Path::new(&request.repo_path);
+    if preflight_core::file_reader::validate_repo_path(repo_path).is_err() {
+        return Json(ValidateRepoResponse {
+            is_git_repo: false,
+            detected_base: None,
+            current_branch: None,
+            changed_file_count: None,
+        });
+    }
+    let detected_base = state.metrics.time_git("resolve_base_ref", || {
+        git_diff::detect_default_base(repo_path)
+    });
+    let changed_file_count = state
+        .metrics
+        .time_git("diff", || {
+            git_diff::diff_against_base_with_threshold(
+                repo_path,
+                &detected_base,
+                None,
+                state.config.rename_threshold,
+            )
+        })
+        .ok()
+        .map(|files| files.len());
+    Json(ValidateRepoResponse {
+        is_git_repo: true,
+        current_branch: git_diff::current_branch(repo_path),
+        detected_base: Some(detected_base),
+        changed_file_count,
+    })
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/reviews/find-or-create",
+    request_body = FindOrCreateReviewRequest,
+    responses(
+        (status = 200, body = ReviewResponse),
+        (status = 400, description = "Invalid repo path or base ref"),
+    ),
+    tag = "reviews"
+)]
 async fn find_or_create_review(
     State(state): State<AppState>,
     Json(request): Json<FindOrCreateReviewRequest>,
 ) -> Result<Json<ReviewResponse>, ApiError> {
     let repo_path_canonical = std::fs::canonicalize(&request.repo_path)
-        .map_err(|e| ApiError::BadRequest(format!("invalid repo_path: {e}")))?
+        .map_err(|e| {
+            ApiError::BadRequest(ErrorCode::BadRepoPath, format!("invalid repo_path: {e}"))
+        })?
         .to_string_lossy()
         .to_string();
 
     // Look for an existing open review for this repo
-    let summaries = state.store.list_reviews().await;
+    let summaries = state.store.list_reviews_by_status(ReviewStatus::Open).await;
     for summary in &summaries {
-        if summary.status != preflight_core::review::ReviewStatus::Open {
-            continue;
-        }
         let review = state.store.get_review(summary.id).await?;
-        let existing_canonical = std::fs::canonicalize(&review.repo_path)
-            .unwrap_or_else(|_| std::path::PathBuf::from(&review.repo_path))
+        let Some(existing_path) = review.repo_path.as_deref() else {
+            continue;
+        };
+        let existing_canonical = std::fs::canonicalize(existing_path)
+            .unwrap_or_else(|_| std::path::PathBuf::from(existing_path))
             .to_string_lossy()
             .to_string();
         if existing_canonical == repo_path_canonical {
             // Found a matching open review — return it
             let threads = state.store.get_threads(review.id, None).await?;
-            let thread_count = threads.len();
-            let open_thread_count = threads
-                .iter()
-                .filter(|t| {
-                    t.status == ThreadStatus::Open && t.origin != ThreadOrigin::AgentExplanation
-                })
-                .count();
             let revisions = state.store.get_revisions(review.id).await?;
-            let file_count = revisions.last().map(|r| r.files.len()).unwrap_or(0);
-            return Ok(Json(ReviewResponse {
-                id: review.id,
-                title: review.title,
-                status: review.status,
-                file_count,
-                thread_count,
-                open_thread_count,
-                revision_count: revisions.len(),
-                created_at: review.created_at,
-                updated_at: review.updated_at,
-            }));
+            return Ok(Json(review_response(
+                review,
+                &threads,
+                &revisions,
+                state.config.count_explanations,
+            )));
         }
     }
 
     // No existing review found — create a new one
     let repo_path = std::path::Path::new(&request.repo_path);
-    let base_ref = request
-        .base_ref
-        .unwrap_or_else(|| preflight_core::git_diff::detect_default_base(repo_path));
+    let base_ref = state.metrics.time_git("resolve_base_ref", || {
+        git_diff::resolve_base_ref(repo_path, request.base_ref.as_deref())
+    });
 
-    let files = preflight_core::git_diff::diff_against_base(repo_path, &base_ref)
-        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let files = state.metrics.time_git("diff", || {
+        git_diff::diff_against_base_with_threshold(
+            repo_path,
+            &base_ref,
+            None,
+            state.config.rename_threshold,
+        )
+    })?;
 
     let review = state
         .store
         .create_review(CreateReviewInput {
-            title: request.title,
-            repo_path: request.repo_path,
+            title: non_blank_title(request.title),
+            repo_path: Some(request.repo_path),
             base_ref,
+            path_prefix: None,
+            extra_repos: Vec::new(),
+            labels: Vec::new(),
+            diff_algorithm: Default::default(),
         })
         .await?;
 
+    let base_snapshots = super::revisions::base_snapshots(&state, &review, &files);
     let revision = state
         .store
         .create_revision(preflight_core::store::CreateRevisionInput {
@@ -151,20 +499,16 @@ async fn find_or_create_review(
             trigger: preflight_core::review::RevisionTrigger::Manual,
             message: None,
             files,
+            base_snapshots,
         })
         .await?;
 
-    let response = ReviewResponse {
-        id: review.id,
-        title: review.title,
-        status: review.status,
-        file_count: revision.files.len(),
-        thread_count: 0,
-        open_thread_count: 0,
-        revision_count: 1,
-        created_at: review.created_at,
-        updated_at: review.updated_at,
-    };
+    let response = review_response(
+        review,
+        &[],
+        std::slice::from_ref(&revision),
+        state.config.count_explanations,
+    );
     let _ = state.ws_tx.send(WsEvent {
         event_type: WsEventType::ReviewCreated,
         review_id: response.id.to_string(),
@@ -174,85 +518,378 @@ async fn find_or_create_review(
     Ok(Json(response))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/reviews",
+    params(ReviewListQuery),
+    responses(
+        (status = 200, body = Vec<ReviewResponse>),
+    ),
+    tag = "reviews"
+)]
 async fn list_reviews(
     State(state): State<AppState>,
+    Query(query): Query<ReviewListQuery>,
 ) -> Result<Json<Vec<ReviewResponse>>, ApiError> {
-    let summaries = state.store.list_reviews().await;
-    let mut responses = Vec::with_capacity(summaries.len());
-    for summary in summaries {
-        let review = state.store.get_review(summary.id).await?;
-        let revision_count = state
-            .store
-            .get_revisions(summary.id)
-            .await
-            .map(|r| r.len())
-            .unwrap_or(0);
-        responses.push(ReviewResponse {
-            id: review.id,
-            title: review.title,
-            status: review.status,
-            file_count: summary.file_count,
-            thread_count: summary.thread_count,
-            open_thread_count: summary.open_thread_count,
-            revision_count,
-            created_at: review.created_at,
-            updated_at: review.updated_at,
-        });
+    let mut summaries = match query.status {
+        Some(status) => state.store.list_reviews_by_status(status).await,
+        None => state.store.list_reviews().await,
+    };
+    if let Some(label) = &query.label {
+        summaries.retain(|s| s.labels.contains(label));
     }
+    let responses = summaries
+        .into_iter()
+        .map(|summary| summary_response(summary, state.config.count_explanations))
+        .collect();
     Ok(Json(responses))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/reviews/{id}",
+    params(("id" = Uuid, Path, description = "Review id")),
+    responses(
+        (status = 200, body = ReviewResponse),
+        (status = 404, description = "Review not found"),
+    ),
+    tag = "reviews"
+)]
 async fn get_review(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
 ) -> Result<Json<ReviewResponse>, ApiError> {
     let review = state.store.get_review(id).await?;
     let threads = state.store.get_threads(id, None).await?;
-    let thread_count = threads.len();
+    let revisions = state.store.get_revisions(id).await?;
+    Ok(Json(review_response(
+        review,
+        &threads,
+        &revisions,
+        state.config.count_explanations,
+    )))
+}
+
+/// Change a review's details. Only the fields present are updated.
+#[utoipa::path(
+    patch,
+    path = "/api/reviews/{id}",
+    params(("id" = Uuid, Path, description = "Review id")),
+    request_body = UpdateReviewRequest,
+    responses(
+        (status = 200, body = ReviewResponse),
+        (status = 404, description = "Review not found"),
+    ),
+    tag = "reviews"
+)]
+async fn update_review(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateReviewRequest>,
+) -> Result<Json<ReviewResponse>, ApiError> {
+    if let Some(labels) = request.labels {
+        state.store.set_labels(id, normalize_labels(labels)).await?;
+    }
+    let review = state.store.get_review(id).await?;
+    let threads = state.store.get_threads(id, None).await?;
+    let revisions = state.store.get_revisions(id).await?;
+    Ok(Json(review_response(
+        review,
+        &threads,
+        &revisions,
+        state.config.count_explanations,
+    )))
+}
+
+/// Trim labels and drop empty and repeated ones, keeping the given order.
+fn normalize_labels(labels: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(labels.len());
+    for label in labels {
+        let label = label.trim();
+        if !label.is_empty() && !normalized.iter().any(|l| l == label) {
+            normalized.push(label.to_string());
+        }
+    }
+    normalized
+}
+
+fn review_response(
+    review: Review,
+    threads: &[CommentThread],
+    revisions: &[Revision],
+    count_explanations: bool,
+) -> ReviewResponse {
     let open_thread_count = threads
         .iter()
-        .filter(|t| t.status == ThreadStatus::Open && t.origin != ThreadOrigin::AgentExplanation)
+        .filter(|t| t.counts_as_open(count_explanations))
         .count();
-    let revisions = state.store.get_revisions(id).await?;
-    let file_count = revisions.last().map(|r| r.files.len()).unwrap_or(0);
-    Ok(Json(ReviewResponse {
+    ReviewResponse {
         id: review.id,
         title: review.title,
         status: review.status,
-        file_count,
-        thread_count,
+        file_count: revisions.last().map(|r| r.files.len()).unwrap_or(0),
+        thread_count: threads.len(),
         open_thread_count,
         revision_count: revisions.len(),
+        path_prefix: review.path_prefix,
         created_at: review.created_at,
         updated_at: review.updated_at,
+        agent_seen_at: review.agent_seen_at,
+        decision: review.decision,
+        extra_repos: review.extra_repos,
+        labels: review.labels,
+    }
+}
+
+/// Like [`review_response`], from the counts a listing already has.
+fn summary_response(
+    summary: preflight_core::store::ReviewSummary,
+    count_explanations: bool,
+) -> ReviewResponse {
+    ReviewResponse {
+        id: summary.id,
+        title: summary.title,
+        status: summary.status,
+        file_count: summary.file_count,
+        thread_count: summary.thread_count,
+        open_thread_count: if count_explanations {
+            summary.open_thread_count_with_explanations
+        } else {
+            summary.open_thread_count
+        },
+        revision_count: summary.revision_count,
+        path_prefix: summary.path_prefix,
+        created_at: summary.created_at,
+        updated_at: summary.updated_at,
+        agent_seen_at: summary.agent_seen_at,
+        decision: summary.decision,
+        extra_repos: summary.extra_repos,
+        labels: summary.labels,
+    }
+}
+
+/// The review together with its files, threads, and revisions, so a client
+/// can load a review in one request. Each part matches what its own endpoint
+/// returns; use those to refresh parts incrementally.
+#[utoipa::path(
+    get,
+    path = "/api/reviews/{id}/full",
+    params(("id" = Uuid, Path, description = "Review id"), DraftsQuery),
+    responses(
+        (status = 200, body = ReviewFullResponse),
+        (status = 404, description = "Review not found"),
+    ),
+    tag = "reviews"
+)]
+async fn get_review_full(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<DraftsQuery>,
+) -> Result<Json<ReviewFullResponse>, ApiError> {
+    let review = state.store.get_review(id).await?;
+    let threads = state.store.get_threads(id, None).await?;
+    let revisions = state.store.get_revisions(id).await?;
+    let renames = preflight_core::renames::build_rename_map(&revisions);
+
+    let files = revisions
+        .last()
+        .map(|latest| {
+            super::files::file_list(latest, &threads, &renames, state.config.count_explanations)
+        })
+        .unwrap_or_default();
+    let human_seen_at = review.human_seen_at;
+    let review = review_response(
+        review,
+        &threads,
+        &revisions,
+        state.config.count_explanations,
+    );
+    let threads = super::threads::thread_responses(
+        threads,
+        &renames,
+        &*state.agent_status.lock().await,
+        human_seen_at,
+        &super::threads::ThreadFilter::all(query.include_drafts),
+    );
+    let revisions = revisions
+        .into_iter()
+        .map(super::revisions::revision_response)
+        .collect();
+    Ok(Json(ReviewFullResponse {
+        review,
+        files,
+        threads,
+        revisions,
     }))
 }
 
+fn activity(kind: ActivityKind, timestamp: chrono::DateTime<Utc>) -> ActivityEntry {
+    ActivityEntry {
+        kind,
+        timestamp,
+        revision_number: None,
+        thread_id: None,
+        comment_id: None,
+        file_path: None,
+        author_type: None,
+        status: None,
+    }
+}
+
+/// Chronological feed of everything that happened in a review, derived from
+/// the review, its revisions, and its threads' comments. With `?author=`,
+/// only that author's comments are listed.
+#[utoipa::path(
+    get,
+    path = "/api/reviews/{id}/activity",
+    params(("id" = Uuid, Path, description = "Review id"), ActivityQuery),
+    responses(
+        (status = 200, body = Vec<ActivityEntry>),
+        (status = 404, description = "Review not found"),
+    ),
+    tag = "reviews"
+)]
+async fn get_activity(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ActivityQuery>,
+) -> Result<Json<Vec<ActivityEntry>>, ApiError> {
+    let review = state.store.get_review(id).await?;
+    let revisions = state.store.get_revisions(id).await?;
+    let threads = state.store.get_threads(id, None).await?;
+
+    let mut entries = vec![activity(ActivityKind::ReviewCreated, review.created_at)];
+    for revision in &revisions {
+        entries.push(ActivityEntry {
+            revision_number: Some(revision.revision_number),
+            ..activity(ActivityKind::RevisionCreated, revision.created_at)
+        });
+    }
+    for thread in &threads {
+        for (i, comment) in thread.comments.iter().enumerate() {
+            let kind = if i == 0 {
+                ActivityKind::ThreadCreated
+            } else {
+                ActivityKind::CommentAdded
+            };
+            entries.push(ActivityEntry {
+                thread_id: Some(thread.id),
+                comment_id: Some(comment.id),
+                file_path: Some(thread.file_path.clone()),
+                author_type: Some(comment.author_type.clone()),
+                ..activity(kind, comment.created_at)
+            });
+        }
+    }
+    // Only the most recent status change is recorded on the review itself
+    if let Some(changed_at) = review.status_changed_at {
+        entries.push(ActivityEntry {
+            status: Some(review.status.clone()),
+            ..activity(ActivityKind::StatusChanged, changed_at)
+        });
+    }
+
+    if query.author.is_some() {
+        entries.retain(|e| {
+            e.author_type
+                .as_ref()
+                .is_some_and(|a| super::threads::author_matches(query.author, a))
+        });
+    }
+
+    entries.sort_by_key(|e| e.timestamp);
+    Ok(Json(entries))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/reviews/{id}/status",
+    params(("id" = Uuid, Path, description = "Review id")),
+    request_body = UpdateReviewStatusRequest,
+    responses(
+        (status = 204),
+        (status = 404, description = "Review not found"),
+    ),
+    tag = "reviews"
+)]
 async fn update_review_status(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
     Json(request): Json<UpdateReviewStatusRequest>,
 ) -> Result<StatusCode, ApiError> {
-    state
-        .store
-        .update_review_status(id, request.status.clone())
-        .await?;
+    set_review_status(&state, id, request.status).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Update a review's status and announce it with `ReviewStatusChanged`.
+pub(crate) async fn set_review_status(
+    state: &AppState,
+    id: Uuid,
+    status: ReviewStatus,
+) -> Result<(), ApiError> {
+    state.store.update_review_status(id, status.clone()).await?;
+    let (thread_count, open_thread_count) = super::threads::thread_counts(state, id).await?;
     let _ = state.ws_tx.send(WsEvent {
         event_type: WsEventType::ReviewStatusChanged,
         review_id: id.to_string(),
-        payload: serde_json::json!({ "status": request.status }),
+        payload: serde_json::json!({
+            "status": status,
+            "thread_count": thread_count,
+            "open_thread_count": open_thread_count
+        }),
+        timestamp: Utc::now(),
+    });
+    Ok(())
+}
+
+/// Record the reviewer's verdict and announce it with `ReviewDecisionChanged`.
+/// The review's status is left alone: approving doesn't close it.
+#[utoipa::path(
+    post,
+    path = "/api/reviews/{id}/decision",
+    params(("id" = Uuid, Path, description = "Review id")),
+    request_body = SetReviewDecisionRequest,
+    responses(
+        (status = 204),
+        (status = 404, description = "Review not found"),
+    ),
+    tag = "reviews"
+)]
+async fn set_review_decision(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<SetReviewDecisionRequest>,
+) -> Result<StatusCode, ApiError> {
+    state.store.set_decision(id, request.decision).await?;
+    let _ = state.ws_tx.send(WsEvent {
+        event_type: WsEventType::ReviewDecisionChanged,
+        review_id: id.to_string(),
+        payload: serde_json::json!({ "decision": request.decision }),
         timestamp: Utc::now(),
     });
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/reviews/{id}/request-revision",
+    params(("id" = Uuid, Path, description = "Review id")),
+    responses(
+        (status = 204),
+        (status = 404, description = "Review not found"),
+    ),
+    tag = "reviews"
+)]
 async fn request_revision(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
 ) -> Result<StatusCode, ApiError> {
     let review = state.store.get_review(id).await?;
     if review.status != preflight_core::review::ReviewStatus::Open {
-        return Err(ApiError::BadRequest("Review is not open".into()));
+        return Err(ApiError::BadRequest(
+            ErrorCode::ReviewNotOpen,
+            "Review is not open".into(),
+        ));
     }
     let _ = state.ws_tx.send(WsEvent {
         event_type: WsEventType::RevisionRequested,
@@ -263,6 +900,17 @@ async fn request_revision(
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/reviews/{id}/agent-presence",
+    params(("id" = Uuid, Path, description = "Review id")),
+    request_body = crate::types::UpdateAgentPresenceRequest,
+    responses(
+        (status = 204),
+        (status = 404, description = "Review not found"),
+    ),
+    tag = "reviews"
+)]
 async fn update_agent_presence(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
@@ -278,6 +926,103 @@ async fn update_agent_presence(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Record that the agent has read the whole review, e.g. every comment on
+/// it. The mark stays until the next human comment.
+#[utoipa::path(
+    put,
+    path = "/api/reviews/{id}/agent-seen",
+    params(("id" = Uuid, Path, description = "Review id")),
+    responses(
+        (status = 204),
+        (status = 404, description = "Review not found"),
+    ),
+    tag = "reviews"
+)]
+async fn mark_agent_seen(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    set_agent_seen(&state, id, Some(Utc::now())).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Record that the human has caught up on the review, so only comments after
+/// now count toward each thread's `unread_count`.
+#[utoipa::path(
+    post,
+    path = "/api/reviews/{id}/mark-seen",
+    params(("id" = Uuid, Path, description = "Review id")),
+    responses(
+        (status = 204),
+        (status = 404, description = "Review not found"),
+    ),
+    tag = "reviews"
+)]
+async fn mark_human_seen(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    state.store.set_human_seen(id, Utc::now()).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Update when the agent last saw the review and announce it with
+/// `AgentSeenChanged`.
+async fn set_agent_seen(
+    state: &AppState,
+    id: Uuid,
+    seen_at: Option<DateTime<Utc>>,
+) -> Result<(), ApiError> {
+    state.store.set_agent_seen(id, seen_at).await?;
+    let _ = state.ws_tx.send(WsEvent {
+        event_type: WsEventType::AgentSeenChanged,
+        review_id: id.to_string(),
+        payload: serde_json::json!({ "agent_seen_at": seen_at }),
+        timestamp: Utc::now(),
+    });
+    Ok(())
+}
+
+/// Clear the agent's seen mark because a human said something new. Does
+/// nothing if the review isn't marked.
+pub(crate) async fn clear_agent_seen(state: &AppState, id: Uuid) -> Result<(), ApiError> {
+    if state.store.get_review(id).await?.agent_seen_at.is_some() {
+        set_agent_seen(state, id, None).await?;
+    }
+    Ok(())
+}
+
+/// Cheaply keep an agent shown as present. Each heartbeat restarts the
+/// disconnect grace period; nothing is broadcast unless the review was
+/// disconnected.
+#[utoipa::path(
+    post,
+    path = "/api/reviews/{id}/heartbeat",
+    params(("id" = Uuid, Path, description = "Review id")),
+    responses(
+        (status = 204),
+        (status = 404, description = "Review not found"),
+    ),
+    tag = "reviews"
+)]
+async fn agent_heartbeat(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    state.store.get_review(id).await?;
+    state.agent_presence.heartbeat(id).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/reviews/{id}/agent-status",
+    params(("id" = Uuid, Path, description = "Review id")),
+    responses(
+        (status = 200, body = crate::types::AgentPresenceResponse),
+    ),
+    tag = "reviews"
+)]
 async fn get_agent_presence(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
@@ -288,10 +1033,47 @@ async fn get_agent_presence(
     Ok(Json(crate::types::AgentPresenceResponse { connected }))
 }
 
+/// Whether a human is viewing the review, i.e. has a WebSocket open with
+/// `?role=human&review_id=...`.
+#[utoipa::path(
+    get,
+    path = "/api/reviews/{id}/human-status",
+    params(("id" = Uuid, Path, description = "Review id")),
+    responses(
+        (status = 200, body = crate::types::HumanPresenceResponse),
+        (status = 404, description = "Review not found"),
+    ),
+    tag = "reviews"
+)]
+async fn get_human_presence(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<crate::types::HumanPresenceResponse>, ApiError> {
+    state.store.get_review(id).await?;
+    let connected = state.human_presence.is_connected(id).await;
+    Ok(Json(crate::types::HumanPresenceResponse { connected }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/reviews/{id}",
+    params(("id" = Uuid, Path, description = "Review id")),
+    responses(
+        (status = 204),
+        (status = 404, description = "Review not found"),
+    ),
+    tag = "reviews"
+)]
 async fn delete_review(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
 ) -> Result<StatusCode, ApiError> {
+    remove_review(&state, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Delete a review and announce it with `ReviewDeleted`.
+pub(crate) async fn remove_review(state: &AppState, id: Uuid) -> Result<(), ApiError> {
     state.store.delete_review(id).await?;
     let _ = state.ws_tx.send(WsEvent {
         event_type: WsEventType::ReviewDeleted,
@@ -299,9 +1081,17 @@ async fn delete_review(
         payload: serde_json::json!({ "review_id": id }),
         timestamp: Utc::now(),
     });
-    Ok(StatusCode::NO_CONTENT)
+    Ok(())
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/reviews",
+    responses(
+        (status = 204),
+    ),
+    tag = "reviews"
+)]
 async fn delete_closed_reviews(State(state): State<AppState>) -> Result<StatusCode, ApiError> {
     let deleted_ids = state.store.delete_closed_reviews().await?;
     for id in deleted_ids {
@@ -323,13 +1113,7 @@ mod tests {
     use tower::ServiceExt;
 
     async fn test_app() -> axum::Router {
-        let dir = tempfile::TempDir::new().unwrap();
-        let path = dir.path().join("state.json");
-        let store = preflight_core::json_store::JsonFileStore::new(&path)
-            .await
-            .unwrap();
-        // Leak the TempDir so it stays alive for the duration of the test
-        Box::leak(Box::new(dir));
+        let store = preflight_core::memory_store::MemoryStore::new();
         crate::app(std::sync::Arc::new(store))
     }
 
@@ -449,91 +1233,914 @@ mod tests {
         assert!(json["updated_at"].is_string());
     }
 
+    const PIPED_DIFF: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -10,3 +10,3 @@ fn helper() {
+ fn answer() -> u32 {
+-    41
++    42
+ }
+diff --git a/README.md b/README.md
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/README.md
+@@ -0,0 +1 @@
++# Piped
+";
+
     #[tokio::test]
-    async fn test_create_review_bad_repo_path() {
+    async fn test_create_review_from_piped_diff_lists_its_files() {
         let app = test_app().await;
 
         let response = app
+            .clone()
             .oneshot(
                 Request::builder()
                     .method("POST")
-                    .uri("/api/reviews")
+                    .uri("/api/reviews/from-diff")
                     .header("content-type", "application/json")
                     .body(Body::from(
-                        serde_json::json!({
-                            "title": "Bad repo",
-                            "repo_path": "/tmp/nonexistent_repo_path_xyz",
-                            "base_ref": "HEAD"
-                        })
-                        .to_string(),
+                        serde_json::json!({ "title": "Piped", "diff": PIPED_DIFF }).to_string(),
                     ))
                     .unwrap(),
             )
             .await
             .unwrap();
-
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
-    }
-
-    #[tokio::test]
-    async fn test_list_reviews() {
-        let app = test_app().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert_eq!(json["file_count"], 2);
+        let id = json["id"].as_str().unwrap();
 
         let response = app
+            .clone()
             .oneshot(
                 Request::builder()
-                    .uri("/api/reviews")
+                    .uri(format!("/api/reviews/{id}/files"))
                     .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
-
         assert_eq!(response.status(), StatusCode::OK);
-
-        let json = body_json(response).await;
-        assert!(json.is_array());
-    }
-
-    #[tokio::test]
-    async fn test_get_review_existing() {
-        let app = test_app().await;
-        let (_repo_dir, repo_path) = setup_test_repo();
-        let id = create_review_for_test(&app, &repo_path).await;
-
-        let get_response = app
+        let files = body_json(response).await;
+        let paths: Vec<&str> = files
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["path"].as_str().unwrap())
+            .collect();
+        // Listed in path order, not the diff's order
+        assert_eq!(paths, ["README.md", "src/lib.rs"]);
+
+        // With no repository to read from, only the diff's lines come back
+        let response = app
             .oneshot(
                 Request::builder()
-                    .uri(format!("/api/reviews/{id}"))
+                    .uri(format!("/api/reviews/{id}/content/src/lib.rs"))
                     .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
-
-        assert_eq!(get_response.status(), StatusCode::OK);
-        let json = body_json(get_response).await;
-        assert_eq!(json["id"], id);
-        assert_eq!(json["title"], "Test review");
-        assert_eq!(json["revision_count"], 1);
+        assert_eq!(response.status(), StatusCode::OK);
+        let content = body_json(response).await;
+        assert_eq!(content["partial"], true);
+        let lines: Vec<(u64, &str)> = content["lines"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|l| {
+                (
+                    l["line_no"].as_u64().unwrap(),
+                    l["content"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            [(10, "fn answer() -> u32 {"), (11, "    42"), (12, "}")]
+        );
     }
 
     #[tokio::test]
-    async fn test_get_review_not_found() {
+    async fn test_create_review_from_diff_rejects_text_without_files() {
         let app = test_app().await;
-        let fake_id = uuid::Uuid::new_v4();
 
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri(format!("/api/reviews/{fake_id}"))
-                    .body(Body::empty())
-                    .unwrap(),
+                    .method("POST")
+                    .uri("/api/reviews/from-diff")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "diff": "not a diff\n" }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_create_review_with_histogram_diff_algorithm() {
+        let app = test_app().await;
+        let (_repo_dir, repo_path) = setup_test_repo();
+
+        let create = |algorithm: &str| {
+            Request::builder()
+                .method("POST")
+                .uri("/api/reviews")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "repo_path": repo_path,
+                        "base_ref": "HEAD",
+                        "diff_algorithm": algorithm
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+        let response = app.clone().oneshot(create("histogram")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert_eq!(json["file_count"], 1);
+        let id = json["id"].as_str().unwrap();
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/files/src/main.rs"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let file = body_json(response).await;
+        assert!(!file["hunks"].as_array().unwrap().is_empty());
+
+        let response = app.oneshot(create("bogus")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_preview_review_creates_nothing_and_matches_real_review() {
+        let app = test_app().await;
+        let (_repo_dir, repo_path) = setup_test_repo();
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/reviews/preview")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "repo_path": repo_path, "base_ref": "HEAD" })
+                            .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let preview = body_json(response).await;
+        assert_eq!(preview["file_count"], 1);
+        assert!(preview["additions"].as_u64().unwrap() > 0);
+        let preview_paths: Vec<_> = preview["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["new_path"].as_str().unwrap().to_string())
+            .collect();
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/api/reviews")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(body_json(response).await.as_array().unwrap().is_empty());
+
+        let id = create_review_for_test(&app, &repo_path).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/files"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let review_paths: Vec<_> = body_json(response)
+            .await
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["path"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(preview_paths, review_paths);
+    }
+
+    #[tokio::test]
+    async fn test_validate_repo_reports_repo_and_non_repo() {
+        let app = test_app().await;
+        let (_repo_dir, repo_path) = setup_test_repo();
+        let not_a_repo = tempfile::TempDir::new().unwrap();
+
+        let validate = |path: String| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/validate-repo")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "repo_path": path }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+        };
+
+        let response = validate(repo_path).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert_eq!(json["is_git_repo"], true);
+        assert!(json["detected_base"].is_string());
+        assert!(json["current_branch"].is_string());
+        assert_eq!(json["changed_file_count"], 1);
+
+        let response = validate(not_a_repo.path().to_str().unwrap().to_string())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            body_json(response).await,
+            serde_json::json!({ "is_git_repo": false })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_review_blank_title_becomes_none() {
+        let app = test_app().await;
+        let (_repo_dir, repo_path) = setup_test_repo();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/reviews")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({
+                            "title": "  ",
+                            "repo_path": repo_path,
+                            "base_ref": "HEAD"
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert!(json["title"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_create_review_auto_base_ref_uses_merge_base() {
+        use std::process::Command;
+
+        let app = test_app().await;
+        let (repo_dir, repo_path) = setup_test_repo();
+        let p = repo_dir.path();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(p)
+                .output()
+                .unwrap()
+        };
+        // Commit the pending change on a feature branch off main
+        git(&["branch", "-M", "main"]);
+        git(&["checkout", "-b", "feature"]);
+        git(&["commit", "-am", "feature work"]);
+
+        let create = |base_ref: serde_json::Value| {
+            let app = app.clone();
+            let repo_path = repo_path.clone();
+            async move {
+                let response = app
+                    .oneshot(
+                        Request::builder()
+                            .method("POST")
+                            .uri("/api/reviews")
+                            .header("content-type", "application/json")
+                            .body(Body::from(
+                                serde_json::json!({
+                                    "repo_path": repo_path,
+                                    "base_ref": base_ref,
+                                    "allow_empty": true
+                                })
+                                .to_string(),
+                            ))
+                            .unwrap(),
+                    )
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                body_json(response).await
+            }
+        };
+
+        // Against HEAD the committed feature work is invisible
+        assert_eq!(create(serde_json::json!("HEAD")).await["file_count"], 0);
+        // "auto" and an omitted base diff against the merge-base with main
+        assert_eq!(create(serde_json::json!("auto")).await["file_count"], 1);
+        assert_eq!(create(serde_json::Value::Null).await["file_count"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_create_review_on_unchanged_repo_is_rejected_unless_allowed() {
+        use std::process::Command;
+
+        let app = test_app().await;
+        let (repo_dir, repo_path) = setup_test_repo();
+        Command::new("git")
+            .args(["commit", "-am", "commit everything"])
+            .current_dir(repo_dir.path())
+            .output()
+            .unwrap();
+        let create = |allow_empty: bool| {
+            Request::builder()
+                .method("POST")
+                .uri("/api/reviews")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "repo_path": repo_path,
+                        "base_ref": "HEAD",
+                        "allow_empty": allow_empty
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(create(false)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let json = body_json(response).await;
+        assert_eq!(json["code"], "no_changes");
+        assert_eq!(json["error"], "no changes to review against HEAD");
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/api/reviews")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(body_json(response).await.as_array().unwrap().is_empty());
+
+        let response = app.oneshot(create(true)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_json(response).await["file_count"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_create_review_with_path_prefix_excludes_other_files() {
+        let app = test_app().await;
+        let (repo_dir, repo_path) = setup_test_repo();
+        std::fs::create_dir_all(repo_dir.path().join("docs")).unwrap();
+        std::fs::write(repo_dir.path().join("docs/notes.md"), "notes\n").unwrap();
+        std::process::Command::new("git")
+            .args(["add", "docs"])
+            .current_dir(repo_dir.path())
+            .output()
+            .unwrap();
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/reviews")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({
+                            "title": "Scoped review",
+                            "repo_path": repo_path,
+                            "base_ref": "HEAD",
+                            "path_prefix": "src"
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert_eq!(json["file_count"], 1);
+        assert_eq!(json["path_prefix"], "src");
+        let id = json["id"].as_str().unwrap().to_string();
+
+        // A change outside the prefix alone does not count as a new revision
+        std::fs::write(repo_dir.path().join("docs/notes.md"), "more notes\n").unwrap();
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/reviews/{id}/revisions"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "trigger": "Manual" }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/files"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let json = body_json(response).await;
+        let files = json.as_array().unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0]["path"], "src/main.rs");
+    }
+
+    #[tokio::test]
+    async fn test_create_review_bad_repo_path() {
+        let app = test_app().await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/reviews")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({
+                            "title": "Bad repo",
+                            "repo_path": "/tmp/nonexistent_repo_path_xyz",
+                            "base_ref": "HEAD"
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let json = body_json(response).await;
+        assert_eq!(json["code"], "bad_repo_path");
+    }
+
+    #[tokio::test]
+    async fn test_create_review_of_single_commit() {
+        use std::process::Command;
+
+        let app = test_app().await;
+        let (repo_dir, repo_path) = setup_test_repo();
+        let p = repo_dir.path();
+        Command::new("git")
+            .args(["commit", "-am", "say hello"])
+            .current_dir(p)
+            .output()
+            .unwrap();
+        let commit = String::from_utf8(
+            Command::new("git")
+                .args(["rev-parse", "HEAD"])
+                .current_dir(p)
+                .output()
+                .unwrap()
+                .stdout,
+        )
+        .unwrap()
+        .trim()
+        .to_string();
+        // Uncommitted work after the commit must not leak into the review
+        std::fs::write(p.join("src/main.rs"), "fn main() { todo!() }\n").unwrap();
+        std::fs::write(p.join("scratch.rs"), "fn scratch() {}\n").unwrap();
+
+        let post = |body: serde_json::Value| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/reviews")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+        let response = post(serde_json::json!({ "repo_path": repo_path, "commit": commit }))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert_eq!(json["file_count"], 1);
+        let id = json["id"].as_str().unwrap();
+
+        let content = |version: &'static str| {
+            app.clone().oneshot(
+                Request::builder()
+                    .uri(format!(
+                        "/api/reviews/{id}/content/src/main.rs?version={version}"
+                    ))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+        let new = body_json(content("new").await.unwrap()).await;
+        assert_eq!(new["lines"][0]["content"], "use std::io;");
+        let old = body_json(content("old").await.unwrap()).await;
+        assert_eq!(old["lines"][0]["content"], "fn main() {}");
+
+        // A ref is pinned to the commit it names when the review is created
+        let response = post(serde_json::json!({ "repo_path": repo_path, "commit": "HEAD" }))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let pinned_id = body_json(response).await["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        Command::new("git")
+            .args(["commit", "-am", "todo"])
+            .current_dir(p)
+            .output()
+            .unwrap();
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!(
+                        "/api/reviews/{pinned_id}/content/src/main.rs?version=new"
+                    ))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let new = body_json(response).await;
+        assert_eq!(new["lines"][0]["content"], "use std::io;");
+
+        let response = post(serde_json::json!({ "repo_path": repo_path, "commit": "deadbeef" }))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(body_json(response).await["code"], "git_error");
+
+        let response = post(serde_json::json!({
+            "repo_path": repo_path,
+            "commit": commit,
+            "base_ref": "HEAD"
+        }))
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_create_review_across_extra_repo() {
+        let app = test_app().await;
+        let (_repo_dir, repo_path) = setup_test_repo();
+        let (sibling_dir, sibling_path) = setup_test_repo();
+        std::fs::write(sibling_dir.path().join("src/main.rs"), "fn sibling() {}\n").unwrap();
+
+        let send = |method: &str, uri: String, body: Option<serde_json::Value>| {
+            let builder = Request::builder().method(method).uri(uri);
+            let request = match body {
+                Some(json) => builder
+                    .header("content-type", "application/json")
+                    .body(Body::from(json.to_string())),
+                None => builder.body(Body::empty()),
+            };
+            app.clone().oneshot(request.unwrap())
+        };
+        let response = send(
+            "POST",
+            "/api/reviews".into(),
+            Some(serde_json::json!({
+                "repo_path": repo_path,
+                "base_ref": "HEAD",
+                "extra_repos": [
+                    { "repo_path": sibling_path, "base_ref": "HEAD", "prefix": "sibling/" }
+                ]
+            })),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert_eq!(json["file_count"], 2);
+        assert_eq!(json["extra_repos"][0]["prefix"], "sibling");
+        let id = json["id"].as_str().unwrap().to_string();
+
+        let response = send("GET", format!("/api/reviews/{id}/files"), None)
+            .await
+            .unwrap();
+        let files = body_json(response).await;
+        let paths: Vec<&str> = files
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["path"].as_str().unwrap())
+            .collect();
+        assert!(paths.contains(&"src/main.rs"));
+        assert!(paths.contains(&"sibling/src/main.rs"));
+
+        // Content is read back from the repo the file came from
+        let content = |version: &str| {
+            send(
+                "GET",
+                format!("/api/reviews/{id}/content/sibling/src/main.rs?version={version}"),
+                None,
+            )
+        };
+        let new = body_json(content("new").await.unwrap()).await;
+        assert_eq!(new["lines"][0]["content"], "fn sibling() {}");
+        let old = body_json(content("old").await.unwrap()).await;
+        assert_eq!(old["lines"][0]["content"], "fn main() {}");
+        let main = send(
+            "GET",
+            format!("/api/reviews/{id}/content/src/main.rs"),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(body_json(main).await["lines"][0]["content"], "use std::io;");
+
+        // New revisions keep diffing both repos
+        std::fs::write(sibling_dir.path().join("src/main.rs"), "fn sibling2() {}\n").unwrap();
+        let response = send(
+            "POST",
+            format!("/api/reviews/{id}/revisions"),
+            Some(serde_json::json!({ "trigger": "Manual" })),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_json(response).await["file_count"], 2);
+
+        for prefix in ["", "/"] {
+            let response = send(
+                "POST",
+                "/api/reviews".into(),
+                Some(serde_json::json!({
+                    "repo_path": repo_path,
+                    "extra_repos": [{ "repo_path": sibling_path, "prefix": prefix }]
+                })),
+            )
+            .await
+            .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_reviews() {
+        let app = test_app().await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/reviews")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let json = body_json(response).await;
+        assert!(json.is_array());
+    }
+
+    #[tokio::test]
+    async fn test_list_reviews_carries_review_timestamps() {
+        let app = test_app().await;
+        let (_repo_dir, repo_path) = setup_test_repo();
+        let id = create_review_for_test(&app, &repo_path).await;
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("PATCH")
+                    .uri(format!("/api/reviews/{id}/status"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"status":"Closed"}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let get = |uri: String| {
+            app.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        };
+        let review = body_json(get(format!("/api/reviews/{id}")).await.unwrap()).await;
+        let list = body_json(get("/api/reviews".into()).await.unwrap()).await;
+        let listed = &list[0];
+        assert_eq!(listed["id"], id.as_str());
+        assert_eq!(listed["created_at"], review["created_at"]);
+        assert_eq!(listed["updated_at"], review["updated_at"]);
+        assert_ne!(listed["updated_at"], listed["created_at"]);
+        assert_eq!(listed["status"], "Closed");
+    }
+
+    #[tokio::test]
+    async fn test_review_labels_set_updated_and_filtered() {
+        let app = test_app().await;
+        let (_repo_dir, repo_path) = setup_test_repo();
+
+        let send = |method: &str, uri: String, body: Option<serde_json::Value>| {
+            let builder = Request::builder().method(method).uri(uri);
+            let request = match body {
+                Some(json) => builder
+                    .header("content-type", "application/json")
+                    .body(Body::from(json.to_string())),
+                None => builder.body(Body::empty()),
+            };
+            app.clone().oneshot(request.unwrap())
+        };
+        let response = send(
+            "POST",
+            "/api/reviews".into(),
+            Some(serde_json::json!({
+                "repo_path": repo_path,
+                "base_ref": "HEAD",
+                "labels": [" pr:42 ", "pr:42", ""]
+            })),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert_eq!(json["labels"], serde_json::json!(["pr:42"]));
+        let labeled = json["id"].as_str().unwrap().to_string();
+        let unlabeled = create_review_for_test(&app, &repo_path).await;
+
+        let listed = |query: &'static str| async move {
+            let response = send("GET", format!("/api/reviews{query}"), None)
+                .await
+                .unwrap();
+            body_json(response)
+                .await
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|r| r["id"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(listed("?label=pr:42").await, [labeled.as_str()]);
+        assert!(listed("?label=pr:7").await.is_empty());
+
+        let response = send(
+            "PATCH",
+            format!("/api/reviews/{unlabeled}"),
+            Some(serde_json::json!({ "labels": ["pr:42", "ticket:ENG-7"] })),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            body_json(response).await["labels"],
+            serde_json::json!(["pr:42", "ticket:ENG-7"])
+        );
+        assert_eq!(listed("?label=pr:42").await.len(), 2);
+        assert_eq!(listed("?label=ticket:ENG-7").await, [unlabeled.as_str()]);
+
+        // Omitted fields are left alone
+        let response = send(
+            "PATCH",
+            format!("/api/reviews/{labeled}"),
+            Some(serde_json::json!({})),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            body_json(response).await["labels"],
+            serde_json::json!(["pr:42"])
+        );
+
+        let response = send(
+            "PATCH",
+            format!("/api/reviews/{}", uuid::Uuid::new_v4()),
+            Some(serde_json::json!({ "labels": [] })),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(response).await["code"], "review_not_found");
+    }
+
+    #[tokio::test]
+    async fn test_list_reviews_filtered_by_status() {
+        let app = test_app().await;
+        let (_repo_dir, repo_path) = setup_test_repo();
+        let open_id = create_review_for_test(&app, &repo_path).await;
+        let closed_id = create_review_for_test(&app, &repo_path).await;
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("PATCH")
+                    .uri(format!("/api/reviews/{closed_id}/status"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "status": "Closed" }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/reviews?status=Open")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        let reviews = json.as_array().unwrap();
+        assert_eq!(reviews.len(), 1);
+        assert_eq!(reviews[0]["id"], open_id);
+    }
+
+    #[tokio::test]
+    async fn test_get_review_existing() {
+        let app = test_app().await;
+        let (_repo_dir, repo_path) = setup_test_repo();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        let get_response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(get_response.status(), StatusCode::OK);
+        let json = body_json(get_response).await;
+        assert_eq!(json["id"], id);
+        assert_eq!(json["title"], "Test review");
+        assert_eq!(json["revision_count"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_review_not_found() {
+        let app = test_app().await;
+        let fake_id = uuid::Uuid::new_v4();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{fake_id}"))
+                    .body(Body::empty())
+                    .unwrap(),
             )
             .await
             .unwrap();
 
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let json = body_json(response).await;
+        assert_eq!(json["code"], "review_not_found");
+        assert_eq!(json["error"], format!("review not found: {fake_id}"));
     }
 
     #[tokio::test]
@@ -559,13 +2166,266 @@ mod tests {
         assert_eq!(patch_response.status(), StatusCode::NO_CONTENT);
     }
 
+    /// Open an agent explanation on a fresh review and return its
+    /// `open_thread_count` from the list, get and file endpoints.
+    async fn explanation_open_counts(count_explanations: bool) -> [serde_json::Value; 3] {
+        let config = crate::state::ServerConfig {
+            count_explanations,
+            ..Default::default()
+        };
+        let app = crate::app_with_config(
+            std::sync::Arc::new(preflight_core::memory_store::MemoryStore::new()),
+            config,
+        );
+        let (_repo_dir, repo_path) = setup_test_repo();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/reviews/{id}/threads"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({
+                            "file_path": "src/main.rs",
+                            "line_start": 1,
+                            "line_end": 1,
+                            "origin": "AgentExplanation",
+                            "body": "switched to io for logging",
+                            "author_type": "Agent"
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let mut counts = Vec::new();
+        for uri in [
+            "/api/reviews".to_string(),
+            format!("/api/reviews/{id}"),
+            format!("/api/reviews/{id}/files"),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let json = body_json(response).await;
+            let entry = if json.is_array() { &json[0] } else { &json };
+            counts.push(entry["open_thread_count"].clone());
+        }
+        counts.try_into().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_explanations_count_as_open_only_when_configured() {
+        assert_eq!(explanation_open_counts(false).await, [0, 0, 0]);
+        assert_eq!(explanation_open_counts(true).await, [1, 1, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_get_review_open_thread_count() {
+        let app = test_app().await;
+        let (_repo_dir, repo_path) = setup_test_repo();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        // Create a thread
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/reviews/{id}/threads"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({
+                            "file_path": "src/main.rs",
+                            "line_start": 1,
+                            "line_end": 1,
+                            "origin": "Comment",
+                            "body": "test comment",
+                            "author_type": "Human"
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let thread_json = body_json(response).await;
+        let thread_id = thread_json["id"].as_str().unwrap();
+
+        // GET review — should have 1 open thread
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let json = body_json(response).await;
+        assert_eq!(json["thread_count"], 1);
+        assert_eq!(json["open_thread_count"], 1);
+
+        // Resolve the thread
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .method("PATCH")
+                    .uri(format!("/api/threads/{thread_id}/status"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "status": "Resolved" }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        // GET review — should have 0 open threads
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let json = body_json(response).await;
+        assert_eq!(json["thread_count"], 1);
+        assert_eq!(json["open_thread_count"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_fork_review_carries_over_unresolved_threads() {
+        let app = test_app().await;
+        let (_repo_dir, repo_path) = setup_test_repo();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        let mut thread_ids = Vec::new();
+        for body in ["keep me", "resolve me"] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .uri(format!("/api/reviews/{id}/threads"))
+                        .header("content-type", "application/json")
+                        .body(Body::from(
+                            serde_json::json!({
+                                "file_path": "src/main.rs",
+                                "line_start": 1,
+                                "line_end": 1,
+                                "origin": "Comment",
+                                "body": body,
+                                "author_type": "Human"
+                            })
+                            .to_string(),
+                        ))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let json = body_json(response).await;
+            thread_ids.push(json["id"].as_str().unwrap().to_string());
+        }
+
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .method("PATCH")
+                    .uri(format!("/api/threads/{}/status", thread_ids[1]))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "status": "Resolved" }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/reviews/{id}/fork"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(serde_json::json!({}).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        let fork_id = json["id"].as_str().unwrap().to_string();
+        assert_ne!(fork_id, id);
+        assert_eq!(json["title"], "Test review");
+        assert_eq!(json["revision_count"], 1);
+        assert_eq!(json["thread_count"], 1);
+        assert_eq!(json["open_thread_count"], 1);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{fork_id}/revisions"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let revisions = body_json(response).await;
+        assert_eq!(revisions.as_array().unwrap().len(), 1);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{fork_id}/threads"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let threads = body_json(response).await;
+        let threads = threads.as_array().unwrap();
+        assert_eq!(threads.len(), 1);
+        assert_ne!(threads[0]["id"], thread_ids[0].as_str());
+        assert_eq!(threads[0]["status"], "Open");
+        assert_eq!(threads[0]["comments"][0]["body"], "keep me");
+
+        // The original review keeps both threads
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let json = body_json(response).await;
+        assert_eq!(json["thread_count"], 2);
+    }
+
     #[tokio::test]
-    async fn test_get_review_open_thread_count() {
+    async fn test_get_review_full() {
         let app = test_app().await;
         let (_repo_dir, repo_path) = setup_test_repo();
         let id = create_review_for_test(&app, &repo_path).await;
 
-        // Create a thread
         let response = app
             .clone()
             .oneshot(
@@ -579,7 +2439,7 @@ mod tests {
                             "line_start": 1,
                             "line_end": 1,
                             "origin": "Comment",
-                            "body": "test comment",
+                            "body": "why io?",
                             "author_type": "Human"
                         })
                         .to_string(),
@@ -589,52 +2449,28 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(response.status(), StatusCode::OK);
-        let thread_json = body_json(response).await;
-        let thread_id = thread_json["id"].as_str().unwrap();
 
-        // GET review — should have 1 open thread
         let response = app
-            .clone()
             .oneshot(
                 Request::builder()
-                    .uri(format!("/api/reviews/{id}"))
+                    .uri(format!("/api/reviews/{id}/full"))
                     .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
         let json = body_json(response).await;
-        assert_eq!(json["thread_count"], 1);
-        assert_eq!(json["open_thread_count"], 1);
-
-        // Resolve the thread
-        app.clone()
-            .oneshot(
-                Request::builder()
-                    .method("PATCH")
-                    .uri(format!("/api/threads/{thread_id}/status"))
-                    .header("content-type", "application/json")
-                    .body(Body::from(
-                        serde_json::json!({ "status": "Resolved" }).to_string(),
-                    ))
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
 
-        // GET review — should have 0 open threads
-        let response = app
-            .oneshot(
-                Request::builder()
-                    .uri(format!("/api/reviews/{id}"))
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-        let json = body_json(response).await;
-        assert_eq!(json["thread_count"], 1);
-        assert_eq!(json["open_thread_count"], 0);
+        assert_eq!(json["review"]["id"], id.as_str());
+        assert_eq!(json["review"]["thread_count"], 1);
+        assert_eq!(json["files"].as_array().unwrap().len(), 1);
+        assert_eq!(json["files"][0]["path"], "src/main.rs");
+        assert_eq!(json["files"][0]["open_thread_count"], 1);
+        assert_eq!(json["threads"].as_array().unwrap().len(), 1);
+        assert_eq!(json["threads"][0]["comments"][0]["body"], "why io?");
+        assert_eq!(json["revisions"].as_array().unwrap().len(), 1);
+        assert_eq!(json["revisions"][0]["revision_number"], 1);
     }
 
     #[tokio::test]
@@ -668,6 +2504,7 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(response).await["code"], "review_not_found");
     }
 
     #[tokio::test]
@@ -687,6 +2524,7 @@ mod tests {
             .unwrap();
 
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(response).await["code"], "review_not_found");
     }
 
     #[tokio::test]
@@ -737,6 +2575,7 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(response).await["code"], "review_not_found");
 
         let response = app
             .oneshot(
@@ -808,6 +2647,7 @@ mod tests {
             .unwrap();
 
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(response).await["code"], "review_not_found");
     }
 
     #[tokio::test]
@@ -866,6 +2706,197 @@ mod tests {
         assert_eq!(response.status(), StatusCode::NO_CONTENT);
     }
 
+    #[tokio::test]
+    async fn test_agent_heartbeat_marks_agent_connected() {
+        let app = test_app().await;
+        let (_repo_dir, repo_path) = setup_test_repo();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        let heartbeat = |id: String| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/reviews/{id}/heartbeat"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+        let response = heartbeat(id.clone()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/agent-status"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(body_json(response).await["connected"], true);
+
+        let response = heartbeat(uuid::Uuid::new_v4().to_string()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(response).await["code"], "review_not_found");
+    }
+
+    #[tokio::test]
+    async fn test_thread_unread_flips_on_new_comment_and_clears_on_mark_seen() {
+        let app = test_app().await;
+        let (_repo_dir, repo_path) = setup_test_repo();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        let send = |method: &str, uri: String, body: Option<serde_json::Value>| {
+            let builder = Request::builder().method(method).uri(uri);
+            let request = match body {
+                Some(json) => builder
+                    .header("content-type", "application/json")
+                    .body(Body::from(json.to_string())),
+                None => builder.body(Body::empty()),
+            };
+            app.clone().oneshot(request.unwrap())
+        };
+        let unread = || async {
+            let response = send("GET", format!("/api/reviews/{id}/threads"), None)
+                .await
+                .unwrap();
+            let thread = body_json(response).await[0].clone();
+            (
+                thread["unread_count"].as_u64().unwrap(),
+                thread["has_unread"].as_bool().unwrap(),
+            )
+        };
+        let mark_seen = || async {
+            let response = send("POST", format!("/api/reviews/{id}/mark-seen"), None)
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        };
+
+        let response = send(
+            "POST",
+            format!("/api/reviews/{id}/threads"),
+            Some(serde_json::json!({
+                "file_path": "src/main.rs",
+                "line_start": 1,
+                "line_end": 1,
+                "origin": "AgentExplanation",
+                "body": "This sets up logging",
+                "author_type": "Agent"
+            })),
+        )
+        .await
+        .unwrap();
+        let thread_id = body_json(response).await["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        // Never seen, so the agent's comment is unread
+        assert_eq!(unread().await, (1, true));
+
+        mark_seen().await;
+        assert_eq!(unread().await, (0, false));
+
+        let reply = |author: &'static str| {
+            send(
+                "POST",
+                format!("/api/threads/{thread_id}/comments"),
+                Some(serde_json::json!({ "author_type": author, "body": "More" })),
+            )
+        };
+        assert_eq!(reply("Agent").await.unwrap().status(), StatusCode::OK);
+        assert_eq!(unread().await, (1, true));
+        // The human's own replies are never unread
+        assert_eq!(reply("Human").await.unwrap().status(), StatusCode::OK);
+        assert_eq!(unread().await, (1, true));
+
+        mark_seen().await;
+        assert_eq!(unread().await, (0, false));
+
+        let response = send(
+            "POST",
+            format!("/api/reviews/{}/mark-seen", uuid::Uuid::new_v4()),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(response).await["code"], "review_not_found");
+    }
+
+    #[tokio::test]
+    async fn test_agent_seen_set_and_cleared_by_human_comment() {
+        let app = test_app().await;
+        let (_repo_dir, repo_path) = setup_test_repo();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        let send = |method: &str, uri: String, body: Option<serde_json::Value>| {
+            let builder = Request::builder().method(method).uri(uri);
+            let request = match body {
+                Some(json) => builder
+                    .header("content-type", "application/json")
+                    .body(Body::from(json.to_string())),
+                None => builder.body(Body::empty()),
+            };
+            app.clone().oneshot(request.unwrap())
+        };
+        let agent_seen_at = || async {
+            let response = send("GET", format!("/api/reviews/{id}"), None)
+                .await
+                .unwrap();
+            body_json(response).await["agent_seen_at"].clone()
+        };
+        assert!(agent_seen_at().await.is_null());
+
+        let response = send("PUT", format!("/api/reviews/{id}/agent-seen"), None)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(agent_seen_at().await.is_string());
+
+        let response = send(
+            "POST",
+            format!("/api/reviews/{id}/threads"),
+            Some(serde_json::json!({
+                "file_path": "src/main.rs",
+                "line_start": 1,
+                "line_end": 1,
+                "origin": "Comment",
+                "body": "Explained",
+                "author_type": "Agent"
+            })),
+        )
+        .await
+        .unwrap();
+        let thread_id = body_json(response).await["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        // The agent's own comments don't clear it
+        assert!(agent_seen_at().await.is_string());
+
+        let response = send(
+            "POST",
+            format!("/api/threads/{thread_id}/comments"),
+            Some(serde_json::json!({ "author_type": "Human", "body": "Why?" })),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(agent_seen_at().await.is_null());
+
+        let response = send(
+            "PUT",
+            format!("/api/reviews/{}/agent-seen", uuid::Uuid::new_v4()),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(response).await["code"], "review_not_found");
+    }
+
     #[tokio::test]
     async fn test_update_agent_presence_not_found() {
         let app = test_app().await;
@@ -885,6 +2916,7 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(response).await["code"], "review_not_found");
     }
 
     #[tokio::test]
@@ -922,6 +2954,247 @@ mod tests {
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
 
+    #[tokio::test]
+    async fn test_get_activity_is_chronological() {
+        let app = test_app().await;
+        let (_repo_dir, repo_path) = setup_test_repo();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/reviews/{id}/threads"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({
+                            "file_path": "src/main.rs",
+                            "line_start": 1,
+                            "line_end": 1,
+                            "origin": "Comment",
+                            "body": "why?",
+                            "author_type": "Human"
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let thread_id = body_json(response).await["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/threads/{thread_id}/comments"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "author_type": "Agent", "body": "because" })
+                            .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .method("PATCH")
+                    .uri(format!("/api/reviews/{id}/status"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "status": "Closed" }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/activity"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        let entries = json.as_array().unwrap();
+        let kinds: Vec<_> = entries
+            .iter()
+            .map(|e| e["kind"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                "review_created",
+                "revision_created",
+                "thread_created",
+                "comment_added",
+                "status_changed"
+            ]
+        );
+        assert_eq!(entries[1]["revision_number"], 1);
+        assert_eq!(entries[2]["thread_id"], thread_id);
+        assert_eq!(entries[3]["author_type"], "Agent");
+        assert_eq!(entries[4]["status"], "Closed");
+        let timestamps: Vec<chrono::DateTime<chrono::Utc>> = entries
+            .iter()
+            .map(|e| e["timestamp"].as_str().unwrap().parse().unwrap())
+            .collect();
+        assert!(timestamps.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[tokio::test]
+    async fn test_get_activity_ignores_label_and_decision_edits() {
+        let app = test_app().await;
+        let (_repo_dir, repo_path) = setup_test_repo();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        let send = |method: &str, uri: String, body: serde_json::Value| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+        let response = send(
+            "PATCH",
+            format!("/api/reviews/{id}"),
+            serde_json::json!({ "labels": ["pr:7"] }),
+        )
+        .await
+        .unwrap();
+        assert!(response.status().is_success());
+        let response = send(
+            "POST",
+            format!("/api/reviews/{id}/decision"),
+            serde_json::json!({ "decision": "Approved" }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/activity"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let json = body_json(response).await;
+        let kinds: Vec<_> = json
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["kind"].as_str().unwrap())
+            .collect();
+        assert_eq!(kinds, ["review_created", "revision_created"]);
+    }
+
+    #[tokio::test]
+    async fn test_get_activity_filtered_by_author() {
+        let app = test_app().await;
+        let (_repo_dir, repo_path) = setup_test_repo();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/reviews/{id}/threads"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({
+                            "file_path": "src/main.rs",
+                            "line_start": 1,
+                            "line_end": 1,
+                            "origin": "Comment",
+                            "body": "why?",
+                            "author_type": "Human"
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let thread_id = body_json(response).await["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/threads/{thread_id}/comments"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "author_type": "Agent", "body": "because" })
+                            .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        for (author, kind, author_type) in [
+            ("human", "thread_created", "Human"),
+            ("agent", "comment_added", "Agent"),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .uri(format!("/api/reviews/{id}/activity?author={author}"))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let json = body_json(response).await;
+            let entries = json.as_array().unwrap();
+            assert_eq!(entries.len(), 1, "{author}: {json}");
+            assert_eq!(entries[0]["kind"], kind);
+            assert_eq!(entries[0]["author_type"], author_type);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_activity_not_found() {
+        let app = test_app().await;
+        let fake_id = uuid::Uuid::new_v4();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{fake_id}/activity"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(response).await["code"], "review_not_found");
+    }
+
     #[tokio::test]
     async fn test_find_or_create_review_creates_new() {
         let app = test_app().await;
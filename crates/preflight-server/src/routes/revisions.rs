@@ -1,76 +1,158 @@
-use axum::{
-    Json,
-    extract::{Path, State},
-};
+use std::collections::HashMap;
+
+use axum::extract::{Path, Query, State};
 use chrono::Utc;
 use uuid::Uuid;
 
-use crate::error::ApiError;
+use crate::error::{ApiError, ErrorCode};
+use crate::extract::Json;
 use crate::state::AppState;
-use crate::types::{CreateRevisionRequest, RevisionResponse};
+use crate::types::{
+    CreateRevisionRequest, DriftResponse, RebasePreviewQuery, RebasePreviewResponse,
+    RevisionResponse,
+};
 use crate::ws::{WsEvent, WsEventType};
+use preflight_core::diff::FileDiff;
+use preflight_core::review::{Review, Revision};
 use preflight_core::store::CreateRevisionInput;
 
 pub fn router() -> axum::Router<AppState> {
     use axum::routing::get;
-    axum::Router::new().route("/{id}/revisions", get(list_revisions).post(create_revision))
+    axum::Router::new()
+        .route("/{id}/revisions", get(list_revisions).post(create_revision))
+        .route("/{id}/drift", get(get_drift))
+        .route("/{id}/rebase-preview", get(get_rebase_preview))
+}
+
+fn display_path(file: &FileDiff) -> &str {
+    file.new_path
+        .as_deref()
+        .or(file.old_path.as_deref())
+        .unwrap_or_default()
+}
+
+fn files_are_equal(a: &FileDiff, b: &FileDiff) -> bool {
+    a.status == b.status
+        && a.mode_change == b.mode_change
+        && a.hunks.len() == b.hunks.len()
+        && a.hunks.iter().zip(&b.hunks).all(|(a, b)| {
+            a.old_start == b.old_start
+                && a.new_start == b.new_start
+                && a.old_count == b.old_count
+                && a.new_count == b.new_count
+                && a.lines.len() == b.lines.len()
+                && a.lines
+                    .iter()
+                    .zip(&b.lines)
+                    .all(|(la, lb)| la.content == lb.content && la.kind == lb.kind)
+        })
 }
 
+/// Paths whose diff differs between two sets of file diffs, including files
+/// present in only one of them. Sorted and deduplicated.
+fn changed_files(old: &[FileDiff], new: &[FileDiff]) -> Vec<String> {
+    let mut changed: Vec<String> = old
+        .iter()
+        .filter(|o| {
+            !new.iter()
+                .any(|n| display_path(n) == display_path(o) && files_are_equal(o, n))
+        })
+        .chain(
+            new.iter()
+                .filter(|n| !old.iter().any(|o| display_path(o) == display_path(n))),
+        )
+        .map(|f| display_path(f).to_string())
+        .collect();
+    changed.sort();
+    changed.dedup();
+    changed
+}
+
+/// The base-side content of each changed file, by old path, when the server
+/// snapshots it into revisions. Files that can't be read are left out.
+pub(crate) fn base_snapshots(
+    state: &AppState,
+    review: &Review,
+    files: &[FileDiff],
+) -> HashMap<String, String> {
+    if !state.config.snapshot_base {
+        return HashMap::new();
+    }
+    files
+        .iter()
+        .filter_map(|file| {
+            let path = file.old_path.as_deref()?;
+            let location = review.locate(path)?;
+            let content = state
+                .metrics
+                .time_git("show", || {
+                    preflight_core::file_reader::read_old_file(
+                        std::path::Path::new(location.repo_path),
+                        location.path,
+                        &preflight_core::git_diff::old_content_ref(location.base_ref),
+                        state.config.max_file_size,
+                    )
+                })
+                .ok()?;
+            Some((path.to_string(), content))
+        })
+        .collect()
+}
+
+/// Whether two sets of file diffs show the same changes.
+pub(crate) fn revisions_are_equal(old: &[FileDiff], new: &[FileDiff]) -> bool {
+    changed_files(old, new).is_empty()
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/reviews/{id}/revisions",
+    params(("id" = Uuid, Path, description = "Review id")),
+    request_body = CreateRevisionRequest,
+    responses(
+        (status = 200, body = RevisionResponse),
+        (status = 400, description = "The review has no repository to diff"),
+        (status = 404, description = "Review not found"),
+    ),
+    tag = "revisions"
+)]
 async fn create_revision(
     State(state): State<AppState>,
     Path(review_id): Path<Uuid>,
     Json(request): Json<CreateRevisionRequest>,
 ) -> Result<Json<RevisionResponse>, ApiError> {
     let review = state.store.get_review(review_id).await?;
-    let repo_path = std::path::Path::new(&review.repo_path);
-    let files = preflight_core::git_diff::diff_against_base(repo_path, &review.base_ref)
-        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
-
-    // Compare against latest revision's files — reject if no changes
-    if let Ok(latest) = state.store.get_latest_revision(review_id).await {
-        let old_paths: std::collections::HashSet<_> = latest
-            .files
-            .iter()
-            .map(|f| {
-                f.new_path
-                    .clone()
-                    .unwrap_or_else(|| f.old_path.clone().unwrap_or_default())
-            })
-            .collect();
-        let new_paths: std::collections::HashSet<_> = files
-            .iter()
-            .map(|f| {
-                f.new_path
-                    .clone()
-                    .unwrap_or_else(|| f.old_path.clone().unwrap_or_default())
-            })
-            .collect();
-
-        if old_paths == new_paths && latest.files.len() == files.len() {
-            // Simple heuristic: check if file count and paths match, then compare hunk contents
-            let old_hunks: Vec<_> = latest.files.iter().flat_map(|f| &f.hunks).collect();
-            let new_hunks: Vec<_> = files.iter().flat_map(|f| &f.hunks).collect();
-            if old_hunks.len() == new_hunks.len() {
-                let same = old_hunks.iter().zip(new_hunks.iter()).all(|(a, b)| {
-                    a.old_start == b.old_start
-                        && a.new_start == b.new_start
-                        && a.old_count == b.old_count
-                        && a.new_count == b.new_count
-                        && a.lines.len() == b.lines.len()
-                        && a.lines
-                            .iter()
-                            .zip(b.lines.iter())
-                            .all(|(la, lb)| la.content == lb.content && la.kind == lb.kind)
-                });
-                if same {
-                    return Err(ApiError::BadRequest(
-                        "no changes detected since last revision".into(),
-                    ));
-                }
-            }
-        }
+    let repo_path = review
+        .repo_path
+        .as_deref()
+        .ok_or_else(|| ApiError::no_repository(review_id))?;
+    let repo_path = std::path::Path::new(repo_path);
+    let mut files = state.metrics.time_git("diff", || {
+        preflight_core::git_diff::diff_against_base_with_options(
+            repo_path,
+            &review.base_ref,
+            review.path_prefix.as_deref(),
+            &state.config.diff_options(review.diff_algorithm),
+        )
+    })?;
+    files.extend(state.metrics.time_git("diff", || {
+        preflight_core::git_diff::diff_extra_repos(
+            &review.extra_repos,
+            &state.config.diff_options(review.diff_algorithm),
+        )
+    })?);
+
+    // Reject if nothing changed since the latest revision
+    if let Ok(latest) = state.store.get_latest_revision(review_id).await
+        && revisions_are_equal(&latest.files, &files)
+    {
+        return Err(ApiError::BadRequest(
+            ErrorCode::NoChanges,
+            "no changes detected since last revision".into(),
+        ));
     }
 
+    let base_snapshots = base_snapshots(&state, &review, &files);
     let revision = state
         .store
         .create_revision(CreateRevisionInput {
@@ -78,6 +160,7 @@ async fn create_revision(
             trigger: request.trigger,
             message: request.message,
             files,
+            base_snapshots,
         })
         .await?;
 
@@ -99,24 +182,139 @@ async fn create_revision(
     Ok(Json(response))
 }
 
+/// Compare the working tree against the latest revision, so the UI can
+/// offer to snapshot changes made outside preflight.
+#[utoipa::path(
+    get,
+    path = "/api/reviews/{id}/drift",
+    params(("id" = Uuid, Path, description = "Review id")),
+    responses(
+        (status = 200, body = DriftResponse),
+        (status = 400, description = "The review's repo can no longer be diffed, or it has none"),
+        (status = 404, description = "Review or revision not found"),
+    ),
+    tag = "revisions"
+)]
+async fn get_drift(
+    State(state): State<AppState>,
+    Path(review_id): Path<Uuid>,
+) -> Result<Json<DriftResponse>, ApiError> {
+    let review = state.store.get_review(review_id).await?;
+    let latest = state.store.get_latest_revision(review_id).await?;
+    let repo_path = review
+        .repo_path
+        .as_deref()
+        .ok_or_else(|| ApiError::no_repository(review_id))?;
+    let mut files = state.metrics.time_git("diff", || {
+        preflight_core::git_diff::diff_against_base_with_options(
+            std::path::Path::new(repo_path),
+            &review.base_ref,
+            review.path_prefix.as_deref(),
+            &state.config.diff_options(review.diff_algorithm),
+        )
+    })?;
+    files.extend(state.metrics.time_git("diff", || {
+        preflight_core::git_diff::diff_extra_repos(
+            &review.extra_repos,
+            &state.config.diff_options(review.diff_algorithm),
+        )
+    })?);
+    let changed_files = changed_files(&latest.files, &files);
+    Ok(Json(DriftResponse {
+        drifted: !changed_files.is_empty(),
+        changed_files,
+    }))
+}
+
+/// Show which files the review would contain against a different base,
+/// without changing the review. Extra repositories keep their own bases and
+/// are left out.
+#[utoipa::path(
+    get,
+    path = "/api/reviews/{id}/rebase-preview",
+    params(("id" = Uuid, Path, description = "Review id"), RebasePreviewQuery),
+    responses(
+        (status = 200, body = RebasePreviewResponse),
+        (status = 400, description = "Invalid base ref, or the review has no repository"),
+        (status = 404, description = "Review not found"),
+    ),
+    tag = "revisions"
+)]
+async fn get_rebase_preview(
+    State(state): State<AppState>,
+    Path(review_id): Path<Uuid>,
+    Query(query): Query<RebasePreviewQuery>,
+) -> Result<Json<RebasePreviewResponse>, ApiError> {
+    let review = state.store.get_review(review_id).await?;
+    let repo_path = review
+        .repo_path
+        .as_deref()
+        .ok_or_else(|| ApiError::no_repository(review_id))?;
+    let repo_path = std::path::Path::new(repo_path);
+    let new_base = state.metrics.time_git("resolve_base_ref", || {
+        preflight_core::git_diff::resolve_base_ref(repo_path, Some(&query.new_base))
+    });
+    let paths = |base_ref: &str| -> Result<Vec<String>, ApiError> {
+        let files = state.metrics.time_git("diff", || {
+            preflight_core::git_diff::diff_against_base_with_options(
+                repo_path,
+                base_ref,
+                review.path_prefix.as_deref(),
+                &state.config.diff_options(review.diff_algorithm),
+            )
+        })?;
+        let mut paths: Vec<String> = files.iter().map(|f| display_path(f).to_string()).collect();
+        paths.sort();
+        Ok(paths)
+    };
+    let current = paths(&review.base_ref)?;
+    let files = paths(&new_base)?;
+    let added_files = files
+        .iter()
+        .filter(|p| !current.contains(p))
+        .cloned()
+        .collect();
+    let removed_files = current
+        .iter()
+        .filter(|p| !files.contains(p))
+        .cloned()
+        .collect();
+    Ok(Json(RebasePreviewResponse {
+        new_base,
+        files,
+        added_files,
+        removed_files,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/reviews/{id}/revisions",
+    params(("id" = Uuid, Path, description = "Review id")),
+    responses(
+        (status = 200, body = Vec<RevisionResponse>),
+        (status = 404, description = "Review not found"),
+    ),
+    tag = "revisions"
+)]
 async fn list_revisions(
     State(state): State<AppState>,
     Path(review_id): Path<Uuid>,
 ) -> Result<Json<Vec<RevisionResponse>>, ApiError> {
     let revisions = state.store.get_revisions(review_id).await?;
-    let responses: Vec<RevisionResponse> = revisions
-        .into_iter()
-        .map(|r| RevisionResponse {
-            id: r.id,
-            review_id: r.review_id,
-            revision_number: r.revision_number,
-            trigger: r.trigger,
-            message: r.message,
-            file_count: r.files.len(),
-            created_at: r.created_at,
-        })
-        .collect();
-    Ok(Json(responses))
+    Ok(Json(revisions.into_iter().map(revision_response).collect()))
+}
+
+pub(crate) fn revision_response(revision: Revision) -> RevisionResponse {
+    RevisionResponse {
+        id: revision.id,
+        review_id: revision.review_id,
+        revision_number: revision.revision_number,
+        trigger: revision.trigger,
+        message: revision.message,
+        file_count: revision.files.len(),
+        created_at: revision.created_at,
+    }
 }
 
 #[cfg(test)]
@@ -127,12 +325,7 @@ mod tests {
     use tower::ServiceExt;
 
     async fn test_app() -> axum::Router {
-        let dir = tempfile::TempDir::new().unwrap();
-        let path = dir.path().join("state.json");
-        let store = preflight_core::json_store::JsonFileStore::new(&path)
-            .await
-            .unwrap();
-        Box::leak(Box::new(dir));
+        let store = preflight_core::memory_store::MemoryStore::new();
         crate::app(std::sync::Arc::new(store))
     }
 
@@ -326,6 +519,91 @@ mod tests {
             .unwrap();
 
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let json = body_json(response).await;
+        assert_eq!(json["code"], "no_changes");
+    }
+
+    async fn get_drift(app: &axum::Router, id: &str) -> serde_json::Value {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/drift"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        body_json(response).await
+    }
+
+    #[tokio::test]
+    async fn test_drift_detected_after_external_change() {
+        let app = test_app().await;
+        let (repo_dir, repo_path) = setup_test_repo();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        let json = get_drift(&app, &id).await;
+        assert_eq!(json["drifted"], false);
+        assert!(json["changed_files"].as_array().unwrap().is_empty());
+
+        // Change the working tree without creating a revision
+        std::fs::write(
+            repo_dir.path().join("src/main.rs"),
+            "use std::io;\n\nfn main() {\n    println!(\"changed\");\n}\n",
+        )
+        .unwrap();
+
+        let json = get_drift(&app, &id).await;
+        assert_eq!(json["drifted"], true);
+        assert_eq!(json["changed_files"], serde_json::json!(["src/main.rs"]));
+    }
+
+    #[tokio::test]
+    async fn test_rebase_preview_compares_file_sets_without_changing_review() {
+        use std::process::Command;
+
+        let app = test_app().await;
+        let (repo_dir, repo_path) = setup_test_repo();
+        let p = repo_dir.path();
+        // A second commit, so HEAD~1 also sees src/lib.rs as changed
+        std::fs::write(p.join("src/lib.rs"), "pub fn lib() {}\n").unwrap();
+        Command::new("git")
+            .args(["add", "src/lib.rs"])
+            .current_dir(p)
+            .output()
+            .unwrap();
+        Command::new("git")
+            .args(["commit", "-m", "add lib"])
+            .current_dir(p)
+            .output()
+            .unwrap();
+        let id = create_review_for_test(&app, &repo_path).await;
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{id}/rebase-preview?new_base=HEAD~1"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert_eq!(json["new_base"], "HEAD~1");
+        assert_eq!(
+            json["files"],
+            serde_json::json!(["src/lib.rs", "src/main.rs"])
+        );
+        assert_eq!(json["added_files"], serde_json::json!(["src/lib.rs"]));
+        assert_eq!(json["removed_files"], serde_json::json!([]));
+
+        // The review still diffs against its original base
+        let json = get_drift(&app, &id).await;
+        assert_eq!(json["drifted"], false);
     }
 
     #[tokio::test]
@@ -351,5 +629,6 @@ mod tests {
             .unwrap();
 
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(response).await["code"], "review_not_found");
     }
 }
@@ -1,48 +1,86 @@
+use std::collections::HashMap;
+
 use axum::{
-    Json,
     extract::{Path, Query, State},
     http::StatusCode,
 };
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use uuid::Uuid;
 
-use crate::error::ApiError;
+use crate::error::{ApiError, ErrorCode};
+use crate::extract::Json;
+use crate::routes::comments::{comment_response, render_comment};
 use crate::state::AppState;
 use crate::types::{
-    CommentResponse, CreateThreadRequest, ThreadResponse, UpdateAgentStatusRequest,
-    UpdateThreadStatusRequest,
+    AllThreadsQuery, AuthorFilter, CreateHunkThreadRequest, CreateThreadRequest,
+    CreateThreadsResponse, LinkThreadsRequest, MAX_THREAD_PAGE_SIZE, PinThreadRequest,
+    RenderFormat, ReviewThreadResponse, THREAD_PAGE_SIZE, ThreadPageResponse, ThreadResponse,
+    UpdateAgentStatusRequest, UpdateThreadStatusRequest,
 };
 use crate::ws::{WsEvent, WsEventType};
-use preflight_core::store::CreateThreadInput;
+use preflight_core::diff::FileDiff;
+use preflight_core::review::{
+    AgentStatus, AuthorType, Comment, CommentThread, ContentSnippet, LineSide, ReviewStatus,
+    ThreadStatus,
+};
+use preflight_core::store::{AllThreadsFilter, CreateThreadInput};
 
 /// Routes nested under /api/reviews
 pub fn review_router() -> axum::Router<AppState> {
     use axum::routing::get;
-    axum::Router::new().route("/{id}/threads", get(list_threads).post(create_thread))
+    use axum::routing::post;
+    axum::Router::new()
+        .route("/{id}/threads", get(list_threads).post(create_thread))
+        .route("/{id}/threads/batch", post(create_threads))
+        .route("/{id}/threads/from-hunk", post(create_hunk_thread))
 }
 
 /// Routes nested under /api/threads
 pub fn thread_router() -> axum::Router<AppState> {
-    use axum::routing::{patch, post, put};
+    use axum::routing::{get, patch, post, put};
     axum::Router::new()
+        .route("/", get(list_all_threads))
         .route("/{id}/status", patch(update_thread_status))
         .route("/{id}/agent-status", put(set_agent_status))
         .route("/{id}/poke", post(poke_thread))
+        .route("/{id}/link", post(link_threads))
+        .route("/{id}/pin", patch(pin_thread))
 }
 
-#[derive(Debug, Deserialize)]
-struct ThreadFilter {
-    file: Option<String>,
+/// Current `(thread_count, open_thread_count)` for a review.
+///
+/// Included as `thread_count`/`open_thread_count` in the `review_status_changed`,
+/// `comment_added` and `thread_status_changed` event payloads so dashboards can
+/// update badges without refetching the review. Agent explanations only count
+/// as open when the server's `count_explanations` setting is on.
+pub(crate) async fn thread_counts(
+    state: &AppState,
+    review_id: Uuid,
+) -> Result<(usize, usize), ApiError> {
+    let threads = state.store.get_threads(review_id, None).await?;
+    let open_thread_count = threads
+        .iter()
+        .filter(|t| t.counts_as_open(state.config.count_explanations))
+        .count();
+    Ok((threads.len(), open_thread_count))
 }
 
-async fn create_thread(
-    State(state): State<AppState>,
-    Path(id): Path<Uuid>,
-    Json(request): Json<CreateThreadRequest>,
-) -> Result<Json<ThreadResponse>, ApiError> {
-    let input = CreateThreadInput {
-        review_id: id,
+/// `files` is the review's latest revision, used to capture the code the
+/// thread is anchored to.
+fn thread_input(
+    review_id: Uuid,
+    request: CreateThreadRequest,
+    files: &[FileDiff],
+) -> CreateThreadInput {
+    let content_snippet = files
+        .iter()
+        .find(|f| f.new_path.as_deref().or(f.old_path.as_deref()) == Some(&request.file_path))
+        .and_then(|f| {
+            ContentSnippet::from_diff(f, request.side, request.line_start, request.line_end)
+        });
+    CreateThreadInput {
+        review_id,
         file_path: request.file_path,
         line_start: request.line_start,
         line_end: request.line_end,
@@ -50,78 +88,460 @@ async fn create_thread(
         initial_comment_body: request.body,
         initial_comment_author: request.author_type,
         revision_number: None,
-        content_snippet: None,
-    };
-    let thread = state.store.create_thread(input).await?;
-    let response = ThreadResponse {
+        content_snippet,
+        initial_comment_suggestion: request.suggestion,
+        side: request.side,
+    }
+}
+
+/// Published comments not by the human that came after `human_seen_at`, or
+/// all of them if the human has never marked the review seen.
+fn unread_count(comments: &[Comment], human_seen_at: Option<DateTime<Utc>>) -> usize {
+    comments
+        .iter()
+        .filter(|c| !c.draft && c.author_type != AuthorType::Human)
+        .filter(|c| human_seen_at.is_none_or(|seen| c.created_at > seen))
+        .count()
+}
+
+/// When the human last marked the review seen.
+async fn human_seen_at(
+    state: &AppState,
+    review_id: Uuid,
+) -> Result<Option<DateTime<Utc>>, ApiError> {
+    Ok(state.store.get_review(review_id).await?.human_seen_at)
+}
+
+fn thread_response(
+    thread: CommentThread,
+    agent_status: Option<AgentStatus>,
+    human_seen_at: Option<DateTime<Utc>>,
+) -> ThreadResponse {
+    let unread_count = unread_count(&thread.comments, human_seen_at);
+    ThreadResponse {
         id: thread.id,
         review_id: thread.review_id,
         file_path: thread.file_path,
         line_start: thread.line_start,
         line_end: thread.line_end,
+        side: thread.side,
         origin: thread.origin,
         status: thread.status,
-        agent_status: None,
-        comments: thread
-            .comments
-            .into_iter()
-            .map(|c| CommentResponse {
-                id: c.id,
-                author_type: c.author_type,
-                body: c.body,
-                created_at: c.created_at,
-            })
-            .collect(),
+        agent_status,
+        comments: thread.comments.into_iter().map(comment_response).collect(),
         created_at: thread.created_at,
         updated_at: thread.updated_at,
-    };
+        related_threads: thread.related_threads,
+        pinned: thread.pinned,
+        version: thread.version,
+        unread_count,
+        has_unread: unread_count > 0,
+        anchor_text: thread
+            .content_snippet
+            .as_ref()
+            .map(ContentSnippet::anchor_text),
+        content_snippet: thread.content_snippet,
+    }
+}
+
+/// Reject a thread that could not be anchored or displayed, or whose first
+/// comment is over `max_body_bytes`.
+fn validate_thread_request(
+    request: &CreateThreadRequest,
+    max_body_bytes: usize,
+) -> Result<(), String> {
+    if request.file_path.is_empty() {
+        return Err("file_path must not be empty".into());
+    }
+    if request.line_start > request.line_end {
+        return Err(format!(
+            "line_start ({}) must not exceed line_end ({})",
+            request.line_start, request.line_end
+        ));
+    }
+    super::comments::validate_comment_body(&request.body, &[], max_body_bytes)
+}
+
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct ThreadFilter {
+    file: Option<String>,
+    /// Only return comments created after this time (RFC 3339).
+    since: Option<DateTime<Utc>>,
+    /// Only return comments by this author.
+    author: Option<AuthorFilter>,
+    /// `html` adds `body_html` to each comment.
+    render: Option<RenderFormat>,
+    /// Also return unsubmitted draft comments, for the reviewer's own UI.
+    #[serde(default)]
+    include_drafts: bool,
+}
+
+impl ThreadFilter {
+    /// No filtering, with or without drafts.
+    pub(crate) fn all(include_drafts: bool) -> Self {
+        Self {
+            include_drafts,
+            ..Default::default()
+        }
+    }
+
+    /// Whether the filter narrows comments; threads left with no comments
+    /// are then omitted rather than returned empty.
+    fn filters_comments(&self) -> bool {
+        self.since.is_some() || self.author.is_some()
+    }
+}
+
+/// Whether a comment's author passes an optional `?author=` filter.
+pub(crate) fn author_matches(filter: Option<AuthorFilter>, author: &AuthorType) -> bool {
+    match filter {
+        None => true,
+        Some(AuthorFilter::Human) => *author == AuthorType::Human,
+        Some(AuthorFilter::Agent) => *author == AuthorType::Agent,
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/reviews/{id}/threads",
+    params(("id" = Uuid, Path, description = "Review id")),
+    request_body = CreateThreadRequest,
+    responses(
+        (status = 200, body = ThreadResponse),
+        (status = 400, description = "Invalid thread, or the review is at its open thread limit"),
+        (status = 404, description = "Review not found"),
+    ),
+    tag = "threads"
+)]
+async fn create_thread(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<CreateThreadRequest>,
+) -> Result<Json<ThreadResponse>, ApiError> {
+    validate_thread_request(&request, state.config.max_comment_bytes)
+        .map_err(ApiError::invalid_request)?;
+    Ok(Json(insert_thread(&state, id, request).await?))
+}
+
+async fn insert_thread(
+    state: &AppState,
+    review_id: Uuid,
+    request: CreateThreadRequest,
+) -> Result<ThreadResponse, ApiError> {
+    let files = latest_files(state, review_id).await;
+    let thread = state
+        .store
+        .create_thread(
+            thread_input(review_id, request, &files),
+            state.config.thread_limit(),
+        )
+        .await?;
+    if thread
+        .comments
+        .iter()
+        .any(|c| c.author_type == AuthorType::Human)
+    {
+        super::reviews::clear_agent_seen(state, review_id).await?;
+    }
+    let response = thread_response(thread, None, human_seen_at(state, review_id).await?);
     let _ = state.ws_tx.send(WsEvent {
         event_type: WsEventType::ThreadCreated,
-        review_id: id.to_string(),
+        review_id: review_id.to_string(),
         payload: serde_json::to_value(&response).unwrap(),
         timestamp: Utc::now(),
     });
-    Ok(Json(response))
+    Ok(response)
+}
+
+/// Files in the review's latest revision, or none if it has no revisions
+/// (a missing review is reported by the store call that follows).
+async fn latest_files(state: &AppState, review_id: Uuid) -> Vec<FileDiff> {
+    state
+        .store
+        .get_latest_revision(review_id)
+        .await
+        .map(|r| r.files)
+        .unwrap_or_default()
+}
+
+/// New-file line range covered by `line_count` lines of a hunk, starting at
+/// `line_offset`. Removed lines inside the range are skipped; a range made
+/// only of removed lines has nothing to anchor to and is rejected.
+fn resolve_hunk_lines(
+    file: &FileDiff,
+    hunk_index: usize,
+    line_offset: usize,
+    line_count: usize,
+) -> Result<(u32, u32), String> {
+    let hunk = file.hunks.get(hunk_index).ok_or_else(|| {
+        format!(
+            "hunk_index {hunk_index} out of range: the file has {} hunks",
+            file.hunks.len()
+        )
+    })?;
+    if line_count == 0 {
+        return Err("line_count must be at least 1".into());
+    }
+    let end = line_offset.saturating_add(line_count);
+    let lines = hunk.lines.get(line_offset..end).ok_or_else(|| {
+        format!(
+            "lines {line_offset}..{end} out of range: hunk {hunk_index} has {} lines",
+            hunk.lines.len()
+        )
+    })?;
+    let mut new_lines = lines.iter().filter_map(|l| l.new_line_no);
+    let start = new_lines
+        .next()
+        .ok_or("the referenced lines were all removed; comment on a context or added line")?;
+    Ok((start, new_lines.next_back().unwrap_or(start)))
+}
+
+/// Create a thread on lines of the latest revision's diff, given as a hunk
+/// index and a line offset within that hunk. The lines are resolved to
+/// absolute new-file line numbers, so callers working from the diff don't
+/// have to count lines themselves.
+#[utoipa::path(
+    post,
+    path = "/api/reviews/{id}/threads/from-hunk",
+    params(("id" = Uuid, Path, description = "Review id")),
+    request_body = CreateHunkThreadRequest,
+    responses(
+        (status = 200, body = ThreadResponse),
+        (status = 400, description = "The hunk or lines don't exist, are all removed lines, or the review is at its open thread limit"),
+        (status = 404, description = "Review or file not found"),
+    ),
+    tag = "threads"
+)]
+async fn create_hunk_thread(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<CreateHunkThreadRequest>,
+) -> Result<Json<ThreadResponse>, ApiError> {
+    let revision = state.store.get_latest_revision(id).await?;
+    let file = revision
+        .files
+        .iter()
+        .find(|f| {
+            f.new_path.as_deref().or(f.old_path.as_deref()) == Some(request.file_path.as_str())
+        })
+        .ok_or_else(|| {
+            ApiError::NotFound(
+                ErrorCode::FileNotFound,
+                format!("file not found: {}", request.file_path),
+            )
+        })?;
+    let (line_start, line_end) = resolve_hunk_lines(
+        file,
+        request.hunk_index,
+        request.line_offset,
+        request.line_count,
+    )
+    .map_err(ApiError::invalid_request)?;
+    let request = CreateThreadRequest {
+        file_path: request.file_path,
+        line_start,
+        line_end,
+        side: LineSide::New,
+        origin: request.origin,
+        body: request.body,
+        author_type: request.author_type,
+        suggestion: request.suggestion,
+    };
+    validate_thread_request(&request, state.config.max_comment_bytes)
+        .map_err(ApiError::invalid_request)?;
+    Ok(Json(insert_thread(&state, id, request).await?))
+}
+
+/// Create several threads in one store operation. Every entry is validated
+/// first, so one bad entry rejects the whole batch and nothing is created.
+/// A `thread_created` event is still emitted per thread so existing
+/// subscribers don't need to understand a batch event.
+#[utoipa::path(
+    post,
+    path = "/api/reviews/{id}/threads/batch",
+    params(("id" = Uuid, Path, description = "Review id")),
+    request_body = Vec<CreateThreadRequest>,
+    responses(
+        (status = 200, body = CreateThreadsResponse),
+        (status = 400, description = "Empty batch, an invalid entry, or more threads than the open thread limit allows"),
+        (status = 404, description = "Review not found"),
+    ),
+    tag = "threads"
+)]
+async fn create_threads(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(requests): Json<Vec<CreateThreadRequest>>,
+) -> Result<Json<CreateThreadsResponse>, ApiError> {
+    if requests.is_empty() {
+        return Err(ApiError::invalid_request("batch must not be empty"));
+    }
+    for (index, request) in requests.iter().enumerate() {
+        validate_thread_request(request, state.config.max_comment_bytes)
+            .map_err(|msg| ApiError::invalid_request(format!("threads[{index}]: {msg}")))?;
+    }
+    let files = latest_files(&state, id).await;
+    let inputs = requests
+        .into_iter()
+        .map(|request| thread_input(id, request, &files))
+        .collect();
+    let threads = state
+        .store
+        .create_threads(id, inputs, state.config.thread_limit())
+        .await?;
+    if threads
+        .iter()
+        .flat_map(|t| &t.comments)
+        .any(|c| c.author_type == AuthorType::Human)
+    {
+        super::reviews::clear_agent_seen(&state, id).await?;
+    }
+    let thread_ids = threads.iter().map(|t| t.id).collect();
+    let human_seen_at = human_seen_at(&state, id).await?;
+    for thread in threads {
+        let response = thread_response(thread, None, human_seen_at);
+        let _ = state.ws_tx.send(WsEvent {
+            event_type: WsEventType::ThreadCreated,
+            review_id: id.to_string(),
+            payload: serde_json::to_value(&response).unwrap(),
+            timestamp: Utc::now(),
+        });
+    }
+    Ok(Json(CreateThreadsResponse { thread_ids }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/reviews/{id}/threads",
+    params(("id" = Uuid, Path, description = "Review id"), ThreadFilter),
+    responses(
+        (status = 200, body = Vec<ThreadResponse>),
+        (status = 404, description = "Review not found"),
+    ),
+    tag = "threads"
+)]
 async fn list_threads(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
     Query(filter): Query<ThreadFilter>,
 ) -> Result<Json<Vec<ThreadResponse>>, ApiError> {
-    let threads = state.store.get_threads(id, filter.file.as_deref()).await?;
+    let threads = state.store.get_threads(id, None).await?;
+    let renames = preflight_core::renames::build_rename_map(&state.store.get_revisions(id).await?);
+    let human_seen_at = human_seen_at(&state, id).await?;
+    let agent_statuses = state.agent_status.lock().await;
+    Ok(Json(thread_responses(
+        threads,
+        &renames,
+        &agent_statuses,
+        human_seen_at,
+        &filter,
+    )))
+}
+
+/// Threads from every review, for a dashboard of open discussions. Paths are
+/// reported as the threads were created, without following later renames.
+#[utoipa::path(
+    get,
+    path = "/api/threads",
+    params(AllThreadsQuery),
+    responses(
+        (status = 200, body = ThreadPageResponse),
+        (status = 400, description = "Invalid filter or page size"),
+    ),
+    tag = "threads"
+)]
+async fn list_all_threads(
+    State(state): State<AppState>,
+    Query(query): Query<AllThreadsQuery>,
+) -> Result<Json<ThreadPageResponse>, ApiError> {
+    let limit = query.limit.unwrap_or(THREAD_PAGE_SIZE);
+    if limit == 0 || limit > MAX_THREAD_PAGE_SIZE {
+        return Err(ApiError::invalid_request(format!(
+            "limit must be between 1 and {MAX_THREAD_PAGE_SIZE}"
+        )));
+    }
+    let page = state
+        .store
+        .all_threads(&AllThreadsFilter {
+            status: query.status,
+            origin: query.origin,
+            offset: query.offset,
+            limit: Some(limit),
+        })
+        .await;
     let agent_statuses = state.agent_status.lock().await;
-    let responses = threads
+    let threads = page
+        .threads
+        .into_iter()
+        .map(|mut t| {
+            if !query.include_drafts {
+                t.thread.comments.retain(|c| !c.draft);
+            }
+            let agent_status = agent_statuses.get(&t.thread.id).cloned();
+            ReviewThreadResponse {
+                review_title: t.review_title,
+                thread: thread_response(t.thread, agent_status, t.human_seen_at),
+            }
+        })
+        .collect();
+    Ok(Json(ThreadPageResponse {
+        threads,
+        total: page.total,
+    }))
+}
+
+/// Build the listing for a review's threads, pinned ones first, reporting
+/// each thread under its file's current path if the file was renamed since.
+pub(crate) fn thread_responses(
+    threads: Vec<CommentThread>,
+    renames: &HashMap<String, String>,
+    agent_statuses: &HashMap<Uuid, AgentStatus>,
+    human_seen_at: Option<DateTime<Utc>>,
+    filter: &ThreadFilter,
+) -> Vec<ThreadResponse> {
+    let mut responses: Vec<ThreadResponse> = threads
         .into_iter()
-        .map(|thread| {
+        .filter_map(|thread| {
+            let file_path =
+                preflight_core::renames::resolve_path(renames, &thread.file_path).to_string();
+            if filter.file.as_ref().is_some_and(|f| *f != file_path) {
+                return None;
+            }
             let agent_status = agent_statuses.get(&thread.id).cloned();
-            ThreadResponse {
-                id: thread.id,
-                review_id: thread.review_id,
-                file_path: thread.file_path,
-                line_start: thread.line_start,
-                line_end: thread.line_end,
-                origin: thread.origin,
-                status: thread.status,
-                agent_status,
-                comments: thread
-                    .comments
-                    .into_iter()
-                    .map(|c| CommentResponse {
-                        id: c.id,
-                        author_type: c.author_type,
-                        body: c.body,
-                        created_at: c.created_at,
-                    })
-                    .collect(),
-                created_at: thread.created_at,
-                updated_at: thread.updated_at,
+            let mut response = thread_response(thread, agent_status, human_seen_at);
+            response.file_path = file_path;
+            response.comments = std::mem::take(&mut response.comments)
+                .into_iter()
+                .filter(|c| filter.include_drafts || !c.draft)
+                .filter(|c| filter.since.is_none_or(|since| c.created_at > since))
+                .filter(|c| author_matches(filter.author, &c.author_type))
+                .map(|c| render_comment(c, filter.render))
+                .collect();
+            if filter.filters_comments() && response.comments.is_empty() {
+                return None;
             }
+            Some(response)
         })
         .collect();
-    Ok(Json(responses))
+    // Stable, so pinned and unpinned threads each keep their order
+    responses.sort_by_key(|t| !t.pinned);
+    responses
 }
 
+#[utoipa::path(
+    patch,
+    path = "/api/threads/{id}/status",
+    params(("id" = Uuid, Path, description = "Thread id")),
+    request_body = UpdateThreadStatusRequest,
+    responses(
+        (status = 204),
+        (status = 400, description = "Reopening would exceed the open thread limit"),
+        (status = 404, description = "Thread not found"),
+        (status = 409, description = "The thread changed since expected_version"),
+    ),
+    tag = "threads"
+)]
 async fn update_thread_status(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
@@ -129,22 +549,50 @@ async fn update_thread_status(
 ) -> Result<StatusCode, ApiError> {
     state
         .store
-        .update_thread_status(id, request.status.clone())
+        .update_thread_status(
+            id,
+            request.status.clone(),
+            request.expected_version,
+            state.config.thread_limit(),
+        )
         .await?;
     if let Ok(thread) = state.store.get_thread(id).await {
+        let (thread_count, open_thread_count) = thread_counts(&state, thread.review_id).await?;
         let _ = state.ws_tx.send(WsEvent {
             event_type: WsEventType::ThreadStatusChanged,
             review_id: thread.review_id.to_string(),
             payload: serde_json::json!({
                 "thread_id": id.to_string(),
-                "status": request.status
+                "status": request.status,
+                "thread_count": thread_count,
+                "open_thread_count": open_thread_count
             }),
             timestamp: Utc::now(),
         });
+        if state.config.auto_close
+            && request.status == ThreadStatus::Resolved
+            && open_thread_count == 0
+        {
+            let review = state.store.get_review(thread.review_id).await?;
+            if review.status == ReviewStatus::Open {
+                super::reviews::set_review_status(&state, review.id, ReviewStatus::Closed).await?;
+            }
+        }
     }
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/threads/{id}/agent-status",
+    params(("id" = Uuid, Path, description = "Thread id")),
+    request_body = UpdateAgentStatusRequest,
+    responses(
+        (status = 204),
+        (status = 404, description = "Thread not found"),
+    ),
+    tag = "threads"
+)]
 async fn set_agent_status(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
@@ -169,6 +617,16 @@ async fn set_agent_status(
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/threads/{id}/poke",
+    params(("id" = Uuid, Path, description = "Thread id")),
+    responses(
+        (status = 204),
+        (status = 404, description = "Thread not found"),
+    ),
+    tag = "threads"
+)]
 async fn poke_thread(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
@@ -186,6 +644,75 @@ async fn poke_thread(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Link a thread to related threads in the same review, e.g. when one
+/// discussion covers a pattern repeated across files. Links are recorded on
+/// both sides.
+#[utoipa::path(
+    post,
+    path = "/api/threads/{id}/link",
+    params(("id" = Uuid, Path, description = "Thread id")),
+    request_body = LinkThreadsRequest,
+    responses(
+        (status = 200, description = "The thread with its links", body = ThreadResponse),
+        (status = 400, description = "No threads given, or a thread is in another review"),
+        (status = 404, description = "Thread not found"),
+    ),
+    tag = "threads"
+)]
+async fn link_threads(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<LinkThreadsRequest>,
+) -> Result<Json<ThreadResponse>, ApiError> {
+    if request.thread_ids.is_empty() {
+        return Err(ApiError::invalid_request("thread_ids must not be empty"));
+    }
+    let thread = state.store.link_threads(id, request.thread_ids).await?;
+    let _ = state.ws_tx.send(WsEvent {
+        event_type: WsEventType::ThreadsLinked,
+        review_id: thread.review_id.to_string(),
+        payload: serde_json::json!({
+            "thread_id": id.to_string(),
+            "related_threads": thread.related_threads
+        }),
+        timestamp: Utc::now(),
+    });
+    let human_seen_at = human_seen_at(&state, thread.review_id).await?;
+    let agent_status = state.agent_status.lock().await.get(&id).cloned();
+    Ok(Json(thread_response(thread, agent_status, human_seen_at)))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/threads/{id}/pin",
+    params(("id" = Uuid, Path, description = "Thread id")),
+    request_body = PinThreadRequest,
+    responses(
+        (status = 200, body = ThreadResponse),
+        (status = 404, description = "Thread not found"),
+    ),
+    tag = "threads"
+)]
+async fn pin_thread(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<PinThreadRequest>,
+) -> Result<Json<ThreadResponse>, ApiError> {
+    let thread = state.store.set_thread_pinned(id, request.pinned).await?;
+    let _ = state.ws_tx.send(WsEvent {
+        event_type: WsEventType::ThreadPinned,
+        review_id: thread.review_id.to_string(),
+        payload: serde_json::json!({
+            "thread_id": id.to_string(),
+            "pinned": thread.pinned
+        }),
+        timestamp: Utc::now(),
+    });
+    let human_seen_at = human_seen_at(&state, thread.review_id).await?;
+    let agent_status = state.agent_status.lock().await.get(&id).cloned();
+    Ok(Json(thread_response(thread, agent_status, human_seen_at)))
+}
+
 #[cfg(test)]
 mod tests {
     use axum::body::Body;
@@ -194,13 +721,12 @@ mod tests {
     use tower::ServiceExt;
 
     async fn test_app() -> axum::Router {
-        let dir = tempfile::TempDir::new().unwrap();
-        let path = dir.path().join("state.json");
-        let store = preflight_core::json_store::JsonFileStore::new(&path)
-            .await
-            .unwrap();
-        Box::leak(Box::new(dir));
-        crate::app(std::sync::Arc::new(store))
+        test_app_with_config(crate::state::ServerConfig::default()).await
+    }
+
+    async fn test_app_with_config(config: crate::state::ServerConfig) -> axum::Router {
+        let store = preflight_core::memory_store::MemoryStore::new();
+        crate::app_with_config(std::sync::Arc::new(store), config)
     }
 
     async fn body_json(response: axum::response::Response) -> serde_json::Value {
@@ -339,23 +865,24 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_create_thread_unknown_review() {
+    async fn test_thread_quotes_the_commented_lines() {
         let app = test_app().await;
-        let fake_id = uuid::Uuid::new_v4();
+        let review_id = create_review(&app).await;
 
         let response = app
+            .clone()
             .oneshot(
                 Request::builder()
                     .method("POST")
-                    .uri(format!("/api/reviews/{fake_id}/threads"))
+                    .uri(format!("/api/reviews/{review_id}/threads"))
                     .header("content-type", "application/json")
                     .body(Body::from(
                         serde_json::json!({
                             "file_path": "src/main.rs",
-                            "line_start": 1,
-                            "line_end": 1,
+                            "line_start": 3,
+                            "line_end": 4,
                             "origin": "Comment",
-                            "body": "test",
+                            "body": "Why print?",
                             "author_type": "Human"
                         })
                         .to_string(),
@@ -364,57 +891,75 @@ mod tests {
             )
             .await
             .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert_eq!(json["anchor_text"], "fn main() {\n    println!(\"hello\");");
+        assert_eq!(
+            json["content_snippet"]["context_before"],
+            serde_json::json!(["use std::io;", ""])
+        );
+        assert_eq!(
+            json["content_snippet"]["context_after"],
+            serde_json::json!(["}"])
+        );
 
-        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        // Lines outside the diff have nothing to quote
+        let json = create_thread(&app, &review_id).await;
+        assert!(json.get("anchor_text").is_none());
+        assert!(json.get("content_snippet").is_none());
     }
 
     #[tokio::test]
-    async fn test_list_threads() {
+    async fn test_create_thread_unknown_review() {
         let app = test_app().await;
-        let review_id = create_review(&app).await;
-
-        // Create two threads
-        create_thread(&app, &review_id).await;
-        create_thread(&app, &review_id).await;
+        let fake_id = uuid::Uuid::new_v4();
 
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri(format!("/api/reviews/{review_id}/threads"))
-                    .body(Body::empty())
+                    .method("POST")
+                    .uri(format!("/api/reviews/{fake_id}/threads"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({
+                            "file_path": "src/main.rs",
+                            "line_start": 1,
+                            "line_end": 1,
+                            "origin": "Comment",
+                            "body": "test",
+                            "author_type": "Human"
+                        })
+                        .to_string(),
+                    ))
                     .unwrap(),
             )
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::OK);
-        let json = body_json(response).await;
-        let arr = json.as_array().unwrap();
-        assert_eq!(arr.len(), 2);
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(response).await["code"], "review_not_found");
     }
 
-    #[tokio::test]
-    async fn test_list_threads_filtered_by_file() {
-        let app = test_app().await;
-        let review_id = create_review(&app).await;
-
-        // Create a thread on src/main.rs (via helper)
-        create_thread(&app, &review_id).await;
-
-        // Create a thread on a different file
+    async fn post_hunk_thread(
+        app: &axum::Router,
+        review_id: &str,
+        line_offset: usize,
+        line_count: usize,
+    ) -> axum::response::Response {
         app.clone()
             .oneshot(
                 Request::builder()
                     .method("POST")
-                    .uri(format!("/api/reviews/{review_id}/threads"))
+                    .uri(format!("/api/reviews/{review_id}/threads/from-hunk"))
                     .header("content-type", "application/json")
                     .body(Body::from(
                         serde_json::json!({
-                            "file_path": "src/lib.rs",
-                            "line_start": 1,
-                            "line_end": 5,
+                            "file_path": "src/main.rs",
+                            "hunk_index": 0,
+                            "line_offset": line_offset,
+                            "line_count": line_count,
                             "origin": "Comment",
-                            "body": "Other file",
+                            "body": "Why print here?",
                             "author_type": "Agent"
                         })
                         .to_string(),
@@ -422,53 +967,172 @@ mod tests {
                     .unwrap(),
             )
             .await
-            .unwrap();
+            .unwrap()
+    }
 
-        // Filter by src/main.rs
-        let response = app
-            .oneshot(
-                Request::builder()
-                    .uri(format!("/api/reviews/{review_id}/threads?file=src/main.rs"))
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
+    #[tokio::test]
+    async fn test_create_thread_rejects_blank_body() {
+        let app = test_app().await;
+        let review_id = create_review(&app).await;
+
+        for body in ["", "  \n\t"] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .uri(format!("/api/reviews/{review_id}/threads"))
+                        .header("content-type", "application/json")
+                        .body(Body::from(
+                            serde_json::json!({
+                                "file_path": "src/main.rs",
+                                "line_start": 1,
+                                "line_end": 1,
+                                "origin": "Comment",
+                                "body": body,
+                                "author_type": "Human"
+                            })
+                            .to_string(),
+                        ))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+            let json = body_json(response).await;
+            assert_eq!(json["error"], "body must not be empty");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_thread_on_old_side_snippets_deleted_lines() {
+        let app = test_app().await;
+        let review_id = create_review(&app).await;
+        let create = |side: Option<&str>| {
+            let mut body = serde_json::json!({
+                "file_path": "src/main.rs",
+                "line_start": 1,
+                "line_end": 1,
+                "origin": "Comment",
+                "body": "Why was this removed?",
+                "author_type": "Human"
+            });
+            if let Some(side) = side {
+                body["side"] = side.into();
+            }
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/reviews/{review_id}/threads"))
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
 
+        // Old line 1 is the removed `fn main() {}`
+        let response = app.clone().oneshot(create(Some("Old"))).await.unwrap();
         assert_eq!(response.status(), StatusCode::OK);
         let json = body_json(response).await;
-        let arr = json.as_array().unwrap();
-        assert_eq!(arr.len(), 1);
-        assert_eq!(arr[0]["file_path"], "src/main.rs");
+        assert_eq!(json["side"], "Old");
+        assert_eq!(
+            json["content_snippet"]["lines"],
+            serde_json::json!(["fn main() {}"])
+        );
+
+        // New line 1 is the added `use std::io;`
+        let response = app.oneshot(create(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert_eq!(json["side"], "New");
+        assert_eq!(
+            json["content_snippet"]["lines"],
+            serde_json::json!(["use std::io;"])
+        );
     }
 
     #[tokio::test]
-    async fn test_set_agent_status() {
+    async fn test_create_thread_with_malformed_json_names_the_field() {
         let app = test_app().await;
         let review_id = create_review(&app).await;
-        let thread_json = create_thread(&app, &review_id).await;
-        let thread_id = thread_json["id"].as_str().unwrap();
+        let post = |body: String| {
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/reviews/{review_id}/threads"))
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .unwrap()
+        };
 
-        // Set agent status to Seen
+        let missing_line_start = serde_json::json!({
+            "file_path": "src/main.rs",
+            "line_end": 1,
+            "origin": "Comment",
+            "body": "Why?",
+            "author_type": "Human"
+        });
         let response = app
             .clone()
+            .oneshot(post(missing_line_start.to_string()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let json = body_json(response).await;
+        assert_eq!(json["code"], "invalid_json");
+        assert!(
+            json["error"].as_str().unwrap().contains("line_start"),
+            "{json}"
+        );
+
+        let response = app.oneshot(post("{\"file_path\":".into())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(body_json(response).await["code"], "invalid_json");
+    }
+
+    #[tokio::test]
+    async fn test_create_thread_from_hunk_reference() {
+        let app = test_app().await;
+        let review_id = create_review(&app).await;
+
+        // The single hunk is `-fn main() {}` followed by the five added lines,
+        // so offsets 3..5 are new lines 3 and 4.
+        let response = post_hunk_thread(&app, &review_id, 3, 2).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert_eq!(json["file_path"], "src/main.rs");
+        assert_eq!(json["line_start"], 3);
+        assert_eq!(json["line_end"], 4);
+        assert_eq!(json["comments"][0]["body"], "Why print here?");
+
+        // Only the removed line: nothing to anchor to on the new side.
+        let response = post_hunk_thread(&app, &review_id, 0, 1).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = post_hunk_thread(&app, &review_id, 5, 2).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(body_json(response).await["code"], "invalid_request");
+    }
+
+    /// Helper: POST a batch of threads and return the raw response.
+    async fn post_batch(
+        app: &axum::Router,
+        review_id: &str,
+        batch: serde_json::Value,
+    ) -> axum::response::Response {
+        app.clone()
             .oneshot(
                 Request::builder()
-                    .method("PUT")
-                    .uri(format!("/api/threads/{thread_id}/agent-status"))
+                    .method("POST")
+                    .uri(format!("/api/reviews/{review_id}/threads/batch"))
                     .header("content-type", "application/json")
-                    .body(Body::from(
-                        serde_json::json!({ "status": "Seen" }).to_string(),
-                    ))
+                    .body(Body::from(batch.to_string()))
                     .unwrap(),
             )
             .await
-            .unwrap();
-
-        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+            .unwrap()
+    }
 
-        // Verify agent_status appears in thread listing
+    async fn thread_count(app: &axum::Router, review_id: &str) -> usize {
         let response = app
+            .clone()
             .oneshot(
                 Request::builder()
                     .uri(format!("/api/reviews/{review_id}/threads"))
@@ -477,16 +1141,421 @@ mod tests {
             )
             .await
             .unwrap();
-
-        let json = body_json(response).await;
-        let threads = json.as_array().unwrap();
-        assert_eq!(threads[0]["agent_status"], "Seen");
+        body_json(response).await.as_array().unwrap().len()
     }
 
     #[tokio::test]
-    async fn test_agent_status_not_found() {
+    async fn test_create_threads_batch() {
         let app = test_app().await;
-        let fake_id = uuid::Uuid::new_v4();
+        let review_id = create_review(&app).await;
+
+        let response = post_batch(
+            &app,
+            &review_id,
+            serde_json::json!([
+                {"file_path": "src/a.rs", "line_start": 1, "line_end": 2, "origin": "Comment", "body": "first", "author_type": "Agent"},
+                {"file_path": "src/b.rs", "line_start": 5, "line_end": 5, "origin": "AgentExplanation", "body": "second", "author_type": "Agent"}
+            ]),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert_eq!(json["thread_ids"].as_array().unwrap().len(), 2);
+        assert_eq!(thread_count(&app, &review_id).await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_create_threads_batch_invalid_entry_rejects_all() {
+        let app = test_app().await;
+        let review_id = create_review(&app).await;
+
+        let response = post_batch(
+            &app,
+            &review_id,
+            serde_json::json!([
+                {"file_path": "src/a.rs", "line_start": 1, "line_end": 2, "origin": "Comment", "body": "fine", "author_type": "Agent"},
+                {"file_path": "src/b.rs", "line_start": 9, "line_end": 3, "origin": "Comment", "body": "backwards", "author_type": "Agent"}
+            ]),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let json = body_json(response).await;
+        assert!(
+            json["error"].as_str().unwrap().starts_with("threads[1]:"),
+            "{json}"
+        );
+        assert_eq!(thread_count(&app, &review_id).await, 0);
+
+        let response = post_batch(&app, &review_id, serde_json::json!([])).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_list_threads() {
+        let app = test_app().await;
+        let review_id = create_review(&app).await;
+
+        // Create two threads
+        create_thread(&app, &review_id).await;
+        create_thread(&app, &review_id).await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{review_id}/threads"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        let arr = json.as_array().unwrap();
+        assert_eq!(arr.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_thread_follows_file_renamed_in_later_revision() {
+        let app = test_app().await;
+        let (repo_dir, repo_path) = setup_test_repo();
+        // Give the file enough content for git to detect the rename by similarity
+        let run_git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(repo_dir.path())
+                .output()
+                .unwrap();
+        };
+        let body: String = (1..=20).map(|i| format!("// line {i}\n")).collect();
+        std::fs::write(repo_dir.path().join("src/main.rs"), &body).unwrap();
+        run_git(&["commit", "-am", "grow"]);
+        std::fs::write(
+            repo_dir.path().join("src/main.rs"),
+            body.replace("// line 10\n", "// changed\n"),
+        )
+        .unwrap();
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/reviews")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({
+                            "repo_path": repo_path,
+                            "base_ref": "HEAD"
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let review_id = body_json(response).await["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let thread = create_thread(&app, &review_id).await;
+
+        // Rename the file and record a new revision
+        run_git(&["mv", "src/main.rs", "src/app.rs"]);
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/reviews/{review_id}/revisions"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "trigger": "Manual" }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{review_id}/threads?file=src/app.rs"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let json = body_json(response).await;
+        let threads = json.as_array().unwrap();
+        assert_eq!(threads.len(), 1);
+        assert_eq!(threads[0]["id"], thread["id"]);
+        assert_eq!(threads[0]["file_path"], "src/app.rs");
+
+        // The old path still resolves to the renamed file's diff
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{review_id}/files/src/main.rs"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert_eq!(json["path"], "src/app.rs");
+        assert_eq!(json["status"], "Renamed");
+    }
+
+    #[tokio::test]
+    async fn test_list_threads_since_returns_only_newer_comments() {
+        let app = test_app().await;
+        let review_id = create_review(&app).await;
+        // An older thread with nothing newer should be left out entirely
+        create_thread(&app, &review_id).await;
+        let thread = create_thread(&app, &review_id).await;
+        let thread_id = thread["id"].as_str().unwrap();
+        let since = thread["comments"][0]["created_at"].as_str().unwrap();
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/threads/{thread_id}/comments"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({"author_type": "Agent", "body": "Fixed"}).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{review_id}/threads?since={since}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        let threads = json.as_array().unwrap();
+        assert_eq!(threads.len(), 1);
+        assert_eq!(threads[0]["id"], thread_id);
+        let comments = threads[0]["comments"].as_array().unwrap();
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0]["body"], "Fixed");
+    }
+
+    #[tokio::test]
+    async fn test_list_threads_filtered_by_author() {
+        let app = test_app().await;
+        let review_id = create_review(&app).await;
+        let thread = create_thread(&app, &review_id).await;
+        let thread_id = thread["id"].as_str().unwrap();
+        create_thread(&app, &review_id).await;
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/threads/{thread_id}/comments"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({"author_type": "Agent", "body": "Done"}).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let list = |author: &'static str| {
+            let app = app.clone();
+            let review_id = review_id.clone();
+            async move {
+                let response = app
+                    .oneshot(
+                        Request::builder()
+                            .uri(format!("/api/reviews/{review_id}/threads?author={author}"))
+                            .body(Body::empty())
+                            .unwrap(),
+                    )
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                body_json(response).await
+            }
+        };
+
+        // Only the thread the agent replied on, with just the agent's comment
+        let json = list("agent").await;
+        let threads = json.as_array().unwrap();
+        assert_eq!(threads.len(), 1);
+        assert_eq!(threads[0]["id"], thread_id);
+        let comments = threads[0]["comments"].as_array().unwrap();
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0]["author_type"], "Agent");
+
+        // Both threads, each with only the human's opening comment
+        let json = list("human").await;
+        let threads = json.as_array().unwrap();
+        assert_eq!(threads.len(), 2);
+        for thread in threads {
+            let comments = thread["comments"].as_array().unwrap();
+            assert_eq!(comments.len(), 1);
+            assert_eq!(comments[0]["author_type"], "Human");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_threads_filtered_by_file() {
+        let app = test_app().await;
+        let review_id = create_review(&app).await;
+
+        // Create a thread on src/main.rs (via helper)
+        create_thread(&app, &review_id).await;
+
+        // Create a thread on a different file
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/reviews/{review_id}/threads"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({
+                            "file_path": "src/lib.rs",
+                            "line_start": 1,
+                            "line_end": 5,
+                            "origin": "Comment",
+                            "body": "Other file",
+                            "author_type": "Agent"
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        // Filter by src/main.rs
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{review_id}/threads?file=src/main.rs"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        let arr = json.as_array().unwrap();
+        assert_eq!(arr.len(), 1);
+        assert_eq!(arr[0]["file_path"], "src/main.rs");
+    }
+
+    #[tokio::test]
+    async fn test_list_all_threads_across_reviews() {
+        let app = test_app().await;
+        let first = create_review(&app).await;
+        let second = create_review(&app).await;
+        let a = create_thread(&app, &first).await;
+        let b = create_thread(&app, &second).await;
+        let resolved = create_thread(&app, &second).await;
+        resolve_thread(&app, resolved["id"].as_str().unwrap()).await;
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/api/threads?status=Open")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert_eq!(json["total"], 2);
+        let threads = json["threads"].as_array().unwrap();
+        let ids: Vec<&serde_json::Value> = threads.iter().map(|t| &t["id"]).collect();
+        assert_eq!(ids, [&a["id"], &b["id"]]);
+        assert_eq!(threads[0]["review_id"], first);
+        assert_eq!(threads[1]["review_id"], second);
+        assert!(
+            threads
+                .iter()
+                .all(|t| t["review_title"] == "Thread test review")
+        );
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/threads?limit=0")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_set_agent_status() {
+        let app = test_app().await;
+        let review_id = create_review(&app).await;
+        let thread_json = create_thread(&app, &review_id).await;
+        let thread_id = thread_json["id"].as_str().unwrap();
+
+        // Set agent status to Seen
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri(format!("/api/threads/{thread_id}/agent-status"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "status": "Seen" }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        // Verify agent_status appears in thread listing
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{review_id}/threads"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let json = body_json(response).await;
+        let threads = json.as_array().unwrap();
+        assert_eq!(threads[0]["agent_status"], "Seen");
+    }
+
+    #[tokio::test]
+    async fn test_agent_status_not_found() {
+        let app = test_app().await;
+        let fake_id = uuid::Uuid::new_v4();
 
         let response = app
             .oneshot(
@@ -503,6 +1572,7 @@ mod tests {
             .unwrap();
 
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(response).await["code"], "thread_not_found");
     }
 
     #[tokio::test]
@@ -529,6 +1599,200 @@ mod tests {
         assert_eq!(response.status(), StatusCode::NO_CONTENT);
     }
 
+    #[tokio::test]
+    async fn test_stale_thread_status_update_is_rejected() {
+        let app = test_app().await;
+        let review_id = create_review(&app).await;
+        let thread_json = create_thread(&app, &review_id).await;
+        let thread_id = thread_json["id"].as_str().unwrap();
+        let read_version = thread_json["version"].as_u64().unwrap();
+
+        let set_status = |status: &str, expected_version: u64| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("PATCH")
+                    .uri(format!("/api/threads/{thread_id}/status"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({
+                            "status": status,
+                            "expected_version": expected_version
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+        };
+
+        // One client resolves, another reopens after reading the resolve
+        let response = set_status("Resolved", read_version).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let response = set_status("Open", read_version + 1).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        // A resolve from the original read is stale
+        let response = set_status("Resolved", read_version).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let json = body_json(response).await;
+        assert_eq!(json["code"], "version_conflict");
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{review_id}/threads"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let threads = body_json(response).await;
+        assert_eq!(threads[0]["status"], "Open");
+        assert_eq!(threads[0]["version"], read_version + 2);
+    }
+
+    async fn resolve_thread(app: &axum::Router, thread_id: &str) {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("PATCH")
+                    .uri(format!("/api/threads/{thread_id}/status"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "status": "Resolved" }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    }
+
+    async fn review_status(app: &axum::Router, review_id: &str) -> serde_json::Value {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{review_id}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        body_json(response).await["status"].clone()
+    }
+
+    #[tokio::test]
+    async fn test_resolving_last_thread_auto_closes_review() {
+        let app = test_app_with_config(crate::state::ServerConfig {
+            auto_close: true,
+            ..Default::default()
+        })
+        .await;
+        let review_id = create_review(&app).await;
+        let first = create_thread(&app, &review_id).await;
+        let second = create_thread(&app, &review_id).await;
+        // Explanations don't need resolving
+        let response = post_batch(
+            &app,
+            &review_id,
+            serde_json::json!([
+                {"file_path": "src/main.rs", "line_start": 1, "line_end": 1, "origin": "AgentExplanation", "body": "why", "author_type": "Agent"}
+            ]),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        resolve_thread(&app, first["id"].as_str().unwrap()).await;
+        assert_eq!(review_status(&app, &review_id).await, "Open");
+
+        resolve_thread(&app, second["id"].as_str().unwrap()).await;
+        assert_eq!(review_status(&app, &review_id).await, "Closed");
+    }
+
+    #[tokio::test]
+    async fn test_auto_close_is_opt_in() {
+        let app = test_app().await;
+        let review_id = create_review(&app).await;
+        let thread = create_thread(&app, &review_id).await;
+
+        resolve_thread(&app, thread["id"].as_str().unwrap()).await;
+        assert_eq!(review_status(&app, &review_id).await, "Open");
+    }
+
+    #[tokio::test]
+    async fn test_open_thread_limit() {
+        let app = test_app_with_config(crate::state::ServerConfig {
+            max_open_threads: 2,
+            ..Default::default()
+        })
+        .await;
+        let review_id = create_review(&app).await;
+        let first = create_thread(&app, &review_id).await;
+        create_thread(&app, &review_id).await;
+
+        let post_single = |origin: &str| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/reviews/{review_id}/threads"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({
+                            "file_path": "src/main.rs",
+                            "line_start": 1,
+                            "line_end": 1,
+                            "origin": origin,
+                            "body": "one too many",
+                            "author_type": "Agent"
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+        };
+        let response = post_single("Comment").await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(body_json(response).await["code"], "thread_limit_reached");
+        let response = post_batch(
+            &app,
+            &review_id,
+            serde_json::json!([
+                {"file_path": "src/main.rs", "line_start": 1, "line_end": 1, "origin": "Comment", "body": "also too many", "author_type": "Agent"}
+            ]),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(thread_count(&app, &review_id).await, 2);
+
+        // Agent explanations don't count towards the limit by default
+        let response = post_single("AgentExplanation").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Resolved threads don't count towards the limit
+        let first_id = first["id"].as_str().unwrap();
+        resolve_thread(&app, first_id).await;
+        let response = post_single("Comment").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // ...and reopening one can't take the review past it either
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("PATCH")
+                    .uri(format!("/api/threads/{first_id}/status"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"status":"Open"}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(body_json(response).await["code"], "thread_limit_reached");
+    }
+
     #[tokio::test]
     async fn test_poke_thread() {
         let app = test_app().await;
@@ -567,5 +1831,142 @@ mod tests {
             .unwrap();
 
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(response).await["code"], "thread_not_found");
+    }
+
+    async fn post_link(
+        app: &axum::Router,
+        thread_id: &str,
+        thread_ids: &[&str],
+    ) -> axum::response::Response {
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/threads/{thread_id}/link"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "thread_ids": thread_ids }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_link_threads_is_bidirectional() {
+        let app = test_app().await;
+        let review_id = create_review(&app).await;
+        let first = create_thread(&app, &review_id).await;
+        let second = create_thread(&app, &review_id).await;
+        let first_id = first["id"].as_str().unwrap();
+        let second_id = second["id"].as_str().unwrap();
+        // Unlinked threads don't mention related threads at all
+        assert!(first.get("related_threads").is_none());
+
+        let response = post_link(&app, first_id, &[second_id]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body_json(response).await;
+        assert_eq!(json["related_threads"], serde_json::json!([second_id]));
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{review_id}/threads"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let threads = body_json(response).await;
+        for thread in threads.as_array().unwrap() {
+            let other = if thread["id"] == first_id {
+                second_id
+            } else {
+                first_id
+            };
+            assert_eq!(thread["related_threads"], serde_json::json!([other]));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pinned_threads_listed_first() {
+        let app = test_app().await;
+        let review_id = create_review(&app).await;
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            let thread = create_thread(&app, &review_id).await;
+            assert!(thread.get("pinned").is_none());
+            ids.push(thread["id"].as_str().unwrap().to_string());
+        }
+
+        let pin = |thread_id: &str, pinned: bool| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("PATCH")
+                    .uri(format!("/api/threads/{thread_id}/pin"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "pinned": pinned }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+        };
+        let listed_ids = || async {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .uri(format!("/api/reviews/{review_id}/threads"))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            body_json(response)
+                .await
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|t| t["id"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let response = pin(&ids[2], true).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_json(response).await["pinned"], true);
+        assert_eq!(listed_ids().await, [ids[2].as_str(), &ids[0], &ids[1]]);
+
+        let response = pin(&ids[2], false).await.unwrap();
+        assert!(body_json(response).await.get("pinned").is_none());
+        assert_eq!(listed_ids().await, ids);
+
+        let response = pin(&uuid::Uuid::new_v4().to_string(), true).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(response).await["code"], "thread_not_found");
+    }
+
+    #[tokio::test]
+    async fn test_link_threads_rejects_other_review() {
+        let app = test_app().await;
+        let review_id = create_review(&app).await;
+        let other_review_id = create_review(&app).await;
+        let thread = create_thread(&app, &review_id).await;
+        let other = create_thread(&app, &other_review_id).await;
+
+        let response = post_link(
+            &app,
+            thread["id"].as_str().unwrap(),
+            &[other["id"].as_str().unwrap()],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let json = body_json(response).await;
+        assert_eq!(json["code"], "link_across_reviews");
+
+        let response = post_link(&app, thread["id"].as_str().unwrap(), &[]).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
 }
@@ -0,0 +1,48 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::{Query, State},
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures_util::Stream;
+use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct EventsQuery {
+    review_id: Option<String>,
+}
+
+/// Server-Sent Events fallback for clients that can't hold a WebSocket open.
+///
+/// Streams the same `WsEvent` JSON as `/api/ws`, one event per `data:` line.
+pub async fn sse_handler(
+    State(state): State<AppState>,
+    Query(query): Query<EventsQuery>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let rx = state.ws_tx.subscribe();
+    let stream = futures_util::stream::unfold(rx, move |mut rx| {
+        let review_id = query.review_id.clone();
+        async move {
+            loop {
+                match rx.recv().await {
+                    Ok(event) => {
+                        if review_id.as_ref().is_some_and(|id| *id != event.review_id) {
+                            continue;
+                        }
+                        if let Ok(sse_event) = Event::default().json_data(&event) {
+                            return Some((Ok(sse_event), rx));
+                        }
+                    }
+                    Err(RecvError::Lagged(n)) => {
+                        eprintln!("SSE client lagged, skipped {n} messages");
+                    }
+                    Err(RecvError::Closed) => return None, // Server shutting down
+                }
+            }
+        }
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
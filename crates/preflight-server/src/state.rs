@@ -2,12 +2,14 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use chrono::Utc;
+use preflight_core::git_diff::{DiffAlgorithm, DiffOptions};
 use preflight_core::highlight::Highlighter;
 use preflight_core::review::AgentStatus;
 use preflight_core::store::ReviewStore;
 use tokio::sync::{Mutex, broadcast};
 use uuid::Uuid;
 
+use crate::metrics::Metrics;
 use crate::ws::{WsEvent, WsEventType};
 
 #[derive(Clone)]
@@ -16,35 +18,191 @@ pub struct AppState {
     pub highlighter: Arc<Highlighter>,
     pub ws_tx: broadcast::Sender<WsEvent>,
     pub agent_status: Arc<Mutex<HashMap<Uuid, AgentStatus>>>,
-    pub agent_presence: Arc<AgentPresenceTracker>,
+    pub agent_presence: Arc<PresenceTracker>,
+    pub human_presence: Arc<PresenceTracker>,
+    pub config: Arc<ServerConfig>,
+    pub metrics: Arc<Metrics>,
+}
+
+/// Default for [`ServerConfig::max_open_threads`]: far more than a human
+/// review needs, low enough to stop a runaway agent.
+pub const DEFAULT_MAX_OPEN_THREADS: usize = 1000;
+
+/// Default for [`ServerConfig::max_comment_bytes`]: room for long
+/// explanations and pasted logs, but not whole build outputs.
+pub const DEFAULT_MAX_COMMENT_BYTES: usize = 256 * 1024;
+
+/// Tunable server limits, set from the command line.
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// Maximum size in bytes of a file served by the content endpoint.
+    pub max_file_size: u64,
+    /// Serve Prometheus metrics at `GET /metrics`.
+    pub metrics: bool,
+    /// Log every request's method, path, status and duration to stderr.
+    pub access_log: bool,
+    /// Similarity (percent) a moved file needs to be diffed as a rename.
+    pub rename_threshold: u8,
+    /// Bearer token required by the `/api/admin` routes; unset means they
+    /// are open like the rest of the API.
+    pub admin_token: Option<String>,
+    /// Close a review once its last open thread is resolved. Agent
+    /// explanations don't need resolving.
+    pub auto_close: bool,
+    /// Most unresolved threads a review may hold; creating more is refused.
+    pub max_open_threads: usize,
+    /// Longest comment body in bytes, including a thread's first comment.
+    pub max_comment_bytes: usize,
+    /// Directory of extra `.sublime-syntax` files to highlight languages the
+    /// built-in set lacks.
+    pub syntax_dir: Option<std::path::PathBuf>,
+    /// URLs every event is POSTed to, as `WsEvent` JSON.
+    pub webhook_urls: Vec<String>,
+    /// Signs webhook bodies with HMAC-SHA256 when set.
+    pub webhook_secret: Option<String>,
+    /// Count open agent explanations in `open_thread_count`, so they hold off
+    /// auto-close like any other open thread.
+    pub count_explanations: bool,
+    /// Expire reviews with no activity for this long; unset keeps them
+    /// forever.
+    pub expire_after: Option<chrono::TimeDelta>,
+    /// Delete expired reviews instead of closing them.
+    pub expire_delete: bool,
+    /// Copy each changed file's base content into new revisions, so the old
+    /// side still shows once the repository moves on or is deleted.
+    pub snapshot_base: bool,
+    /// Threads and comments one client address may create per minute; unset means
+    /// unlimited.
+    pub rate_limit: Option<u32>,
+}
+
+impl ServerConfig {
+    /// The store-side form of `max_open_threads`.
+    pub fn thread_limit(&self) -> Option<preflight_core::store::ThreadLimit> {
+        Some(preflight_core::store::ThreadLimit {
+            max_open: self.max_open_threads,
+            count_explanations: self.count_explanations,
+        })
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            max_file_size: preflight_core::file_reader::DEFAULT_MAX_FILE_SIZE,
+            metrics: false,
+            access_log: false,
+            rename_threshold: preflight_core::git_diff::DEFAULT_RENAME_THRESHOLD,
+            admin_token: None,
+            auto_close: false,
+            max_open_threads: DEFAULT_MAX_OPEN_THREADS,
+            max_comment_bytes: DEFAULT_MAX_COMMENT_BYTES,
+            syntax_dir: None,
+            webhook_urls: Vec::new(),
+            webhook_secret: None,
+            count_explanations: false,
+            expire_after: None,
+            expire_delete: false,
+            snapshot_base: false,
+            rate_limit: None,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// How to diff a review that uses `algorithm`.
+    pub fn diff_options(&self, algorithm: DiffAlgorithm) -> DiffOptions {
+        DiffOptions {
+            rename_threshold: self.rename_threshold,
+            algorithm,
+        }
+    }
 }
 
 struct PresenceState {
     connected: bool,
+    /// Agents currently registered; the review only goes disconnected once
+    /// this drops to zero and the grace period passes.
+    registrations: usize,
     disconnect_handle: Option<tokio::task::JoinHandle<()>>,
 }
 
-pub struct AgentPresenceTracker {
+impl PresenceState {
+    fn new() -> Self {
+        Self {
+            connected: false,
+            registrations: 0,
+            disconnect_handle: None,
+        }
+    }
+}
+
+/// Who is present on each review, for one kind of participant (agents or
+/// human viewers). Changes are broadcast as `event_type` events.
+pub struct PresenceTracker {
     inner: Arc<Mutex<HashMap<Uuid, PresenceState>>>,
     ws_tx: broadcast::Sender<WsEvent>,
+    event_type: WsEventType,
 }
 
-impl AgentPresenceTracker {
-    pub fn new(ws_tx: broadcast::Sender<WsEvent>) -> Self {
+impl PresenceTracker {
+    pub fn new(ws_tx: broadcast::Sender<WsEvent>, event_type: WsEventType) -> Self {
         Self {
             inner: Arc::new(Mutex::new(HashMap::new())),
             ws_tx,
+            event_type,
         }
     }
 
+    /// Tracks agents, announced with `AgentPresenceChanged`.
+    pub fn agents(ws_tx: broadcast::Sender<WsEvent>) -> Self {
+        Self::new(ws_tx, WsEventType::AgentPresenceChanged)
+    }
+
+    /// Tracks human viewers, announced with `HumanPresenceChanged`.
+    pub fn humans(ws_tx: broadcast::Sender<WsEvent>) -> Self {
+        Self::new(ws_tx, WsEventType::HumanPresenceChanged)
+    }
+
     pub async fn register(&self, review_id: Uuid) {
         let mut map = self.inner.lock().await;
-        let entry = map.entry(review_id).or_insert(PresenceState {
-            connected: false,
-            disconnect_handle: None,
-        });
+        let entry = map.entry(review_id).or_insert_with(PresenceState::new);
+        entry.registrations += 1;
+        self.mark_connected(review_id, entry);
+    }
+
+    pub async fn deregister(&self, review_id: Uuid) {
+        let mut map = self.inner.lock().await;
+        if let Some(entry) = map.get_mut(&review_id) {
+            // Ignore unmatched deregisters rather than underflowing
+            if entry.registrations == 0 {
+                return;
+            }
+            entry.registrations -= 1;
+            if entry.registrations > 0 {
+                // Another agent is still connected
+                return;
+            }
+            self.schedule_disconnect(review_id, entry);
+        }
+    }
 
-        // Cancel any pending disconnect timer
+    /// Keep an agent marked present without registering it: each heartbeat
+    /// restarts the grace period, so the review goes disconnected once
+    /// heartbeats stop for that long. Registered agents keep the review
+    /// connected regardless.
+    pub async fn heartbeat(&self, review_id: Uuid) {
+        let mut map = self.inner.lock().await;
+        let entry = map.entry(review_id).or_insert_with(PresenceState::new);
+        self.mark_connected(review_id, entry);
+        if entry.registrations == 0 {
+            self.schedule_disconnect(review_id, entry);
+        }
+    }
+
+    /// Cancel any pending disconnect and broadcast if the review wasn't
+    /// already connected.
+    fn mark_connected(&self, review_id: Uuid, entry: &mut PresenceState) {
         if let Some(handle) = entry.disconnect_handle.take() {
             handle.abort();
         }
@@ -54,7 +212,7 @@ impl AgentPresenceTracker {
 
         if !was_connected {
             let _ = self.ws_tx.send(WsEvent {
-                event_type: WsEventType::AgentPresenceChanged,
+                event_type: self.event_type.clone(),
                 review_id: review_id.to_string(),
                 payload: serde_json::json!({ "connected": true }),
                 timestamp: Utc::now(),
@@ -62,32 +220,44 @@ impl AgentPresenceTracker {
         }
     }
 
-    pub async fn deregister(&self, review_id: Uuid) {
+    /// (Re)start the grace timer that marks the review disconnected unless
+    /// an agent registers or heartbeats first.
+    fn schedule_disconnect(&self, review_id: Uuid, entry: &mut PresenceState) {
+        if let Some(handle) = entry.disconnect_handle.take() {
+            handle.abort();
+        }
+
+        let ws_tx = self.ws_tx.clone();
+        let inner = self.inner.clone();
+        let event_type = self.event_type.clone();
+        entry.disconnect_handle = Some(tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            let mut map = inner.lock().await;
+            if let Some(entry) = map.get_mut(&review_id)
+                && entry.connected
+                && entry.registrations == 0
+            {
+                entry.connected = false;
+                let _ = ws_tx.send(WsEvent {
+                    event_type,
+                    review_id: review_id.to_string(),
+                    payload: serde_json::json!({ "connected": false }),
+                    timestamp: Utc::now(),
+                });
+            }
+        }));
+    }
+
+    /// Forget reviews `keep` rejects, e.g. ones a restore removed.
+    pub async fn retain(&self, keep: impl Fn(&Uuid) -> bool) {
         let mut map = self.inner.lock().await;
-        if let Some(entry) = map.get_mut(&review_id) {
-            // Cancel any existing timer
-            if let Some(handle) = entry.disconnect_handle.take() {
+        map.retain(|review_id, entry| {
+            let kept = keep(review_id);
+            if !kept && let Some(handle) = entry.disconnect_handle.take() {
                 handle.abort();
             }
-
-            let ws_tx = self.ws_tx.clone();
-            let inner = self.inner.clone();
-            entry.disconnect_handle = Some(tokio::spawn(async move {
-                tokio::time::sleep(std::time::Duration::from_secs(5)).await;
-                let mut map = inner.lock().await;
-                if let Some(entry) = map.get_mut(&review_id)
-                    && entry.connected
-                {
-                    entry.connected = false;
-                    let _ = ws_tx.send(WsEvent {
-                        event_type: WsEventType::AgentPresenceChanged,
-                        review_id: review_id.to_string(),
-                        payload: serde_json::json!({ "connected": false }),
-                        timestamp: Utc::now(),
-                    });
-                }
-            }));
-        }
+            kept
+        });
     }
 
     pub async fn is_connected(&self, review_id: Uuid) -> bool {
@@ -103,7 +273,7 @@ mod tests {
     #[tokio::test]
     async fn test_register_broadcasts_connected() {
         let (ws_tx, mut ws_rx) = broadcast::channel(16);
-        let tracker = AgentPresenceTracker::new(ws_tx);
+        let tracker = PresenceTracker::agents(ws_tx);
         let review_id = Uuid::new_v4();
 
         tracker.register(review_id).await;
@@ -117,7 +287,7 @@ mod tests {
     #[tokio::test]
     async fn test_register_twice_only_broadcasts_once() {
         let (ws_tx, mut ws_rx) = broadcast::channel(16);
-        let tracker = AgentPresenceTracker::new(ws_tx);
+        let tracker = PresenceTracker::agents(ws_tx);
         let review_id = Uuid::new_v4();
 
         tracker.register(review_id).await;
@@ -132,7 +302,7 @@ mod tests {
     #[tokio::test]
     async fn test_is_connected_default_false() {
         let (ws_tx, _) = broadcast::channel(16);
-        let tracker = AgentPresenceTracker::new(ws_tx);
+        let tracker = PresenceTracker::agents(ws_tx);
 
         assert!(!tracker.is_connected(Uuid::new_v4()).await);
     }
@@ -140,17 +310,30 @@ mod tests {
     #[tokio::test]
     async fn test_is_connected_after_register() {
         let (ws_tx, _) = broadcast::channel(16);
-        let tracker = AgentPresenceTracker::new(ws_tx);
+        let tracker = PresenceTracker::agents(ws_tx);
         let review_id = Uuid::new_v4();
 
         tracker.register(review_id).await;
         assert!(tracker.is_connected(review_id).await);
     }
 
+    #[tokio::test]
+    async fn test_retain_forgets_other_reviews() {
+        let (ws_tx, _) = broadcast::channel(16);
+        let tracker = PresenceTracker::agents(ws_tx);
+        let (kept, dropped) = (Uuid::new_v4(), Uuid::new_v4());
+
+        tracker.register(kept).await;
+        tracker.register(dropped).await;
+        tracker.retain(|id| *id == kept).await;
+        assert!(tracker.is_connected(kept).await);
+        assert!(!tracker.is_connected(dropped).await);
+    }
+
     #[tokio::test]
     async fn test_deregister_disconnects_after_grace_period() {
         let (ws_tx, mut ws_rx) = broadcast::channel(16);
-        let tracker = Arc::new(AgentPresenceTracker::new(ws_tx));
+        let tracker = Arc::new(PresenceTracker::agents(ws_tx));
         let review_id = Uuid::new_v4();
 
         tracker.register(review_id).await;
@@ -171,7 +354,7 @@ mod tests {
     #[tokio::test]
     async fn test_register_cancels_deregister_grace_period() {
         let (ws_tx, mut ws_rx) = broadcast::channel(16);
-        let tracker = Arc::new(AgentPresenceTracker::new(ws_tx));
+        let tracker = Arc::new(PresenceTracker::agents(ws_tx));
         let review_id = Uuid::new_v4();
 
         tracker.register(review_id).await;
@@ -189,4 +372,88 @@ mod tests {
         // Should still be connected (grace period was cancelled)
         assert!(tracker.is_connected(review_id).await);
     }
+
+    #[tokio::test]
+    async fn test_stays_connected_until_last_deregister() {
+        let (ws_tx, mut ws_rx) = broadcast::channel(16);
+        let tracker = Arc::new(PresenceTracker::agents(ws_tx));
+        let review_id = Uuid::new_v4();
+
+        tracker.register(review_id).await;
+        tracker.register(review_id).await;
+        let _connect_event = ws_rx.recv().await.unwrap();
+
+        // One agent leaves; the other is still there past the grace period
+        tracker.deregister(review_id).await;
+        tokio::time::sleep(std::time::Duration::from_secs(6)).await;
+        assert!(tracker.is_connected(review_id).await);
+        assert!(ws_rx.try_recv().is_err());
+
+        // The last agent leaves: still connected during the grace period
+        tracker.deregister(review_id).await;
+        assert!(tracker.is_connected(review_id).await);
+        tokio::time::sleep(std::time::Duration::from_secs(6)).await;
+        assert!(!tracker.is_connected(review_id).await);
+
+        let event = ws_rx.recv().await.unwrap();
+        assert_eq!(event.payload["connected"], false);
+        assert!(ws_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_heartbeats_keep_presence_past_grace_period() {
+        let (ws_tx, mut ws_rx) = broadcast::channel(16);
+        let tracker = Arc::new(PresenceTracker::agents(ws_tx));
+        let review_id = Uuid::new_v4();
+
+        tracker.heartbeat(review_id).await;
+        let event = ws_rx.recv().await.unwrap();
+        assert_eq!(event.payload["connected"], true);
+
+        // Heartbeating every 3s keeps the review connected well past one
+        // 5s grace period, without re-broadcasting
+        for _ in 0..3 {
+            tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+            tracker.heartbeat(review_id).await;
+        }
+        assert!(tracker.is_connected(review_id).await);
+        assert!(ws_rx.try_recv().is_err());
+
+        // Once heartbeats stop, it disconnects after the grace period
+        tokio::time::sleep(std::time::Duration::from_secs(6)).await;
+        assert!(!tracker.is_connected(review_id).await);
+        let event = ws_rx.recv().await.unwrap();
+        assert_eq!(event.payload["connected"], false);
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_does_not_count_as_registration() {
+        let (ws_tx, _) = broadcast::channel(16);
+        let tracker = Arc::new(PresenceTracker::agents(ws_tx));
+        let review_id = Uuid::new_v4();
+
+        tracker.register(review_id).await;
+        tracker.heartbeat(review_id).await;
+        // The registered agent keeps the review connected after the
+        // heartbeat's grace period lapses
+        tokio::time::sleep(std::time::Duration::from_secs(6)).await;
+        assert!(tracker.is_connected(review_id).await);
+
+        tracker.deregister(review_id).await;
+        tokio::time::sleep(std::time::Duration::from_secs(6)).await;
+        assert!(!tracker.is_connected(review_id).await);
+    }
+
+    #[tokio::test]
+    async fn test_human_tracker_broadcasts_human_event() {
+        let (ws_tx, mut ws_rx) = broadcast::channel(16);
+        let tracker = PresenceTracker::humans(ws_tx);
+        let review_id = Uuid::new_v4();
+
+        tracker.register(review_id).await;
+
+        let event = ws_rx.recv().await.unwrap();
+        assert_eq!(event.event_type, WsEventType::HumanPresenceChanged);
+        assert_eq!(event.payload["connected"], true);
+    }
 }
@@ -1,78 +1,433 @@
 use chrono::{DateTime, Utc};
-use preflight_core::diff::{FileStatus, Hunk};
-use preflight_core::review::{AgentStatus, AuthorType, ReviewStatus, ThreadOrigin, ThreadStatus};
+use preflight_core::diff::{FileStatus, Hunk, ModeChange};
+use preflight_core::git_diff::DiffAlgorithm;
+use preflight_core::review::{
+    AgentStatus, Attachment, AuthorType, ContentSnippet, ExtraRepo, LineSide, ReviewDecision,
+    ReviewStatus, ThreadOrigin, ThreadStatus,
+};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
 // --- Requests ---
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateReviewRequest {
     pub title: Option<String>,
     pub repo_path: String,
-    pub base_ref: String,
+    /// Git ref to diff against. Omit or pass `"auto"` to use the merge-base
+    /// with the default branch. `<commit>^!` reviews just that commit.
+    pub base_ref: Option<String>,
+    /// Review exactly the changes this commit introduced, instead of the
+    /// working tree. Shorthand for a `<commit>^!` base ref.
+    pub commit: Option<String>,
+    /// Limit the review to changes under this repo-relative directory.
+    pub path_prefix: Option<String>,
+    /// More repositories to review together with this one, such as a
+    /// submodule or a sibling worktree.
+    #[serde(default)]
+    pub extra_repos: Vec<ExtraRepoRequest>,
+    /// Tags such as an external id (`pr:123`) or a model name.
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// How lines are matched up in this review's diffs. Kept for every later
+    /// revision.
+    #[serde(default)]
+    pub diff_algorithm: DiffAlgorithm,
+    /// Create the review even if there is nothing to review yet. Without
+    /// it, a repo with no changes against the base ref is rejected.
+    #[serde(default)]
+    pub allow_empty: bool,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateReviewFromDiffRequest {
+    pub title: Option<String>,
+    /// Unified diff text in `git diff` format. The review has no repository,
+    /// so only the diff's hunks can be shown.
+    pub diff: String,
+    /// What the diff was taken against, kept as a label. Defaults to `HEAD`.
+    pub base_ref: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ExtraRepoRequest {
+    pub repo_path: String,
+    /// Same as `CreateReviewRequest::base_ref`, resolved in this repository.
+    pub base_ref: Option<String>,
+    /// Directory the repository's files are listed under, e.g. the
+    /// submodule's path.
+    pub prefix: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateReviewRequest {
+    /// Replaces the review's labels.
+    pub labels: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ForkReviewRequest {
+    /// Defaults to the original review's title.
+    pub title: Option<String>,
+    /// Defaults to the original review's base ref; `"auto"` picks the
+    /// merge-base with the default branch.
+    pub base_ref: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PreviewReviewRequest {
+    pub repo_path: String,
+    /// Same as `CreateReviewRequest::base_ref`.
+    pub base_ref: Option<String>,
+    pub path_prefix: Option<String>,
+    /// Same as `CreateReviewRequest::diff_algorithm`.
+    #[serde(default)]
+    pub diff_algorithm: DiffAlgorithm,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ValidateRepoRequest {
+    pub repo_path: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct FindOrCreateReviewRequest {
     pub repo_path: String,
     pub title: Option<String>,
+    /// Same as `CreateReviewRequest::base_ref`.
     pub base_ref: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateRevisionRequest {
     pub trigger: preflight_core::review::RevisionTrigger,
     pub message: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateThreadRequest {
     pub file_path: String,
     pub line_start: u32,
     pub line_end: u32,
+    /// `Old` anchors the thread to lines of the file before the change, e.g.
+    /// to comment on deleted code. Defaults to `New`.
+    #[serde(default)]
+    pub side: LineSide,
     pub origin: ThreadOrigin,
     pub body: String,
     pub author_type: AuthorType,
+    /// Replacement text proposed for the anchored lines.
+    #[serde(default)]
+    pub suggestion: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+/// Create a thread by pointing at lines of the diff instead of giving
+/// absolute line numbers.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateHunkThreadRequest {
+    pub file_path: String,
+    /// Index of the hunk in the file's diff, from 0.
+    pub hunk_index: usize,
+    /// Index of the first line within the hunk, from 0, counting context,
+    /// added and removed lines.
+    pub line_offset: usize,
+    /// Number of hunk lines to cover; defaults to 1.
+    #[serde(default = "default_line_count")]
+    pub line_count: usize,
+    pub origin: ThreadOrigin,
+    pub body: String,
+    pub author_type: AuthorType,
+    #[serde(default)]
+    pub suggestion: Option<String>,
+}
+
+fn default_line_count() -> usize {
+    1
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateReviewStatusRequest {
     pub status: ReviewStatus,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetReviewDecisionRequest {
+    /// `null` withdraws an earlier decision.
+    pub decision: Option<ReviewDecision>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateThreadStatusRequest {
     pub status: ThreadStatus,
+    /// The thread's `version` when the client read it. If the thread has
+    /// changed since, the update is refused with 409 instead of overwriting
+    /// the newer change.
+    #[serde(default)]
+    pub expected_version: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct LinkThreadsRequest {
+    /// Threads in the same review to link to.
+    pub thread_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PinThreadRequest {
+    pub pinned: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateAgentStatusRequest {
     pub status: AgentStatus,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct AddCommentRequest {
     pub author_type: AuthorType,
     pub body: String,
+    /// Store without broadcasting until the review's drafts are submitted.
+    #[serde(default)]
+    pub draft: bool,
+    /// An earlier comment in the same thread this one replies to.
+    #[serde(default)]
+    pub in_reply_to: Option<Uuid>,
+    /// Replacement text proposed for the thread's anchored lines.
+    #[serde(default)]
+    pub suggestion: Option<String>,
+    /// Structured content such as code blocks with a language; `body` is
+    /// still required as the plain-text fallback.
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
 }
 
 // --- Query parameters ---
 
-#[derive(Debug, Deserialize)]
-pub struct RevisionQuery {
+/// Comment author to filter by, written lowercase in query strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthorFilter {
+    Human,
+    Agent,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ActivityQuery {
+    /// Only include comments by this author; other activity is left out.
+    pub author: Option<AuthorFilter>,
+}
+
+/// Renderings of comment bodies a client can ask for besides the raw text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RenderFormat {
+    Html,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RenderQuery {
+    /// `html` adds `body_html` to each comment.
+    pub render: Option<RenderFormat>,
+}
+
+/// Whether to return unsubmitted draft comments. Only the reviewer's own UI
+/// should ask for them; everyone else sees comments once they are published.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DraftsQuery {
+    #[serde(default)]
+    pub include_drafts: bool,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CommentsBatchQuery {
+    /// `html` adds `body_html` to each comment.
+    pub render: Option<RenderFormat>,
+    /// Also return unsubmitted drafts; see [`DraftsQuery`].
+    #[serde(default)]
+    pub include_drafts: bool,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReviewListQuery {
+    /// Only list reviews with this status.
+    pub status: Option<ReviewStatus>,
+    /// Only list reviews carrying this label.
+    pub label: Option<String>,
+}
+
+/// Default and largest page size for `GET /api/threads`.
+pub const THREAD_PAGE_SIZE: usize = 100;
+pub const MAX_THREAD_PAGE_SIZE: usize = 500;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AllThreadsQuery {
+    pub status: Option<ThreadStatus>,
+    pub origin: Option<ThreadOrigin>,
+    /// Matching threads to skip.
+    #[serde(default)]
+    pub offset: usize,
+    /// Page size, at most 500. Defaults to 100.
+    pub limit: Option<usize>,
+    /// Also return unsubmitted drafts; see [`DraftsQuery`].
+    #[serde(default)]
+    pub include_drafts: bool,
+}
+
+/// Orders `GET /api/reviews/{id}/files` can return files in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FileSort {
+    /// Alphabetical by path.
+    #[default]
+    Path,
+    /// Added, then modified, renamed, deleted, and binary or symlink files.
+    Status,
+    /// Most added plus removed lines first.
+    Size,
+    /// Most threads first.
+    Threads,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FileListQuery {
+    pub revision: Option<u32>,
+    /// Order of the files; ties are broken by path. Defaults to `path`.
+    pub sort: Option<FileSort>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FileDiffQuery {
+    pub revision: Option<u32>,
+    /// Only show what changed after this revision, instead of the full diff against base.
+    pub since: Option<u32>,
+    /// Hide changes that only touch whitespace, like `git diff -w`.
+    #[serde(default)]
+    pub ignore_whitespace: bool,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReviewDiffQuery {
     pub revision: Option<u32>,
+    /// Highlight every line, as the single-file diff does.
+    #[serde(default)]
+    pub highlight: bool,
+    /// Hide changes that only touch whitespace, like `git diff -w`.
+    #[serde(default)]
+    pub ignore_whitespace: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct InterdiffQuery {
     pub from: u32,
     pub to: u32,
 }
 
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RebasePreviewQuery {
+    /// Base ref to diff against instead, resolved like
+    /// `CreateReviewRequest::base_ref`.
+    pub new_base: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RestoreQuery {
+    /// Must be `true`: restoring replaces everything in the store.
+    #[serde(default)]
+    pub confirm: bool,
+}
+
 // --- Responses ---
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReviewPreviewResponse {
+    pub file_count: usize,
+    pub additions: usize,
+    pub deletions: usize,
+    pub files: Vec<preflight_core::diff::FileDiff>,
+}
+
+/// What a review of a repo path would start from. Everything but
+/// `is_git_repo` is left out when the path isn't a repository.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ValidateRepoResponse {
+    pub is_git_repo: bool,
+    /// The base a review would use by default: the merge-base with the
+    /// default branch, or `HEAD`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detected_base: Option<String>,
+    /// `None` on a detached HEAD.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_branch: Option<String>,
+    /// Files changed against `detected_base`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changed_file_count: Option<usize>,
+}
+
+/// Everything the review page needs, in one response.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReviewFullResponse {
+    pub review: ReviewResponse,
+    /// Files in the latest revision.
+    pub files: Vec<FileListEntry>,
+    pub threads: Vec<ThreadResponse>,
+    pub revisions: Vec<RevisionResponse>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RebasePreviewResponse {
+    /// The new base as it would be stored on the review.
+    pub new_base: String,
+    /// Paths the review would contain against the new base.
+    pub files: Vec<String>,
+    /// Paths that only differ from the new base.
+    pub added_files: Vec<String>,
+    /// Paths that only differ from the current base.
+    pub removed_files: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DriftResponse {
+    /// Whether the working tree no longer matches the latest revision.
+    pub drifted: bool,
+    /// Paths whose diff differs from the latest revision.
+    pub changed_files: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ThreadPageResponse {
+    pub threads: Vec<ReviewThreadResponse>,
+    /// Matching threads across all pages.
+    pub total: usize,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReviewThreadResponse {
+    pub review_title: Option<String>,
+    #[serde(flatten)]
+    pub thread: ThreadResponse,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreateThreadsResponse {
+    pub thread_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ReviewResponse {
     pub id: Uuid,
     pub title: Option<String>,
@@ -81,11 +436,21 @@ pub struct ReviewResponse {
     pub thread_count: usize,
     pub open_thread_count: usize,
     pub revision_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path_prefix: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// When the agent last marked the whole review as seen; cleared by the
+    /// next human comment.
+    pub agent_seen_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extra_repos: Vec<ExtraRepo>,
+    pub labels: Vec<String>,
+    /// The reviewer's verdict, set with `POST /api/reviews/{id}/decision`.
+    pub decision: Option<ReviewDecision>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RevisionResponse {
     pub id: Uuid,
     pub review_id: Uuid,
@@ -96,23 +461,67 @@ pub struct RevisionResponse {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct FileListEntry {
     pub path: String,
     pub status: FileStatus,
     pub thread_count: usize,
     pub open_thread_count: usize,
+    /// The changes only touch whitespace, imports or comments, so the file
+    /// can be collapsed in the list.
+    pub trivial: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct FileDiffResponse {
     pub path: String,
     pub old_path: Option<String>,
     pub status: FileStatus,
-    pub hunks: Vec<Hunk>,
+    /// Display name of the file's language (e.g. `Rust`), if recognized.
+    pub language: Option<String>,
+    pub hunks: Vec<HunkResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode_change: Option<ModeChange>,
+    /// Hunks were left out to keep a whole-review diff within its size
+    /// limit; fetch the file on its own to see them.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+/// A hunk plus the span of lines it actually covers on each side, so
+/// clients can place it (e.g. in a minimap) without scanning its lines.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HunkResponse {
+    #[serde(flatten)]
+    pub hunk: Hunk,
+    pub new_line_start: Option<u32>,
+    pub new_line_end: Option<u32>,
+    pub old_line_start: Option<u32>,
+    pub old_line_end: Option<u32>,
+}
+
+impl From<Hunk> for HunkResponse {
+    fn from(hunk: Hunk) -> Self {
+        let new_span = hunk.new_line_span();
+        let old_span = hunk.old_line_span();
+        Self {
+            new_line_start: new_span.map(|(start, _)| start),
+            new_line_end: new_span.map(|(_, end)| end),
+            old_line_start: old_span.map(|(start, _)| start),
+            old_line_end: old_span.map(|(_, end)| end),
+            hunk,
+        }
+    }
+}
+
+/// One added line of a file's diff, numbered in the new file.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChangedLine {
+    pub line_no: u32,
+    pub content: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct FileContentLine {
     pub line_no: u32,
     pub content: String,
@@ -120,20 +529,25 @@ pub struct FileContentLine {
     pub highlighted: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct FileContentResponse {
     pub path: String,
     pub language: Option<String>,
     pub lines: Vec<FileContentLine>,
+    /// Only the lines the diff shows are included, because the review has no
+    /// repository to read the whole file from.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub partial: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ThreadResponse {
     pub id: Uuid,
     pub review_id: Uuid,
     pub file_path: String,
     pub line_start: u32,
     pub line_end: u32,
+    pub side: LineSide,
     pub origin: ThreadOrigin,
     pub status: ThreadStatus,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -141,22 +555,90 @@ pub struct ThreadResponse {
     pub comments: Vec<CommentResponse>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Linked threads elsewhere in the review.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub related_threads: Vec<Uuid>,
+    /// Pinned threads are listed first.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+    /// Bumped on every change; pass it back as `expected_version` to make a
+    /// status update conditional.
+    pub version: u64,
+    /// Comments by others since the human last marked the review seen (all of
+    /// them if they never have).
+    pub unread_count: usize,
+    /// Whether `unread_count` is above zero.
+    pub has_unread: bool,
+    /// The code the thread was anchored to when it was created. Missing for
+    /// older threads and for lines outside the diff.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_snippet: Option<ContentSnippet>,
+    /// `content_snippet`'s lines joined with newlines.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor_text: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CommentResponse {
     pub id: Uuid,
     pub author_type: AuthorType,
     pub body: String,
     pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub draft: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub in_reply_to: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+    /// `body` rendered from Markdown to sanitized HTML. Only included when
+    /// asked for with `?render=html`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body_html: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    ReviewCreated,
+    RevisionCreated,
+    ThreadCreated,
+    CommentAdded,
+    StatusChanged,
+}
+
+/// A single entry in a review's chronological activity feed.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ActivityEntry {
+    pub kind: ActivityKind,
+    pub timestamp: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revision_number: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_type: Option<AuthorType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ReviewStatus>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateAgentPresenceRequest {
     pub connected: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct AgentPresenceResponse {
     pub connected: bool,
 }
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HumanPresenceResponse {
+    /// Whether someone has the review open in the UI.
+    pub connected: bool,
+}
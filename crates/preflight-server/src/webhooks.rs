@@ -0,0 +1,244 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use ring::hmac;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+use crate::ws::WsEvent;
+
+/// Header carrying `sha256=<hex HMAC of the body>` when a secret is set.
+pub const SIGNATURE_HEADER: &str = "x-preflight-signature";
+
+/// Header naming the event, so receivers can route without parsing the body.
+pub const EVENT_HEADER: &str = "x-preflight-event";
+
+/// Waits between delivery attempts; a delivery is given up after the last.
+const RETRY_DELAYS: [Duration; 3] = [
+    Duration::from_secs(1),
+    Duration::from_secs(5),
+    Duration::from_secs(25),
+];
+
+/// Longest one attempt may take, so a receiver that accepts the connection
+/// and never answers can't stall its queue.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest to wait for a receiver to accept the connection.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Events waiting per URL behind the one being delivered; once full, new
+/// events for that URL are dropped.
+const QUEUE_DEPTH: usize = 256;
+
+/// One event, ready to POST.
+struct Delivery {
+    body: Vec<u8>,
+    event_name: String,
+    signature: Option<String>,
+}
+
+/// POST every event broadcast on `ws_tx` to each of `urls`, as the same
+/// `WsEvent` JSON the WebSocket sends. Each URL has its own queue and task,
+/// so a receiver gets events in order and a slow one doesn't hold up the
+/// others.
+pub fn spawn(ws_tx: &broadcast::Sender<WsEvent>, urls: Vec<String>, secret: Option<String>) {
+    let client = match reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .connect_timeout(CONNECT_TIMEOUT)
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("warning: webhooks disabled, failed to build HTTP client: {e}");
+            return;
+        }
+    };
+    let queues: Vec<_> = urls
+        .into_iter()
+        .map(|url| {
+            let (tx, mut rx) = mpsc::channel::<Arc<Delivery>>(QUEUE_DEPTH);
+            let client = client.clone();
+            let worker_url = url.clone();
+            tokio::spawn(async move {
+                while let Some(delivery) = rx.recv().await {
+                    deliver(&client, &worker_url, &delivery).await;
+                }
+            });
+            (url, tx)
+        })
+        .collect();
+    let mut rx = ws_tx.subscribe();
+    let key = secret.map(|s| hmac::Key::new(hmac::HMAC_SHA256, s.as_bytes()));
+    tokio::spawn(async move {
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(n)) => {
+                    eprintln!("webhooks lagged, skipped {n} events");
+                    continue;
+                }
+                Err(RecvError::Closed) => return,
+            };
+            let Ok(body) = serde_json::to_vec(&event) else {
+                continue;
+            };
+            let event_name = serde_json::to_value(&event.event_type)
+                .ok()
+                .and_then(|v| v.as_str().map(str::to_string))
+                .unwrap_or_default();
+            let signature = key.as_ref().map(|key| sign(key, &body));
+            let delivery = Arc::new(Delivery {
+                body,
+                event_name,
+                signature,
+            });
+            for (url, queue) in &queues {
+                if let Err(TrySendError::Full(_)) = queue.try_send(delivery.clone()) {
+                    eprintln!(
+                        "webhook {url}: queue full, dropping {} event",
+                        delivery.event_name
+                    );
+                }
+            }
+        }
+    });
+}
+
+/// `sha256=` followed by the hex HMAC-SHA256 of `body`.
+pub fn sign(key: &hmac::Key, body: &[u8]) -> String {
+    let tag = hmac::sign(key, body);
+    let hex: String = tag.as_ref().iter().map(|b| format!("{b:02x}")).collect();
+    format!("sha256={hex}")
+}
+
+async fn deliver(client: &reqwest::Client, url: &str, delivery: &Delivery) {
+    let Delivery {
+        body,
+        event_name,
+        signature,
+    } = delivery;
+    for (attempt, delay) in std::iter::once(None)
+        .chain(RETRY_DELAYS.iter().map(Some))
+        .enumerate()
+    {
+        if let Some(delay) = delay {
+            tokio::time::sleep(*delay).await;
+        }
+        let mut request = client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, event_name)
+            .body(body.clone());
+        if let Some(signature) = signature {
+            request = request.header(SIGNATURE_HEADER, signature);
+        }
+        match request.send().await {
+            Ok(response) if response.status().is_success() => return,
+            Ok(response) => {
+                eprintln!(
+                    "webhook {url} answered {} (attempt {})",
+                    response.status(),
+                    attempt + 1
+                );
+            }
+            Err(e) => eprintln!("webhook {url} failed: {e} (attempt {})", attempt + 1),
+        }
+    }
+    eprintln!("webhook {url}: giving up on {event_name} event");
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::{HeaderMap, Request, StatusCode};
+    use tokio::sync::mpsc;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    /// Start a receiver that forwards each request's headers and body.
+    async fn mock_receiver() -> (String, mpsc::UnboundedReceiver<(HeaderMap, String)>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let receiver = axum::Router::new().route(
+            "/hook",
+            axum::routing::post(move |headers: HeaderMap, body: String| {
+                let tx = tx.clone();
+                async move {
+                    let _ = tx.send((headers, body));
+                    StatusCode::NO_CONTENT
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, receiver).await.unwrap() });
+        (format!("http://{addr}/hook"), rx)
+    }
+
+    #[tokio::test]
+    async fn test_review_created_is_posted_and_signed() {
+        let (url, mut received) = mock_receiver().await;
+        let store = preflight_core::memory_store::MemoryStore::new();
+        let config = crate::state::ServerConfig {
+            webhook_urls: vec![url],
+            webhook_secret: Some("s3cret".into()),
+            ..Default::default()
+        };
+        let app = crate::app_with_config(std::sync::Arc::new(store), config);
+
+        let diff = "diff --git a/a.txt b/a.txt\n--- a/a.txt\n+++ b/a.txt\n@@ -1 +1 @@\n-a\n+b\n";
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/reviews/from-diff")
+                    .header("content-type", "application/json")
+                    .body(Body::from(serde_json::json!({ "diff": diff }).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let (headers, body) = tokio::time::timeout(Duration::from_secs(5), received.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        let event: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(event["event_type"], "review_created");
+        assert_eq!(headers[EVENT_HEADER], "review_created");
+        assert_eq!(event["payload"]["file_count"], 1);
+        let key = hmac::Key::new(hmac::HMAC_SHA256, b"s3cret");
+        assert_eq!(
+            headers[SIGNATURE_HEADER],
+            sign(&key, body.as_bytes()).as_str()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_events_arrive_in_order() {
+        let (url, mut received) = mock_receiver().await;
+        let (ws_tx, _) = broadcast::channel(64);
+        spawn(&ws_tx, vec![url], None);
+
+        for i in 0..10 {
+            ws_tx
+                .send(WsEvent {
+                    event_type: preflight_core::ws::WsEventType::ReviewCreated,
+                    review_id: i.to_string(),
+                    payload: serde_json::Value::Null,
+                    timestamp: chrono::Utc::now(),
+                })
+                .unwrap();
+        }
+        for i in 0..10 {
+            let (_, body) = tokio::time::timeout(Duration::from_secs(5), received.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            let event: serde_json::Value = serde_json::from_str(&body).unwrap();
+            assert_eq!(event["review_id"], i.to_string());
+        }
+    }
+}
@@ -1,36 +1,84 @@
 use axum::{
-    extract::State,
     extract::ws::{Message, WebSocket, WebSocketUpgrade},
+    extract::{Query, State},
     response::Response,
 };
+use serde::Deserialize;
+use uuid::Uuid;
 
 pub use preflight_core::ws::{WsEvent, WsEventType};
 
+use crate::error::ApiError;
 use crate::state::AppState;
 
-pub async fn ws_handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
-    ws.on_upgrade(|socket| handle_socket(socket, state))
+/// Who is on the other end of a WebSocket connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ViewerRole {
+    Human,
 }
 
-async fn handle_socket(mut socket: WebSocket, state: AppState) {
+#[derive(Debug, Deserialize)]
+pub struct WsQuery {
+    /// `human` counts the connection as someone viewing `review_id` for as
+    /// long as it stays open.
+    role: Option<ViewerRole>,
+    review_id: Option<Uuid>,
+}
+
+pub async fn ws_handler(
+    ws: WebSocketUpgrade,
+    Query(query): Query<WsQuery>,
+    State(state): State<AppState>,
+) -> Result<Response, ApiError> {
+    let viewing = match query.role {
+        Some(ViewerRole::Human) => {
+            let review_id = query
+                .review_id
+                .ok_or_else(|| ApiError::invalid_request("role=human needs a review_id"))?;
+            state.store.get_review(review_id).await?;
+            Some(review_id)
+        }
+        None => None,
+    };
+    Ok(ws.on_upgrade(move |socket| handle_socket(socket, state, viewing)))
+}
+
+async fn handle_socket(mut socket: WebSocket, state: AppState, viewing: Option<Uuid>) {
+    state.metrics.ws_connected();
+    if let Some(review_id) = viewing {
+        state.human_presence.register(review_id).await;
+    }
     let mut rx = state.ws_tx.subscribe();
     loop {
-        match rx.recv().await {
-            Ok(event) => {
-                if let Ok(json) = serde_json::to_string(&event)
-                    && socket.send(Message::Text(json.into())).await.is_err()
-                {
-                    break; // Client disconnected
+        tokio::select! {
+            event = rx.recv() => match event {
+                Ok(event) => {
+                    if let Ok(json) = serde_json::to_string(&event)
+                        && socket.send(Message::Text(json.into())).await.is_err()
+                    {
+                        break; // Client disconnected
+                    }
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                    eprintln!("WebSocket client lagged, skipped {n} messages");
                 }
-            }
-            Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
-                eprintln!("WebSocket client lagged, skipped {n} messages");
-            }
-            Err(tokio::sync::broadcast::error::RecvError::Closed) => {
-                break; // Channel closed (server shutting down)
-            }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                    break; // Channel closed (server shutting down)
+                }
+            },
+            // Notice a closed connection right away rather than on the next
+            // send, so presence is released promptly
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
         }
     }
+    if let Some(review_id) = viewing {
+        state.human_presence.deregister(review_id).await;
+    }
+    state.metrics.ws_disconnected();
 }
 
 #[cfg(test)]
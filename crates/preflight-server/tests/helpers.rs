@@ -6,14 +6,9 @@ use http_body_util::BodyExt;
 use std::sync::Arc;
 use tower::ServiceExt;
 
-/// Build a fresh app with an ephemeral JSON store.
+/// Build a fresh app with an in-memory store.
 pub async fn test_app() -> axum::Router {
-    let dir = tempfile::TempDir::new().unwrap();
-    let path = dir.path().join("state.json");
-    let store = preflight_core::json_store::JsonFileStore::new(&path)
-        .await
-        .unwrap();
-    Box::leak(Box::new(dir));
+    let store = preflight_core::memory_store::MemoryStore::new();
     preflight_server::app(Arc::new(store))
 }
 
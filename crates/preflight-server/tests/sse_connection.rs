@@ -0,0 +1,88 @@
+mod helpers;
+
+use tokio::net::TcpListener;
+
+#[tokio::test]
+async fn sse_client_receives_filtered_events() {
+    // Bind on an ephemeral port
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let app = helpers::test_app().await;
+
+    // Spawn the server
+    tokio::spawn(async move {
+        axum::serve(listener, app.into_make_service())
+            .await
+            .unwrap();
+    });
+
+    let repo_path = helpers::setup_test_repo();
+    let client = reqwest::Client::new();
+    let create_review = |title: &'static str| {
+        client
+            .post(format!("http://{addr}/api/reviews"))
+            .json(&serde_json::json!({
+                "title": title,
+                "repo_path": repo_path,
+                "base_ref": "HEAD"
+            }))
+            .send()
+    };
+    let review: serde_json::Value = create_review("SSE target")
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let review_id = review["id"].as_str().unwrap().to_string();
+
+    // Subscribe to events for that review only
+    let mut events = client
+        .get(format!("http://{addr}/api/events?review_id={review_id}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(events.status(), 200);
+    assert_eq!(
+        events.headers()["content-type"].to_str().unwrap(),
+        "text/event-stream"
+    );
+
+    // An event for another review must be filtered out
+    let resp = create_review("SSE other").await.unwrap();
+    assert_eq!(resp.status(), 200);
+
+    let resp = client
+        .post(format!("http://{addr}/api/reviews/{review_id}/threads"))
+        .json(&serde_json::json!({
+            "file_path": "file.txt",
+            "line_start": 1,
+            "line_end": 1,
+            "origin": "Comment",
+            "body": "over SSE",
+            "author_type": "Human"
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+
+    // Read until the first complete `data:` frame arrives
+    let mut buf = String::new();
+    let data = loop {
+        let chunk = tokio::time::timeout(std::time::Duration::from_secs(5), events.chunk())
+            .await
+            .expect("Timed out waiting for SSE event")
+            .unwrap()
+            .expect("Stream ended");
+        buf.push_str(std::str::from_utf8(&chunk).unwrap());
+        if let Some(frame) = buf.split("\n\n").find(|f| f.starts_with("data:")) {
+            break frame.trim_start_matches("data:").trim().to_string();
+        }
+    };
+
+    let event: serde_json::Value = serde_json::from_str(&data).unwrap();
+    assert_eq!(event["event_type"], "thread_created");
+    assert_eq!(event["review_id"], review_id);
+}
@@ -52,3 +52,99 @@ async fn websocket_client_receives_events() {
     assert!(event["review_id"].is_string());
     assert!(event["timestamp"].is_string());
 }
+
+type WsStream =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+async fn next_event(ws: &mut WsStream) -> serde_json::Value {
+    let msg = tokio::time::timeout(std::time::Duration::from_secs(10), ws.next())
+        .await
+        .expect("Timed out waiting for WS message")
+        .expect("Stream ended")
+        .expect("WS error");
+    serde_json::from_str(&msg.into_text().unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn human_viewer_presence_follows_websocket_connection() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = helpers::test_app().await;
+    tokio::spawn(async move {
+        axum::serve(listener, app.into_make_service())
+            .await
+            .unwrap();
+    });
+
+    let repo_path = helpers::setup_test_repo();
+    let client = reqwest::Client::new();
+    let review: serde_json::Value = client
+        .post(format!("http://{addr}/api/reviews"))
+        .json(&serde_json::json!({ "repo_path": repo_path, "base_ref": "HEAD" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let review_id = review["id"].as_str().unwrap();
+    let human_status = || async {
+        let status: serde_json::Value = client
+            .get(format!(
+                "http://{addr}/api/reviews/{review_id}/human-status"
+            ))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        status["connected"].as_bool().unwrap()
+    };
+    assert!(!human_status().await);
+
+    // A plain listener sees the presence events
+    let (mut listener_ws, _) = connect_async(format!("ws://{addr}/api/ws")).await.unwrap();
+    let (mut viewer_ws, _) = connect_async(format!(
+        "ws://{addr}/api/ws?role=human&review_id={review_id}"
+    ))
+    .await
+    .unwrap();
+
+    let event = next_event(&mut listener_ws).await;
+    assert_eq!(event["event_type"], "human_presence_changed");
+    assert_eq!(event["review_id"], review_id);
+    assert_eq!(event["payload"]["connected"], true);
+    assert!(human_status().await);
+
+    // Closing the viewer's socket releases presence after the grace period
+    viewer_ws.close(None).await.unwrap();
+    let event = next_event(&mut listener_ws).await;
+    assert_eq!(event["event_type"], "human_presence_changed");
+    assert_eq!(event["payload"]["connected"], false);
+    assert!(!human_status().await);
+}
+
+#[tokio::test]
+async fn human_role_requires_existing_review() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = helpers::test_app().await;
+    tokio::spawn(async move {
+        axum::serve(listener, app.into_make_service())
+            .await
+            .unwrap();
+    });
+
+    assert!(
+        connect_async(format!("ws://{addr}/api/ws?role=human"))
+            .await
+            .is_err()
+    );
+    let unknown = uuid::Uuid::new_v4();
+    assert!(
+        connect_async(format!("ws://{addr}/api/ws?role=human&review_id={unknown}"))
+            .await
+            .is_err()
+    );
+}
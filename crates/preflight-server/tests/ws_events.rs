@@ -12,15 +12,13 @@ async fn app_with_ws_rx() -> (
     axum::Router,
     broadcast::Receiver<preflight_server::ws::WsEvent>,
 ) {
-    let dir = tempfile::TempDir::new().unwrap();
-    let path = dir.path().join("state.json");
-    let store = preflight_core::json_store::JsonFileStore::new(&path)
-        .await
-        .unwrap();
-    Box::leak(Box::new(dir));
+    let store = preflight_core::memory_store::MemoryStore::new();
 
     let (ws_tx, ws_rx) = broadcast::channel(64);
-    let agent_presence = Arc::new(preflight_server::state::AgentPresenceTracker::new(
+    let agent_presence = Arc::new(preflight_server::state::PresenceTracker::agents(
+        ws_tx.clone(),
+    ));
+    let human_presence = Arc::new(preflight_server::state::PresenceTracker::humans(
         ws_tx.clone(),
     ));
     let state = preflight_server::state::AppState {
@@ -29,6 +27,9 @@ async fn app_with_ws_rx() -> (
         ws_tx,
         agent_status: Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
         agent_presence,
+        human_presence,
+        config: Arc::new(preflight_server::state::ServerConfig::default()),
+        metrics: Arc::new(preflight_server::metrics::Metrics::default()),
     };
 
     use axum::routing::get;
@@ -57,6 +58,10 @@ async fn app_with_ws_rx() -> (
             preflight_server::routes::threads::thread_router(),
         )
         .nest("/api/threads", preflight_server::routes::comments::router())
+        .nest(
+            "/api/reviews",
+            preflight_server::routes::comments::review_router(),
+        )
         .with_state(state);
 
     (router, ws_rx)
@@ -134,6 +139,58 @@ async fn update_review_status_emits_event() {
         preflight_server::ws::WsEventType::ReviewStatusChanged
     ));
     assert_eq!(event.payload["status"], "Closed");
+    assert_eq!(event.payload["thread_count"], 0);
+    assert_eq!(event.payload["open_thread_count"], 0);
+}
+
+#[tokio::test]
+async fn set_review_decision_emits_event_and_is_returned() {
+    let (app, mut rx) = app_with_ws_rx().await;
+    let repo_path = helpers::setup_test_repo();
+    let review_id = helpers::create_review(&app, &repo_path).await;
+    // Drain the ReviewCreated event
+    let _ = rx.try_recv();
+
+    for decision in ["Approved", "ChangesRequested", "Commented"] {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/reviews/{review_id}/decision"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "decision": decision }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let event = rx.try_recv().unwrap();
+        assert_eq!(event.review_id, review_id);
+        assert!(matches!(
+            event.event_type,
+            preflight_server::ws::WsEventType::ReviewDecisionChanged
+        ));
+        assert_eq!(event.payload["decision"], decision);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/reviews/{review_id}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let json = body_json(response).await;
+        assert_eq!(json["decision"], decision);
+        // A decision doesn't change the review's status
+        assert_eq!(json["status"], "Open");
+    }
 }
 
 #[tokio::test]
@@ -213,6 +270,68 @@ async fn add_comment_emits_event() {
     assert_eq!(event.payload["thread_id"], thread_id);
 }
 
+#[tokio::test]
+async fn draft_comments_broadcast_only_when_submitted() {
+    let (app, mut rx) = app_with_ws_rx().await;
+    let repo_path = helpers::setup_test_repo();
+    let review_id = helpers::create_review(&app, &repo_path).await;
+    let _ = rx.try_recv(); // drain ReviewCreated
+    let thread_id = helpers::create_thread(&app, &review_id).await;
+    let _ = rx.try_recv(); // drain ThreadCreated
+
+    for body in ["first draft", "second draft"] {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/threads/{thread_id}/comments"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({
+                            "author_type": "Human",
+                            "body": body,
+                            "draft": true
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = helpers::body_json(response).await;
+        assert_eq!(json["draft"], true);
+    }
+    assert!(rx.try_recv().is_err(), "drafts must not broadcast");
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/reviews/{review_id}/submit-drafts"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = helpers::body_json(response).await;
+    assert_eq!(json.as_array().unwrap().len(), 2);
+
+    for expected in ["first draft", "second draft"] {
+        let event = rx.try_recv().unwrap();
+        assert!(matches!(
+            event.event_type,
+            preflight_server::ws::WsEventType::CommentAdded
+        ));
+        assert_eq!(event.payload["thread_id"], thread_id);
+        assert_eq!(event.payload["comment"]["body"], expected);
+        assert!(event.payload["comment"].get("draft").is_none());
+    }
+    assert!(rx.try_recv().is_err());
+}
+
 #[tokio::test]
 async fn update_thread_status_emits_event() {
     let (app, mut rx) = app_with_ws_rx().await;
@@ -246,4 +365,6 @@ async fn update_thread_status_emits_event() {
     ));
     assert_eq!(event.payload["thread_id"], thread_id);
     assert_eq!(event.payload["status"], "Resolved");
+    assert_eq!(event.payload["thread_count"], 1);
+    assert_eq!(event.payload["open_thread_count"], 0);
 }
@@ -58,6 +58,10 @@ const FIXTURE: FileDiffResponse = {
           new_line_no: 4,
         },
       ],
+      new_line_start: 1,
+      new_line_end: 4,
+      old_line_start: 1,
+      old_line_end: 3,
     },
     {
       old_start: 10,
@@ -73,6 +77,10 @@ const FIXTURE: FileDiffResponse = {
           new_line_no: 11,
         },
       ],
+      new_line_start: 11,
+      new_line_end: 11,
+      old_line_start: 10,
+      old_line_end: 10,
     },
   ],
 };
@@ -89,6 +97,10 @@ const THREAD_ON_LINE_2: ThreadResponse = {
   comments: [{ id: "c-1", author_type: "Human", body: "Why?", created_at: "" }],
   created_at: "",
   updated_at: "",
+  side: "New",
+  version: 0,
+  unread_count: 0,
+  has_unread: false,
 };
 
 async function renderDiff(
@@ -215,6 +227,10 @@ describe("DiffView", () => {
               highlighted: '<span class="sy-keyword">const</span> x = 1;',
             },
           ],
+          new_line_start: 1,
+          new_line_end: 1,
+          old_line_start: 1,
+          old_line_end: 1,
         },
       ],
     };
@@ -245,6 +261,10 @@ describe("DiffView", () => {
       comments: [],
       created_at: "",
       updated_at: "",
+      side: "New",
+      version: 0,
+      unread_count: 0,
+      has_unread: false,
     });
     await renderDiff([], { onThreadCreated });
     const buttons = screen.getAllByRole("button");
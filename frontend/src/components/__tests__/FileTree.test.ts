@@ -13,6 +13,7 @@ function makeFile(
     status: "Modified",
     thread_count: 0,
     open_thread_count: 0,
+    trivial: false,
     ...overrides,
   };
 }
@@ -77,6 +77,10 @@ describe("InlineCommentForm", () => {
       comments: [],
       created_at: "",
       updated_at: "",
+      side: "New",
+      version: 0,
+      unread_count: 0,
+      has_unread: false,
     });
 
     renderForm({ onSubmit });
@@ -125,6 +129,10 @@ describe("InlineCommentForm", () => {
       comments: [],
       created_at: "",
       updated_at: "",
+      side: "New",
+      version: 0,
+      unread_count: 0,
+      has_unread: false,
     });
 
     renderForm({ onSubmit });
@@ -176,6 +184,10 @@ describe("InlineCommentForm", () => {
       comments: [],
       created_at: "",
       updated_at: "",
+      side: "New",
+      version: 0,
+      unread_count: 0,
+      has_unread: false,
     });
     renderForm({ onSubmit });
     await user.type(screen.getByRole("textbox"), "quick fix");
@@ -33,6 +33,9 @@ function makeReview(overrides: Partial<ReviewResponse> = {}): ReviewResponse {
     revision_count: 1,
     created_at: "2025-01-01T00:00:00Z",
     updated_at: "2025-01-01T00:00:00Z",
+    agent_seen_at: null,
+    labels: [],
+    decision: null,
     ...overrides,
   };
 }
@@ -20,6 +20,9 @@ const mockReview: ReviewResponse = {
   revision_count: 1,
   created_at: "2025-01-01T00:00:00Z",
   updated_at: "2025-01-01T00:00:00Z",
+  agent_seen_at: null,
+  labels: [],
+  decision: null,
 };
 
 const mockFiles: FileListEntry[] = [
@@ -28,6 +31,7 @@ const mockFiles: FileListEntry[] = [
     status: "Modified",
     thread_count: 0,
     open_thread_count: 0,
+    trivial: false,
   },
 ];
 
@@ -94,6 +98,7 @@ vi.stubGlobal(
 vi.mock("../../lib/ws", () => ({
   onEvent: vi.fn(() => () => {}),
   onReconnect: vi.fn(() => () => {}),
+  setViewingReview: vi.fn(),
 }));
 
 import {
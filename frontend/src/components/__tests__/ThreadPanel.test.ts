@@ -30,6 +30,10 @@ const OPEN_THREAD: ThreadResponse = {
   ],
   created_at: "2026-02-09T01:00:00Z",
   updated_at: "",
+  side: "New",
+  version: 0,
+  unread_count: 0,
+  has_unread: false,
 };
 
 const RESOLVED_THREAD: ThreadResponse = {
@@ -7,7 +7,13 @@ function makeFile(
   status: "Added" | "Modified" | "Deleted" | "Renamed" | "Binary" = "Modified",
   open_thread_count = 0,
 ): FileListEntry {
-  return { path, status, thread_count: open_thread_count, open_thread_count };
+  return {
+    path,
+    status,
+    thread_count: open_thread_count,
+    open_thread_count,
+    trivial: false,
+  };
 }
 
 describe("buildFileTree", () => {
@@ -54,14 +54,21 @@ class MockWebSocket {
 vi.stubGlobal("WebSocket", MockWebSocket);
 
 // Dynamic import after mock is installed
-const { connect, disconnect, onEvent, onReconnect, onStatus } =
-  await import("../ws");
+const {
+  connect,
+  disconnect,
+  onEvent,
+  onReconnect,
+  onStatus,
+  setViewingReview,
+} = await import("../ws");
 
 describe("ws", () => {
   beforeEach(() => {
     vi.useFakeTimers();
     MockWebSocket.instances = [];
     disconnect(); // reset module state
+    setViewingReview(null);
   });
 
   afterEach(() => {
@@ -75,6 +82,37 @@ describe("ws", () => {
     expect(MockWebSocket.instances[0].url).toContain("/api/ws");
   });
 
+  it("connect() sends no role when no review is open", () => {
+    connect();
+    expect(MockWebSocket.instances[0].url).not.toContain("role=");
+  });
+
+  it("connect() identifies the review page as a human viewer", () => {
+    setViewingReview("r-1");
+    connect();
+    expect(MockWebSocket.instances[0].url).toMatch(
+      /\/api\/ws\?role=human&review_id=r-1$/,
+    );
+  });
+
+  it("setViewingReview() reopens an open socket with the new URL", () => {
+    const statusCb = vi.fn();
+    onStatus(statusCb);
+    connect();
+    MockWebSocket.instances[0].simulateOpen();
+    statusCb.mockClear();
+
+    setViewingReview("r-2");
+    expect(MockWebSocket.instances).toHaveLength(2);
+    expect(MockWebSocket.instances[0].readyState).toBe(MockWebSocket.CLOSED);
+    expect(MockWebSocket.instances[1].url).toContain("review_id=r-2");
+    expect(statusCb).not.toHaveBeenCalledWith("disconnected");
+
+    setViewingReview(null);
+    expect(MockWebSocket.instances).toHaveLength(3);
+    expect(MockWebSocket.instances[2].url).not.toContain("role=");
+  });
+
   it("connect() is a no-op when already connected", () => {
     connect();
     const ws = MockWebSocket.instances[0];
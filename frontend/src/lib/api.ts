@@ -3,23 +3,36 @@ import type {
   AgentPresenceResponse,
   AgentStatus,
   CommentResponse,
+  CreateHunkThreadRequest,
   CreateReviewRequest,
   CreateRevisionRequest,
   CreateThreadRequest,
+  DriftResponse,
   FileContentResponse,
   FileDiffResponse,
   FileListEntry,
+  FileSort,
+  ForkReviewRequest,
+  HumanPresenceResponse,
+  LinkThreadsRequest,
+  PinThreadRequest,
+  ReviewFullResponse,
   ReviewResponse,
   RevisionResponse,
+  SetReviewDecisionRequest,
   ThreadResponse,
+  UpdateReviewRequest,
   UpdateReviewStatusRequest,
   UpdateThreadStatusRequest,
+  ValidateRepoRequest,
+  ValidateRepoResponse,
 } from "./types";
 
 export class ApiError extends Error {
   constructor(
     public status: number,
     message: string,
+    public code?: string,
   ) {
     super(message);
     this.name = "ApiError";
@@ -33,7 +46,7 @@ async function request<T>(path: string, options?: RequestInit): Promise<T> {
   });
   if (!res.ok) {
     const body = await res.json().catch(() => ({ error: res.statusText }));
-    throw new ApiError(res.status, body.error ?? res.statusText);
+    throw new ApiError(res.status, body.error ?? res.statusText, body.code);
   }
   if (res.status === 204) return undefined as T;
   return res.json();
@@ -49,6 +62,10 @@ export function getReview(id: string): Promise<ReviewResponse> {
   return request(`/api/reviews/${id}`);
 }
 
+export function getReviewFull(id: string): Promise<ReviewFullResponse> {
+  return request(`/api/reviews/${id}/full?include_drafts=true`);
+}
+
 export function createReview(
   req: CreateReviewRequest,
 ): Promise<ReviewResponse> {
@@ -58,6 +75,35 @@ export function createReview(
   });
 }
 
+export function validateRepo(
+  req: ValidateRepoRequest,
+): Promise<ValidateRepoResponse> {
+  return request("/api/validate-repo", {
+    method: "POST",
+    body: JSON.stringify(req),
+  });
+}
+
+export function forkReview(
+  id: string,
+  req: ForkReviewRequest,
+): Promise<ReviewResponse> {
+  return request(`/api/reviews/${id}/fork`, {
+    method: "POST",
+    body: JSON.stringify(req),
+  });
+}
+
+export function updateReview(
+  id: string,
+  req: UpdateReviewRequest,
+): Promise<ReviewResponse> {
+  return request(`/api/reviews/${id}`, {
+    method: "PATCH",
+    body: JSON.stringify(req),
+  });
+}
+
 export function updateReviewStatus(
   id: string,
   req: UpdateReviewStatusRequest,
@@ -68,6 +114,16 @@ export function updateReviewStatus(
   });
 }
 
+export function setReviewDecision(
+  id: string,
+  req: SetReviewDecisionRequest,
+): Promise<void> {
+  return request(`/api/reviews/${id}/decision`, {
+    method: "POST",
+    body: JSON.stringify(req),
+  });
+}
+
 export function deleteReview(id: string): Promise<void> {
   return request(`/api/reviews/${id}`, { method: "DELETE" });
 }
@@ -92,14 +148,22 @@ export function createRevision(
   });
 }
 
+export function getDrift(reviewId: string): Promise<DriftResponse> {
+  return request(`/api/reviews/${reviewId}/drift`);
+}
+
 // --- Files ---
 
 export function listFiles(
   reviewId: string,
   revision?: number,
+  sort?: FileSort,
 ): Promise<FileListEntry[]> {
-  const params = revision != null ? `?revision=${revision}` : "";
-  return request(`/api/reviews/${reviewId}/files${params}`);
+  const params = new URLSearchParams();
+  if (revision != null) params.set("revision", String(revision));
+  if (sort) params.set("sort", sort);
+  const query = params.size ? `?${params}` : "";
+  return request(`/api/reviews/${reviewId}/files${query}`);
 }
 
 export function getFileDiff(
@@ -111,6 +175,22 @@ export function getFileDiff(
   return request(`/api/reviews/${reviewId}/files/${path}${params}`);
 }
 
+export function getReviewDiff(
+  reviewId: string,
+  revision?: number,
+): Promise<FileDiffResponse[]> {
+  const params = new URLSearchParams({ highlight: "true" });
+  if (revision != null) params.set("revision", String(revision));
+  return request(`/api/reviews/${reviewId}/diff?${params}`);
+}
+
+export function getRevisionDiff(
+  reviewId: string,
+  revision: number,
+): Promise<FileDiffResponse[]> {
+  return request(`/api/reviews/${reviewId}/revisions/${revision}/diff`);
+}
+
 export function getFileInterdiff(
   reviewId: string,
   path: string,
@@ -126,9 +206,22 @@ export function getFileContent(
   reviewId: string,
   path: string,
   version?: "old" | "new",
+  language?: string,
 ): Promise<FileContentResponse> {
-  const params = version ? `?version=${version}` : "";
-  return request(`/api/reviews/${reviewId}/content/${path}${params}`);
+  const params = new URLSearchParams();
+  if (version) params.set("version", version);
+  if (language) params.set("language", language);
+  const query = params.size ? `?${params}` : "";
+  return request(`/api/reviews/${reviewId}/content/${path}${query}`);
+}
+
+export function rawFileUrl(
+  reviewId: string,
+  path: string,
+  version?: "old" | "new",
+): string {
+  const query = version ? `?version=${version}` : "";
+  return `/api/reviews/${reviewId}/raw/${path}${query}`;
 }
 
 // --- Threads ---
@@ -137,8 +230,9 @@ export function listThreads(
   reviewId: string,
   filePath?: string,
 ): Promise<ThreadResponse[]> {
-  const params = filePath ? `?file=${encodeURIComponent(filePath)}` : "";
-  return request(`/api/reviews/${reviewId}/threads${params}`);
+  const params = new URLSearchParams({ include_drafts: "true" });
+  if (filePath) params.set("file", filePath);
+  return request(`/api/reviews/${reviewId}/threads?${params}`);
 }
 
 export function createThread(
@@ -151,6 +245,16 @@ export function createThread(
   });
 }
 
+export function createThreadFromHunk(
+  reviewId: string,
+  req: CreateHunkThreadRequest,
+): Promise<ThreadResponse> {
+  return request(`/api/reviews/${reviewId}/threads/from-hunk`, {
+    method: "POST",
+    body: JSON.stringify(req),
+  });
+}
+
 export function updateThreadStatus(
   threadId: string,
   req: UpdateThreadStatusRequest,
@@ -177,8 +281,44 @@ export function pokeThread(threadId: string): Promise<void> {
   });
 }
 
+export function linkThreads(
+  threadId: string,
+  req: LinkThreadsRequest,
+): Promise<ThreadResponse> {
+  return request(`/api/threads/${threadId}/link`, {
+    method: "POST",
+    body: JSON.stringify(req),
+  });
+}
+
+export function pinThread(
+  threadId: string,
+  req: PinThreadRequest,
+): Promise<ThreadResponse> {
+  return request(`/api/threads/${threadId}/pin`, {
+    method: "PATCH",
+    body: JSON.stringify(req),
+  });
+}
+
 // --- Comments ---
 
+export function getComment(
+  threadId: string,
+  commentId: string,
+): Promise<CommentResponse> {
+  return request(`/api/threads/${threadId}/comments/${commentId}`);
+}
+
+export function getCommentsBatch(
+  threadIds: string[],
+): Promise<Record<string, CommentResponse[]>> {
+  return request("/api/threads/comments/batch?include_drafts=true", {
+    method: "POST",
+    body: JSON.stringify(threadIds),
+  });
+}
+
 export function addComment(
   threadId: string,
   req: AddCommentRequest,
@@ -197,6 +337,24 @@ export function getAgentPresence(
   return request(`/api/reviews/${reviewId}/agent-status`);
 }
 
+export function getHumanPresence(
+  reviewId: string,
+): Promise<HumanPresenceResponse> {
+  return request(`/api/reviews/${reviewId}/human-status`);
+}
+
+export function markAgentSeen(reviewId: string): Promise<void> {
+  return request(`/api/reviews/${reviewId}/agent-seen`, {
+    method: "PUT",
+  });
+}
+
+export function markHumanSeen(reviewId: string): Promise<void> {
+  return request(`/api/reviews/${reviewId}/mark-seen`, {
+    method: "POST",
+  });
+}
+
 export function requestRevision(reviewId: string): Promise<void> {
   return request(`/api/reviews/${reviewId}/request-revision`, {
     method: "POST",
@@ -1,12 +1,24 @@
 // --- Enums (match Rust serde default: PascalCase variant names) ---
 
 export type ReviewStatus = "Open" | "Closed";
+export type ReviewDecision = "Approved" | "ChangesRequested" | "Commented";
 export type FileStatus =
   | "Added"
   | "Modified"
   | "Deleted"
   | "Renamed"
-  | "Binary";
+  | "Binary"
+  | "Symlink";
+export type LineSide = "Old" | "New";
+
+export interface CodeBlockAttachment {
+  type: "CodeBlock";
+  language?: string;
+  code: string;
+}
+
+export type Attachment = CodeBlockAttachment;
+
 export type ThreadOrigin =
   | "Comment"
   | "ExplanationRequest"
@@ -27,8 +39,19 @@ export interface ReviewResponse {
   thread_count: number;
   open_thread_count: number;
   revision_count: number;
+  path_prefix?: string;
   created_at: string;
   updated_at: string;
+  agent_seen_at: string | null;
+  extra_repos?: ExtraRepo[];
+  labels: string[];
+  decision: ReviewDecision | null;
+}
+
+export interface ExtraRepo {
+  repo_path: string;
+  base_ref: string;
+  prefix: string;
 }
 
 export interface RevisionResponse {
@@ -41,18 +64,41 @@ export interface RevisionResponse {
   created_at: string;
 }
 
+export interface ReviewFullResponse {
+  review: ReviewResponse;
+  files: FileListEntry[];
+  threads: ThreadResponse[];
+  revisions: RevisionResponse[];
+}
+
+export interface DriftResponse {
+  drifted: boolean;
+  changed_files: string[];
+}
+
+export type FileSort = "path" | "status" | "size" | "threads";
+
 export interface FileListEntry {
   path: string;
   status: FileStatus;
   thread_count: number;
   open_thread_count: number;
+  trivial: boolean;
 }
 
 export interface FileDiffResponse {
   path: string;
   old_path: string | null;
   status: FileStatus;
+  language: string | null;
   hunks: Hunk[];
+  mode_change?: ModeChange;
+  truncated?: boolean;
+}
+
+export interface ModeChange {
+  old_mode: string;
+  new_mode: string;
 }
 
 export interface Hunk {
@@ -62,6 +108,10 @@ export interface Hunk {
   new_count: number;
   context: string | null;
   lines: DiffLine[];
+  new_line_start: number | null;
+  new_line_end: number | null;
+  old_line_start: number | null;
+  old_line_end: number | null;
 }
 
 export interface DiffLine {
@@ -70,6 +120,7 @@ export interface DiffLine {
   old_line_no: number | null;
   new_line_no: number | null;
   highlighted?: string;
+  whitespace_only?: boolean;
 }
 
 export interface FileContentLine {
@@ -82,6 +133,7 @@ export interface FileContentResponse {
   path: string;
   language: string | null;
   lines: FileContentLine[];
+  partial?: boolean;
 }
 
 export interface ThreadResponse {
@@ -90,12 +142,35 @@ export interface ThreadResponse {
   file_path: string;
   line_start: number;
   line_end: number;
+  side: LineSide;
   origin: ThreadOrigin;
   status: ThreadStatus;
   agent_status: AgentStatus | null;
   comments: CommentResponse[];
   created_at: string;
   updated_at: string;
+  related_threads?: string[];
+  pinned?: boolean;
+  version: number;
+  unread_count: number;
+  has_unread: boolean;
+  content_snippet?: ContentSnippet;
+  anchor_text?: string;
+}
+
+export interface ReviewThreadResponse extends ThreadResponse {
+  review_title: string | null;
+}
+
+export interface ThreadPageResponse {
+  threads: ReviewThreadResponse[];
+  total: number;
+}
+
+export interface ContentSnippet {
+  lines: string[];
+  context_before: string[];
+  context_after: string[];
 }
 
 export interface CommentResponse {
@@ -103,6 +178,11 @@ export interface CommentResponse {
   author_type: AuthorType;
   body: string;
   created_at: string;
+  draft?: boolean;
+  in_reply_to?: string;
+  suggestion?: string;
+  attachments?: Attachment[];
+  body_html?: string;
 }
 
 // --- Request types ---
@@ -110,7 +190,41 @@ export interface CommentResponse {
 export interface CreateReviewRequest {
   title?: string;
   repo_path: string;
-  base_ref: string;
+  base_ref?: string;
+  commit?: string;
+  path_prefix?: string;
+  extra_repos?: ExtraRepoRequest[];
+  labels?: string[];
+  diff_algorithm?: DiffAlgorithm;
+  allow_empty?: boolean;
+}
+
+export interface ValidateRepoRequest {
+  repo_path: string;
+}
+
+export interface ValidateRepoResponse {
+  is_git_repo: boolean;
+  detected_base?: string;
+  current_branch?: string;
+  changed_file_count?: number;
+}
+
+export type DiffAlgorithm = "myers" | "minimal" | "patience" | "histogram";
+
+export interface ExtraRepoRequest {
+  repo_path: string;
+  base_ref?: string;
+  prefix: string;
+}
+
+export interface UpdateReviewRequest {
+  labels?: string[];
+}
+
+export interface ForkReviewRequest {
+  title?: string;
+  base_ref?: string;
 }
 
 export interface CreateRevisionRequest {
@@ -122,22 +236,51 @@ export interface UpdateReviewStatusRequest {
   status: ReviewStatus;
 }
 
+export interface SetReviewDecisionRequest {
+  decision: ReviewDecision | null;
+}
+
 export interface CreateThreadRequest {
   file_path: string;
   line_start: number;
   line_end: number;
+  side?: LineSide;
   origin: ThreadOrigin;
   body: string;
   author_type: AuthorType;
+  suggestion?: string;
+}
+
+export interface CreateHunkThreadRequest {
+  file_path: string;
+  hunk_index: number;
+  line_offset: number;
+  line_count?: number;
+  origin: ThreadOrigin;
+  body: string;
+  author_type: AuthorType;
+  suggestion?: string;
+}
+
+export interface LinkThreadsRequest {
+  thread_ids: string[];
+}
+
+export interface PinThreadRequest {
+  pinned: boolean;
 }
 
 export interface UpdateThreadStatusRequest {
   status: ThreadStatus;
+  expected_version?: number;
 }
 
 export interface AddCommentRequest {
   author_type: AuthorType;
   body: string;
+  draft?: boolean;
+  suggestion?: string;
+  attachments?: Attachment[];
 }
 
 // --- WebSocket events ---
@@ -145,6 +288,7 @@ export interface AddCommentRequest {
 export type WsEventType =
   | "review_created"
   | "review_status_changed"
+  | "review_decision_changed"
   | "review_deleted"
   | "revision_created"
   | "thread_created"
@@ -152,13 +296,21 @@ export type WsEventType =
   | "thread_status_changed"
   | "thread_acknowledged"
   | "thread_poked"
+  | "threads_linked"
+  | "thread_pinned"
   | "revision_requested"
-  | "agent_presence_changed";
+  | "agent_presence_changed"
+  | "human_presence_changed"
+  | "agent_seen_changed";
 
 export interface AgentPresenceResponse {
   connected: boolean;
 }
 
+export interface HumanPresenceResponse {
+  connected: boolean;
+}
+
 export interface WsEvent {
   event_type: WsEventType;
   review_id: string;
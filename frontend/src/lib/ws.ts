@@ -12,10 +12,15 @@ const statusCallbacks: Set<StatusCallback> = new Set();
 let reconnectDelay = 1000;
 let reconnectTimer: ReturnType<typeof setTimeout> | null = null;
 let intentionallyClosed = false;
+let viewingReview: string | null = null;
 
 function getWsUrl(): string {
   const proto = location.protocol === "https:" ? "wss:" : "ws:";
-  return `${proto}//${location.host}/api/ws`;
+  const base = `${proto}//${location.host}/api/ws`;
+  if (!viewingReview) return base;
+  // Tells the server a human has this review open, which is what agents
+  // see as presence.
+  return `${base}?role=human&review_id=${encodeURIComponent(viewingReview)}`;
 }
 
 function notifyStatus(status: "connected" | "reconnecting" | "disconnected") {
@@ -84,6 +89,19 @@ export function disconnect(): void {
   socket = null;
 }
 
+/** Marks the review page that is open (or none) and reopens the socket so
+ * the server sees the change. */
+export function setViewingReview(reviewId: string | null): void {
+  if (viewingReview === reviewId) return;
+  viewingReview = reviewId;
+  if (!socket) return; // the next connect() picks up the new URL
+  const old = socket;
+  old.onclose = null;
+  old.close();
+  socket = null;
+  connect();
+}
+
 export function onEvent(
   type: WsEventType,
   callback: EventCallback,